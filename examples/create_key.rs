@@ -1,7 +1,7 @@
-use rrsa_lib::key::KeyPair;
+use rrsa_lib::key::{KeyPair, KeySize};
 
 fn main() {
-    let key_pair = KeyPair::generate(Some(512), true, true, true);
+    let key_pair = KeyPair::generate(Some(KeySize::B512), true, true, true).unwrap();
     let pub_key = key_pair.public_key;
     let priv_key = key_pair.private_key;
     println!();
@@ -0,0 +1,22 @@
+use rrsa_lib::dh::{DhParams, DhParty};
+
+fn main() {
+    let params = DhParams::generate(256).unwrap();
+    println!(
+        "Public parameters:\np = {:x}\ng = {}",
+        params.prime, params.generator
+    );
+
+    let alice = DhParty::new(&params);
+    let bob = DhParty::new(&params);
+    println!();
+    println!("Alice sends A = {:x}", alice.public_value());
+    println!("Bob sends   B = {:x}", bob.public_value());
+
+    let alice_secret = alice.shared_secret(bob.public_value()).unwrap();
+    let bob_secret = bob.shared_secret(alice.public_value()).unwrap();
+    println!();
+    println!("Alice computes B^a mod p = {alice_secret:x}");
+    println!("Bob computes   A^b mod p = {bob_secret:x}");
+    assert_eq!(alice_secret, bob_secret);
+}
@@ -0,0 +1,68 @@
+//! Subcommands only available with the `extras` feature.
+
+use rrsa_lib::{
+    elgamal::ElGamalKeyPair,
+    encoding::padding::PaddingScheme,
+    error::RsaResult,
+    key::{KeyPair, KeySize},
+};
+use std::{
+    io::Cursor,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Encrypts and decrypts the file at `in_path` with both RSA and ElGamal keys
+/// of `key_size` bits, printing the ciphertext sizes and the time taken by each.
+pub fn compare_elgamal(in_path: &Path, key_size: u16) -> RsaResult<()> {
+    let plaintext = std::fs::read(in_path)?;
+
+    let rsa_pair = KeyPair::generate(Some(KeySize::custom(key_size.into())?), true, false, false)?;
+    let (rsa_ciphertext, rsa_encrypt) = timed(|| {
+        let mut ciphertext = Vec::new();
+        rsa_pair.public_key.encode(
+            &mut Cursor::new(&plaintext),
+            &mut ciphertext,
+            PaddingScheme::None,
+        )?;
+        Ok(ciphertext)
+    })?;
+    let (_, rsa_decrypt) = timed(|| {
+        let mut decoded = Vec::new();
+        rsa_pair.private_key.decode(
+            &mut Cursor::new(&rsa_ciphertext),
+            &mut decoded,
+            PaddingScheme::None,
+        )?;
+        Ok(decoded)
+    })?;
+
+    let elgamal_pair = ElGamalKeyPair::generate(key_size)?;
+    let (elgamal_ciphertext, elgamal_encrypt) =
+        timed(|| elgamal_pair.public_key.encrypt(&plaintext))?;
+    let (_, elgamal_decrypt) = timed(|| elgamal_pair.private_key.decrypt(&elgamal_ciphertext))?;
+
+    println!("Plaintext: {} bytes", plaintext.len());
+    println!(
+        "RSA:     {} bytes ({:.2}x), encrypt {rsa_encrypt:?}, decrypt {rsa_decrypt:?}",
+        rsa_ciphertext.len(),
+        expansion(rsa_ciphertext.len(), plaintext.len())
+    );
+    println!(
+        "ElGamal: {} bytes ({:.2}x), encrypt {elgamal_encrypt:?}, decrypt {elgamal_decrypt:?}",
+        elgamal_ciphertext.len(),
+        expansion(elgamal_ciphertext.len(), plaintext.len())
+    );
+    Ok(())
+}
+
+fn timed<T>(operation: impl FnOnce() -> RsaResult<T>) -> RsaResult<(T, Duration)> {
+    let start = Instant::now();
+    let result = operation()?;
+    Ok((result, start.elapsed()))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn expansion(ciphertext_len: usize, plaintext_len: usize) -> f64 {
+    ciphertext_len as f64 / plaintext_len.max(1) as f64
+}
@@ -0,0 +1,1671 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use progress::{Progress, ProgressMode};
+use rrsa_lib::{
+    archive::{ArchiveReader, ArchiveWriter},
+    atomic::{write_atomic, AtomicFile},
+    capabilities,
+    config::Config,
+    corpus,
+    encoding::{
+        armor::ArmorWriter,
+        envelope::{self, EnvelopeCipher, EnvelopeOptions},
+        padding::PaddingScheme,
+        Plaintext,
+    },
+    error::{RsaError, RsaResult},
+    jwt,
+    key::{
+        Key, KeyBundle, KeyFormat, KeyGenOptions, KeyPair, KeySize, KeyVariant, StorageLocation,
+        ValidationReport,
+    },
+    math::{mod_pow, parse_number, PrimeGenerator},
+    message::EncryptedMessage,
+    paths::{
+        is_stdio, refuse_overwrite, resolve_key_path, resolve_output_path, Operation, STDIO_PATH,
+    },
+    progress::{CheckpointOptions, ProgressReader},
+    rng::{self, RandomSource},
+    secret::EncryptedSecret,
+    signature::{HashAlgorithm, SignatureFile, SignatureScheme},
+    testvectors::{self, VectorStatus},
+    version,
+    warning::Warning,
+};
+use std::{
+    fs::File,
+    io::{stdin, stdout, Read, StdoutLock, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "extras")]
+mod extras;
+mod progress;
+mod setup;
+
+fn main() -> Result<(), String> {
+    run_cli().map_err(|e| e.to_string())
+}
+
+fn run_cli() -> RsaResult<()> {
+    let cli = RsaCli::parse();
+    init_logging(cli.verbose, cli.quiet);
+    if cli.version {
+        println!("rrsa {}", version::CRATE_VERSION);
+        if cli.formats {
+            for (format, format_version) in version::FORMATS {
+                println!("{format} format version {format_version}");
+            }
+        }
+        return Ok(());
+    }
+    let Some(sub_command) = cli.sub_command else {
+        return Ok(());
+    };
+    let config = Config::load()?;
+    if let Some(storage) = config.storage {
+        if let Some(keys_dir) = Key::ensure_storage(storage)? {
+            tracing::info!("Keys migrated to {}", keys_dir.display());
+        }
+    }
+
+    match sub_command {
+        RsaCommands::Keygen {
+            key_size,
+            out_path,
+            ndex,
+            exponent,
+            results,
+            progress,
+            rng,
+            audit,
+            comment,
+            force,
+        } => {
+            rng::set_default_source(rng.into());
+            let mut options = KeyGenOptions::from_config(&config)?;
+            options.key_size_bits = key_size.or(options.key_size_bits);
+            options.default_exponent = exponent.unwrap_or(options.default_exponent);
+            options.use_default_exponent = !ndex;
+            let mut key_pair = KeyPair::generate_with_options(&options, results, progress)?;
+            if let Some(comment) = comment {
+                key_pair.public_key.metadata_mut().comment = Some(comment.clone());
+                key_pair.private_key.metadata_mut().comment = Some(comment);
+            }
+            if audit {
+                println!("{}", key_pair.audit()?);
+            }
+
+            match (out_path, force) {
+                (Some(path), true) => key_pair.write_to_path(&path)?,
+                (Some(path), false) => key_pair.write_new_to_path(&path)?,
+                (None, true) => key_pair.write_to_default()?,
+                (None, false) => key_pair.write_new_to_default()?,
+            };
+        }
+        RsaCommands::Init { yes, key_size } => {
+            if !setup::is_first_run() {
+                println!("Keys already present in {}", Key::default_dir().display());
+            } else if yes || setup::confirm("Generate a default Key Pair now?")? {
+                setup::bootstrap(key_size, !yes)?;
+            }
+        }
+        RsaCommands::Validate {
+            args,
+            output_format,
+        } => {
+            let report = ValidationReport::new(
+                args.public_key_path
+                    .map(|path| Key::read_from_path(&path))
+                    .transpose()?,
+                args.private_key_path
+                    .map(|path| Key::read_from_path(&path))
+                    .transpose()?,
+            );
+            // in text, the problem is only printed as the error
+            if report.is_valid() || output_format != OutputFormat::Text {
+                print_report(output_format, &report, &report.to_json())?;
+            }
+            if let Some(problem) = report.problem {
+                return Err(RsaError::UnknownError(problem));
+            }
+        }
+        RsaCommands::Inspect { args } => inspect_key(&args)?,
+        RsaCommands::Key { action } => match action {
+            KeyAction::Fingerprint { key_path } => {
+                let key = Key::read_from_path(&key_path)?;
+                println!("{}", key.fingerprint_hex());
+                println!("{}", key.fingerprint_randomart());
+            }
+            KeyAction::Inspect { args } => inspect_key(&args)?,
+            KeyAction::Convert {
+                key_path,
+                out_path,
+                from,
+                to,
+                force,
+            } => {
+                let key = Key::from_format(&std::fs::read(&key_path)?, from.map(Into::into))?;
+                let converted = key.to_format(to.into())?;
+                match out_path {
+                    Some(out_path) => {
+                        if !force {
+                            refuse_overwrite(&out_path)?;
+                        }
+                        write_atomic(&out_path, converted)?;
+                    }
+                    None => stdout().lock().write_all(&converted)?,
+                }
+            }
+            KeyAction::Use { name } => {
+                let key_path = Key::default_dir().join(&name);
+                if !Key::read_from_path(&key_path)?.is_private() {
+                    return Err(RsaError::UnknownError(format!(
+                        "{} is not a Private Key",
+                        key_path.display()
+                    )));
+                }
+                update_config_file(|file_config| file_config.key_path = Some(key_path))?;
+                println!("Default Key Pair is now {name}");
+            }
+        },
+        RsaCommands::Encrypt {
+            in_path,
+            out_path,
+            key_path,
+            key_bundle,
+            padding,
+            raw_rsa,
+            cipher,
+            no_key_hint,
+            armor,
+            no_armor,
+            archive,
+            force,
+            progress,
+            progress_json,
+        } => {
+            let pub_keys: Vec<Key> = if let Some(key_bundle) = key_bundle {
+                let bundle = KeyBundle::read_from_path(&key_bundle)?;
+                bundle.keys.into_iter().map(|(_, key)| key).collect()
+            } else {
+                vec![Key::read_from_path(&key_path_for(
+                    key_path,
+                    &config,
+                    KeyVariant::PublicKey,
+                )?)?]
+            };
+            let recipients: Vec<&Key> = pub_keys.iter().collect();
+            print_warnings(&pub_keys.iter().flat_map(Key::warnings).collect::<Vec<_>>());
+            let escrow_path = config.escrow_key_path_for(KeyVariant::PublicKey);
+            if escrow_path.is_some() && raw_rsa {
+                return Err(RsaError::UnknownError(
+                    "`escrow_key_path` is configured, but --raw-rsa has no data key to escrow"
+                        .into(),
+                ));
+            }
+            let escrow = escrow_path
+                .as_deref()
+                .map(Key::read_from_path)
+                .transpose()?;
+
+            let chunk_len = if raw_rsa {
+                pub_keys[0].chunk_len(padding.into())?
+            } else {
+                envelope::ENVELOPE_SEGMENT_LEN
+            };
+            let mut input = open_with_progress(
+                &in_path,
+                archive,
+                progress,
+                progress_json,
+                chunk_len,
+                &config,
+            )?;
+            let out_path = config.resolve_output_path(&in_path, out_path, Operation::Encode);
+            let mut output = Output::create(&out_path, force, false)?;
+
+            let armor = (armor || config.armor == Some(true)) && !no_armor;
+            let options = EnvelopeOptions {
+                cipher: cipher.into(),
+                escrow: escrow.as_ref(),
+                key_hint: !no_key_hint,
+            };
+            match (raw_rsa, armor) {
+                (true, true) => {
+                    let mut armored = ArmorWriter::new(&mut output)?;
+                    encode_raw(&pub_keys[0], no_key_hint, &mut input, &mut armored, padding)?;
+                    armored.finish()?;
+                }
+                (true, false) => {
+                    encode_raw(&pub_keys[0], no_key_hint, &mut input, &mut output, padding)?;
+                }
+                (false, true) => {
+                    let mut armored = ArmorWriter::new(&mut output)?;
+                    Key::encode_envelope_with_options(
+                        &recipients,
+                        &options,
+                        &mut input,
+                        &mut armored,
+                    )?;
+                    armored.finish()?;
+                }
+                (false, false) => {
+                    Key::encode_envelope_with_options(
+                        &recipients,
+                        &options,
+                        &mut input,
+                        &mut output,
+                    )?;
+                }
+            }
+            input.finish();
+            output.commit()?;
+            if let Some(escrow_path) = escrow_path {
+                print_status(
+                    &out_path,
+                    &format!("Data key escrowed to {}", escrow_path.display()),
+                );
+            }
+            print_status(
+                &out_path,
+                &format!("Done encoding file {}", out_path.display()),
+            );
+        }
+        RsaCommands::Decrypt {
+            in_path,
+            out_path,
+            key_path,
+            any_key,
+            as_escrow,
+            legacy_format,
+            best_effort,
+            unpack,
+            to_stdout,
+            force,
+            padding,
+            progress,
+            progress_json,
+        } => {
+            let (out_path, progress, progress_json) = if to_stdout {
+                (Some(PathBuf::from(STDIO_PATH)), false, false)
+            } else {
+                (out_path, progress, progress_json)
+            };
+            if any_key && is_stdio(&in_path) {
+                return Err(RsaError::UnknownError(
+                    "--any-key needs to read the input twice, it cannot be stdin".into(),
+                ));
+            }
+            // an anonymous header does not tell which Key to use, so every one is tried
+            let any_key = any_key
+                || (key_path.is_none()
+                    && !as_escrow
+                    && !legacy_format
+                    && !is_stdio(&in_path)
+                    && !Key::has_key_hint(&mut File::open(&in_path)?).unwrap_or(true));
+            let priv_key = if any_key {
+                setup::ensure_default_keys()?;
+                let mut input = File::open(&in_path)?;
+                let mut keys = Key::read_private_keys_from_dir(&Key::default_dir())?.into_iter();
+                loop {
+                    let Some((path, key)) = keys.next() else {
+                        return Err(RsaError::UnknownError(format!(
+                            "no Private Key in {} can decrypt {}",
+                            Key::default_dir().display(),
+                            in_path.display()
+                        )));
+                    };
+                    if key.decodes_first_chunk(&mut input, padding.into())? {
+                        print_status(
+                            out_path.as_deref().unwrap_or(&in_path),
+                            &format!("Decrypting with {}", path.display()),
+                        );
+                        break key;
+                    }
+                }
+            } else if as_escrow {
+                let escrow_path = config
+                    .escrow_key_path_for(KeyVariant::PrivateKey)
+                    .ok_or_else(|| {
+                        RsaError::UnknownError(
+                            "--as-escrow needs `escrow_key_path` in the config".into(),
+                        )
+                    })?;
+                Key::read_from_path(&escrow_path)?
+            } else {
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?
+            };
+
+            let chunk_len = decode_chunk_len(&priv_key, &in_path, padding.into())?;
+            let mut input =
+                open_with_progress(&in_path, false, progress, progress_json, chunk_len, &config)?;
+            let out_path = config.resolve_output_path(&in_path, out_path, Operation::Decode);
+            // tampered chunks are only detected at the end, so nothing is printed before
+            let mut output = Output::create(&out_path, force, unpack)?.held_back();
+
+            if best_effort {
+                print_warnings(&priv_key.warnings());
+                let lost = priv_key.decode_best_effort(&mut input, &mut output, padding.into())?;
+                for range in &lost {
+                    tracing::warn!("Lost bytes {}..{} of the output", range.start, range.end);
+                }
+            } else if legacy_format {
+                let mut warnings = priv_key.warnings();
+                warnings.push(Warning::LegacyFormat);
+                print_warnings(&warnings);
+                priv_key.decode_legacy(&mut input, &mut output)?;
+            } else {
+                let outcome =
+                    priv_key.decode_with_outcome(&mut input, &mut output, padding.into())?;
+                print_warnings(&outcome.warnings);
+            }
+            input.finish();
+            output.commit()?;
+            print_status(
+                &out_path,
+                &format!("Done encoding file {}", out_path.display()),
+            );
+        }
+        RsaCommands::VerifyFile {
+            in_path,
+            key_path,
+            padding,
+        } => {
+            let key =
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?;
+
+            let damaged = key.verify_chunks(&mut File::open(&in_path)?, padding.into())?;
+            if !damaged.is_empty() {
+                for chunk_index in &damaged {
+                    println!("Chunk {chunk_index} is damaged");
+                }
+                return Err(RsaError::UnknownError(format!(
+                    "{} damaged chunk(s) in file {}",
+                    damaged.len(),
+                    in_path.display()
+                )));
+            }
+            println!("File {} is intact!", in_path.display());
+        }
+        RsaCommands::Reencrypt {
+            in_path,
+            out_path,
+            from,
+            to,
+            force,
+        } => {
+            let priv_key = Key::read_from_path(&from)?;
+            let recipient = Key::read_from_path(&to)?;
+            let mut input: Box<dyn Read> = if is_stdio(&in_path) {
+                Box::new(stdin().lock())
+            } else {
+                Box::new(File::open(&in_path)?)
+            };
+            let mut output = Output::create(&out_path, force, false)?;
+            priv_key.reencrypt_envelope(&recipient, &mut input, &mut output)?;
+            output.commit()?;
+            print_status(
+                &out_path,
+                &format!("Done re-encrypting file {}", out_path.display()),
+            );
+        }
+        RsaCommands::Sign {
+            file,
+            key_path,
+            out_path,
+            scheme,
+            force,
+        } => {
+            let out_path = resolve_output_path(&file, out_path, Operation::Sign);
+            if !force {
+                refuse_overwrite(&out_path)?;
+            }
+            let priv_key =
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?;
+            let scheme = SignatureScheme::from(scheme);
+
+            let digest = scheme.hash().digest_reader(&mut File::open(&file)?)?;
+            let signature = priv_key.sign(&digest, scheme)?;
+            write_atomic(
+                &out_path,
+                SignatureFile::new(&priv_key, scheme, signature).to_string(),
+            )?;
+            println!("Done signing file {}", out_path.display());
+        }
+        RsaCommands::Verify {
+            file,
+            signature_path,
+            key_path,
+        } => {
+            let pub_key =
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PublicKey)?)?;
+
+            let signature_path = resolve_output_path(&file, signature_path, Operation::Sign);
+            let signature_file =
+                SignatureFile::from_str(&std::fs::read_to_string(&signature_path)?)?;
+            if !signature_file.is_from_key(&pub_key) {
+                return Err(RsaError::UnknownError(format!(
+                    "Signature {} was made by key {}, not by the given key {}!",
+                    signature_path.display(),
+                    signature_file.key_fingerprint,
+                    pub_key.fingerprint_hex()
+                )));
+            }
+
+            let scheme = signature_file.scheme;
+            let digest = scheme.hash().digest_reader(&mut File::open(&file)?)?;
+            if !pub_key.verify(&digest, &signature_file.signature, scheme)? {
+                return Err(RsaError::UnknownError(format!(
+                    "Signature {} is not valid for file {}!",
+                    signature_path.display(),
+                    file.display()
+                )));
+            }
+            println!("Signature is valid!");
+        }
+        RsaCommands::Jwt { action } => match action {
+            JwtAction::Sign { claims, key_path } => {
+                let priv_key =
+                    Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?;
+                println!("{}", jwt::sign_rs256(&priv_key, &claims)?);
+            }
+            JwtAction::Verify { token, key_path } => {
+                let pub_key =
+                    Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PublicKey)?)?;
+                println!("{}", jwt::verify_rs256(&pub_key, &token)?);
+            }
+        },
+        RsaCommands::Secret { action } => match action {
+            SecretAction::Encrypt { prompt, key_path } => {
+                let pub_key =
+                    Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PublicKey)?)?;
+                let secret = if prompt {
+                    Zeroizing::new(rpassword::prompt_password("Secret: ")?)
+                } else {
+                    read_stdin_line()?
+                };
+                let secret = Plaintext::from_bytes(secret.as_bytes().to_vec());
+                println!("{}", EncryptedSecret::encrypt(&pub_key, &secret)?);
+            }
+            SecretAction::Decrypt { secret, key_path } => {
+                let priv_key =
+                    Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?;
+                let line = match secret {
+                    Some(line) => line,
+                    None => read_stdin_line()?.to_string(),
+                };
+                let secret = EncryptedSecret::from_str(&line)?.decrypt(&priv_key)?;
+                let mut stdout = stdout().lock();
+                stdout.write_all(secret.as_bytes())?;
+                writeln!(stdout)?;
+            }
+        },
+        RsaCommands::EncryptText { message, key_path } => {
+            let pub_key =
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PublicKey)?)?;
+            let message = match message {
+                Some(message) => Zeroizing::new(message),
+                None => Zeroizing::new(rpassword::prompt_password("Message: ")?),
+            };
+            let plaintext = Plaintext::from_bytes(message.as_bytes().to_vec());
+            print!("{}", pub_key.encrypt_text(&plaintext)?);
+        }
+        RsaCommands::DecryptText { armored, key_path } => {
+            let priv_key =
+                Key::read_from_path(&key_path_for(key_path, &config, KeyVariant::PrivateKey)?)?;
+            let armored = match armored {
+                Some(armored) => armored,
+                None => {
+                    let mut armored = String::new();
+                    stdin().read_to_string(&mut armored)?;
+                    armored
+                }
+            };
+            let plaintext = priv_key.decrypt_text(&armored)?;
+            let mut stdout = stdout().lock();
+            stdout.write_all(plaintext.as_bytes())?;
+            writeln!(stdout)?;
+        }
+        RsaCommands::Message { action } => match action {
+            MessageAction::Encrypt {
+                in_path,
+                out_path,
+                to,
+                subject,
+                no_key_hint,
+                force,
+            } => {
+                let pub_key = Key::read_from_path(&to)?;
+                let plaintext = std::fs::read(&in_path)?;
+                let message = if no_key_hint {
+                    EncryptedMessage::encrypt_without_key_hint(&pub_key, &subject, &plaintext)?
+                } else {
+                    EncryptedMessage::encrypt(&pub_key, &subject, &plaintext)?
+                };
+                let out_path = config.resolve_output_path(&in_path, out_path, Operation::Encode);
+                if !force {
+                    refuse_overwrite(&out_path)?;
+                }
+                write_atomic(&out_path, message.to_string())?;
+                println!("Done encrypting message {}", out_path.display());
+            }
+            MessageAction::Decrypt {
+                in_path,
+                out_path,
+                key_path,
+                force,
+            } => {
+                let message = EncryptedMessage::from_str(&std::fs::read_to_string(&in_path)?)?;
+                let plaintext = if key_path.is_none() && message.to.is_none() {
+                    // anonymous message, try every key in the keys directory
+                    setup::ensure_default_keys()?;
+                    let keys = Key::read_private_keys_from_dir(&Key::default_dir())?;
+                    let (index, plaintext) =
+                        message.decrypt_with_any(keys.iter().map(|(_, key)| key))?;
+                    println!("Decrypted with {}", keys[index].0.display());
+                    plaintext
+                } else {
+                    let priv_key = Key::read_from_path(&key_path_for(
+                        key_path,
+                        &config,
+                        KeyVariant::PrivateKey,
+                    )?)?;
+                    message.decrypt(&priv_key)?
+                };
+                let out_path = config.resolve_output_path(&in_path, out_path, Operation::Decode);
+                if !force {
+                    refuse_overwrite(&out_path)?;
+                }
+                write_atomic(&out_path, plaintext)?;
+                println!("Subject: {}", message.subject);
+                println!("Done decrypting message {}", out_path.display());
+            }
+        },
+        #[cfg(feature = "extras")]
+        RsaCommands::CompareElgamal { in_path, key_size } => {
+            extras::compare_elgamal(&in_path, key_size)?;
+        }
+        RsaCommands::Capabilities { json } => {
+            let capabilities = capabilities();
+            if json {
+                println!("{}", capabilities.to_json());
+            } else {
+                println!("rrsa {}", capabilities.version);
+                println!("Key formats: {}", capabilities.key_formats.join(", "));
+                println!(
+                    "Padding schemes: {}",
+                    capabilities.padding_schemes.join(", ")
+                );
+                println!(
+                    "Signature schemes: {}",
+                    capabilities.signature_schemes.join(", ")
+                );
+                println!(
+                    "Key sizes: {} to {} bits",
+                    capabilities.min_key_size, capabilities.max_key_size
+                );
+                for (format, format_version) in capabilities.formats {
+                    println!("{format} format version {format_version}");
+                }
+                println!("Features: {}", capabilities.features.join(", "));
+            }
+        }
+        #[cfg(unix)]
+        RsaCommands::Daemon { socket, keys } => {
+            let mut daemon = rrsa_lib::daemon::Daemon::new();
+            for named_key in keys {
+                let (name, path) = named_key.split_once('=').ok_or_else(|| {
+                    RsaError::UnknownError(format!("`{named_key}` is not in NAME=PATH form"))
+                })?;
+                daemon.add_key(name, KeyPair::read_from_path(Path::new(path))?)?;
+            }
+            let listener = rrsa_lib::daemon::bind(&socket)?;
+            println!("Listening on {}", socket.display());
+            daemon.serve(&listener)?;
+        }
+        RsaCommands::MigrateStorage { location } => {
+            update_config_file(|file_config| file_config.storage = Some(location.into()))?;
+            let keys_dir = Key::migrate_storage(location.into())?;
+            println!("Keys are now stored in {}", keys_dir.display());
+        }
+        RsaCommands::Benchmark { generate_corpus } => {
+            for path in corpus::write_to_dir(&generate_corpus, &corpus::standard())? {
+                println!("Wrote {}", path.display());
+            }
+        }
+        RsaCommands::Math { action } => match action {
+            MathAction::Modpow {
+                base,
+                exponent,
+                modulus,
+            } => {
+                if modulus.is_zero() {
+                    return Err(RsaError::UnknownError(
+                        "the modulus must not be zero".into(),
+                    ));
+                }
+                print_number(&mod_pow(&base, &exponent, &modulus));
+            }
+            MathAction::IsPrime { number } => {
+                if PrimeGenerator::miller_rabin(&number) {
+                    println!("{number} is probably prime");
+                } else {
+                    println!("{number} is composite");
+                }
+            }
+            MathAction::GenPrime { bits } => {
+                print_number(&PrimeGenerator::new().random_prime(bits));
+            }
+        },
+        RsaCommands::Bundle { action } => match action {
+            BundleAction::Add {
+                bundle_path,
+                label,
+                key_path,
+            } => {
+                let key = Key::read_from_path(&key_path)?;
+                KeyBundle::update_path(&bundle_path, |bundle| bundle.add(&label, key))?;
+                println!("Added {label} to {}", bundle_path.display());
+            }
+            BundleAction::Remove { bundle_path, label } => {
+                if !bundle_path.is_file() {
+                    return Err(RsaError::UnknownError(format!(
+                        "no bundle at {}",
+                        bundle_path.display()
+                    )));
+                }
+                KeyBundle::update_path(&bundle_path, |bundle| bundle.remove(&label))?;
+                println!("Removed {label} from {}", bundle_path.display());
+            }
+            BundleAction::List { bundle_path } => {
+                for (label, key) in KeyBundle::read_from_path(&bundle_path)?.keys {
+                    println!("{label} {}", key.fingerprint_hex());
+                }
+            }
+        },
+        RsaCommands::Testvectors { action } => match action {
+            TestvectorsAction::Generate { out_dir } => {
+                for path in testvectors::write_to_dir(&out_dir)? {
+                    println!("Wrote {}", path.display());
+                }
+            }
+            TestvectorsAction::Check { dir } => {
+                let mut failed = false;
+                for (name, status) in testvectors::check_dir(&dir)? {
+                    println!("{name}: {status:?}");
+                    failed |= status != VectorStatus::Match;
+                }
+                if failed {
+                    return Err(RsaError::UnknownError(
+                        "Test vectors do not match the current formats".into(),
+                    ));
+                }
+                println!("All test vectors match!");
+            }
+        },
+    };
+    Ok(())
+}
+
+/// Opens the file at `path`, or a tar archive of the directory at `path` if `archive`, reporting the progress of reading it on stderr,
+/// and as checkpoints of `chunk_len` bytes chunks if configured in `config`, see [`CheckpointOptions`].
+fn open_with_progress(
+    path: &Path,
+    archive: bool,
+    progress: bool,
+    progress_json: bool,
+    chunk_len: usize,
+    config: &Config,
+) -> RsaResult<ProgressReader<Box<dyn Read>, Progress>> {
+    let (input, total): (Box<dyn Read>, u64) = if archive {
+        // the length of the archive is unknown until it is built
+        (Box::new(ArchiveReader::pack(path)?), 0)
+    } else if is_stdio(path) {
+        // the length of stdin is unknown
+        (Box::new(stdin().lock()), 0)
+    } else {
+        let file = File::open(path)?;
+        let total = file.metadata()?.len();
+        (Box::new(file), total)
+    };
+    let sink = Progress::new(ProgressMode::from_flags(progress, progress_json), total)
+        .with_checkpoints(
+            &CheckpointOptions::from_config(config),
+            path.display().to_string(),
+            chunk_len,
+        )?;
+    Ok(ProgressReader::new(input, total, sink))
+}
+
+/// Output of `encrypt` and `decrypt`, only complete at its path once committed.
+/// Most bytes [`Output::HeldStdout`] holds back, larger plaintexts must be decrypted to a file.
+const HELD_STDOUT_LIMIT: usize = 64 * 1024 * 1024;
+
+enum Output {
+    Stdout(StdoutLock<'static>),
+    /// Stdout, with everything held back until committed, see [`Output::held_back()`].
+    HeldStdout(StdoutLock<'static>, Zeroizing<Vec<u8>>),
+    File(AtomicFile),
+    Unpack(ArchiveWriter),
+}
+
+impl Output {
+    /// Creates the file at `path`, or the directory extracted into with `unpack`,
+    /// replacing an existing one only with `force`, or locks stdout if it is `-`.
+    fn create(path: &Path, force: bool, unpack: bool) -> RsaResult<Self> {
+        if is_stdio(path) {
+            if unpack {
+                return Err(RsaError::UnknownError(
+                    "--unpack extracts into a directory, it cannot be stdout".into(),
+                ));
+            }
+            return Ok(Output::Stdout(stdout().lock()));
+        }
+        if !force {
+            refuse_overwrite(path)?;
+        }
+        Ok(if unpack {
+            Output::Unpack(ArchiveWriter::unpack_to(path)?)
+        } else {
+            Output::File(AtomicFile::create(path)?)
+        })
+    }
+
+    /// Holds back everything written to stdout until committed, so plaintext is only printed
+    /// once verified, as files are only moved to their path then.
+    /// Writes fail past [`HELD_STDOUT_LIMIT`] bytes, rather than holding any amount in memory.
+    fn held_back(self) -> Self {
+        match self {
+            Output::Stdout(stdout) => Output::HeldStdout(stdout, Zeroizing::new(Vec::new())),
+            output => output,
+        }
+    }
+
+    /// Moves the complete output to its path.
+    fn commit(self) -> RsaResult<()> {
+        match self {
+            Output::Stdout(mut stdout) => Ok(stdout.flush()?),
+            Output::HeldStdout(mut stdout, held) => {
+                stdout.write_all(&held)?;
+                Ok(stdout.flush()?)
+            }
+            Output::File(file) => file.commit(),
+            Output::Unpack(unpacker) => unpacker.finish(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::HeldStdout(_, held) => {
+                if held.len() + buf.len() > HELD_STDOUT_LIMIT {
+                    return Err(std::io::Error::other(format!(
+                        "stdout only holds back {} MiB until verified, \
+                         decrypt larger files with `-o` instead",
+                        HELD_STDOUT_LIMIT / (1024 * 1024)
+                    )));
+                }
+                held.write(buf)
+            }
+            Output::File(file) => file.write(buf),
+            Output::Unpack(unpacker) => unpacker.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::HeldStdout(..) => Ok(()),
+            Output::File(file) => file.flush(),
+            Output::Unpack(unpacker) => unpacker.flush(),
+        }
+    }
+}
+
+/// Sets fields of config.toml with `update`, creating the file if missing.
+fn update_config_file(update: impl FnOnce(&mut Config)) -> RsaResult<()> {
+    let config_path = Config::file_path();
+    let mut file_config = if config_path.is_file() {
+        Config::load_from_path(&config_path)?
+    } else {
+        Config::default()
+    };
+    update(&mut file_config);
+    file_config.write_to_path(&config_path)
+}
+
+/// Resolves the path of a key file, falling back to `key_path` in config.toml,
+/// then to the default keys, which are created if missing.
+fn key_path_for(
+    key_path: Option<PathBuf>,
+    config: &Config,
+    variant: KeyVariant,
+) -> RsaResult<PathBuf> {
+    match key_path.or_else(|| config.key_path_for(variant)) {
+        Some(path) => Ok(path),
+        None => {
+            setup::ensure_default_keys()?;
+            Ok(resolve_key_path(None, variant))
+        }
+    }
+}
+
+/// Encrypts every block of `input` with `key`, leaving out its fingerprint if `no_key_hint`.
+fn encode_raw<R: Read, W: Write>(
+    key: &Key,
+    no_key_hint: bool,
+    input: &mut R,
+    output: &mut W,
+    padding: Padding,
+) -> RsaResult<()> {
+    if no_key_hint {
+        key.encode_without_key_hint(input, output, padding.into())
+    } else {
+        key.encode(input, output, padding.into())
+    }
+}
+
+/// Prints a status message to stdout, or to stderr if the output at `out_path`
+/// is stdout itself, to keep it clean for pipelines.
+fn print_status(out_path: &Path, message: &str) {
+    if is_stdio(out_path) {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+/// Amount of input bytes per chunk when decoding the file at `path` with `key`,
+/// whether it is an envelope or not.
+fn decode_chunk_len(key: &Key, path: &Path, padding: PaddingScheme) -> RsaResult<usize> {
+    let mut prefix = [0u8; envelope::ENVELOPE_MAGIC.len()];
+    // stdin cannot be peeked without consuming it
+    let is_envelope = !is_stdio(path)
+        && File::open(path)?.read_exact(&mut prefix).is_ok()
+        && envelope::is_envelope(&prefix);
+    if is_envelope {
+        Ok(envelope::ENVELOPE_SEGMENT_LEN + envelope::ENVELOPE_TAG_LEN)
+    } else {
+        key.chunk_len(padding)
+    }
+}
+
+/// Prints a report as its `text`, or its `json` as JSON or YAML.
+fn print_report(
+    format: OutputFormat,
+    text: &impl std::fmt::Display,
+    json: &serde_json::Value,
+) -> RsaResult<()> {
+    match format {
+        OutputFormat::Text => println!("{text}"),
+        OutputFormat::Json => println!("{json}"),
+        OutputFormat::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(json).map_err(|e| RsaError::UnknownError(e.to_string()))?
+        ),
+    }
+    Ok(())
+}
+
+/// Prints the metadata of the Key at `args.key_path`, for `rrsa key inspect` and its alias.
+fn inspect_key(args: &InspectArgs) -> RsaResult<()> {
+    let report = Key::read_from_path(&args.key_path)?.report();
+    print_report(args.output_format, &report, &report.to_json())
+}
+
+/// Logs every warning as a WARN event, so they do not mix with the output of a command.
+fn print_warnings(warnings: &[Warning]) {
+    for warning in warnings {
+        tracing::warn!("{warning}");
+    }
+}
+
+/// Logs the events of the library and the cli to STDERR,
+/// at INFO level unless raised by `-v`/`-vv` or lowered by `--quiet`.
+fn init_logging(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => tracing::Level::ERROR,
+        (false, 0) => tracing::Level::INFO,
+        (false, 1) => tracing::Level::DEBUG,
+        (false, _) => tracing::Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Prints `number` in decimal and hexadecimal.
+fn print_number(number: &BigUint) {
+    println!("{number}");
+    println!("{number:#x}");
+}
+
+/// Reads a single line from stdin, without its line ending.
+fn read_stdin_line() -> RsaResult<Zeroizing<String>> {
+    let mut line = Zeroizing::new(String::new());
+    stdin().read_line(&mut line)?;
+    let len = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(len);
+    Ok(line)
+}
+
+#[derive(Parser)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    disable_version_flag = true,
+    arg_required_else_help = true
+)]
+struct RsaCli {
+    /// Print version
+    #[arg(short = 'V', long, action = clap::ArgAction::SetTrue)]
+    version: bool,
+    /// OPTIONAL Together with `--version`, also lists the supported format versions
+    #[arg(long, requires = "version", action = clap::ArgAction::SetTrue)]
+    formats: bool,
+    /// OPTIONAL Logs more details to stderr, `-vv` for every detail
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// OPTIONAL Only logs errors to stderr (False if absent)
+    #[arg(short, long, global = true, conflicts_with = "verbose", action = clap::ArgAction::SetTrue)]
+    quiet: bool,
+    #[command(subcommand)]
+    sub_command: Option<RsaCommands>,
+}
+
+#[deny(missing_docs)]
+#[derive(Subcommand)]
+enum RsaCommands {
+    /// Generates a Public and a Private key, and stores then in output file
+    Keygen {
+        /// OPTIONAL Key size in bits (defaults to `key_size` in config.toml, or 4096, must be even and in (32..=4096))
+        #[arg(short, long)]
+        key_size: Option<KeySize>,
+        /// OPTIONAL Path to save key file (Ex: ./keys/key or ./keys/, defaults to `~/.config/rrsa/`),
+        /// directories must be pre-existing.
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Generates a key with non default exponent value (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        ndex: bool,
+        /// OPTIONAL Default exponent to use (defaults to `default_exponent` in config.toml, or 65537)
+        #[arg(long, value_name = "E", conflicts_with = "ndex")]
+        exponent: Option<u32>,
+        /// OPTIONAL Logs the key generation internal results to stderr (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        results: bool,
+        /// OPTIONAL Logs the progress of the key generation to stderr (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        progress: bool,
+        /// OPTIONAL Source of randomness for the prime search
+        #[arg(long, value_enum, default_value_t = Rng::Os)]
+        rng: Rng,
+        /// OPTIONAL Prints an audit of the generated Key Pair against FIPS 186-4 criteria (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        audit: bool,
+        /// OPTIONAL Comment stored in both key files, e.g. `me@laptop`, like an OpenSSH key comment
+        #[arg(short = 'C', long)]
+        comment: Option<String>,
+        /// OPTIONAL Replaces the key files if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Sets up rrsa on first use, generating the default Key Pair
+    Init {
+        /// OPTIONAL Does not ask for confirmation, for use in scripts (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        yes: bool,
+        /// OPTIONAL Key size in bits (defaults to 4096, must be even and in (32..=4096))
+        #[arg(short, long)]
+        key_size: Option<KeySize>,
+    },
+    /// Validates a Key format (at least one of the Keys must be present)
+    /// and/or validates that two Keys are is mathematically
+    /// related (both Public and Private key must be present)
+    Validate {
+        #[command(flatten)]
+        args: ValidateArgs,
+        /// OPTIONAL Format of the result (Defaults to text)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output_format: OutputFormat,
+    },
+    /// Hidden alias of `rrsa key inspect`, kept for scripts calling it at the top level
+    #[command(hide = true)]
+    Inspect {
+        #[command(flatten)]
+        args: InspectArgs,
+    },
+    /// Prints information about a single Key
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+    /// Encrypts a plain text file using a Public Key
+    Encrypt {
+        /// Input file path, `-` for stdin.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Output file path, `-` for stdout (Defaults to cwd, or stdout with stdin)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Path to Public Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+        /// OPTIONAL Path to a bundle of Public Keys (Ex: team.pubs), any of which can decrypt
+        /// the output, see `rrsa bundle`
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["key_path", "raw_rsa"])]
+        key_bundle: Option<PathBuf>,
+        /// OPTIONAL Padding scheme of every encrypted block, only with `--raw-rsa` (Defaults to none)
+        #[arg(long, value_enum, default_value_t = Padding::None, requires = "raw_rsa")]
+        padding: Padding,
+        /// OPTIONAL Encrypts every block of the file with RSA, instead of a symmetric key
+        /// encrypted with RSA (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        raw_rsa: bool,
+        /// OPTIONAL Symmetric cipher of the file, recorded in it, not with `--raw-rsa`
+        /// (Defaults to aes256gcm)
+        #[arg(long, value_enum, default_value_t = Cipher::Aes256gcm, conflicts_with = "raw_rsa")]
+        cipher: Cipher,
+        /// OPTIONAL Leaves out the fingerprint of the recipients, so the file does not reveal
+        /// who can decrypt it, `rrsa decrypt` then tries every Private Key (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        no_key_hint: bool,
+        /// OPTIONAL Wraps the output in base64 ASCII armor (Defaults to `armor` in config.toml)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        armor: bool,
+        /// OPTIONAL Does not wrap the output in ASCII armor, even if enabled in config.toml
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "armor")]
+        no_armor: bool,
+        /// OPTIONAL Encrypts the directory at the input path as a single tar archive,
+        /// restored by `rrsa decrypt --unpack` (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        archive: bool,
+        /// OPTIONAL Replaces the output file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+        /// OPTIONAL Prints the progress, throughput and ETA to stderr (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        progress: bool,
+        /// OPTIONAL Prints the progress as JSON lines to stderr (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        progress_json: bool,
+    },
+    /// Decrypts an encrypted file using a Private Key
+    Decrypt {
+        /// Input file path, `-` for stdin.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Output file path, `-` for stdout, which is only written once the whole
+        /// file is verified, so at most 64 MiB (Defaults to cwd, or stdout with stdin)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+        /// OPTIONAL Tries every Private Key in `~/.config/rrsa/` until one can decrypt
+        /// the first chunk, then uses it (False if absent, unless the file was encrypted
+        /// with `--no-key-hint` and no other Key is given)
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["key_path", "legacy_format"])]
+        any_key: bool,
+        /// OPTIONAL Decrypts an envelope with the Private Key of the escrow agent,
+        /// `escrow_key_path` in the config (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with_all = ["key_path", "any_key"])]
+        as_escrow: bool,
+        /// OPTIONAL Decrypts a legacy `.cypher` file, without any header (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        legacy_format: bool,
+        /// OPTIONAL Skips damaged chunks, filling them with a marker, instead of aborting (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "legacy_format")]
+        best_effort: bool,
+        /// OPTIONAL Extracts a file encrypted with `rrsa encrypt --archive` into
+        /// the output directory (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        unpack: bool,
+        /// OPTIONAL Writes the plaintext to stdout, never to disk, without any progress,
+        /// e.g. to pipe it into a pager, holding it back until the whole file is verified,
+        /// so at most 64 MiB of it (False if absent)
+        #[arg(
+            long = "stdout",
+            action = clap::ArgAction::SetTrue,
+            conflicts_with_all = ["out_path", "unpack"]
+        )]
+        to_stdout: bool,
+        /// OPTIONAL Replaces the output file, or extracts into an existing directory with `--unpack` if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+        /// OPTIONAL Padding scheme of every encrypted block (Defaults to none)
+        #[arg(long, value_enum, default_value_t = Padding::None)]
+        padding: Padding,
+        /// OPTIONAL Prints the progress, throughput and ETA to stderr (False if absent)
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        progress: bool,
+        /// OPTIONAL Prints the progress as JSON lines to stderr (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        progress_json: bool,
+    },
+    /// Verifies the chunk checksums of an encrypted file, or the segments of an envelope,
+    /// without decrypting it
+    VerifyFile {
+        /// Input file path.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Path to either Key of the pair, only the Private Key detects modified
+        /// envelope segments, the Public Key only truncated ones (Defaults to the Private Key
+        /// in `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+        /// OPTIONAL Padding scheme of every encrypted block (Defaults to none)
+        #[arg(long, value_enum, default_value_t = Padding::None)]
+        padding: Padding,
+    },
+    /// Re-keys an envelope for another recipient: only its data key is decrypted and
+    /// encrypted again, the encrypted file itself is copied as is
+    Reencrypt {
+        /// Input file path, `-` for stdin.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// Output file path, `-` for stdout.
+        #[arg(short, long, value_name = "PATH")]
+        out_path: PathBuf,
+        /// Path to the Private Key the envelope was encrypted for.
+        #[arg(long, value_name = "PATH")]
+        from: PathBuf,
+        /// Path to the Public Key of the new recipient.
+        #[arg(long, value_name = "PATH")]
+        to: PathBuf,
+        /// OPTIONAL Replaces the output file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Signs a file using a Private Key
+    Sign {
+        /// File to sign.
+        #[arg(short, long, value_name = "PATH")]
+        file: PathBuf,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+        /// OPTIONAL Signature file path (Defaults to the file path with `.sig` appended)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Signature scheme (Defaults to pss)
+        #[arg(long, value_enum, default_value_t = Scheme::Pss)]
+        scheme: Scheme,
+        /// OPTIONAL Replaces the signature file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Verifies the signature of a file using a Public Key,
+    /// exiting with a failure code if it is not valid
+    Verify {
+        /// Signed file.
+        #[arg(short, long, value_name = "PATH")]
+        file: PathBuf,
+        /// OPTIONAL Signature file path (Defaults to the file path with `.sig` appended)
+        #[arg(short, long, value_name = "PATH")]
+        signature_path: Option<PathBuf>,
+        /// OPTIONAL Path to Public Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+    /// Encrypts or decrypts MIME like messages, as used for email encryption
+    Message {
+        #[command(subcommand)]
+        action: MessageAction,
+    },
+    /// Signs or verifies RS256 JSON Web Tokens
+    Jwt {
+        #[command(subcommand)]
+        action: JwtAction,
+    },
+    /// Encrypts or decrypts short secrets, such as passwords,
+    /// as single lines suitable for config files
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+    /// Encrypts a short message, given or typed in a hidden prompt, printing it as ASCII armor
+    EncryptText {
+        /// OPTIONAL Message to encrypt (Prompted for if absent)
+        #[arg(short, long, value_name = "TEXT")]
+        message: Option<String>,
+        /// OPTIONAL Path to Public Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+    /// Decrypts ASCII armor printed by `rrsa encrypt-text`, read from stdin if not given
+    DecryptText {
+        /// OPTIONAL ASCII armor to decrypt (Read from stdin if absent)
+        #[arg(short, long, value_name = "ARMOR")]
+        armored: Option<String>,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+    /// Encrypts and decrypts a file with both RSA and ElGamal,
+    /// comparing ciphertext expansion and performance
+    #[cfg(feature = "extras")]
+    CompareElgamal {
+        /// Input file path.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Key size in bits of both keys (defaults to 512, must be in (32..=2048))
+        #[arg(short, long, default_value_t = 512, value_parser = clap::value_parser!(u16).range(32..=2048))]
+        key_size: u16,
+    },
+    /// Lists the supported formats, schemes and compiled in features
+    Capabilities {
+        /// OPTIONAL Prints a JSON object instead, for tooling (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        json: bool,
+    },
+    /// Serves encrypt, decrypt and sign requests for loaded keys over a Unix socket,
+    /// see `rrsa_lib::daemon` for the protocol
+    #[cfg(unix)]
+    Daemon {
+        /// Path of the socket to create, readable and writable by its owner only
+        #[arg(short, long, value_name = "PATH")]
+        socket: PathBuf,
+        /// Key Pair to load, as a name for requests and the path to read it from
+        #[arg(short, long = "key", value_name = "NAME=PATH", required = true)]
+        keys: Vec<String>,
+    },
+    /// Moves the default keys to the config or data directory, and sets `storage` in config.toml,
+    /// leaving a pointer file in the config directory when needed.
+    /// Keys in the `RRSA_HOME` directory are not moved
+    MigrateStorage {
+        /// Base directory to store the keys in
+        #[arg(value_enum)]
+        location: Storage,
+    },
+    /// Prepares benchmarks of the encryption and decryption throughput
+    Benchmark {
+        /// Writes the standard corpus (1 KiB to 1 GiB files, random and compressible)
+        /// to a directory, keeping files already generated
+        #[arg(long, value_name = "DIR")]
+        generate_corpus: PathBuf,
+    },
+    /// Modular arithmetic and primality calculator, numbers are decimal or `0x` prefixed hexadecimal,
+    /// optionally with `_` separators
+    Math {
+        #[command(subcommand)]
+        action: MathAction,
+    },
+    /// Adds, removes or lists the Public Keys of a bundle file,
+    /// to encrypt a file for several recipients
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+    /// Generates or checks the canonical golden files of every supported format
+    Testvectors {
+        #[command(subcommand)]
+        action: TestvectorsAction,
+    },
+}
+
+/// Padding scheme of every encrypted block.
+#[derive(Clone, Copy, ValueEnum)]
+enum Padding {
+    /// Textbook RSA, only zero padded
+    None,
+    /// RSAES-OAEP with SHA-256
+    Oaep,
+    /// RSAES-PKCS1-v1_5
+    Pkcs1v15,
+}
+
+impl From<Padding> for PaddingScheme {
+    fn from(padding: Padding) -> Self {
+        match padding {
+            Padding::None => PaddingScheme::None,
+            Padding::Oaep => PaddingScheme::Oaep,
+            Padding::Pkcs1v15 => PaddingScheme::Pkcs1v15,
+        }
+    }
+}
+
+/// Symmetric cipher of an envelope, see [`EnvelopeCipher`].
+#[derive(Clone, Copy, ValueEnum)]
+enum Cipher {
+    /// AES-256-GCM, fastest with AES instructions
+    Aes256gcm,
+    /// ChaCha20-Poly1305, fastest without AES instructions
+    Chacha20,
+}
+
+impl From<Cipher> for EnvelopeCipher {
+    fn from(cipher: Cipher) -> Self {
+        match cipher {
+            Cipher::Aes256gcm => EnvelopeCipher::Aes256Gcm,
+            Cipher::Chacha20 => EnvelopeCipher::ChaCha20Poly1305,
+        }
+    }
+}
+
+/// Output format of reports.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human readable lines
+    Text,
+    /// A single JSON object
+    Json,
+    /// A YAML document
+    Yaml,
+}
+
+/// Signature scheme and hash algorithm.
+#[derive(Clone, Copy, ValueEnum)]
+enum Scheme {
+    /// RSASSA-PSS with SHA-256
+    Pss,
+    /// RSASSA-PKCS1-v1_5 with SHA-256
+    Pkcs1v15Sha256,
+    /// RSASSA-PKCS1-v1_5 with SHA-512
+    Pkcs1v15Sha512,
+}
+
+impl From<Scheme> for SignatureScheme {
+    fn from(scheme: Scheme) -> Self {
+        match scheme {
+            Scheme::Pss => SignatureScheme::Pss,
+            Scheme::Pkcs1v15Sha256 => SignatureScheme::Pkcs1v15(HashAlgorithm::Sha256),
+            Scheme::Pkcs1v15Sha512 => SignatureScheme::Pkcs1v15(HashAlgorithm::Sha512),
+        }
+    }
+}
+
+/// Source of randomness, see [`RandomSource`].
+#[derive(Clone, Copy, ValueEnum)]
+enum Rng {
+    /// Every value read from the operating system
+    Os,
+    /// ChaCha20 DRBG seeded and periodically reseeded from the operating system
+    Chacha20,
+}
+
+impl From<Rng> for RandomSource {
+    fn from(rng: Rng) -> Self {
+        match rng {
+            Rng::Os => RandomSource::Os,
+            Rng::Chacha20 => RandomSource::ChaCha20,
+        }
+    }
+}
+
+/// Base directory of the default keys directory.
+#[derive(Clone, Copy, ValueEnum)]
+enum Storage {
+    /// Platform config directory (e.g. `$XDG_CONFIG_HOME/rrsa/`)
+    Config,
+    /// Platform data directory (e.g. `$XDG_DATA_HOME/rrsa/`)
+    Data,
+}
+
+impl From<Storage> for StorageLocation {
+    fn from(storage: Storage) -> Self {
+        match storage {
+            Storage::Config => StorageLocation::Config,
+            Storage::Data => StorageLocation::Data,
+        }
+    }
+}
+
+/// Format of a Key, see [`KeyFormat`].
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Native rrsa format
+    Rrsa,
+    /// PKCS#1 PEM, as written by OpenSSL
+    Pem,
+    /// PKCS#1 DER
+    Der,
+    /// OpenSSH public key line (Public Keys only)
+    Openssh,
+    /// JSON Web Key
+    #[cfg(feature = "jwk")]
+    Jwk,
+}
+
+impl From<Format> for KeyFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Rrsa => KeyFormat::Rrsa,
+            Format::Pem => KeyFormat::Pem,
+            Format::Der => KeyFormat::Der,
+            Format::Openssh => KeyFormat::OpenSsh,
+            #[cfg(feature = "jwk")]
+            Format::Jwk => KeyFormat::Jwk,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum KeyAction {
+    /// Prints the fingerprint of a Key as hex and as randomart, to verify it out-of-band
+    Fingerprint {
+        /// Path to either Key of a pair, both have the same fingerprint
+        #[arg(short, long, value_name = "PATH")]
+        key_path: PathBuf,
+    },
+    /// Prints the metadata of a Key in any supported format: variant, size, public exponent
+    /// and whether it is the default, fingerprint, and whether a Private Key has CRT components
+    Inspect {
+        #[command(flatten)]
+        args: InspectArgs,
+    },
+    /// Converts a Key between formats, e.g. `rrsa key convert -k key --to pem`
+    Convert {
+        /// Path to the Key to convert
+        #[arg(short, long, value_name = "PATH")]
+        key_path: PathBuf,
+        /// OPTIONAL Output file path (Defaults to stdout)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Format of the Key (Detected from its contents if absent)
+        #[arg(long, value_enum)]
+        from: Option<Format>,
+        /// Format of the output
+        #[arg(long, value_enum)]
+        to: Format,
+        /// OPTIONAL Replaces the output file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Makes a Key Pair of the keys directory the default one, used when no key path is given,
+    /// by setting `key_path` in config.toml
+    Use {
+        /// File name of the Private Key inside the keys directory, e.g. `work` for `work`
+        /// and `work.pub`
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MathAction {
+    /// Prints `BASE^EXPONENT mod MODULUS`
+    Modpow {
+        #[arg(value_parser = parse_number)]
+        base: BigUint,
+        #[arg(value_parser = parse_number)]
+        exponent: BigUint,
+        #[arg(value_parser = parse_number)]
+        modulus: BigUint,
+    },
+    /// Tests whether a number is prime with Miller-Rabin
+    IsPrime {
+        #[arg(value_parser = parse_number)]
+        number: BigUint,
+    },
+    /// Prints a random prime number
+    GenPrime {
+        /// Maximum size of the prime in bits (must be in (2..=4096))
+        #[arg(short, long, value_parser = clap::value_parser!(u16).range(2..=4096))]
+        bits: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum BundleAction {
+    /// Adds a Public Key to a bundle, creating the bundle file if needed
+    Add {
+        /// Bundle file path (Ex: team.pubs)
+        #[arg(short, long, value_name = "PATH")]
+        bundle_path: PathBuf,
+        /// Label of the Key, unique in the bundle
+        #[arg(short, long)]
+        label: String,
+        /// Path to the Public Key
+        #[arg(short, long, value_name = "PATH")]
+        key_path: PathBuf,
+    },
+    /// Removes a Public Key from a bundle
+    Remove {
+        /// Bundle file path (Ex: team.pubs)
+        #[arg(short, long, value_name = "PATH")]
+        bundle_path: PathBuf,
+        /// Label of the Key
+        #[arg(short, long)]
+        label: String,
+    },
+    /// Lists the label and fingerprint of every Public Key in a bundle
+    List {
+        /// Bundle file path (Ex: team.pubs)
+        #[arg(short, long, value_name = "PATH")]
+        bundle_path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TestvectorsAction {
+    /// Writes the golden files to a directory
+    Generate {
+        /// Output directory path (created if not present)
+        #[arg(short, long, value_name = "PATH")]
+        out_dir: PathBuf,
+    },
+    /// Checks the golden files in a directory against the current formats
+    Check {
+        /// Directory path containing the golden files
+        #[arg(short, long, value_name = "PATH")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum MessageAction {
+    /// Encrypts a file as the body of a message to the owner of a Public Key
+    Encrypt {
+        /// Input file path.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Output file path (Defaults to cwd)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// Path to the Public Key of the recipient
+        #[arg(long, value_name = "PATH")]
+        to: PathBuf,
+        /// OPTIONAL Subject of the message, which is not encrypted
+        #[arg(short, long, default_value = "")]
+        subject: String,
+        /// OPTIONAL Leaves out the fingerprint of the recipient, so the message
+        /// does not reveal who can read it (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        no_key_hint: bool,
+        /// OPTIONAL Replaces the output file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    /// Decrypts the body of a message using a Private Key
+    Decrypt {
+        /// Input file path.
+        #[arg(short, long, value_name = "PATH")]
+        in_path: PathBuf,
+        /// OPTIONAL Output file path (Defaults to cwd)
+        #[arg(short, long, value_name = "PATH")]
+        out_path: Option<PathBuf>,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`,
+        /// where every Private Key is tried for messages without a recipient)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+        /// OPTIONAL Replaces the output file if it already exists (False if absent)
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretAction {
+    /// Prints a secret read from stdin as an encrypted single line
+    Encrypt {
+        /// OPTIONAL Reads the secret from a hidden prompt instead of stdin
+        #[arg(short = 'P', long, action = clap::ArgAction::SetTrue)]
+        prompt: bool,
+        /// OPTIONAL Path to Public Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+    /// Prints the secret of an encrypted line, read from stdin if not given
+    Decrypt {
+        /// OPTIONAL Encrypted line (Ex: 'rrsa-secret:1:...')
+        #[arg(short, long, value_name = "LINE")]
+        secret: Option<String>,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum JwtAction {
+    /// Prints a token with the given claims, signed by a Private Key
+    Sign {
+        /// Claims of the token, as a JSON object (Ex: '{"sub":"rrsa"}')
+        #[arg(short, long, value_name = "JSON")]
+        claims: String,
+        /// OPTIONAL Path to Private Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+    /// Prints the claims of a token, if its signature is valid and it has not expired
+    Verify {
+        /// Token to verify
+        #[arg(short, long)]
+        token: String,
+        /// OPTIONAL Path to Public Key (Defaults to `~/.config/rrsa/`)
+        #[arg(short, long, value_name = "PATH")]
+        key_path: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct InspectArgs {
+    /// Path to either Key of a pair
+    #[arg(short, long, value_name = "PATH")]
+    key_path: PathBuf,
+    /// OPTIONAL Format of the metadata (Defaults to text)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = true)]
+struct ValidateArgs {
+    /// Path to a Public Key.
+    #[arg(short, long, value_name = "PATH")]
+    public_key_path: Option<PathBuf>,
+    /// Path to a Private Key.
+    #[arg(short = 'k', long, value_name = "PATH")]
+    private_key_path: Option<PathBuf>,
+}
@@ -0,0 +1,246 @@
+//! Progress reporting of long running operations, always written to stderr,
+//! and checkpoints appended to the configured log file.
+
+use indicatif::{FormattedDuration, HumanBytes, HumanDuration, ProgressBar, ProgressStyle};
+use rrsa_lib::{
+    error::RsaResult,
+    progress::{CheckpointOptions, ProgressSink},
+};
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::Write,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How progress is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    Hidden,
+    /// A progress bar with smoothed throughput and ETA.
+    Bar,
+    /// One JSON object per line, for external UIs and scripts.
+    Json,
+}
+
+impl ProgressMode {
+    pub fn from_flags(progress: bool, progress_json: bool) -> Self {
+        if progress_json {
+            ProgressMode::Json
+        } else if progress {
+            ProgressMode::Bar
+        } else {
+            ProgressMode::Hidden
+        }
+    }
+}
+
+/// Checkpoint lines appended to a log file, see [`CheckpointOptions`].
+struct Checkpoints {
+    log: File,
+    interval: Duration,
+    /// Name of the operation, e.g. the input file.
+    label: String,
+    chunk_len: u64,
+    started: Instant,
+    last_checkpoint: Instant,
+}
+
+/// Tracks processed bytes, with a moving average of the throughput.
+pub struct Progress {
+    mode: ProgressMode,
+    bar: Option<ProgressBar>,
+    total: u64,
+    done: u64,
+    samples: VecDeque<(Instant, u64)>,
+    last_report: Option<Instant>,
+    checkpoints: Option<Checkpoints>,
+}
+
+impl Progress {
+    /// Time window of the throughput moving average.
+    const WINDOW: Duration = Duration::from_secs(5);
+    /// Minimum time between two reports.
+    const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn new(mode: ProgressMode, total: u64) -> Self {
+        let bar = (mode == ProgressMode::Bar).then(|| {
+            let bar = ProgressBar::new(total);
+            if let Ok(style) = ProgressStyle::with_template(
+                "[{elapsed_precise}] {wide_bar} {bytes}/{total_bytes} {msg}",
+            ) {
+                bar.set_style(style);
+            }
+            bar
+        });
+        Self {
+            mode,
+            bar,
+            total,
+            done: 0,
+            samples: VecDeque::from([(Instant::now(), 0)]),
+            last_report: None,
+            checkpoints: None,
+        }
+    }
+
+    /// Appends a checkpoint line to the log file of `options` every `options.interval`,
+    /// and when done, counting chunks of `chunk_len` bytes.
+    ///
+    /// Does nothing if `options` has no log file.
+    pub fn with_checkpoints(
+        mut self,
+        options: &CheckpointOptions,
+        label: String,
+        chunk_len: usize,
+    ) -> RsaResult<Self> {
+        let Some(log_path) = &options.log_path else {
+            return Ok(self);
+        };
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?;
+        writeln!(
+            log,
+            "{} {label}: started, {} bytes",
+            unix_time(),
+            self.total
+        )?;
+        let now = Instant::now();
+        self.checkpoints = Some(Checkpoints {
+            log,
+            interval: options.interval,
+            label,
+            chunk_len: chunk_len.max(1) as u64,
+            started: now,
+            last_checkpoint: now,
+        });
+        Ok(self)
+    }
+
+    /// Moving average of the throughput, in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((first_at, first_done)), Some((last_at, last_done))) => {
+                let seconds = last_at.duration_since(*first_at).as_secs_f64();
+                if seconds > 0.0 {
+                    (last_done - first_done) as f64 / seconds
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Estimated time left, based on the smoothed throughput.
+    pub fn eta(&self) -> Option<Duration> {
+        let throughput = self.throughput();
+        (throughput > 0.0).then(|| {
+            Duration::from_secs_f64(self.total.saturating_sub(self.done) as f64 / throughput)
+        })
+    }
+
+    /// Appends a checkpoint line, if one is due or the operation is `finished`.
+    ///
+    /// A failing log never aborts the operation, checkpoints are disabled with a warning instead.
+    fn checkpoint(&mut self, finished: bool) {
+        let throughput = self.throughput();
+        let eta = self.eta();
+        let Some(checkpoints) = &mut self.checkpoints else {
+            return;
+        };
+        let now = Instant::now();
+        if !finished && now.duration_since(checkpoints.last_checkpoint) < checkpoints.interval {
+            return;
+        }
+        checkpoints.last_checkpoint = now;
+        let status = if finished {
+            "finished".to_string()
+        } else {
+            format!(
+                "ETA {}",
+                eta.map_or("-".into(), |eta| HumanDuration(eta).to_string())
+            )
+        };
+        let line = format!(
+            "{} {}: [{}] {}/{} chunks, {}/{}, {}/s, {status}",
+            unix_time(),
+            checkpoints.label,
+            FormattedDuration(now.duration_since(checkpoints.started)),
+            self.done.div_ceil(checkpoints.chunk_len),
+            self.total.div_ceil(checkpoints.chunk_len),
+            HumanBytes(self.done),
+            HumanBytes(self.total),
+            HumanBytes(throughput as u64),
+        );
+        if let Err(e) = writeln!(checkpoints.log, "{line}") {
+            tracing::warn!("Could not write checkpoint, disabling checkpoints: {e}");
+            self.checkpoints = None;
+        }
+    }
+
+    fn report(&self, finished: bool) {
+        match self.mode {
+            ProgressMode::Hidden => {}
+            ProgressMode::Bar => {
+                if let Some(bar) = &self.bar {
+                    bar.set_position(self.done);
+                    bar.set_message(format!(
+                        "{}/s ETA {}",
+                        HumanBytes(self.throughput() as u64),
+                        self.eta()
+                            .map_or("-".into(), |eta| HumanDuration(eta).to_string())
+                    ));
+                    if finished {
+                        bar.finish();
+                    }
+                }
+            }
+            ProgressMode::Json => {
+                eprintln!(
+                    "{{\"done\":{},\"total\":{},\"bytes_per_sec\":{:.0},\"eta_secs\":{},\"finished\":{}}}",
+                    self.done,
+                    self.total,
+                    self.throughput(),
+                    self.eta()
+                        .map_or("null".into(), |eta| eta.as_secs().to_string()),
+                    finished
+                );
+            }
+        }
+    }
+}
+
+impl ProgressSink for Progress {
+    fn progress(&mut self, processed: u64, total: u64) {
+        self.done = processed;
+        self.total = total;
+        let now = Instant::now();
+        self.samples.push_back((now, self.done));
+        while self.samples.len() > 2 && now.duration_since(self.samples[0].0) > Progress::WINDOW {
+            self.samples.pop_front();
+        }
+        if self
+            .last_report
+            .is_none_or(|last| now.duration_since(last) >= Progress::REPORT_INTERVAL)
+        {
+            self.last_report = Some(now);
+            self.report(false);
+        }
+        self.checkpoint(false);
+    }
+
+    fn finish(&mut self) {
+        self.report(true);
+        self.checkpoint(true);
+    }
+}
+
+/// Seconds since the Unix epoch, so checkpoints can be matched with other logs.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
@@ -0,0 +1,76 @@
+//! First run setup, bootstrapping the default Key Pair.
+
+use rrsa_lib::{
+    config::Config,
+    error::{RsaError, RsaResult},
+    key::{Key, KeyGenOptions, KeyPair, KeySize},
+};
+use std::io::{stderr, stdin, IsTerminal, Write};
+
+/// Returns `true` if neither of the default key files exist yet.
+pub fn is_first_run() -> bool {
+    let default_dir = Key::default_dir();
+    !default_dir.join(Key::DEFAULT_PUBLIC_KEY_NAME).is_file()
+        && !default_dir.join(Key::DEFAULT_PRIVATE_KEY_NAME).is_file()
+}
+
+/// Asks a yes/no `question` on stderr, defaulting to no.
+///
+/// Always answers no if stdin is not a terminal.
+pub fn confirm(question: &str) -> RsaResult<bool> {
+    if !stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{question} [y/N] ");
+    stderr().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+/// Generates the default Key Pair with the configured [`KeyGenOptions`] and `key_size` if given,
+/// writes it to the default directory and prints its location and fingerprint.
+///
+/// If there is no config file yet, one is written with the keys directory and `key_size`.
+pub fn bootstrap(key_size: Option<KeySize>, progress: bool) -> RsaResult<()> {
+    let mut options = KeyGenOptions::load()?;
+    options.key_size_bits = key_size.or(options.key_size_bits);
+    let key_pair = KeyPair::generate_with_options(&options, false, progress)?;
+    key_pair.write_to_default()?;
+    println!("Key Pair written to {}", Key::default_dir().display());
+
+    let config_path = Config::file_path();
+    if !config_path.exists() {
+        let config = Config {
+            key_size,
+            key_path: Some(Key::default_dir()),
+            ..Config::default()
+        };
+        config.write_to_path(&config_path)?;
+        println!("Config written to {}", config_path.display());
+    }
+    println!("Fingerprint: {}", key_pair.public_key.fingerprint_hex());
+    Ok(())
+}
+
+/// On first run, offers to bootstrap the default Key Pair if running interactively.
+///
+/// # Errors
+/// If there are no default keys and the bootstrap was declined.
+pub fn ensure_default_keys() -> RsaResult<()> {
+    if !is_first_run() {
+        return Ok(());
+    }
+    let question = format!(
+        "No keys found in {}. Generate a default Key Pair now?",
+        Key::default_dir().display()
+    );
+    if confirm(&question)? {
+        return bootstrap(None, true);
+    }
+    Err(RsaError::UnknownError(format!(
+        "No keys found in {}, run `init` to generate them or pass a key path",
+        Key::default_dir().display()
+    )))
+}
@@ -0,0 +1,29 @@
+//! Writes the seed corpus of a fuzz target of `rrsa_lib::encoding::parse_container()`,
+//! see `rrsa_lib::encoding::container::corpus()`.
+
+use clap::Parser;
+use rrsa_lib::{encoding::container, error::RsaResult};
+use std::{fs::create_dir_all, path::PathBuf};
+
+/// Writes valid and mutated encoded inputs, one per file, to seed a fuzz target
+#[derive(Parser)]
+#[command(author, version)]
+struct FuzzCorpusCli {
+    /// Directory of the corpus, created if needed (Ex: fuzz/corpus/parse_container).
+    #[arg(short, long, value_name = "PATH")]
+    out_dir: PathBuf,
+}
+
+fn main() -> Result<(), String> {
+    run(&FuzzCorpusCli::parse()).map_err(|e| e.to_string())
+}
+
+fn run(cli: &FuzzCorpusCli) -> RsaResult<()> {
+    create_dir_all(&cli.out_dir)?;
+    let corpus = container::corpus()?;
+    for (name, contents) in &corpus {
+        std::fs::write(cli.out_dir.join(name), contents)?;
+    }
+    println!("Wrote {} inputs to {}", corpus.len(), cli.out_dir.display());
+    Ok(())
+}
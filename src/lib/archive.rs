@@ -0,0 +1,315 @@
+//! Module with the packing of a directory into a tar archive streamed to the encoders,
+//! and its unpacking as the decoders write it, so whole trees are encrypted as a single file.
+//!
+//! The archive is built and extracted on a separate thread, so it is never held in memory whole.
+
+use std::{
+    fs::{create_dir_all, read_dir, remove_dir_all, rename},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{sync_channel, Receiver, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use crate::atomic::create_tmp_dir_for;
+use crate::error::{RsaError, RsaResult};
+
+/// Amount of written chunks buffered between the two threads.
+const PIPE_DEPTH: usize = 16;
+
+/// Reads a tar archive of a directory while it is being built.
+///
+/// ```
+/// use rrsa_lib::archive::{ArchiveReader, ArchiveWriter};
+/// use rrsa_lib::key::KeyPair;
+///
+/// let source = tempfile::tempdir().unwrap();
+/// std::fs::write(source.path().join("notes.txt"), b"remember the milk").unwrap();
+///
+/// let pair = KeyPair::demo();
+/// let mut sealed = Vec::new();
+/// let mut archive = ArchiveReader::pack(source.path()).unwrap();
+/// pair.public_key.encode_envelope(&mut archive, &mut sealed).unwrap();
+///
+/// let destination = tempfile::tempdir().unwrap();
+/// let mut unpacker = ArchiveWriter::unpack_to(destination.path()).unwrap();
+/// pair.private_key.decode_envelope(&mut sealed.as_slice(), &mut unpacker).unwrap();
+/// unpacker.finish().unwrap();
+/// assert_eq!(
+///     std::fs::read(destination.path().join("notes.txt")).unwrap(),
+///     b"remember the milk"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct ArchiveReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ArchiveReader {
+    /// Starts archiving every file and subdirectory of `dir`, with paths relative to it.
+    ///
+    /// # Errors
+    /// If `dir` is not a directory. Errors while archiving are returned by [`Read::read()`].
+    pub fn pack(dir: &Path) -> RsaResult<Self> {
+        if !dir.is_dir() {
+            return Err(RsaError::UnknownError(format!(
+                "{} is not a directory",
+                dir.display()
+            )));
+        }
+        let dir = dir.to_path_buf();
+        let (sender, receiver) = sync_channel(PIPE_DEPTH);
+        let error_sender = sender.clone();
+        thread::spawn(move || {
+            let mut builder = tar::Builder::new(PipeWriter { sender });
+            let packed = builder
+                .append_dir_all(".", &dir)
+                .and_then(|()| builder.into_inner())
+                .map(drop);
+            if let Err(err) = packed {
+                // the reader may be gone already, then nobody needs the error
+                let _ = error_sender.send(Err(err));
+            }
+        });
+        Ok(ArchiveReader {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        })
+    }
+}
+
+impl Read for ArchiveReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.position = 0;
+                }
+                // every sender is dropped once the archive is complete
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Extracts a tar archive into a directory while it is being written.
+///
+/// Entries with absolute paths or `..` components are skipped, so nothing is written
+/// outside of the directory. See [`ArchiveReader`] for an example.
+///
+/// Everything is extracted into a temporary sibling of the directory, only moved to it
+/// by [`ArchiveWriter::finish()`], so a decoder failing midway leaves nothing behind.
+/// Dropping it without finishing removes the temporary directory.
+#[derive(Debug)]
+pub struct ArchiveWriter {
+    writer: Option<PipeWriter>,
+    unpacking: Option<JoinHandle<io::Result<()>>>,
+    dir: PathBuf,
+    tmp_dir: PathBuf,
+}
+
+impl ArchiveWriter {
+    /// Starts extracting into a temporary directory of `dir`, see [`create_tmp_dir_for()`],
+    /// creating the parents of `dir` if needed.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from creating the directories.
+    pub fn unpack_to(dir: &Path) -> RsaResult<Self> {
+        if let Some(parent) = dir.parent() {
+            create_dir_all(parent)?;
+        }
+        let tmp_dir = create_tmp_dir_for(dir)?;
+        let unpack_dir = tmp_dir.clone();
+        let (sender, receiver) = sync_channel(PIPE_DEPTH);
+        let unpacking = thread::spawn(move || {
+            tar::Archive::new(ArchiveReader {
+                receiver,
+                chunk: Vec::new(),
+                position: 0,
+            })
+            .unpack(&unpack_dir)
+        });
+        Ok(ArchiveWriter {
+            writer: Some(PipeWriter { sender }),
+            unpacking: Some(unpacking),
+            dir: dir.to_path_buf(),
+            tmp_dir,
+        })
+    }
+
+    /// Waits for the extraction of everything written so far, then moves it to the directory,
+    /// merging it into an existing one, whose files with the same paths are replaced.
+    ///
+    /// # Errors
+    /// If the written bytes are not a complete tar archive, or extracting or moving it failed.
+    pub fn finish(mut self) -> RsaResult<()> {
+        // closing the pipe ends the archive
+        self.writer = None;
+        self.join()?;
+        Ok(move_into(&self.tmp_dir, &self.dir)?)
+    }
+
+    /// Waits for the extracting thread, returning its error.
+    fn join(&mut self) -> io::Result<()> {
+        match self.unpacking.take().map(JoinHandle::join) {
+            Some(Ok(unpacked)) => unpacked,
+            Some(Err(_)) => Err(io::Error::other("the archive extraction panicked")),
+            // already joined by a write, which returned its error
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ArchiveWriter {
+    fn drop(&mut self) {
+        // the extraction must stop before its directory is removed
+        self.writer = None;
+        let _ = self.join();
+        let _ = remove_dir_all(&self.tmp_dir);
+    }
+}
+
+/// Moves `from` to `to`, or if `to` is an existing directory, every entry of `from` into it,
+/// merging the directories existing in both and replacing everything else.
+fn move_into(from: &Path, to: &Path) -> io::Result<()> {
+    let is_dir = |path: &Path| path.symlink_metadata().is_ok_and(|meta| meta.is_dir());
+    if !is_dir(to) {
+        return rename(from, to);
+    }
+    for entry in read_dir(from)? {
+        let entry = entry?;
+        let destination = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() && is_dir(&destination) {
+            move_into(&entry.path(), &destination)?;
+        } else {
+            rename(entry.path(), destination)?;
+        }
+    }
+    Ok(())
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(writer) = &mut self.writer else {
+            // the end of the archive was reached, what follows is padding
+            return Ok(buf.len());
+        };
+        if writer.write(buf).is_err() {
+            // the extraction stopped, either at the end of the archive or on an error
+            self.writer = None;
+            self.join()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sends every write as a chunk to the other end of a channel.
+#[derive(Debug)]
+struct PipeWriter {
+    sender: SyncSender<io::Result<Vec<u8>>>,
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            self.sender
+                .send(Ok(buf.to_vec()))
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let source = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(source.path().join("nested/deeper")).unwrap();
+        std::fs::write(source.path().join("top.txt"), b"top").unwrap();
+        let big = vec![7u8; 300_000];
+        std::fs::write(source.path().join("nested/deeper/big.bin"), &big).unwrap();
+
+        let mut archive = Vec::new();
+        ArchiveReader::pack(source.path())
+            .unwrap()
+            .read_to_end(&mut archive)
+            .unwrap();
+
+        let destination = tempfile::tempdir().unwrap();
+        let mut unpacker = ArchiveWriter::unpack_to(&destination.path().join("out")).unwrap();
+        unpacker.write_all(&archive).unwrap();
+        unpacker.finish().unwrap();
+        let out = destination.path().join("out");
+        assert_eq!(std::fs::read(out.join("top.txt")).unwrap(), b"top");
+        assert_eq!(
+            std::fs::read(out.join("nested/deeper/big.bin")).unwrap(),
+            big
+        );
+
+        // extracting again into it merges the trees, replacing the files
+        std::fs::write(out.join("kept.txt"), b"kept").unwrap();
+        std::fs::write(out.join("top.txt"), b"changed").unwrap();
+        let mut unpacker = ArchiveWriter::unpack_to(&out).unwrap();
+        unpacker.write_all(&archive).unwrap();
+        unpacker.finish().unwrap();
+        assert_eq!(std::fs::read(out.join("top.txt")).unwrap(), b"top");
+        assert_eq!(std::fs::read(out.join("kept.txt")).unwrap(), b"kept");
+        assert_eq!(
+            std::fs::read(out.join("nested/deeper/big.bin")).unwrap(),
+            big
+        );
+
+        assert!(ArchiveReader::pack(&source.path().join("top.txt")).is_err());
+        {
+            let mut unpacker = ArchiveWriter::unpack_to(&destination.path().join("bad")).unwrap();
+            let written = unpacker.write_all(&[1u8; 1024]);
+            assert!(written.is_err() || unpacker.finish().is_err());
+        }
+        // nothing is left of a failed extraction, nor of the temporary directories
+        let entries: Vec<_> = std::fs::read_dir(destination.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["out"]);
+    }
+
+    #[test]
+    fn test_archive_unfinished() {
+        let source = tempfile::tempdir().unwrap();
+        std::fs::write(source.path().join("secret.txt"), b"not yet verified").unwrap();
+        let mut archive = Vec::new();
+        ArchiveReader::pack(source.path())
+            .unwrap()
+            .read_to_end(&mut archive)
+            .unwrap();
+
+        // a complete archive, but the decoder fails before finishing
+        let destination = tempfile::tempdir().unwrap();
+        let out = destination.path().join("out");
+        let mut unpacker = ArchiveWriter::unpack_to(&out).unwrap();
+        unpacker.write_all(&archive).unwrap();
+        assert!(!out.exists());
+        drop(unpacker);
+        assert!(!out.exists());
+        assert_eq!(std::fs::read_dir(destination.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Module with atomic writes of output files, through a temporary sibling renamed
+//! over the output once complete, so an interrupted run never leaves a truncated file behind.
+
+use std::{
+    fs::{create_dir, remove_file, rename, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::error::RsaResult;
+
+/// Extension appended to the name of an output while it is being written.
+pub const TMP_EXTENSION: &str = "tmp";
+
+/// Counter making the temporary files of a process unique, see [`tmp_path_for()`].
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Path of a temporary sibling of `path`, unique to this process and `counter`,
+/// e.g. `msg.txt.encoded.4242-0.tmp`.
+#[must_use]
+pub fn tmp_path_for(path: &Path, counter: u64) -> PathBuf {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}-{counter}.", std::process::id()));
+    tmp_name.push(TMP_EXTENSION);
+    path.with_file_name(tmp_name)
+}
+
+/// Creates a new temporary directory of `path`, never reusing an existing one,
+/// e.g. to extract into it before it is moved to `path`.
+///
+/// # Returns
+/// The path of the temporary directory.
+///
+/// # Errors
+/// Propagates [`std::io::Error`].
+pub fn create_tmp_dir_for(path: &Path) -> RsaResult<PathBuf> {
+    Ok(create_new_tmp(path, |tmp_path| create_dir(tmp_path))?.0)
+}
+
+/// Creates a temporary sibling of `path` with `create`, which must fail with
+/// [`io::ErrorKind::AlreadyExists`] rather than reuse an existing one, trying the next name then.
+pub(crate) fn create_new_tmp<T>(
+    path: &Path,
+    create: impl Fn(&Path) -> io::Result<T>,
+) -> io::Result<(PathBuf, T)> {
+    loop {
+        let tmp_path = tmp_path_for(path, TMP_COUNTER.fetch_add(1, Ordering::Relaxed));
+        match create(&tmp_path) {
+            Ok(created) => return Ok((tmp_path, created)),
+            // left over by a crashed process with the same id
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Writes `contents` to `path` atomically, see [`AtomicFile`].
+///
+/// # Errors
+/// Propagates [`std::io::Error`].
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> RsaResult<()> {
+    let mut file = AtomicFile::create(path)?;
+    file.write_all(contents.as_ref())?;
+    file.commit()
+}
+
+/// File written to a [`tmp_path_for()`] its path, only renamed to it by [`AtomicFile::commit()`].
+///
+/// Dropping it without committing removes the temporary file, leaving any previous file untouched.
+///
+/// ```
+/// use rrsa_lib::atomic::AtomicFile;
+/// use std::io::Write;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("out.txt");
+/// let mut file = AtomicFile::create(&path).unwrap();
+/// file.write_all(b"complete").unwrap();
+/// assert!(!path.exists());
+/// file.commit().unwrap();
+/// assert_eq!(std::fs::read(&path).unwrap(), b"complete");
+/// ```
+#[derive(Debug)]
+pub struct AtomicFile {
+    file: Option<File>,
+    path: PathBuf,
+    tmp_path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Creates a new temporary file of `path`, never opening an existing one,
+    /// so concurrent writers of the same output each get their own.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn create(path: &Path) -> RsaResult<Self> {
+        let (tmp_path, file) = create_new_tmp(path, |tmp_path| {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(tmp_path)
+        })?;
+        Ok(AtomicFile {
+            file: Some(file),
+            path: path.to_path_buf(),
+            tmp_path,
+        })
+    }
+
+    /// Flushes the written bytes to disk, then renames the temporary file over the output.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`], the temporary file is removed then.
+    pub fn commit(mut self) -> RsaResult<()> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        rename(&self.tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        self.file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("the file was already committed"))
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // after a successful commit the temporary file no longer exists
+        self.file = None;
+        let _ = remove_file(&self.tmp_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.encoded");
+        let files = || std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(
+            tmp_path_for(&path, 7),
+            dir.path()
+                .join(format!("out.encoded.{}-7.tmp", std::process::id()))
+        );
+
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+
+        // an interrupted write leaves the previous output untouched
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"partial").unwrap();
+        assert!(file.tmp_path.is_file());
+        drop(file);
+        assert_eq!(files(), 1);
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"second").unwrap();
+        file.commit().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert_eq!(files(), 1);
+    }
+
+    #[test]
+    fn test_atomic_file_concurrent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.encoded");
+        // a leftover of another process is neither reused nor removed
+        let leftover = tmp_path_for(&path, TMP_COUNTER.load(Ordering::Relaxed));
+        std::fs::write(&leftover, b"leftover").unwrap();
+
+        let mut first = AtomicFile::create(&path).unwrap();
+        let mut second = AtomicFile::create(&path).unwrap();
+        assert_ne!(first.tmp_path, second.tmp_path);
+        assert_ne!(first.tmp_path, leftover);
+        first.write_all(b"first").unwrap();
+        second.write_all(b"second").unwrap();
+        second.commit().unwrap();
+        first.commit().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+        assert_eq!(std::fs::read(&leftover).unwrap(), b"leftover");
+    }
+}
@@ -0,0 +1,116 @@
+//! Module describing what this build of the crate supports,
+//! for tooling that drives rrsa programmatically.
+
+use serde_json::{json, Value};
+
+use crate::key::KeySize;
+use crate::version;
+
+/// Structured description of the formats, schemes and features of this build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Version of this crate.
+    pub version: &'static str,
+    /// Key formats that can be read and written.
+    pub key_formats: Vec<&'static str>,
+    /// Padding schemes of encrypted blocks.
+    pub padding_schemes: Vec<&'static str>,
+    /// Signature schemes, with their hash algorithm.
+    pub signature_schemes: Vec<&'static str>,
+    /// Name and current version of every versioned format, see [`version::FORMATS`].
+    pub formats: Vec<(&'static str, u32)>,
+    /// Smallest size in bits of a generated key.
+    pub min_key_size: u16,
+    /// Largest size in bits of a generated key.
+    pub max_key_size: u16,
+    /// Cargo features compiled in.
+    pub features: Vec<&'static str>,
+}
+
+/// Returns the [`Capabilities`] of this build.
+///
+/// ```
+/// let capabilities = rrsa_lib::capabilities();
+/// assert!(capabilities.padding_schemes.contains(&"oaep"));
+/// assert_eq!(capabilities.to_json()["max_key_size"], 4096);
+/// ```
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    let mut key_formats = vec!["rrsa", "rrsa-ndex", "pkcs1-pem", "pkcs1-der", "openssh"];
+    if cfg!(feature = "jwk") {
+        key_formats.push("jwk");
+    }
+    let features = [
+        ("extras", cfg!(feature = "extras")),
+        ("jwk", cfg!(feature = "jwk")),
+        ("cli-test", cfg!(feature = "cli-test")),
+        ("fixtures", cfg!(feature = "fixtures")),
+        ("rayon", cfg!(feature = "rayon")),
+        ("tokio", cfg!(feature = "tokio")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect();
+
+    Capabilities {
+        version: version::CRATE_VERSION,
+        key_formats,
+        padding_schemes: vec!["none", "oaep", "pkcs1v15"],
+        signature_schemes: vec!["pss", "pkcs1v15-sha256", "pkcs1v15-sha512"],
+        formats: version::FORMATS.to_vec(),
+        min_key_size: KeySize::MIN.bits(),
+        max_key_size: KeySize::MAX.bits(),
+        features,
+    }
+}
+
+impl Capabilities {
+    /// Formats these [`Capabilities`] as a JSON object, with the same member names as the fields.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let formats: serde_json::Map<String, Value> = self
+            .formats
+            .iter()
+            .map(|(format, format_version)| (format.to_string(), json!(format_version)))
+            .collect();
+        json!({
+            "version": self.version,
+            "key_formats": self.key_formats,
+            "padding_schemes": self.padding_schemes,
+            "signature_schemes": self.signature_schemes,
+            "formats": formats,
+            "min_key_size": self.min_key_size,
+            "max_key_size": self.max_key_size,
+            "features": self.features,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::Key;
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.version, version::CRATE_VERSION);
+        assert_eq!(
+            (capabilities.min_key_size, capabilities.max_key_size),
+            (32, 4096)
+        );
+        assert_eq!(
+            capabilities.key_formats.contains(&"jwk"),
+            cfg!(feature = "jwk")
+        );
+        assert_eq!(
+            capabilities.features.contains(&"jwk"),
+            cfg!(feature = "jwk")
+        );
+
+        let json = capabilities.to_json();
+        assert_eq!(json["formats"]["private key"], Key::FORMAT_VERSION);
+        assert_eq!(json["padding_schemes"][1], "oaep");
+        assert_eq!(json["signature_schemes"].as_array().unwrap().len(), 3);
+    }
+}
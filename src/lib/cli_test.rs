@@ -0,0 +1,173 @@
+//! Module with a harness for end to end tests of the `rrsa-cli` binary,
+//! only available with the `cli-test` feature.
+//!
+//! Every [`CliHarness`] runs the binary inside its own temporary directory,
+//! which is also used as the home directory, so the default keys of the user are never touched.
+
+use crate::config::Config;
+use crate::error::RsaResult;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+use tempfile::TempDir;
+
+/// Spawns the cli binary inside a temporary directory, removed on drop.
+#[derive(Debug)]
+pub struct CliHarness {
+    binary: PathBuf,
+    dir: TempDir,
+}
+
+/// Result of a single run of the cli binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CliHarness {
+    /// Creates a harness for the binary at `binary`,
+    /// which integration tests get from `env!("CARGO_BIN_EXE_rrsa-cli")`.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from creating the temporary directory.
+    pub fn new(binary: impl Into<PathBuf>) -> RsaResult<Self> {
+        Ok(Self {
+            binary: binary.into(),
+            dir: TempDir::new()?,
+        })
+    }
+
+    /// The temporary directory, used as working and home directory of every run.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Path of `name` inside [`CliHarness::dir()`].
+    #[must_use]
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir().join(name)
+    }
+
+    /// Writes `contents` to `name` inside [`CliHarness::dir()`].
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn write_file(&self, name: &str, contents: impl AsRef<[u8]>) -> RsaResult<PathBuf> {
+        let path = self.path(name);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// Reads `name` from [`CliHarness::dir()`].
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn read_file(&self, name: &str) -> RsaResult<Vec<u8>> {
+        Ok(std::fs::read(self.path(name))?)
+    }
+
+    /// Runs the binary with `args`, waiting for it to exit.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from spawning the binary.
+    pub fn run(&self, args: &[&str]) -> RsaResult<CliOutput> {
+        Ok(self.command(args).output()?.into())
+    }
+
+    /// Runs the binary with `args`, writing `input` to its stdin, waiting for it to exit.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from spawning the binary or writing to its stdin.
+    pub fn run_with_stdin(&self, args: &[&str], input: impl AsRef<[u8]>) -> RsaResult<CliOutput> {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // dropping stdin closes it, so the binary sees the end of the input
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_ref())?;
+        }
+        Ok(child.wait_with_output()?.into())
+    }
+
+    /// Runs the binary with `args` and the environment variables `vars`, waiting for it to exit.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from spawning the binary.
+    pub fn run_with_env(&self, args: &[&str], vars: &[(&str, &str)]) -> RsaResult<CliOutput> {
+        let mut command = self.command(args);
+        command.envs(vars.iter().copied());
+        Ok(command.output()?.into())
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(&self.binary);
+        command
+            .args(args)
+            .current_dir(self.dir())
+            .env("HOME", self.dir())
+            .env("XDG_CONFIG_HOME", self.dir().join(".config"))
+            .env("APPDATA", self.dir());
+        // the variables of the user must not leak into the runs
+        for (name, _) in std::env::vars_os() {
+            if name.to_string_lossy().starts_with(Config::ENV_PREFIX) {
+                command.env_remove(name);
+            }
+        }
+        command
+    }
+}
+
+impl From<Output> for CliOutput {
+    fn from(output: Output) -> Self {
+        CliOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+}
+
+// the assertions are called for their panics, returning `self` only chains them
+#[allow(clippy::must_use_candidate)]
+impl CliOutput {
+    /// Asserts the run exited successfully.
+    ///
+    /// # Panics
+    /// If the run failed, showing its stderr.
+    pub fn assert_success(&self) -> &Self {
+        assert!(self.success, "command failed: {}", self.stderr);
+        self
+    }
+
+    /// Asserts the run failed.
+    ///
+    /// # Panics
+    /// If the run exited successfully, showing its stdout.
+    pub fn assert_failure(&self) -> &Self {
+        assert!(!self.success, "command succeeded: {}", self.stdout);
+        self
+    }
+
+    /// Asserts stdout has a line equal to `line`, ignoring surrounding whitespace.
+    ///
+    /// # Panics
+    /// If no line matches, showing the whole stdout.
+    pub fn assert_stdout_line(&self, line: &str) -> &Self {
+        assert!(
+            self.stdout
+                .lines()
+                .any(|stdout_line| stdout_line.trim() == line),
+            "`{line}` not found in stdout:\n{}",
+            self.stdout
+        );
+        self
+    }
+}
@@ -0,0 +1,427 @@
+//! Defaults of the cli, read from [`Config::FILE_NAME`] and overridden by environment variables,
+//! so flags take precedence over the environment, which takes precedence over the file.
+//!
+//! This is the only config file, [`KeyGenOptions`] and [`CheckpointOptions`] are read from it.
+
+use std::{
+    fmt,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::atomic::write_atomic;
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyGenOptions, KeySize, KeyVariant, StorageLocation};
+use crate::paths::{is_stdio, resolve_output_path, Operation};
+
+/// Defaults used when the matching flag is absent, every field is unset unless configured.
+///
+/// Example of a config file, a flat subset of TOML:
+/// ```text
+/// # comments and empty lines are ignored
+/// key_size = 2048
+/// key_path = "/home/me/keys/work"
+/// out_dir = "/home/me/encrypted"
+/// armor = true
+/// storage = "data"
+/// default_exponent = 3
+/// checkpoint_log = "/var/log/rrsa.log"
+/// checkpoint_interval = 300
+/// escrow_key_path = "/home/me/keys/escrow"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Key size of new Key Pairs, taking precedence over [`crate::key::KeyGenOptions`].
+    pub key_size: Option<KeySize>,
+    /// Key Pair used when no key path is given, either a directory with the default key names,
+    /// or the path of the Private Key, whose Public Key has the `.pub` extension.
+    pub key_path: Option<PathBuf>,
+    /// Directory of the output files when no output path is given.
+    pub out_dir: Option<PathBuf>,
+    /// Whether encrypted output is wrapped in ASCII armor.
+    pub armor: Option<bool>,
+    /// Where the default keys are stored, they are migrated there on first use,
+    /// see [`Key::ensure_storage()`].
+    pub storage: Option<StorageLocation>,
+    /// Exponent of new Key Pairs, see [`KeyGenOptions::default_exponent`].
+    pub default_exponent: Option<u32>,
+    /// Log file the checkpoints of long operations are appended to, see [`CheckpointOptions`].
+    pub checkpoint_log: Option<PathBuf>,
+    /// Minimum time between two checkpoints, configured in seconds.
+    pub checkpoint_interval: Option<Duration>,
+    /// Key Pair of the escrow agent, in the same form as [`Config::key_path`],
+    /// the data key of every envelope is also wrapped for its Public Key,
+    /// see [`Key::encode_envelope_escrowed()`].
+    pub escrow_key_path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Name of the file, inside the config directory, with the defaults.
+    pub const FILE_NAME: &'static str = "config.toml";
+    const KEY_SIZE_FIELD: &'static str = "key_size";
+    const KEY_PATH_FIELD: &'static str = "key_path";
+    const OUT_DIR_FIELD: &'static str = "out_dir";
+    const ARMOR_FIELD: &'static str = "armor";
+    const STORAGE_FIELD: &'static str = "storage";
+    const DEFAULT_EXPONENT_FIELD: &'static str = "default_exponent";
+    const CHECKPOINT_LOG_FIELD: &'static str = "checkpoint_log";
+    const CHECKPOINT_INTERVAL_FIELD: &'static str = "checkpoint_interval";
+    const ESCROW_KEY_PATH_FIELD: &'static str = "escrow_key_path";
+    const FIELDS: [&'static str; 9] = [
+        Self::KEY_SIZE_FIELD,
+        Self::KEY_PATH_FIELD,
+        Self::OUT_DIR_FIELD,
+        Self::ARMOR_FIELD,
+        Self::STORAGE_FIELD,
+        Self::DEFAULT_EXPONENT_FIELD,
+        Self::CHECKPOINT_LOG_FIELD,
+        Self::CHECKPOINT_INTERVAL_FIELD,
+        Self::ESCROW_KEY_PATH_FIELD,
+    ];
+    /// Prefix of the environment variables overriding each field, e.g. `RRSA_KEY_SIZE`.
+    pub const ENV_PREFIX: &'static str = "RRSA_";
+    /// Environment variable with the same meaning as `RRSA_KEY_PATH`,
+    /// ignored if `RRSA_KEY_PATH` is set too.
+    pub const KEY_VAR: &'static str = "RRSA_KEY";
+
+    /// Reads the defaults from [`Config::FILE_NAME`] inside the config directory,
+    /// see [`Key::default_dir_for()`], then overrides them with the environment variables
+    /// named by [`Config::ENV_PREFIX`] followed by the uppercase field, and [`Config::KEY_VAR`].
+    ///
+    /// # Errors
+    /// - If the file or a variable is not valid, see [`Config::from_config_str()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn load() -> RsaResult<Self> {
+        let path = Self::file_path();
+        let mut config = if path.is_file() {
+            Self::load_from_path(&path)?
+        } else {
+            Self::default()
+        };
+        config.apply_vars(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Path of [`Config::FILE_NAME`] inside the config directory.
+    #[must_use]
+    pub fn file_path() -> PathBuf {
+        Key::default_dir_for(StorageLocation::Config).join(Self::FILE_NAME)
+    }
+
+    /// Reads the defaults from the config file at `path`, without the environment.
+    ///
+    /// # Errors
+    /// - If the file is not a valid config, see [`Config::from_config_str()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn load_from_path(path: &Path) -> RsaResult<Self> {
+        Self::from_config_str(&read_to_string(path)?)
+    }
+
+    /// Writes the set fields to `path`, comments of a previous file are not kept.
+    ///
+    /// The file is replaced atomically, see [`write_atomic()`].
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn write_to_path(&self, path: &Path) -> RsaResult<()> {
+        write_atomic(path, self.to_string())
+    }
+
+    /// Parses `key = value` lines, with integer, boolean or quoted string values.
+    ///
+    /// ```
+    /// use rrsa_lib::config::Config;
+    /// use rrsa_lib::key::KeySize;
+    ///
+    /// let config = Config::from_config_str("key_size = 2048\nout_dir = \"out\"\n").unwrap();
+    /// assert_eq!(config.key_size, Some(KeySize::B2048));
+    /// assert_eq!(config.out_dir.unwrap().to_str(), Some("out"));
+    /// assert!(Config::from_config_str("armor = maybe").is_err());
+    /// assert!(Config::from_config_str("default_exponent = 4").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// - If a line is not a known `key = value` field.
+    /// - If the key size is invalid, see [`KeySize::custom()`].
+    /// - If the default exponent is invalid, see [`KeyGenOptions::validate()`].
+    /// - If the checkpoint interval is not a positive amount of seconds.
+    pub fn from_config_str(s: &str) -> RsaResult<Self> {
+        let mut config = Self::default();
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (field, value) = line
+                .split_once('=')
+                .ok_or_else(|| RsaError::UnknownError(format!("invalid config line `{line}`")))?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            config.set(field.trim(), value)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the fields only valid together, e.g. the default exponent for the key size.
+    fn validate(&self) -> RsaResult<()> {
+        KeyGenOptions::from_config(self).map(drop)
+    }
+
+    /// Overrides every field whose variable is returned by `var`.
+    fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> RsaResult<()> {
+        // applied first, so `RRSA_KEY_PATH` overrides it
+        if let Some(value) = var(Self::KEY_VAR) {
+            self.set(Self::KEY_PATH_FIELD, &value)?;
+        }
+        for field in Self::FIELDS {
+            if let Some(value) = var(&format!("{}{}", Self::ENV_PREFIX, field.to_uppercase())) {
+                self.set(field, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, field: &str, value: &str) -> RsaResult<()> {
+        let invalid_value =
+            || RsaError::UnknownError(format!("invalid config value `{value}` for `{field}`"));
+        match field {
+            Self::KEY_SIZE_FIELD => self.key_size = Some(value.parse()?),
+            Self::KEY_PATH_FIELD => self.key_path = Some(PathBuf::from(value)),
+            Self::OUT_DIR_FIELD => self.out_dir = Some(PathBuf::from(value)),
+            Self::ARMOR_FIELD => self.armor = Some(value.parse().map_err(|_| invalid_value())?),
+            Self::STORAGE_FIELD => self.storage = Some(value.parse()?),
+            Self::DEFAULT_EXPONENT_FIELD => {
+                self.default_exponent = Some(value.parse().map_err(|_| invalid_value())?);
+            }
+            Self::CHECKPOINT_LOG_FIELD => {
+                if value.is_empty() {
+                    return Err(invalid_value());
+                }
+                self.checkpoint_log = Some(PathBuf::from(value));
+            }
+            Self::CHECKPOINT_INTERVAL_FIELD => {
+                let seconds: u64 = value.parse().map_err(|_| invalid_value())?;
+                if seconds == 0 {
+                    return Err(invalid_value());
+                }
+                self.checkpoint_interval = Some(Duration::from_secs(seconds));
+            }
+            Self::ESCROW_KEY_PATH_FIELD => {
+                if value.is_empty() {
+                    return Err(invalid_value());
+                }
+                self.escrow_key_path = Some(PathBuf::from(value));
+            }
+            _ => {
+                return Err(RsaError::UnknownError(format!(
+                    "unknown config field `{field}`"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the Key of `variant` inside [`Config::key_path`], `None` if unset.
+    #[must_use]
+    pub fn key_path_for(&self, variant: KeyVariant) -> Option<PathBuf> {
+        Some(pair_path(self.key_path.as_ref()?, variant))
+    }
+
+    /// Path of the Key of `variant` inside [`Config::escrow_key_path`], `None` if unset.
+    #[must_use]
+    pub fn escrow_key_path_for(&self, variant: KeyVariant) -> Option<PathBuf> {
+        Some(pair_path(self.escrow_key_path.as_ref()?, variant))
+    }
+
+    /// Same as [`resolve_output_path()`], but the default output file is placed
+    /// inside [`Config::out_dir`] if set, unless the output is stdout.
+    #[must_use]
+    pub fn resolve_output_path(
+        &self,
+        input: &Path,
+        explicit: Option<PathBuf>,
+        operation: Operation,
+    ) -> PathBuf {
+        if explicit.is_some() {
+            return resolve_output_path(input, explicit, operation);
+        }
+        let default = resolve_output_path(input, None, operation);
+        match (&self.out_dir, default.file_name()) {
+            (Some(out_dir), Some(file_name)) if !is_stdio(&default) => out_dir.join(file_name),
+            _ => default,
+        }
+    }
+}
+
+/// Path of the Key of `variant` in the Key Pair at `path`, either a directory with the default
+/// key names, or the path of the Private Key, whose Public Key has the `.pub` extension.
+fn pair_path(path: &Path, variant: KeyVariant) -> PathBuf {
+    match (path.is_dir(), variant) {
+        (true, KeyVariant::PublicKey) => path.join(Key::DEFAULT_PUBLIC_KEY_NAME),
+        (true, KeyVariant::PrivateKey) => path.join(Key::DEFAULT_PRIVATE_KEY_NAME),
+        (false, KeyVariant::PublicKey) => path.with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION),
+        (false, KeyVariant::PrivateKey) => path.to_path_buf(),
+    }
+}
+
+impl fmt::Display for Config {
+    /// Formats the given [`Config`] as the file content of it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key_size) = self.key_size {
+            writeln!(f, "{} = {key_size}", Self::KEY_SIZE_FIELD)?;
+        }
+        if let Some(key_path) = &self.key_path {
+            writeln!(f, "{} = \"{}\"", Self::KEY_PATH_FIELD, key_path.display())?;
+        }
+        if let Some(out_dir) = &self.out_dir {
+            writeln!(f, "{} = \"{}\"", Self::OUT_DIR_FIELD, out_dir.display())?;
+        }
+        if let Some(armor) = self.armor {
+            writeln!(f, "{} = {armor}", Self::ARMOR_FIELD)?;
+        }
+        if let Some(storage) = self.storage {
+            writeln!(f, "{} = \"{storage}\"", Self::STORAGE_FIELD)?;
+        }
+        if let Some(default_exponent) = self.default_exponent {
+            writeln!(f, "{} = {default_exponent}", Self::DEFAULT_EXPONENT_FIELD)?;
+        }
+        if let Some(checkpoint_log) = &self.checkpoint_log {
+            writeln!(
+                f,
+                "{} = \"{}\"",
+                Self::CHECKPOINT_LOG_FIELD,
+                checkpoint_log.display()
+            )?;
+        }
+        if let Some(checkpoint_interval) = self.checkpoint_interval {
+            writeln!(
+                f,
+                "{} = {}",
+                Self::CHECKPOINT_INTERVAL_FIELD,
+                checkpoint_interval.as_secs()
+            )?;
+        }
+        if let Some(escrow_key_path) = &self.escrow_key_path {
+            writeln!(
+                f,
+                "{} = \"{}\"",
+                Self::ESCROW_KEY_PATH_FIELD,
+                escrow_key_path.display()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        assert_eq!(Config::from_config_str("").unwrap(), Config::default());
+        let mut config = Config::from_config_str(
+            "# rrsa\n\nkey_size = 512\nkey_path = \"keys/work\"\nout_dir=out\narmor = false\nstorage = \"data\"\n\
+             default_exponent = 17\ncheckpoint_log = /tmp/rrsa checkpoints.log\ncheckpoint_interval =300\n\
+             escrow_key_path = \"keys/escrow\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                key_size: Some(KeySize::B512),
+                key_path: Some(PathBuf::from("keys/work")),
+                out_dir: Some(PathBuf::from("out")),
+                armor: Some(false),
+                storage: Some(StorageLocation::Data),
+                default_exponent: Some(17),
+                checkpoint_log: Some(PathBuf::from("/tmp/rrsa checkpoints.log")),
+                checkpoint_interval: Some(Duration::from_secs(300)),
+                escrow_key_path: Some(PathBuf::from("keys/escrow")),
+            }
+        );
+        for invalid in [
+            "key_size",
+            "key_size = 16",
+            "armor = 1",
+            "exponent = 3",
+            "storage = cache",
+            "default_exponent = 1",
+            "default_exponent = 65536",
+            "key_size = 32\ndefault_exponent = 4294967295",
+            "checkpoint_log =",
+            "checkpoint_interval = 0",
+            "checkpoint_interval = -5",
+            "checkpoint_interval = soon",
+            "escrow_key_path = \"\"",
+        ] {
+            assert!(Config::from_config_str(invalid).is_err(), "{invalid}");
+        }
+
+        config
+            .apply_vars(|name| (name == "RRSA_ARMOR").then(|| "true".to_string()))
+            .unwrap();
+        assert_eq!(config.armor, Some(true));
+        assert_eq!(config.key_size, Some(KeySize::B512));
+        assert!(config
+            .apply_vars(|name| (name == "RRSA_KEY_SIZE").then(|| "big".to_string()))
+            .is_err());
+        let mut from_env = Config::default();
+        from_env
+            .apply_vars(|name| match name {
+                "RRSA_KEY" => Some("keys/ci".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(from_env.key_path, Some(PathBuf::from("keys/ci")));
+        from_env
+            .apply_vars(|name| match name {
+                "RRSA_KEY" => Some("keys/ci".to_string()),
+                "RRSA_KEY_PATH" => Some("keys/work".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        // the variable named after the field wins
+        assert_eq!(from_env.key_path, Some(PathBuf::from("keys/work")));
+
+        assert_eq!(
+            config.key_path_for(KeyVariant::PublicKey),
+            Some(PathBuf::from("keys/work.pub"))
+        );
+        assert_eq!(
+            config.key_path_for(KeyVariant::PrivateKey),
+            Some(PathBuf::from("keys/work"))
+        );
+        assert_eq!(
+            config.resolve_output_path(Path::new("msg.txt"), None, Operation::Encode),
+            PathBuf::from("out/msg.txt.encoded")
+        );
+        assert_eq!(
+            config.resolve_output_path(Path::new("-"), None, Operation::Encode),
+            PathBuf::from("-")
+        );
+        assert_eq!(
+            config.resolve_output_path(
+                Path::new("msg.txt"),
+                Some(PathBuf::from("here")),
+                Operation::Encode
+            ),
+            PathBuf::from("here")
+        );
+        assert_eq!(
+            config.escrow_key_path_for(KeyVariant::PublicKey),
+            Some(PathBuf::from("keys/escrow.pub"))
+        );
+        assert_eq!(Config::default().key_path_for(KeyVariant::PublicKey), None);
+        assert_eq!(
+            Config::from_config_str(&config.to_string()).unwrap(),
+            config
+        );
+        assert_eq!(Config::default().to_string(), "");
+    }
+}
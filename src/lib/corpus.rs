@@ -0,0 +1,180 @@
+//! Module to generate the standard benchmark corpus, files from 1 KiB to 1 GiB,
+//! both random and compressible.
+//!
+//! Every file is fully deterministic, so measurements taken on different machines
+//! are run over the exact same bytes.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::{
+    fs::{create_dir_all, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::error::RsaResult;
+use crate::testvectors::PLAINTEXT;
+
+/// Sizes of the standard corpus files, in bytes.
+pub const SIZES: [u64; 6] = [1 << 10, 64 << 10, 1 << 20, 16 << 20, 256 << 20, 1 << 30];
+/// Size of the buffer every file is written with.
+const WRITE_BUFFER_LEN: usize = 64 * 1024;
+
+/// Contents of a corpus file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusKind {
+    /// Bytes from a `ChaCha20` generator with a fixed seed, incompressible.
+    Random,
+    /// [`PLAINTEXT`] repeated, highly compressible.
+    Compressible,
+}
+
+/// A single corpus file, with its kind and size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorpusFile {
+    pub kind: CorpusKind,
+    pub size: u64,
+}
+
+impl CorpusFile {
+    /// Name of the file, e.g. `random_64KiB.bin` or `compressible_1GiB.txt`.
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        let size = match self.size {
+            size if size >= 1 << 30 && size.is_multiple_of(1 << 30) => format!("{}GiB", size >> 30),
+            size if size >= 1 << 20 && size.is_multiple_of(1 << 20) => format!("{}MiB", size >> 20),
+            size if size >= 1 << 10 && size.is_multiple_of(1 << 10) => format!("{}KiB", size >> 10),
+            size => format!("{size}B"),
+        };
+        match self.kind {
+            CorpusKind::Random => format!("random_{size}.bin"),
+            CorpusKind::Compressible => format!("compressible_{size}.txt"),
+        }
+    }
+
+    /// Writes the contents of this file to `output`.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn write_to<W: Write>(&self, output: &mut W) -> RsaResult<()> {
+        let mut rng = ChaCha20Rng::seed_from_u64(self.size);
+        // long enough for a whole buffer starting at any offset of the plaintext
+        let pattern = PLAINTEXT
+            .as_bytes()
+            .repeat(WRITE_BUFFER_LEN / PLAINTEXT.len() + 2);
+        let mut buffer = vec![0u8; WRITE_BUFFER_LEN];
+        let mut left = self.size;
+        let mut offset = 0usize;
+        while left > 0 {
+            let len =
+                usize::try_from(left).map_or(WRITE_BUFFER_LEN, |left| left.min(WRITE_BUFFER_LEN));
+            match self.kind {
+                CorpusKind::Random => rng.fill_bytes(&mut buffer[..len]),
+                CorpusKind::Compressible => {
+                    buffer[..len].copy_from_slice(&pattern[offset..offset + len]);
+                    offset = (offset + len) % PLAINTEXT.len();
+                }
+            }
+            output.write_all(&buffer[..len])?;
+            left -= len as u64;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// Returns every file of the standard corpus, a random and a compressible one for each of [`SIZES`].
+#[must_use]
+pub fn standard() -> Vec<CorpusFile> {
+    SIZES
+        .iter()
+        .flat_map(|&size| {
+            [CorpusKind::Random, CorpusKind::Compressible].map(|kind| CorpusFile { kind, size })
+        })
+        .collect()
+}
+
+/// Writes `files` to `dir`, creating it if needed.
+///
+/// Files already present with the expected size are kept, so an interrupted
+/// generation can be resumed without writing gigabytes again.
+///
+/// # Returns
+/// The path of every file.
+///
+/// # Errors
+/// Propagates [`std::io::Error`].
+pub fn write_to_dir(dir: &Path, files: &[CorpusFile]) -> RsaResult<Vec<PathBuf>> {
+    create_dir_all(dir)?;
+    files
+        .iter()
+        .map(|file| -> RsaResult<PathBuf> {
+            let path = dir.join(file.file_name());
+            if !path
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() == file.size)
+            {
+                file.write_to(&mut BufWriter::new(File::create(&path)?))?;
+            }
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_file_names() {
+        let names: Vec<_> = standard().iter().map(CorpusFile::file_name).collect();
+        assert_eq!(names.len(), 2 * SIZES.len());
+        assert_eq!(names[0], "random_1KiB.bin");
+        assert_eq!(names[1], "compressible_1KiB.txt");
+        assert_eq!(names[names.len() - 1], "compressible_1GiB.txt");
+        assert_eq!(
+            CorpusFile {
+                kind: CorpusKind::Random,
+                size: 1500
+            }
+            .file_name(),
+            "random_1500B.bin"
+        );
+    }
+
+    #[test]
+    fn test_write_corpus_to_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            CorpusFile {
+                kind: CorpusKind::Random,
+                size: 100_000,
+            },
+            CorpusFile {
+                kind: CorpusKind::Compressible,
+                size: 100_000,
+            },
+        ];
+        let paths = write_to_dir(dir.path(), &files).unwrap();
+        let random = std::fs::read(&paths[0]).unwrap();
+        let compressible = std::fs::read(&paths[1]).unwrap();
+        assert_eq!(random.len(), 100_000);
+        assert_eq!(compressible.len(), 100_000);
+        assert!(compressible.starts_with(PLAINTEXT.as_bytes()));
+        assert!(PLAINTEXT
+            .as_bytes()
+            .repeat(100_000 / PLAINTEXT.len() + 1)
+            .starts_with(&compressible));
+        assert_ne!(random[..1000], random[1000..2000]);
+
+        // deterministic, and kept when already present
+        std::fs::remove_file(&paths[0]).unwrap();
+        let modified = std::fs::metadata(&paths[1]).unwrap().modified().unwrap();
+        write_to_dir(dir.path(), &files).unwrap();
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), random);
+        assert_eq!(
+            std::fs::metadata(&paths[1]).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+}
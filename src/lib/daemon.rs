@@ -0,0 +1,382 @@
+//! Module with a local daemon serving requests over a Unix socket,
+//! so other processes can reuse loaded keys without spawning a process per file.
+//!
+//! Every message is a big endian `u32` length followed by that many bytes.
+//! A request holds the [`Operation`] byte, the length of the key name as one byte,
+//! the key name and the data. A response holds a status byte, `0` on success,
+//! followed by the result or by the error message.
+//!
+//! Any process able to connect to the socket can use the loaded Private Keys,
+//! so [`bind()`] restricts it to its owner with mode `0600`, before it appears at its path.
+//! Sockets bound otherwise should be placed in a directory only the owner can access,
+//! e.g. with mode `0700`.
+
+use std::collections::HashMap;
+use std::fs::{hard_link, remove_dir_all, set_permissions, DirBuilder, Permissions};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+
+use crate::atomic::create_new_tmp;
+use crate::encoding::{padding::PaddingScheme, Ciphertext, Plaintext};
+use crate::error::{RsaError, RsaResult};
+use crate::key::KeyPair;
+use crate::signature::SignatureScheme;
+
+/// Mode of the socket created by [`bind()`], read and write for its owner only.
+pub const SOCKET_MODE: u32 = 0o600;
+/// Mode of the directory [`bind()`] creates the socket in, accessible by its owner only.
+const PRIVATE_DIR_MODE: u32 = 0o700;
+/// Largest message accepted by either side.
+const MAX_MESSAGE_LEN: u32 = 64 * 1024 * 1024;
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+/// Operation requested from a [`Daemon`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Encodes the data with [`PaddingScheme::Oaep`] using the Public Key.
+    Encrypt = 1,
+    /// Decodes the data with [`PaddingScheme::Oaep`] using the Private Key.
+    Decrypt = 2,
+    /// Signs the SHA-256 digest of the data with [`SignatureScheme::Pss`] using the Private Key.
+    Sign = 3,
+}
+
+impl TryFrom<u8> for Operation {
+    type Error = RsaError;
+
+    fn try_from(byte: u8) -> RsaResult<Self> {
+        match byte {
+            1 => Ok(Operation::Encrypt),
+            2 => Ok(Operation::Decrypt),
+            3 => Ok(Operation::Sign),
+            _ => Err(RsaError::DaemonProtocol(format!(
+                "unknown operation {byte}"
+            ))),
+        }
+    }
+}
+
+/// Request sent to a [`Daemon`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub operation: Operation,
+    /// Name the key was added to the [`Daemon`] with.
+    pub key_name: String,
+    pub data: Vec<u8>,
+}
+
+impl Request {
+    /// Writes this [`Request`] as a single length prefixed message.
+    ///
+    /// # Errors
+    /// - If the key name is longer than 255 bytes or the message is too long.
+    /// - Propagates [`std::io::Error`].
+    pub fn write_to<W: Write>(&self, output: &mut W) -> RsaResult<()> {
+        let name_len = u8::try_from(self.key_name.len())
+            .map_err(|_| RsaError::DaemonProtocol("key name is too long".into()))?;
+        let mut message = vec![self.operation as u8, name_len];
+        message.extend_from_slice(self.key_name.as_bytes());
+        message.extend_from_slice(&self.data);
+        write_message(output, &message)
+    }
+
+    /// Reads a [`Request`] written by [`Request::write_to()`].
+    ///
+    /// # Returns
+    /// [`None`] if `input` ended before a new request.
+    ///
+    /// # Errors
+    /// - If the request is malformed or too long.
+    /// - Propagates [`std::io::Error`].
+    pub fn read_from<R: Read>(input: &mut R) -> RsaResult<Option<Self>> {
+        let Some(message) = read_message(input)? else {
+            return Ok(None);
+        };
+        let malformed = || RsaError::DaemonProtocol("malformed request".into());
+        let (&[operation, name_len], rest) = message.split_first_chunk().ok_or_else(malformed)?;
+        let name_len = usize::from(name_len);
+        if rest.len() < name_len {
+            return Err(malformed());
+        }
+        let (key_name, data) = rest.split_at(name_len);
+        Ok(Some(Self {
+            operation: operation.try_into()?,
+            key_name: String::from_utf8(key_name.to_vec()).map_err(|_| malformed())?,
+            data: data.to_vec(),
+        }))
+    }
+}
+
+/// Serves requests using the Key Pairs it holds, loaded once.
+#[derive(Debug, Default)]
+pub struct Daemon {
+    keys: HashMap<String, KeyPair>,
+}
+
+impl Daemon {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `pair` under `name`, precomputing the CRT components of its Private Key.
+    ///
+    /// # Errors
+    /// If the CRT components of the Private Key can not be recovered.
+    pub fn add_key(&mut self, name: &str, mut pair: KeyPair) -> RsaResult<()> {
+        if !pair.private_key.has_crt() {
+            pair.private_key.precompute_crt()?;
+        }
+        self.keys.insert(name.to_string(), pair);
+        Ok(())
+    }
+
+    /// Runs a single [`Request`].
+    ///
+    /// # Errors
+    /// - If no key was added under the requested name.
+    /// - Any error of the requested [`Operation`].
+    pub fn handle(&self, request: &Request) -> RsaResult<Vec<u8>> {
+        let pair = self
+            .keys
+            .get(&request.key_name)
+            .ok_or_else(|| RsaError::DaemonUnknownKey(request.key_name.clone()))?;
+        match request.operation {
+            Operation::Encrypt => Ok(pair
+                .public_key
+                .encrypt(
+                    &Plaintext::from_bytes(request.data.clone()),
+                    PaddingScheme::Oaep,
+                )?
+                .as_bytes()
+                .to_vec()),
+            Operation::Decrypt => Ok(pair
+                .private_key
+                .decrypt(
+                    &Ciphertext::from_bytes(request.data.clone()),
+                    PaddingScheme::Oaep,
+                )?
+                .as_bytes()
+                .to_vec()),
+            Operation::Sign => {
+                let scheme = SignatureScheme::Pss;
+                let digest = scheme.hash().digest(&request.data);
+                Ok(pair.private_key.sign(&digest, scheme)?.as_bytes().to_vec())
+            }
+        }
+    }
+
+    /// Answers every request read from `stream` until it is closed.
+    ///
+    /// Errors of a request are sent back to the client, only I/O and framing errors stop serving.
+    ///
+    /// # Errors
+    /// - If a request is malformed or too long.
+    /// - Propagates [`std::io::Error`].
+    pub fn serve_connection<S: Read + Write>(&self, stream: &mut S) -> RsaResult<()> {
+        while let Some(request) = Request::read_from(stream)? {
+            let response = match self.handle(&request) {
+                Ok(mut result) => {
+                    result.insert(0, STATUS_OK);
+                    result
+                }
+                Err(error) => [&[STATUS_ERROR][..], error.to_string().as_bytes()].concat(),
+            };
+            write_message(stream, &response)?;
+        }
+        Ok(())
+    }
+
+    /// Accepts connections on `listener` forever, serving each one in its own thread.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from accepting connections.
+    pub fn serve(&self, listener: &UnixListener) -> RsaResult<()> {
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let mut stream = stream?;
+                scope.spawn(move || {
+                    if let Err(error) = self.serve_connection(&mut stream) {
+                        tracing::warn!("Connection closed: {error}");
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Binds a listener to `socket`, restricted to its owner, see [`SOCKET_MODE`].
+///
+/// The socket is bound in a new sibling directory only its owner can access,
+/// since it is created with the mode of the umask, then linked at `socket` once restricted,
+/// so no other user can ever connect to it.
+///
+/// # Errors
+/// Propagates [`std::io::Error`], e.g. if `socket` already exists.
+pub fn bind(socket: &Path) -> RsaResult<UnixListener> {
+    let (private_dir, ()) = create_new_tmp(socket, |path| {
+        DirBuilder::new().mode(PRIVATE_DIR_MODE).create(path)
+    })?;
+    let bound = bind_linked(&private_dir.join("socket"), socket);
+    remove_dir_all(&private_dir)?;
+    bound
+}
+
+/// Binds a listener to `private_socket`, restricts it, then links it at `socket`.
+fn bind_linked(private_socket: &Path, socket: &Path) -> RsaResult<UnixListener> {
+    let listener = UnixListener::bind(private_socket)?;
+    set_permissions(private_socket, Permissions::from_mode(SOCKET_MODE))?;
+    hard_link(private_socket, socket)?;
+    Ok(listener)
+}
+
+/// Sends a single `request` to the daemon listening on `socket`.
+///
+/// # Returns
+/// The result of the request.
+///
+/// # Errors
+/// - If the daemon failed to run the request, with its error message.
+/// - Propagates [`std::io::Error`].
+pub fn send_request(socket: &Path, request: &Request) -> RsaResult<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket)?;
+    request.write_to(&mut stream)?;
+    let response = read_message(&mut stream)?
+        .ok_or_else(|| RsaError::DaemonProtocol("the daemon closed the connection".into()))?;
+    match response.split_first() {
+        Some((&STATUS_OK, result)) => Ok(result.to_vec()),
+        Some((_, message)) => Err(RsaError::DaemonRequest(
+            String::from_utf8_lossy(message).into_owned(),
+        )),
+        None => Err(RsaError::DaemonProtocol("empty response".into())),
+    }
+}
+
+fn write_message<W: Write>(output: &mut W, message: &[u8]) -> RsaResult<()> {
+    let len = u32::try_from(message.len())
+        .ok()
+        .filter(|len| *len <= MAX_MESSAGE_LEN)
+        .ok_or_else(|| RsaError::DaemonProtocol("message is too long".into()))?;
+    output.write_all(&len.to_be_bytes())?;
+    output.write_all(message)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Reads a length prefixed message, or [`None`] if `input` ended before it.
+fn read_message<R: Read>(input: &mut R) -> RsaResult<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+    let len = u32::from_be_bytes(len);
+    if len > MAX_MESSAGE_LEN {
+        return Err(RsaError::DaemonProtocol("message is too long".into()));
+    }
+    let mut message = vec![0u8; len as usize];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::Signature;
+    use std::str::FromStr;
+
+    fn demo_pair() -> KeyPair {
+        let pair = KeyPair::demo();
+        KeyPair {
+            public_key: crate::key::Key::from_str(&pair.public_key.to_string()).unwrap(),
+            private_key: crate::key::Key::from_str(&pair.private_key.to_string()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_daemon_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("rrsa.sock");
+        let listener = bind(&socket).unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, SOCKET_MODE);
+        // the private directory it was bound in is gone, and the path is not reused
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert!(bind(&socket).is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        let mut daemon = Daemon::new();
+        daemon.add_key("demo", demo_pair()).unwrap();
+
+        thread::scope(|scope| {
+            let daemon = &daemon;
+            scope.spawn(move || {
+                // one connection per request
+                for _ in 0..4 {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    daemon.serve_connection(&mut stream).unwrap();
+                }
+            });
+
+            let request = |operation, key_name: &str, data: &[u8]| {
+                send_request(
+                    &socket,
+                    &Request {
+                        operation,
+                        key_name: key_name.to_string(),
+                        data: data.to_vec(),
+                    },
+                )
+            };
+            let ciphertext = request(Operation::Encrypt, "demo", b"attack at dawn").unwrap();
+            assert_eq!(
+                request(Operation::Decrypt, "demo", &ciphertext).unwrap(),
+                b"attack at dawn"
+            );
+
+            let signature = request(Operation::Sign, "demo", b"release v1.0").unwrap();
+            let digest = SignatureScheme::Pss.hash().digest(b"release v1.0");
+            assert!(KeyPair::demo()
+                .public_key
+                .verify(
+                    &digest,
+                    &Signature::from_bytes(signature),
+                    SignatureScheme::Pss
+                )
+                .unwrap());
+
+            assert!(matches!(
+                request(Operation::Sign, "missing", b"data"),
+                Err(RsaError::DaemonRequest(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_request_framing() {
+        let request = Request {
+            operation: Operation::Decrypt,
+            key_name: "backups".into(),
+            data: vec![1, 2, 3],
+        };
+        let mut message = Vec::new();
+        request.write_to(&mut message).unwrap();
+        assert_eq!(&message[..6], &[0, 0, 0, 12, 2, 7]);
+
+        let mut input = message.as_slice();
+        assert_eq!(Request::read_from(&mut input).unwrap(), Some(request));
+        assert_eq!(Request::read_from(&mut input).unwrap(), None);
+
+        assert!(matches!(
+            Request::read_from(&mut &[0, 0, 0, 2, 9, 0][..]),
+            Err(RsaError::DaemonProtocol(_))
+        ));
+        assert!(Request::read_from(&mut &[0, 0, 0, 2, 1, 5][..]).is_err());
+        assert!(Request::read_from(&mut &[0xff, 0, 0, 0][..]).is_err());
+    }
+}
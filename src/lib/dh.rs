@@ -0,0 +1,165 @@
+//! Module with a classic finite field Diffie-Hellman key exchange,
+//! built on the same number theory used for RSA key generation.
+//!
+//! Only meant to show how two parties agree on a secret over a public channel,
+//! the exchange is unauthenticated and the parameters are not vetted.
+
+use crate::error::{RsaError, RsaResult};
+use crate::math::{mod_pow, PrimeGenerator};
+use crate::rng;
+use num_bigint::BigUint;
+
+/// Public parameters shared by both parties of an exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhParams {
+    /// Safe prime `p = 2q + 1`, with `q` also prime.
+    pub prime: BigUint,
+    /// Generator of the subgroup of prime order `q`.
+    pub generator: BigUint,
+}
+
+impl DhParams {
+    /// Valid range of the prime size in bits.
+    const PRIME_SIZE_RANGE: std::ops::RangeInclusive<u16> = 16..=2048;
+    /// Generator of the quadratic residues subgroup of any safe prime, since `4 = 2^2`.
+    const GENERATOR: u8 = 4;
+
+    /// Generates new parameters with a safe prime of exactly `prime_bits` bits.
+    ///
+    /// Safe primes are rare, so this is noticeably slower than generating
+    /// an RSA prime of the same size.
+    ///
+    /// # Errors
+    /// If `prime_bits` is not in `16..=2048`.
+    pub fn generate(prime_bits: u16) -> RsaResult<Self> {
+        if !DhParams::PRIME_SIZE_RANGE.contains(&prime_bits) {
+            return Err(RsaError::UnknownError(format!(
+                "prime size must be in {:?} bits",
+                DhParams::PRIME_SIZE_RANGE
+            )));
+        }
+        let mut generator = PrimeGenerator::new();
+        loop {
+            let q = generator.random_prime(prime_bits - 1);
+            if q.bits() != u64::from(prime_bits - 1) {
+                continue;
+            }
+            let prime = &q * 2u8 + 1u8;
+            if PrimeGenerator::miller_rabin(&prime) {
+                return Ok(Self {
+                    prime,
+                    generator: BigUint::from(DhParams::GENERATOR),
+                });
+            }
+        }
+    }
+
+    /// Order `q` of the subgroup generated by [`DhParams::generator`].
+    fn subgroup_order(&self) -> BigUint {
+        (&self.prime - 1u8) >> 1u8
+    }
+}
+
+/// One side of an exchange, holding its private exponent.
+#[derive(Debug)]
+pub struct DhParty {
+    params: DhParams,
+    private_exponent: BigUint,
+    public_value: BigUint,
+}
+
+impl DhParty {
+    /// Creates a party with a random private exponent in `[2, q)`.
+    #[must_use]
+    pub fn new(params: &DhParams) -> Self {
+        let private_exponent =
+            rng::gen_biguint_range(&BigUint::from(2u8), &params.subgroup_order());
+        Self::with_private_exponent(params, private_exponent)
+    }
+
+    fn with_private_exponent(params: &DhParams, private_exponent: BigUint) -> Self {
+        let public_value = mod_pow(&params.generator, &private_exponent, &params.prime);
+        Self {
+            params: params.clone(),
+            private_exponent,
+            public_value,
+        }
+    }
+
+    /// Value sent to the other party, `g^a mod p`.
+    #[must_use]
+    pub fn public_value(&self) -> &BigUint {
+        &self.public_value
+    }
+
+    /// Computes the shared secret `(g^b)^a mod p` from the other party's public value.
+    ///
+    /// ```
+    /// use rrsa_lib::dh::{DhParams, DhParty};
+    ///
+    /// let params = DhParams::generate(64).unwrap();
+    /// let (alice, bob) = (DhParty::new(&params), DhParty::new(&params));
+    /// assert_eq!(
+    ///     alice.shared_secret(bob.public_value()).unwrap(),
+    ///     bob.shared_secret(alice.public_value()).unwrap()
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    /// If `peer_public_value` is not in `[2, p - 2]` or is not in the prime order subgroup,
+    /// which would leak bits of the private exponent or force a trivial secret.
+    pub fn shared_secret(&self, peer_public_value: &BigUint) -> RsaResult<BigUint> {
+        let prime = &self.params.prime;
+        if *peer_public_value < BigUint::from(2u8)
+            || *peer_public_value > prime - 2u8
+            || mod_pow(peer_public_value, &self.params.subgroup_order(), prime)
+                != BigUint::from(1u8)
+        {
+            return Err(RsaError::InvalidPeerValue);
+        }
+        Ok(mod_pow(peer_public_value, &self.private_exponent, prime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange() {
+        let params = DhParams::generate(64).unwrap();
+        assert!(PrimeGenerator::miller_rabin(&params.prime));
+        assert!(PrimeGenerator::miller_rabin(&params.subgroup_order()));
+        assert_eq!(params.prime.bits(), 64);
+
+        let alice = DhParty::new(&params);
+        let bob = DhParty::new(&params);
+        assert_eq!(
+            alice.shared_secret(bob.public_value()).unwrap(),
+            bob.shared_secret(alice.public_value()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fixed_exchange() {
+        // p = 23 = 2 * 11 + 1
+        let params = DhParams {
+            prime: BigUint::from(23u8),
+            generator: BigUint::from(4u8),
+        };
+        let alice = DhParty::with_private_exponent(&params, BigUint::from(6u8));
+        let bob = DhParty::with_private_exponent(&params, BigUint::from(9u8));
+        assert_eq!(*alice.public_value(), BigUint::from(2u8));
+        assert_eq!(*bob.public_value(), BigUint::from(13u8));
+        assert_eq!(
+            alice.shared_secret(bob.public_value()).unwrap(),
+            BigUint::from(6u8)
+        );
+
+        assert!(alice.shared_secret(&BigUint::from(1u8)).is_err());
+        assert!(alice.shared_secret(&BigUint::from(22u8)).is_err());
+        // 5 generates the whole group, not the subgroup of order 11
+        assert!(alice.shared_secret(&BigUint::from(5u8)).is_err());
+        assert!(DhParams::generate(8).is_err());
+    }
+}
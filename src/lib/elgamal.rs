@@ -0,0 +1,170 @@
+//! Module with `ElGamal` key generation, encryption and decryption,
+//! built on the same parameters as the [`crate::dh`] key exchange.
+//!
+//! Every ciphertext block is a pair of group elements, so ciphertexts are a bit over
+//! twice the size of the plaintext, against roughly the same size for RSA.
+
+use crate::dh::DhParams;
+use crate::encoding::{i2osp, SizeInBytes};
+use crate::error::{RsaError, RsaResult};
+use crate::math::mod_pow;
+use crate::rng;
+use num_bigint::BigUint;
+
+/// Public part of an `ElGamal` key, `h = g^x mod p`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElGamalPublicKey {
+    params: DhParams,
+    public_value: BigUint,
+}
+
+/// Private part of an `ElGamal` key, the exponent `x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElGamalPrivateKey {
+    params: DhParams,
+    private_exponent: BigUint,
+}
+
+/// Contains both the `ElGamal` Public and Private keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElGamalKeyPair {
+    pub public_key: ElGamalPublicKey,
+    pub private_key: ElGamalPrivateKey,
+}
+
+/// Byte prepended to every plaintext block, so leading zeros are kept
+/// and the encrypted value is never zero.
+const BLOCK_MARKER: u8 = 0x01;
+
+impl ElGamalKeyPair {
+    /// Generates a new [`ElGamalKeyPair`] over a safe prime of `prime_bits` bits.
+    ///
+    /// # Errors
+    /// If `prime_bits` is not a valid [`DhParams`] prime size.
+    pub fn generate(prime_bits: u16) -> RsaResult<Self> {
+        let params = DhParams::generate(prime_bits)?;
+        let subgroup_order = (&params.prime - 1u8) >> 1u8;
+        let private_exponent = rng::gen_biguint_range(&BigUint::from(2u8), &subgroup_order);
+        Ok(Self::from_private_exponent(params, private_exponent))
+    }
+
+    fn from_private_exponent(params: DhParams, private_exponent: BigUint) -> Self {
+        let public_value = mod_pow(&params.generator, &private_exponent, &params.prime);
+        Self {
+            public_key: ElGamalPublicKey {
+                params: params.clone(),
+                public_value,
+            },
+            private_key: ElGamalPrivateKey {
+                params,
+                private_exponent,
+            },
+        }
+    }
+}
+
+impl ElGamalPublicKey {
+    /// Amount of plaintext bytes in every block, leaving room for the [`BLOCK_MARKER`].
+    fn max_message_bytes(&self) -> RsaResult<usize> {
+        self.params
+            .prime
+            .size_in_bytes_floored()
+            .checked_sub(1)
+            .filter(|len| *len > 0)
+            .ok_or(RsaError::KeyTooSmallForPadding)
+    }
+
+    /// Encrypts `message`, one block of `(g^k, m * h^k)` per chunk of it,
+    /// with a fresh random `k` for every block.
+    ///
+    /// # Errors
+    /// If the prime is too small to hold a single plaintext byte per block.
+    pub fn encrypt(&self, message: &[u8]) -> RsaResult<Vec<u8>> {
+        let prime = &self.params.prime;
+        let element_bytes = prime.size_in_bytes();
+        let mut ciphertext = Vec::new();
+
+        for chunk in message.chunks(self.max_message_bytes()?) {
+            let mut block = vec![BLOCK_MARKER];
+            block.extend_from_slice(chunk);
+            let m = BigUint::from_bytes_be(&block);
+
+            let k = rng::gen_biguint_range(&BigUint::from(2u8), &(prime - 1u8));
+            let c1 = mod_pow(&self.params.generator, &k, prime);
+            let c2 = (m * mod_pow(&self.public_value, &k, prime)) % prime;
+            ciphertext.extend(i2osp(&c1, element_bytes)?);
+            ciphertext.extend(i2osp(&c2, element_bytes)?);
+        }
+        Ok(ciphertext)
+    }
+}
+
+impl ElGamalPrivateKey {
+    /// Decrypts a `ciphertext` produced by [`ElGamalPublicKey::encrypt()`],
+    /// computing `m = c2 * c1^(p - 1 - x) mod p` for every block.
+    ///
+    /// # Errors
+    /// If the ciphertext is truncated or was not produced with the matching Public Key.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> RsaResult<Vec<u8>> {
+        let prime = &self.params.prime;
+        let element_bytes = prime.size_in_bytes();
+        let inverse_exponent = prime - 1u8 - &self.private_exponent;
+        let mut message = Vec::new();
+
+        if !ciphertext.len().is_multiple_of(2 * element_bytes) {
+            return Err(RsaError::EncodingError);
+        }
+        for block in ciphertext.chunks(2 * element_bytes) {
+            let (c1, c2) = block.split_at(element_bytes);
+            let c1 = BigUint::from_bytes_be(c1);
+            let c2 = BigUint::from_bytes_be(c2);
+            if c1 >= *prime || c2 >= *prime {
+                return Err(RsaError::EncodingError);
+            }
+
+            let m = (c2 * mod_pow(&c1, &inverse_exponent, prime)) % prime;
+            match m.to_bytes_be().split_first() {
+                Some((&BLOCK_MARKER, chunk)) => message.extend_from_slice(chunk),
+                _ => return Err(RsaError::InvalidPadding),
+            }
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testvectors::PLAINTEXT;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let pair = ElGamalKeyPair::generate(128).unwrap();
+        let message = PLAINTEXT.as_bytes();
+
+        let ciphertext = pair.public_key.encrypt(message).unwrap();
+        // 15 bytes per block, 2 elements of 16 bytes each
+        assert_eq!(ciphertext.len(), 3 * 2 * 16);
+        assert_ne!(ciphertext, pair.public_key.encrypt(message).unwrap());
+        assert_eq!(pair.private_key.decrypt(&ciphertext).unwrap(), message);
+
+        let leading_zeros = [0u8, 0, 0, 7];
+        let ciphertext = pair.public_key.encrypt(&leading_zeros).unwrap();
+        assert_eq!(
+            pair.private_key.decrypt(&ciphertext).unwrap(),
+            leading_zeros
+        );
+
+        assert!(pair.private_key.decrypt(&ciphertext[1..]).is_err());
+    }
+
+    #[test]
+    fn test_small_prime() {
+        let params = DhParams {
+            prime: BigUint::from(23u8),
+            generator: BigUint::from(4u8),
+        };
+        let pair = ElGamalKeyPair::from_private_exponent(params, BigUint::from(6u8));
+        assert!(pair.public_key.encrypt(b"rrsa").is_err());
+    }
+}
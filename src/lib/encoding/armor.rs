@@ -0,0 +1,355 @@
+//! ASCII armor for encoded output, so ciphertext can be pasted in text only channels.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::error::{RsaError, RsaResult};
+use crate::version;
+
+pub const ARMOR_BEGIN: &str = "-----BEGIN RRSA MESSAGE-----";
+pub const ARMOR_END: &str = "-----END RRSA MESSAGE-----";
+/// Current version of the armor format, bumped on any incompatible change.
+pub const ARMOR_VERSION: u32 = 1;
+/// Length of each base64 line between the armor markers.
+const LINE_LEN: usize = 64;
+/// Amount of bytes encoded in each full line.
+const LINE_BYTES: usize = LINE_LEN / 4 * 3;
+
+/// Wraps everything written to it in ASCII armor written to `W`, line by line,
+/// so armoring never holds more than a line in memory.
+///
+/// The markers and header lines are written by [`ArmorWriter::new()`],
+/// the last line and the end marker only by [`ArmorWriter::finish()`].
+#[derive(Debug)]
+pub struct ArmorWriter<W: Write> {
+    inner: W,
+    /// Bytes of the current line, encoded once [`LINE_BYTES`] are buffered.
+    line: Vec<u8>,
+}
+
+impl<W: Write> ArmorWriter<W> {
+    /// Wraps `inner`, writing the begin marker and the `Format` and `Producer` header lines.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::armor::{dearmor, ArmorWriter};
+    /// use std::io::Write;
+    ///
+    /// let mut writer = ArmorWriter::new(Vec::new()).unwrap();
+    /// writer.write_all(b"any bytes").unwrap();
+    /// let armored = String::from_utf8(writer.finish().unwrap()).unwrap();
+    /// assert_eq!(dearmor(&armored).unwrap(), b"any bytes");
+    /// ```
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn new(mut inner: W) -> RsaResult<Self> {
+        write!(
+            inner,
+            "{ARMOR_BEGIN}\nFormat: {ARMOR_VERSION}\nProducer: {}\n\n",
+            version::producer()
+        )?;
+        Ok(Self {
+            inner,
+            line: Vec::with_capacity(LINE_BYTES),
+        })
+    }
+
+    /// Writes the last line and the end marker, then flushes the inner writer.
+    ///
+    /// # Returns
+    /// The inner writer.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn finish(mut self) -> RsaResult<W> {
+        self.write_line()?;
+        writeln!(self.inner, "{ARMOR_END}")?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encodes and writes the buffered bytes as a single line, if any.
+    fn write_line(&mut self) -> io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let mut encoded = STANDARD.encode(&self.line);
+        encoded.push('\n');
+        self.inner.write_all(encoded.as_bytes())?;
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ArmorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.line.len() == LINE_BYTES {
+            self.write_line()?;
+        }
+        let len = buf.len().min(LINE_BYTES - self.line.len());
+        self.line.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    /// Flushes the inner writer, the current line stays buffered.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Extracts the bytes wrapped in ASCII armor read from `R`, line by line,
+/// with the same leniency as [`dearmor()`].
+///
+/// Reading fails if the body is not valid base64, or the input ends without the end marker.
+#[derive(Debug)]
+pub struct ArmorReader<R: Read> {
+    inner: BufReader<R>,
+    line: String,
+    /// Base64 characters of the body not decoded yet, always fewer than 4.
+    pending: String,
+    decoded: Vec<u8>,
+    position: usize,
+    /// Whether the last decoded characters were padded, so nothing else may follow.
+    padded: bool,
+    /// Whether the end marker was read.
+    ended: bool,
+}
+
+impl<R: Read> ArmorReader<R> {
+    /// Wraps `inner`, reading the begin marker and the header lines before the body.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::armor::{armor, ArmorReader};
+    /// use std::io::Read;
+    ///
+    /// let armored = armor(b"any bytes");
+    /// let mut bytes = Vec::new();
+    /// ArmorReader::new(armored.as_bytes()).unwrap().read_to_end(&mut bytes).unwrap();
+    /// assert_eq!(bytes, b"any bytes");
+    /// ```
+    ///
+    /// # Errors
+    /// - If the begin marker is missing.
+    /// - If the armor was produced by a newer version.
+    /// - Propagates [`std::io::Error`].
+    pub fn new(inner: R) -> RsaResult<Self> {
+        let mut reader = Self {
+            inner: BufReader::new(inner),
+            line: String::new(),
+            pending: String::new(),
+            decoded: Vec::new(),
+            position: 0,
+            padded: false,
+            ended: false,
+        };
+        loop {
+            reader.line.clear();
+            if reader.inner.read_line(&mut reader.line)? == 0 {
+                return Err(RsaError::EncodingError);
+            }
+            match reader.line.trim() {
+                "" => {}
+                ARMOR_BEGIN => break,
+                _ => return Err(RsaError::EncodingError),
+            }
+        }
+        reader.decode_next()?;
+        Ok(reader)
+    }
+
+    /// Reads lines until some bytes are decoded, checking the header lines on the way.
+    ///
+    /// # Returns
+    /// `false` once the end marker was read.
+    fn decode_next(&mut self) -> RsaResult<bool> {
+        while !self.ended {
+            self.line.clear();
+            if self.inner.read_line(&mut self.line)? == 0 {
+                return Err(RsaError::EncodingError);
+            }
+            let line = self.line.trim();
+            if line == ARMOR_END {
+                self.ended = true;
+                if !self.pending.is_empty() {
+                    return Err(RsaError::EncodingError);
+                }
+                // only whitespace may follow the armor
+                let mut rest = String::new();
+                self.inner.read_to_string(&mut rest)?;
+                if !rest.trim().is_empty() {
+                    return Err(RsaError::EncodingError);
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                if name == "Format" {
+                    version::check_supported("armor", value, ARMOR_VERSION)?;
+                }
+            } else {
+                self.pending.push_str(line);
+                let whole_len = self.pending.len() / 4 * 4;
+                if whole_len > 0 {
+                    if self.padded {
+                        return Err(RsaError::EncodingError);
+                    }
+                    let whole = &self.pending[..whole_len];
+                    self.decoded = STANDARD
+                        .decode(whole)
+                        .map_err(|_| RsaError::EncodingError)?;
+                    self.padded = whole.ends_with('=');
+                    self.pending.drain(..whole_len);
+                    self.position = 0;
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads the rest of the armor.
+    fn read_all(mut self) -> RsaResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        loop {
+            bytes.extend_from_slice(&self.decoded[self.position..]);
+            self.position = self.decoded.len();
+            if !self.decode_next()? {
+                return Ok(bytes);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for ArmorReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if !self.decode_next().map_err(io::Error::other)? {
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.decoded.len() - self.position);
+        buf[..len].copy_from_slice(&self.decoded[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Wraps `bytes` in base64 lines between [`ARMOR_BEGIN`] and [`ARMOR_END`],
+/// after `Format` and `Producer` header lines, see [`ArmorWriter`].
+///
+/// ```
+/// use rrsa_lib::encoding::armor::{armor, dearmor, is_armored};
+///
+/// let armored = armor(b"any bytes");
+/// assert!(is_armored(armored.as_bytes()));
+/// assert_eq!(dearmor(&armored).unwrap(), b"any bytes");
+/// ```
+///
+/// # Panics
+/// Never, writing to a [`Vec`] cannot fail and base64 is always valid UTF-8.
+#[must_use]
+pub fn armor(bytes: &[u8]) -> String {
+    let mut writer = ArmorWriter::new(Vec::new()).expect("writing to a vec never fails");
+    writer
+        .write_all(bytes)
+        .expect("writing to a vec never fails");
+    let armored = writer.finish().expect("writing to a vec never fails");
+    String::from_utf8(armored).expect("armor is ascii")
+}
+
+/// Extracts the bytes wrapped by [`armor()`], ignoring surrounding whitespace,
+/// see [`ArmorReader`].
+///
+/// Header lines are optional, so armor without them is read as version 1.
+///
+/// # Errors
+/// - If the markers are missing or the body is not valid base64.
+/// - If the armor was produced by a newer version.
+pub fn dearmor(armored: &str) -> RsaResult<Vec<u8>> {
+    ArmorReader::new(armored.as_bytes())?.read_all()
+}
+
+/// Returns the `name: value` header lines of armor produced by [`armor()`].
+///
+/// # Errors
+/// If the markers are missing.
+pub fn headers(armored: &str) -> RsaResult<Vec<(String, String)>> {
+    Ok(body(armored)?
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect())
+}
+
+/// Returns everything between the armor markers.
+fn body(armored: &str) -> RsaResult<&str> {
+    armored
+        .trim()
+        .strip_prefix(ARMOR_BEGIN)
+        .and_then(|s| s.strip_suffix(ARMOR_END))
+        .ok_or(RsaError::EncodingError)
+}
+
+/// Returns `true` if `bytes` starts with [`ARMOR_BEGIN`].
+#[must_use]
+pub fn is_armored(bytes: &[u8]) -> bool {
+    bytes.starts_with(ARMOR_BEGIN.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_armor() {
+        let bytes: Vec<u8> = (0..=255u8).collect();
+        let armored = armor(&bytes);
+        assert!(armored.starts_with("-----BEGIN RRSA MESSAGE-----\n"));
+        assert!(armored.ends_with("\n-----END RRSA MESSAGE-----\n"));
+        assert!(armored.lines().all(|line| line.len() <= LINE_LEN));
+        assert!(is_armored(armored.as_bytes()));
+        assert!(!is_armored(&bytes));
+
+        assert_eq!(dearmor(&armored).unwrap(), bytes);
+        assert_eq!(dearmor(&armored.replace('\n', "\r\n")).unwrap(), bytes);
+        assert!(dearmor(&armored.replace(ARMOR_END, "")).is_err());
+        assert!(dearmor(&armored.replace('A', "?")).is_err());
+
+        // headers are optional
+        let headerless = format!("{ARMOR_BEGIN}\nAAEC\n{ARMOR_END}");
+        assert_eq!(dearmor(&headerless).unwrap(), [0, 1, 2]);
+        assert!(matches!(
+            dearmor(&armored.replace("Format: 1", "Format: 2")),
+            Err(RsaError::UnsupportedVersion { found: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_armor_streaming() {
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(3 * LINE_BYTES + 5).collect();
+        let mut writer = ArmorWriter::new(Vec::new()).unwrap();
+        // small writes, crossing line boundaries
+        for piece in bytes.chunks(7) {
+            writer.write_all(piece).unwrap();
+        }
+        let armored = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(armored, armor(&bytes));
+
+        let mut read = Vec::new();
+        ArmorReader::new(armored.as_bytes())
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, bytes);
+
+        // lines are not required to hold whole base64 quanta
+        let rewrapped = format!("{ARMOR_BEGIN}\nAA\nE\nCAw==\n{ARMOR_END}\n\n");
+        assert_eq!(dearmor(&rewrapped).unwrap(), [0, 1, 2, 3]);
+        for invalid in [
+            format!("{ARMOR_BEGIN}\nAAEC\n"),
+            format!("{ARMOR_BEGIN}\nAA==\nAAEC\n{ARMOR_END}"),
+            format!("{ARMOR_BEGIN}\nAAE\n{ARMOR_END}"),
+            format!("{ARMOR_BEGIN}\nAAEC\n{ARMOR_END}\ntrailing"),
+            format!("leading\n{ARMOR_BEGIN}\nAAEC\n{ARMOR_END}"),
+        ] {
+            assert!(dearmor(&invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Encoding and decoding over [`AsyncRead`] and [`AsyncWrite`], only available with the `tokio` feature.
+//!
+//! The modular exponentiations run on [`task::spawn_blocking()`], so the async runtime
+//! is never blocked by them.
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::task;
+use zeroize::Zeroizing;
+
+use super::{
+    header::{CiphertextHeader, CIPHERTEXT_HEADER_LEN},
+    padding::PaddingScheme,
+};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+
+impl Key {
+    /// Number of chunks read and processed by every blocking task.
+    const ASYNC_BATCH_CHUNKS: usize = 64;
+
+    /// Same as [`Key::encode()`], reading from an [`AsyncRead`] and writing to an [`AsyncWrite`].
+    ///
+    /// Random padding bytes are drawn from the default source of the blocking threads,
+    /// so a source set with [`crate::rng::with_source()`] is not used.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let pair = KeyPair::demo();
+    /// runtime.block_on(async {
+    ///     let mut encoded = Vec::new();
+    ///     pair.public_key
+    ///         .encode_async(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     let mut decoded = Vec::new();
+    ///     pair.private_key
+    ///         .decode_async(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(decoded, b"attack at dawn");
+    /// });
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub async fn encode_async<R, W>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        if self.variant != KeyVariant::PublicKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        let max_bytes_read = self.max_message_bytes(padding)?;
+        output
+            .write_all(&CiphertextHeader::new(self, padding).to_bytes())
+            .await?;
+        let batch_len = max_bytes_read * Key::ASYNC_BATCH_CHUNKS;
+        let key = self.shared();
+        let mut hasher = Sha256::new();
+        loop {
+            let mut batch = Zeroizing::new(vec![0u8; batch_len]);
+            let bytes_amount_read = read_chunk_async(input, &mut batch).await?;
+            batch.truncate(bytes_amount_read);
+            hasher.update(&*batch);
+
+            let key = Arc::clone(&key);
+            let encoded = run_blocking(move || {
+                let mut encoded = Vec::new();
+                for chunk in batch.chunks(max_bytes_read) {
+                    let block = key.encrypt_block(chunk, padding, true)?;
+                    encoded.extend_from_slice(&block);
+                    encoded.extend_from_slice(&crc32fast::hash(&block).to_le_bytes());
+                }
+                Ok(encoded)
+            })
+            .await?;
+            output.write_all(&encoded).await?;
+
+            if bytes_amount_read < batch_len {
+                break;
+            }
+        }
+        let tag = run_blocking(move || key.encrypt_integrity_tag(hasher, padding)).await?;
+        output.write_all(&tag).await?;
+        output.flush().await?;
+        Ok(())
+    }
+
+    /// Same as [`Key::decode()`], reading from an [`AsyncRead`] and writing to an [`AsyncWrite`].
+    ///
+    /// ASCII armor is not detected.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If the checksum of any chunk does not match its contents.
+    /// - If the padding of any chunk is invalid.
+    /// - [`RsaError::IntegrityTagMismatch`] if the decoded data does not match the integrity tag.
+    /// - If any [`std::io::Error`] occurs.
+    pub async fn decode_async<R, W>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        // the header is parsed from a buffer, since it is read synchronously
+        let mut header = vec![0u8; CIPHERTEXT_HEADER_LEN];
+        let bytes_amount_read = read_chunk_async(input, &mut header).await?;
+        self.read_verified_header(&mut &header[..bytes_amount_read], padding)?;
+        let chunk_bytes = self.encrypted_block_bytes(padding) + Key::CHUNK_CHECKSUM_SIZE;
+        let batch_len = chunk_bytes * Key::ASYNC_BATCH_CHUNKS;
+        let key = self.shared();
+        let mut chunk_index = 0usize;
+        // the integrity tag is the last chunk, see `Key::decode()`
+        let mut hasher = Sha256::new();
+        let mut held_back: Option<Zeroizing<Vec<u8>>> = None;
+        loop {
+            let mut batch = vec![0u8; batch_len];
+            let bytes_amount_read = read_chunk_async(input, &mut batch).await?;
+            batch.truncate(bytes_amount_read);
+
+            let key = Arc::clone(&key);
+            let first_index = chunk_index;
+            let messages = run_blocking(move || {
+                let mut messages = Vec::new();
+                for (index, chunk) in (first_index..).zip(batch.chunks(chunk_bytes)) {
+                    if chunk.len() < chunk_bytes || !Key::chunk_checksum_matches(chunk) {
+                        return Err(RsaError::ChecksumMismatch(index));
+                    }
+                    let block = Zeroizing::new(key.decrypt_block(
+                        &chunk[..chunk_bytes - Key::CHUNK_CHECKSUM_SIZE],
+                        padding,
+                        true,
+                    )?);
+                    messages.push(block);
+                }
+                Ok(messages)
+            })
+            .await?;
+            let mut decoded = Zeroizing::new(Vec::new());
+            for message in messages {
+                if let Some(previous) = held_back.replace(message) {
+                    hasher.update(&*previous);
+                    decoded.extend_from_slice(&previous);
+                }
+            }
+            output.write_all(&decoded).await?;
+            chunk_index += bytes_amount_read.div_ceil(chunk_bytes);
+
+            if bytes_amount_read < batch_len {
+                break;
+            }
+        }
+        output.flush().await?;
+        self.verify_integrity_tag(hasher, held_back.as_deref().map(Vec::as_slice), padding)?;
+        Ok(())
+    }
+
+    /// Copy of this Key, owned by the blocking tasks.
+    fn shared(&self) -> Arc<Key> {
+        Arc::new(Key {
+            exponent: self.exponent.clone(),
+            modulus: self.modulus.clone(),
+            variant: self.variant,
+            crt: self.crt.clone(),
+            metadata: self.metadata.clone(),
+        })
+    }
+}
+
+/// Runs `work` on the blocking thread pool.
+async fn run_blocking<T, F>(work: F) -> RsaResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> RsaResult<T> + Send + 'static,
+{
+    task::spawn_blocking(work)
+        .await
+        .map_err(|error| RsaError::UnknownError(format!("blocking task failed: {error}")))?
+}
+
+/// Same as `read_chunk()`, for an [`AsyncRead`].
+async fn read_chunk_async<R: AsyncRead + Unpin>(
+    input: &mut R,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        match input.read(&mut buf[total..]).await? {
+            0 => break,
+            n => total += n,
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, KeyPair};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_encode_decode_async() {
+        let pair = KeyPair::demo();
+        let chunk_len = pair
+            .public_key
+            .max_message_bytes(PaddingScheme::Oaep)
+            .unwrap();
+        // crosses a batch boundary
+        let original: Vec<u8> = (0..chunk_len * Key::ASYNC_BATCH_CHUNKS + 5)
+            .map(|i| u8::try_from(i % 256).unwrap())
+            .collect();
+
+        block_on(async {
+            let mut encoded = Vec::new();
+            pair.public_key
+                .encode_async(&mut original.as_slice(), &mut encoded, PaddingScheme::Oaep)
+                .await
+                .unwrap();
+
+            let mut decoded = Vec::new();
+            pair.private_key
+                .decode(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+                .unwrap();
+            assert_eq!(decoded, original);
+
+            let mut decoded = Vec::new();
+            pair.private_key
+                .decode_async(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+                .await
+                .unwrap();
+            assert_eq!(decoded, original);
+
+            // the last chunk is the integrity tag, after the data chunks of both batches
+            let last = encoded.len() - 1;
+            encoded[last] ^= 0x01;
+            assert!(matches!(
+                pair.private_key
+                    .decode_async(&mut encoded.as_slice(), &mut Vec::new(), PaddingScheme::Oaep)
+                    .await,
+                Err(RsaError::ChecksumMismatch(index)) if index == Key::ASYNC_BATCH_CHUNKS + 1
+            ));
+        });
+    }
+
+    #[test]
+    fn test_encode_decode_async_errors() {
+        block_on(async {
+            let pair = test_pair();
+            assert!(pair
+                .private_key
+                .encode_async(&mut &b"data"[..], &mut Vec::new(), PaddingScheme::None)
+                .await
+                .is_err());
+            assert!(pair
+                .public_key
+                .decode_async(&mut &b"data"[..], &mut Vec::new(), PaddingScheme::None)
+                .await
+                .is_err());
+
+            let mut encoded = Vec::new();
+            pair.public_key
+                .encode_async(&mut &b""[..], &mut encoded, PaddingScheme::None)
+                .await
+                .unwrap();
+            // only the integrity tag
+            assert_eq!(
+                encoded.len(),
+                CIPHERTEXT_HEADER_LEN + 5 + Key::CHUNK_CHECKSUM_SIZE
+            );
+        });
+    }
+}
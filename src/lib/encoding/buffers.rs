@@ -0,0 +1,331 @@
+//! In memory plaintext and ciphertext, as distinct types so one is never passed for the other.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io::Cursor;
+use zeroize::Zeroizing;
+
+use super::{armor, padding::PaddingScheme};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+
+/// Bytes before encoding, wiped on drop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plaintext(Zeroizing<Vec<u8>>);
+
+/// Bytes produced by [`Key::encode()`], every chunk followed by its CRC32.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext(Vec<u8>);
+
+impl Plaintext {
+    #[must_use]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(Zeroizing::new(bytes))
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Ciphertext {
+    #[must_use]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Formats this [`Ciphertext`] as ASCII armor, see [`armor::armor()`].
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::{padding::PaddingScheme, Ciphertext, Plaintext};
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let plaintext = Plaintext::from_bytes(b"attack at dawn".to_vec());
+    /// let ciphertext = KeyPair::demo().public_key.encrypt(&plaintext, PaddingScheme::Oaep).unwrap();
+    /// let armored = ciphertext.to_armored();
+    /// assert_eq!(Ciphertext::from_armored(&armored).unwrap(), ciphertext);
+    /// ```
+    #[must_use]
+    pub fn to_armored(&self) -> String {
+        armor::armor(&self.0)
+    }
+
+    /// Extracts a [`Ciphertext`] from ASCII armor, see [`armor::dearmor()`].
+    ///
+    /// # Errors
+    /// Same as [`armor::dearmor()`].
+    pub fn from_armored(armored: &str) -> RsaResult<Self> {
+        armor::dearmor(armored).map(Self)
+    }
+
+    /// Returns the header fields of ASCII armor, such as its `Format` and `Producer`.
+    ///
+    /// # Errors
+    /// If the armor markers are missing.
+    pub fn armor_headers(armored: &str) -> RsaResult<Vec<(String, String)>> {
+        armor::headers(armored)
+    }
+}
+
+impl Key {
+    /// Encodes a [`Plaintext`] using this Public Key and the given [`PaddingScheme`].
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::{padding::PaddingScheme, Plaintext};
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let plaintext = Plaintext::from_bytes(b"attack at dawn".to_vec());
+    /// let ciphertext = pair.public_key.encrypt(&plaintext, PaddingScheme::Pkcs1v15).unwrap();
+    /// let decrypted = pair.private_key.decrypt(&ciphertext, PaddingScheme::Pkcs1v15).unwrap();
+    /// assert_eq!(decrypted, plaintext);
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encrypt(&self, plaintext: &Plaintext, padding: PaddingScheme) -> RsaResult<Ciphertext> {
+        self.encode_bytes(plaintext.as_bytes(), padding)
+            .map(Ciphertext)
+    }
+
+    /// Decodes a [`Ciphertext`] using this Private Key and the given [`PaddingScheme`].
+    ///
+    /// # Errors
+    /// Same as [`Key::decode()`].
+    pub fn decrypt(&self, ciphertext: &Ciphertext, padding: PaddingScheme) -> RsaResult<Plaintext> {
+        let mut output = Zeroizing::new(Vec::new());
+        self.decode(
+            &mut Cursor::new(ciphertext.as_bytes()),
+            &mut *output,
+            padding,
+        )?;
+        Ok(Plaintext(output))
+    }
+
+    /// Same as [`Key::encode()`], from and to memory.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let encoded = pair.public_key.encode_bytes(b"attack at dawn", PaddingScheme::Oaep).unwrap();
+    /// let decoded = pair.private_key.decode_bytes(&encoded, PaddingScheme::Oaep).unwrap();
+    /// assert_eq!(decoded, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encode_bytes(&self, bytes: &[u8], padding: PaddingScheme) -> RsaResult<Vec<u8>> {
+        let mut output = Vec::new();
+        self.encode(&mut Cursor::new(bytes), &mut output, padding)?;
+        Ok(output)
+    }
+
+    /// Same as [`Key::decode()`], from and to memory.
+    ///
+    /// The output is not wiped on drop, see [`Key::decrypt()`] for that.
+    ///
+    /// # Errors
+    /// Same as [`Key::decode()`].
+    pub fn decode_bytes(&self, bytes: &[u8], padding: PaddingScheme) -> RsaResult<Vec<u8>> {
+        let mut output = Vec::new();
+        self.decode(&mut Cursor::new(bytes), &mut output, padding)?;
+        Ok(output)
+    }
+
+    /// Encrypts `message` with [`PaddingScheme::Oaep`] using this Public Key,
+    /// returning the ciphertext as base64, e.g. for tokens or config values.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let encrypted = pair.public_key.encrypt_str("attack at dawn").unwrap();
+    /// assert_eq!(pair.private_key.decrypt_str(&encrypted).unwrap(), "attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encrypt_str(&self, message: &str) -> RsaResult<String> {
+        let encoded = self.encode_bytes(message.as_bytes(), PaddingScheme::Oaep)?;
+        Ok(STANDARD.encode(encoded))
+    }
+
+    /// Decrypts the output of [`Key::encrypt_str()`] using this Private Key.
+    ///
+    /// # Errors
+    /// - If `encrypted` is not valid base64.
+    /// - If the decrypted message is not valid UTF-8.
+    /// - Same as [`Key::decode()`].
+    pub fn decrypt_str(&self, encrypted: &str) -> RsaResult<String> {
+        let encoded = STANDARD.decode(encrypted.trim()).map_err(|_| {
+            RsaError::ImproperlyFormattedStr("because the ciphertext is not valid base64".into())
+        })?;
+        let decoded = Zeroizing::new(self.decode_bytes(&encoded, PaddingScheme::Oaep)?);
+        String::from_utf8(decoded.to_vec()).map_err(|_| RsaError::EncodingError)
+    }
+
+    /// Seals `plaintext` in an envelope for this Public Key, see [`Key::encode_envelope()`],
+    /// returned as ASCII armor so short messages can be pasted without a file.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::Plaintext, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let plaintext = Plaintext::from_bytes(b"attack at dawn".to_vec());
+    /// let armored = pair.public_key.encrypt_text(&plaintext).unwrap();
+    /// assert!(armored.starts_with("-----BEGIN RRSA MESSAGE-----"));
+    /// assert_eq!(pair.private_key.decrypt_text(&armored).unwrap(), plaintext);
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode_envelope()`].
+    pub fn encrypt_text(&self, plaintext: &Plaintext) -> RsaResult<String> {
+        let mut sealed = Vec::new();
+        self.encode_envelope(&mut Cursor::new(plaintext.as_bytes()), &mut sealed)?;
+        Ok(armor::armor(&sealed))
+    }
+
+    /// Decrypts ASCII armor using this Private Key, such as the output of [`Key::encrypt_text()`].
+    ///
+    /// # Errors
+    /// - If `armored` is not ASCII armor.
+    /// - Same as [`Key::decode()`].
+    pub fn decrypt_text(&self, armored: &str) -> RsaResult<Plaintext> {
+        let armored = armored.trim();
+        if !armor::is_armored(armored.as_bytes()) {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because it is not ASCII armor".into(),
+            ));
+        }
+        let mut output = Zeroizing::new(Vec::new());
+        self.decode(
+            &mut Cursor::new(armored.as_bytes()),
+            &mut *output,
+            PaddingScheme::None,
+        )?;
+        Ok(Plaintext(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::header::CIPHERTEXT_HEADER_LEN;
+    use crate::key::{tests::test_pair, KeyPair};
+    use crate::version;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let plaintext = Plaintext::from_bytes(b"typed buffers".to_vec());
+        let ciphertext = test_pair()
+            .public_key
+            .encrypt(&plaintext, PaddingScheme::None)
+            .unwrap();
+        assert_ne!(ciphertext.as_bytes(), plaintext.as_bytes());
+
+        let armored = ciphertext.to_armored();
+        assert_eq!(Ciphertext::from_armored(&armored).unwrap(), ciphertext);
+        assert_eq!(
+            Ciphertext::armor_headers(&armored).unwrap(),
+            vec![
+                ("Format".to_string(), armor::ARMOR_VERSION.to_string()),
+                ("Producer".to_string(), version::producer()),
+            ]
+        );
+
+        let decrypted = test_pair()
+            .private_key
+            .decrypt(&ciphertext, PaddingScheme::None)
+            .unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_str() {
+        let pair = KeyPair::demo();
+        let encrypted = pair.public_key.encrypt_str("token: ✓").unwrap();
+        assert!(STANDARD.decode(&encrypted).is_ok());
+        assert_ne!(encrypted, pair.public_key.encrypt_str("token: ✓").unwrap());
+        assert_eq!(
+            pair.private_key
+                .decrypt_str(&format!("{encrypted}\n"))
+                .unwrap(),
+            "token: ✓"
+        );
+        assert_eq!(
+            pair.private_key
+                .decrypt_str(&pair.public_key.encrypt_str("").unwrap())
+                .unwrap(),
+            ""
+        );
+
+        assert!(pair.private_key.decrypt_str("not base64!").is_err());
+        assert!(pair.public_key.decrypt_str(&encrypted).is_err());
+        let invalid_utf8 = STANDARD.encode(
+            pair.public_key
+                .encode_bytes(&[0xff, 0xfe], PaddingScheme::Oaep)
+                .unwrap(),
+        );
+        assert!(matches!(
+            pair.private_key.decrypt_str(&invalid_utf8),
+            Err(RsaError::EncodingError)
+        ));
+
+        let plaintext = Plaintext::from_bytes(b"one line secret".to_vec());
+        let armored = pair.public_key.encrypt_text(&plaintext).unwrap();
+        assert_eq!(
+            pair.private_key
+                .decrypt_text(&format!("\n{armored}\n"))
+                .unwrap(),
+            plaintext
+        );
+        assert!(pair.private_key.decrypt_text(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_bytes() {
+        let pair = test_pair();
+        let encoded = pair
+            .public_key
+            .encode_bytes(b"in memory", PaddingScheme::None)
+            .unwrap();
+        assert_eq!(
+            encoded,
+            pair.public_key
+                .encrypt(
+                    &Plaintext::from_bytes(b"in memory".to_vec()),
+                    PaddingScheme::None
+                )
+                .unwrap()
+                .as_bytes()
+        );
+        assert_eq!(
+            pair.private_key
+                .decode_bytes(&encoded, PaddingScheme::None)
+                .unwrap(),
+            b"in memory"
+        );
+        assert!(
+            pair.public_key
+                .encode_bytes(&[], PaddingScheme::None)
+                .unwrap()
+                .len()
+                == CIPHERTEXT_HEADER_LEN + 5 + Key::CHUNK_CHECKSUM_SIZE
+        );
+        assert!(pair
+            .private_key
+            .encode_bytes(b"in memory", PaddingScheme::None)
+            .is_err());
+        assert!(pair
+            .private_key
+            .decode_bytes(&encoded[1..], PaddingScheme::None)
+            .is_err());
+    }
+}
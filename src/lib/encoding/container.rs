@@ -0,0 +1,273 @@
+//! Parser of the framing of every encoded input, without any Key, for fuzzing.
+//!
+//! [`parse_container()`] takes arbitrary bytes and must never panic, whatever they are,
+//! e.g. as the body of a `cargo fuzz` target:
+//! ```text
+//! fuzz_target!(|bytes: &[u8]| {
+//!     let _ = rrsa_lib::encoding::parse_container(bytes);
+//! });
+//! ```
+//! [`corpus()`] emits the seed inputs of such a target, valid containers of every kind
+//! and mutations of them, the `rrsa-fuzz-corpus` binary writes them to a directory.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::io::Cursor;
+
+use super::{
+    armor,
+    envelope::{self, EnvelopeCipher, EnvelopeHeader, ENVELOPE_SEGMENT_LEN, ENVELOPE_TAG_LEN},
+    header::{CiphertextHeader, CIPHERTEXT_HEADER_LEN},
+    padding::PaddingScheme,
+};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyPair};
+use crate::rng;
+use crate::testvectors::PLAINTEXT;
+
+/// Seed of the generator of the corpus, both for encoding and for the mutations.
+pub const CORPUS_SEED: u64 = 0xF022_C0E9_0500_5EED;
+/// Amount of mutations of every valid container in the corpus.
+const MUTATIONS_PER_CONTAINER: usize = 8;
+
+/// Framing of an encoded input, as parsed by [`parse_container()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Container {
+    /// Chunks written by [`Key::encode()`], the last one being the integrity tag.
+    Blocks {
+        header: CiphertextHeader,
+        /// Amount of chunks, each with a matching checksum.
+        chunks: usize,
+    },
+    /// Envelope written by [`Key::encode_envelope()`].
+    Envelope {
+        cipher: EnvelopeCipher,
+        /// Amount of wrapped data keys, including the escrowed one if any.
+        recipients: usize,
+        /// Amount of sealed segments, the last one possibly empty.
+        segments: usize,
+    },
+}
+
+/// Parses the header and framing of an encoded input, armored or not,
+/// checking everything that does not need a Key: the chunk checksums and sizes
+/// of [`Key::encode()`], or the wrapped data keys and segment sizes of an envelope.
+///
+/// ```
+/// use rrsa_lib::encoding::{padding::PaddingScheme, parse_container, Container};
+/// use rrsa_lib::key::KeyPair;
+///
+/// let mut encoded = Vec::new();
+/// KeyPair::demo()
+///     .public_key
+///     .encode(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+///     .unwrap();
+/// assert!(matches!(
+///     parse_container(&encoded),
+///     Ok(Container::Blocks { chunks: 2, .. })
+/// ));
+/// assert!(parse_container(&encoded[..encoded.len() - 1]).is_err());
+/// ```
+///
+/// # Errors
+/// - [`RsaError::MissingHeader`] if `bytes` are neither an envelope nor have a header.
+/// - If the armor, the header or the framing is invalid.
+/// - [`RsaError::ChecksumMismatch`] with the index of the first damaged chunk.
+pub fn parse_container(bytes: &[u8]) -> RsaResult<Container> {
+    if armor::is_armored(bytes) {
+        let armored = std::str::from_utf8(bytes)
+            .map_err(|_| RsaError::ImproperlyFormattedStr("armor, it is not UTF-8".into()))?;
+        return parse_dearmored(&armor::dearmor(armored)?);
+    }
+    parse_dearmored(bytes)
+}
+
+fn parse_dearmored(bytes: &[u8]) -> RsaResult<Container> {
+    if envelope::is_envelope(bytes) {
+        parse_envelope(bytes)
+    } else {
+        parse_blocks(bytes)
+    }
+}
+
+fn parse_blocks(bytes: &[u8]) -> RsaResult<Container> {
+    let header = CiphertextHeader::parse(bytes)?.ok_or(RsaError::MissingHeader)?;
+    let block_bytes = super::encrypted_block_bytes_for(header.modulus_bits.into(), header.padding);
+    let chunk_len = block_bytes + Key::CHUNK_CHECKSUM_SIZE;
+    let body = &bytes[CIPHERTEXT_HEADER_LEN..];
+    if body.is_empty() || !body.len().is_multiple_of(chunk_len) {
+        return Err(RsaError::ImproperlyFormattedStr(format!(
+            "chunks, {} bytes are not a whole amount of {chunk_len} bytes chunks",
+            body.len()
+        )));
+    }
+    if let Some(damaged) = body
+        .chunks(chunk_len)
+        .position(|chunk| !Key::chunk_checksum_matches(chunk))
+    {
+        return Err(RsaError::ChecksumMismatch(damaged));
+    }
+    Ok(Container::Blocks {
+        header,
+        chunks: body.len() / chunk_len,
+    })
+}
+
+fn parse_envelope(bytes: &[u8]) -> RsaResult<Container> {
+    let mut input = Cursor::new(bytes);
+    let header = EnvelopeHeader::read(&mut input)?;
+    let body = &bytes[usize::try_from(input.position()).unwrap_or(bytes.len())..];
+    // every segment but the last one is full, and the last one has at least its tag
+    let sealed_len = ENVELOPE_SEGMENT_LEN + ENVELOPE_TAG_LEN;
+    if body.len() % sealed_len < ENVELOPE_TAG_LEN {
+        return Err(RsaError::ChecksumMismatch(body.len() / sealed_len));
+    }
+    Ok(Container::Envelope {
+        cipher: header.cipher,
+        recipients: header.wrapped_keys.len(),
+        segments: body.len() / sealed_len + 1,
+    })
+}
+
+/// Generates the seed corpus of a fuzz target of [`parse_container()`]: valid containers
+/// of every kind, then mutations of each, truncated, extended or with bytes flipped.
+///
+/// Every input is deterministic, its randomness coming from [`CORPUS_SEED`].
+///
+/// # Returns
+/// The file name and contents of every input, valid ones named `valid_*`.
+///
+/// # Errors
+/// Propagates any error from encoding the valid containers.
+pub fn corpus() -> RsaResult<Vec<(String, Vec<u8>)>> {
+    let valid = rng::with_source(ChaCha20Rng::seed_from_u64(CORPUS_SEED), valid_containers)?;
+    let mut rng = ChaCha20Rng::seed_from_u64(CORPUS_SEED);
+    let mut corpus = Vec::new();
+    for (name, contents) in valid {
+        for mutation in 0..MUTATIONS_PER_CONTAINER {
+            corpus.push((
+                format!("mutated_{name}_{mutation}"),
+                mutate(&contents, &mut rng),
+            ));
+        }
+        corpus.push((format!("valid_{name}"), contents));
+    }
+    Ok(corpus)
+}
+
+/// Valid containers of every kind, with short inputs so the corpus stays small.
+fn valid_containers() -> RsaResult<Vec<(&'static str, Vec<u8>)>> {
+    let pair = KeyPair::demo();
+    let plaintext = PLAINTEXT.as_bytes();
+    let mut containers = Vec::new();
+    for (name, padding) in [
+        ("blocks_none", PaddingScheme::None),
+        ("blocks_oaep", PaddingScheme::Oaep),
+        ("blocks_pkcs1v15", PaddingScheme::Pkcs1v15),
+    ] {
+        let mut encoded = Vec::new();
+        pair.public_key
+            .encode(&mut &plaintext[..], &mut encoded, padding)?;
+        containers.push((name, encoded));
+    }
+    let mut empty = Vec::new();
+    pair.public_key
+        .encode(&mut &[][..], &mut empty, PaddingScheme::Oaep)?;
+    containers.push(("blocks_empty", empty));
+
+    for (name, cipher) in [
+        ("envelope_aes", EnvelopeCipher::Aes256Gcm),
+        ("envelope_chacha", EnvelopeCipher::ChaCha20Poly1305),
+    ] {
+        let mut sealed = Vec::new();
+        Key::encode_envelope_with(
+            &[&pair.public_key],
+            cipher,
+            &mut &plaintext[..],
+            &mut sealed,
+        )?;
+        containers.push((name, sealed));
+    }
+    // the second recipient makes a header with several wrapped keys
+    let mut sealed = Vec::new();
+    Key::encode_envelope_for(
+        &[&pair.public_key, &pair.public_key],
+        &mut vec![0u8; ENVELOPE_SEGMENT_LEN].as_slice(),
+        &mut sealed,
+    )?;
+    containers.push(("envelope_two_segments", sealed));
+
+    let mut armored = Vec::new();
+    pair.public_key
+        .encode_armored(&mut &plaintext[..], &mut armored, PaddingScheme::Oaep)?;
+    containers.push(("armored", armored));
+    Ok(containers)
+}
+
+/// Mutates a copy of `bytes`, biased towards the header, where the parser branches the most.
+fn mutate(bytes: &[u8], rng: &mut ChaCha20Rng) -> Vec<u8> {
+    let mut mutated = bytes.to_vec();
+    let header_len = mutated.len().min(2 * CIPHERTEXT_HEADER_LEN);
+    match rng.gen_range(0..4) {
+        0 => mutated.truncate(rng.gen_range(0..mutated.len())),
+        1 => mutated.extend((0..rng.gen_range(1..64)).map(|_| rng.gen::<u8>())),
+        2 => {
+            let index = rng.gen_range(0..header_len);
+            mutated[index] ^= 1 << rng.gen_range(0..8);
+        }
+        _ => {
+            let index = rng.gen_range(0..mutated.len());
+            mutated[index] = rng.gen();
+        }
+    }
+    mutated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_container() {
+        let inputs = corpus().unwrap();
+        assert_eq!(inputs, corpus().unwrap());
+        for (name, contents) in &inputs {
+            let parsed = parse_container(contents);
+            if name.starts_with("valid_") {
+                assert!(parsed.is_ok(), "{name}: {parsed:?}");
+            }
+        }
+        let find = |name: &str| &inputs.iter().find(|(found, _)| found == name).unwrap().1;
+        assert!(matches!(
+            parse_container(find("valid_blocks_empty")),
+            Ok(Container::Blocks { chunks: 1, .. })
+        ));
+        assert_eq!(
+            parse_container(find("valid_envelope_two_segments")).unwrap(),
+            Container::Envelope {
+                cipher: EnvelopeCipher::Aes256Gcm,
+                recipients: 2,
+                segments: 2,
+            }
+        );
+
+        let mut damaged = find("valid_blocks_oaep").clone();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0x01;
+        assert!(matches!(
+            parse_container(&damaged),
+            Err(RsaError::ChecksumMismatch(1))
+        ));
+        // truncated at a segment boundary
+        let envelope = find("valid_envelope_two_segments");
+        assert!(matches!(
+            parse_container(&envelope[..envelope.len() - ENVELOPE_TAG_LEN]),
+            Err(RsaError::ChecksumMismatch(1))
+        ));
+        assert!(matches!(
+            parse_container(b"no header"),
+            Err(RsaError::MissingHeader)
+        ));
+        assert!(parse_container(b"").is_err());
+    }
+}
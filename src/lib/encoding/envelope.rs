@@ -0,0 +1,1015 @@
+//! Hybrid envelope encryption: a random data key encrypts the input with AES-256-GCM
+//! or ChaCha20-Poly1305, and only that data key is encrypted with RSA, as in PGP or CMS.
+//!
+//! Encrypting whole files block by block with RSA costs a modular exponentiation per block,
+//! the envelope only needs one to wrap the data key, however large the input is.
+//!
+//! ## Format
+//! 1. [`ENVELOPE_MAGIC`] followed by [`ENVELOPE_VERSION`] as a big endian `u32`.
+//! 2. The [`EnvelopeCipher`] as one byte, `0` for AES-256-GCM and `1` for ChaCha20-Poly1305.
+//!    Envelopes of version 2 or lower have no cipher byte and use AES-256-GCM.
+//! 3. Number of recipients as a big endian `u32`. For each recipient, the length of the
+//!    wrapped data key as a big endian `u32`, then the data key encoded by [`Key::encode()`]
+//!    with [`PaddingScheme::Oaep`] and the Public Key of that recipient, or by
+//!    [`Key::encode_without_key_hint()`] so the envelope does not reveal its recipients.
+//!    Envelopes of version 1 have a single recipient and no number of recipients.
+//!    An escrowed envelope has one more wrapped data key, the last one, for the escrow agent,
+//!    see [`Key::encode_envelope_escrowed()`].
+//! 4. The input split in segments of [`ENVELOPE_SEGMENT_LEN`] bytes, each sealed with
+//!    the cipher and followed by its 16 bytes tag. The last segment is always shorter,
+//!    possibly empty, and its nonce is flagged as the last one (the STREAM construction),
+//!    so a truncated envelope is detected.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+use super::{padding::PaddingScheme, read_chunk};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+use crate::{rng, version};
+
+/// First bytes of every envelope.
+pub const ENVELOPE_MAGIC: &[u8; 8] = b"RRSA-ENV";
+/// Current version of the format, bumped on any incompatible change.
+pub const ENVELOPE_VERSION: u32 = 3;
+/// Amount of plaintext bytes sealed in every segment but the last one.
+pub const ENVELOPE_SEGMENT_LEN: usize = 64 * 1024;
+/// Size of the authentication tag after every segment.
+pub const ENVELOPE_TAG_LEN: usize = 16;
+/// Size of the data key, the same for both ciphers.
+const DATA_KEY_LEN: usize = 32;
+/// Largest wrapped data key accepted, far above what a 4096 bits key produces.
+const MAX_WRAPPED_KEY_LEN: usize = 64 * 1024;
+/// Largest number of recipients accepted.
+const MAX_RECIPIENTS: u32 = 1024;
+
+/// Returns `true` if `bytes` start like an envelope.
+#[must_use]
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    bytes.starts_with(ENVELOPE_MAGIC)
+}
+
+/// Symmetric cipher sealing the segments of an envelope, recorded in its header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnvelopeCipher {
+    /// AES-256-GCM, the fastest where the CPU has AES instructions.
+    #[default]
+    Aes256Gcm,
+    /// ChaCha20-Poly1305, faster than AES in software, e.g. on CPUs without AES instructions.
+    ChaCha20Poly1305,
+}
+
+impl EnvelopeCipher {
+    fn to_byte(self) -> u8 {
+        match self {
+            EnvelopeCipher::Aes256Gcm => 0,
+            EnvelopeCipher::ChaCha20Poly1305 => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> RsaResult<Self> {
+        match byte {
+            0 => Ok(EnvelopeCipher::Aes256Gcm),
+            1 => Ok(EnvelopeCipher::ChaCha20Poly1305),
+            _ => Err(RsaError::InvalidEnvelope(format!("unknown cipher {byte}"))),
+        }
+    }
+}
+
+/// Options of [`Key::encode_envelope_with_options()`].
+#[derive(Debug, Clone, Copy)]
+pub struct EnvelopeOptions<'k> {
+    /// Cipher sealing the segments.
+    pub cipher: EnvelopeCipher,
+    /// Public Key of an escrow agent the data key is also wrapped for,
+    /// see [`Key::encode_envelope_escrowed()`].
+    pub escrow: Option<&'k Key>,
+    /// Whether the header of every wrapped data key holds the fingerprint of its recipient,
+    /// see [`Key::encode_without_key_hint()`].
+    pub key_hint: bool,
+}
+
+impl Default for EnvelopeOptions<'_> {
+    fn default() -> Self {
+        Self {
+            cipher: EnvelopeCipher::default(),
+            escrow: None,
+            key_hint: true,
+        }
+    }
+}
+
+/// An [`EnvelopeCipher`] keyed with the data key of an envelope.
+pub(crate) enum SegmentCipher {
+    Aes256Gcm(Box<Aes256Gcm>),
+    ChaCha20Poly1305(ChaCha20Poly1305),
+}
+
+impl SegmentCipher {
+    fn new(cipher: EnvelopeCipher, data_key: &[u8]) -> RsaResult<Self> {
+        let invalid = |_| RsaError::InvalidEnvelope("invalid data key length".into());
+        Ok(match cipher {
+            EnvelopeCipher::Aes256Gcm => SegmentCipher::Aes256Gcm(Box::new(
+                Aes256Gcm::new_from_slice(data_key).map_err(invalid)?,
+            )),
+            EnvelopeCipher::ChaCha20Poly1305 => SegmentCipher::ChaCha20Poly1305(
+                ChaCha20Poly1305::new_from_slice(data_key).map_err(invalid)?,
+            ),
+        })
+    }
+
+    /// Seals the segment number `counter`, see [`segment_nonce()`].
+    fn seal(&self, counter: u32, last: bool, segment: &[u8]) -> RsaResult<Vec<u8>> {
+        let nonce = segment_nonce(counter, last);
+        match self {
+            SegmentCipher::Aes256Gcm(cipher) => cipher.encrypt(&nonce, segment),
+            SegmentCipher::ChaCha20Poly1305(cipher) => cipher.encrypt(&nonce, segment),
+        }
+        .map_err(|_| RsaError::EncodingError)
+    }
+
+    /// Opens the segment number `counter`.
+    ///
+    /// # Errors
+    /// [`RsaError::ChecksumMismatch`] if the segment was modified, or is not the one expected.
+    fn open(&self, counter: u32, last: bool, sealed: &[u8]) -> RsaResult<Vec<u8>> {
+        let nonce = segment_nonce(counter, last);
+        match self {
+            SegmentCipher::Aes256Gcm(cipher) => cipher.decrypt(&nonce, sealed),
+            SegmentCipher::ChaCha20Poly1305(cipher) => cipher.decrypt(&nonce, sealed),
+        }
+        .map_err(|_| RsaError::ChecksumMismatch(counter as usize))
+    }
+}
+
+impl Key {
+    /// Encrypts a [`Read`] implementor into an envelope written to a [`Write`] implementor,
+    /// see the [module documentation](self) for the format.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut sealed = Vec::new();
+    /// pair.public_key
+    ///     .encode_envelope(&mut &b"attack at dawn"[..], &mut sealed)
+    ///     .unwrap();
+    ///
+    /// // decoding detects the envelope, the padding scheme is not used
+    /// let mut opened = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut sealed.as_slice(), &mut opened, PaddingScheme::None)
+    ///     .unwrap();
+    /// assert_eq!(opened, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PublicKey`].
+    /// - If the key is too small for [`PaddingScheme::Oaep`].
+    /// - If any [`std::io::Error`] occurs.
+    pub fn encode_envelope<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        Key::encode_envelope_for(&[self], input, output)
+    }
+
+    /// Same as [`Key::encode_envelope()`], but the envelope can be decrypted
+    /// by the Private Key of any of `recipients`.
+    ///
+    /// The data key is wrapped once per recipient, so the envelope only grows
+    /// by one RSA block per recipient, however large the input is.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::padding::PaddingScheme;
+    /// use rrsa_lib::key::{Key, KeyPair, KeySize};
+    ///
+    /// let alice = KeyPair::demo();
+    /// let bob = KeyPair::builder().size(KeySize::B1024).build().unwrap();
+    /// let mut sealed = Vec::new();
+    /// Key::encode_envelope_for(
+    ///     &[&alice.public_key, &bob.public_key],
+    ///     &mut &b"attack at dawn"[..],
+    ///     &mut sealed,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut opened = Vec::new();
+    /// bob.private_key
+    ///     .decode(&mut sealed.as_slice(), &mut opened, PaddingScheme::None)
+    ///     .unwrap();
+    /// assert_eq!(opened, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// - If `recipients` is empty, or has more than 1024 Keys.
+    /// - If any of `recipients` is not a [`KeyVariant::PublicKey`].
+    /// - If any of `recipients` is too small for [`PaddingScheme::Oaep`].
+    /// - If any [`std::io::Error`] occurs.
+    pub fn encode_envelope_for<R: Read, W: Write>(
+        recipients: &[&Key],
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        Key::encode_envelope_with(recipients, EnvelopeCipher::default(), input, output)
+    }
+
+    /// Same as [`Key::encode_envelope_for()`], but the segments are sealed with `cipher`,
+    /// which is recorded in the header, so decoding needs no option.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::{envelope::EnvelopeCipher, padding::PaddingScheme};
+    /// use rrsa_lib::key::{Key, KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut sealed = Vec::new();
+    /// Key::encode_envelope_with(
+    ///     &[&pair.public_key],
+    ///     EnvelopeCipher::ChaCha20Poly1305,
+    ///     &mut &b"attack at dawn"[..],
+    ///     &mut sealed,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut opened = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut sealed.as_slice(), &mut opened, PaddingScheme::None)
+    ///     .unwrap();
+    /// assert_eq!(opened, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode_envelope_for()`].
+    pub fn encode_envelope_with<R: Read, W: Write>(
+        recipients: &[&Key],
+        cipher: EnvelopeCipher,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        let options = EnvelopeOptions {
+            cipher,
+            ..EnvelopeOptions::default()
+        };
+        Key::encode_envelope_with_options(recipients, &options, input, output)
+    }
+
+    /// Same as [`Key::encode_envelope_with()`], but the data key is also wrapped for `escrow`,
+    /// the Public Key of an escrow agent, whose Private Key then decrypts it as any recipient.
+    ///
+    /// Key escrow trades the privacy of every envelope for the recovery of any of them:
+    /// whoever holds the escrow Private Key can read everything, without the recipients
+    /// knowing, as the escrow wrapped data key looks like the one of any other recipient.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::{envelope::EnvelopeCipher, padding::PaddingScheme};
+    /// use rrsa_lib::key::{Key, KeyPair, KeySize};
+    ///
+    /// let alice = KeyPair::demo();
+    /// let escrow = KeyPair::builder().size(KeySize::B1024).build().unwrap();
+    /// let mut sealed = Vec::new();
+    /// Key::encode_envelope_escrowed(
+    ///     &[&alice.public_key],
+    ///     &escrow.public_key,
+    ///     EnvelopeCipher::default(),
+    ///     &mut &b"attack at dawn"[..],
+    ///     &mut sealed,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut opened = Vec::new();
+    /// escrow
+    ///     .private_key
+    ///     .decode(&mut sealed.as_slice(), &mut opened, PaddingScheme::None)
+    ///     .unwrap();
+    /// assert_eq!(opened, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode_envelope_for()`], counting `escrow` as one more recipient.
+    pub fn encode_envelope_escrowed<R: Read, W: Write>(
+        recipients: &[&Key],
+        escrow: &Key,
+        cipher: EnvelopeCipher,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        let options = EnvelopeOptions {
+            cipher,
+            escrow: Some(escrow),
+            ..EnvelopeOptions::default()
+        };
+        Key::encode_envelope_with_options(recipients, &options, input, output)
+    }
+
+    /// Same as [`Key::encode_envelope_for()`], with every option of [`EnvelopeOptions`].
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::envelope::EnvelopeOptions;
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::io::Cursor;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let options = EnvelopeOptions {
+    ///     key_hint: false,
+    ///     ..EnvelopeOptions::default()
+    /// };
+    /// let mut sealed = Vec::new();
+    /// Key::encode_envelope_with_options(
+    ///     &[&pair.public_key],
+    ///     &options,
+    ///     &mut &b"attack at dawn"[..],
+    ///     &mut sealed,
+    /// )
+    /// .unwrap();
+    /// assert!(!Key::has_key_hint(&mut Cursor::new(&sealed)).unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode_envelope_for()`], counting the escrow Key as one more recipient.
+    pub fn encode_envelope_with_options<R: Read, W: Write>(
+        recipients: &[&Key],
+        options: &EnvelopeOptions,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!(
+            "encode_envelope",
+            recipients = recipients.len(),
+            escrowed = options.escrow.is_some(),
+            key_hint = options.key_hint,
+            cipher = ?options.cipher
+        )
+        .entered();
+        let mut data_key = Zeroizing::new([0u8; DATA_KEY_LEN]);
+        rng::fill_bytes(&mut *data_key);
+        write_envelope_header(recipients, options, &*data_key, output)?;
+
+        let cipher = SegmentCipher::new(options.cipher, &*data_key)?;
+        let mut segment = Zeroizing::new(vec![0u8; ENVELOPE_SEGMENT_LEN]);
+        let mut counter = 0u32;
+        loop {
+            let bytes_amount_read = read_chunk(input, &mut segment)?;
+            let last = bytes_amount_read < ENVELOPE_SEGMENT_LEN;
+            output.write_all(&cipher.seal(counter, last, &segment[..bytes_amount_read])?)?;
+            if last {
+                break;
+            }
+            counter = counter.checked_add(1).ok_or(RsaError::EncodingError)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Decrypts an envelope written by [`Key::encode_envelope()`].
+    ///
+    /// ASCII armor is not detected, [`Key::decode()`] detects both armor and envelopes.
+    ///
+    /// Every segment is authenticated before being written, but segments
+    /// before a damaged one have already been written when the error is returned.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If the header is invalid or the data key was only wrapped for other keys.
+    /// - [`RsaError::ChecksumMismatch`] if a segment was modified, or the envelope truncated.
+    /// - If any [`std::io::Error`] occurs.
+    pub fn decode_envelope<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!("decode_envelope").entered();
+        let cipher = self.read_envelope_header(input)?;
+        let mut sealed = vec![0u8; ENVELOPE_SEGMENT_LEN + ENVELOPE_TAG_LEN];
+        let mut counter = 0u32;
+        loop {
+            let bytes_amount_read = read_chunk(input, &mut sealed)?;
+            let last = bytes_amount_read < sealed.len();
+            let segment =
+                Zeroizing::new(cipher.open(counter, last, &sealed[..bytes_amount_read])?);
+            output.write_all(&segment)?;
+            if last {
+                break;
+            }
+            counter = counter.checked_add(1).ok_or(RsaError::EncodingError)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Verifies every segment of an envelope written by [`Key::encode_envelope()`],
+    /// without writing the plaintext anywhere.
+    ///
+    /// A Private Key unwraps the data key and authenticates every segment. A Public Key
+    /// cannot unwrap it, so only the framing is checked: a truncated envelope is detected,
+    /// but a modified one is not.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut sealed = Vec::new();
+    /// pair.public_key
+    ///     .encode_envelope(&mut &b"attack at dawn"[..], &mut sealed)
+    ///     .unwrap();
+    /// let last = sealed.len() - 1;
+    /// sealed[last] ^= 0x01;
+    ///
+    /// assert_eq!(pair.private_key.verify_envelope(&mut sealed.as_slice()).unwrap(), [0]);
+    /// assert!(pair.public_key.verify_envelope(&mut sealed.as_slice()).unwrap().is_empty());
+    /// ```
+    ///
+    /// # Returns
+    /// The indexes of the damaged segments, empty if the whole envelope is intact.
+    ///
+    /// # Errors
+    /// - If the header is invalid, or with a Private Key, the data key was only wrapped
+    ///   for other keys.
+    /// - If any [`std::io::Error`] occurs.
+    pub fn verify_envelope<R: Read>(&self, input: &mut R) -> RsaResult<Vec<usize>> {
+        let cipher = match self.variant {
+            KeyVariant::PrivateKey => Some(self.read_envelope_header(input)?),
+            KeyVariant::PublicKey => {
+                EnvelopeHeader::read(input)?;
+                None
+            }
+        };
+        let mut sealed = vec![0u8; ENVELOPE_SEGMENT_LEN + ENVELOPE_TAG_LEN];
+        let mut damaged = Vec::new();
+        let mut counter = 0u32;
+        loop {
+            let bytes_amount_read = read_chunk(input, &mut sealed)?;
+            let last = bytes_amount_read < sealed.len();
+            let intact = bytes_amount_read >= ENVELOPE_TAG_LEN
+                && match &cipher {
+                    Some(cipher) => cipher
+                        .open(counter, last, &sealed[..bytes_amount_read])
+                        .is_ok(),
+                    None => true,
+                };
+            if !intact {
+                damaged.push(counter as usize);
+            }
+            if last {
+                break;
+            }
+            counter = counter.checked_add(1).ok_or(RsaError::EncodingError)?;
+        }
+        Ok(damaged)
+    }
+
+    /// Re-keys an envelope written by [`Key::encode_envelope()`] for `recipient`: only the data key
+    /// is unwrapped with this Private Key and wrapped again, the sealed segments are copied
+    /// byte for byte, so re-keying costs the same however large the envelope is.
+    ///
+    /// The data key is not changed, so whoever could decrypt the original envelope can still
+    /// decrypt the segments of the new one, re-keying grants access but never revokes it.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::padding::PaddingScheme;
+    /// use rrsa_lib::key::{KeyPair, KeySize};
+    ///
+    /// let alice = KeyPair::demo();
+    /// let bob = KeyPair::builder().size(KeySize::B1024).build().unwrap();
+    /// let mut sealed = Vec::new();
+    /// alice
+    ///     .public_key
+    ///     .encode_envelope(&mut &b"attack at dawn"[..], &mut sealed)
+    ///     .unwrap();
+    ///
+    /// let mut rekeyed = Vec::new();
+    /// alice
+    ///     .private_key
+    ///     .reencrypt_envelope(&bob.public_key, &mut sealed.as_slice(), &mut rekeyed)
+    ///     .unwrap();
+    /// let mut opened = Vec::new();
+    /// bob.private_key
+    ///     .decode(&mut rekeyed.as_slice(), &mut opened, PaddingScheme::None)
+    ///     .unwrap();
+    /// assert_eq!(opened, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// - Same as [`Key::decode_envelope()`] for the header, the segments are not verified.
+    /// - If `recipient` is not a [`KeyVariant::PublicKey`], or is too small for
+    ///   [`PaddingScheme::Oaep`].
+    pub fn reencrypt_envelope<R: Read, W: Write>(
+        &self,
+        recipient: &Key,
+        input: &mut R,
+        output: &mut W,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!("reencrypt_envelope").entered();
+        let (cipher, data_key) = self.unwrap_envelope_header(input)?;
+        let options = EnvelopeOptions {
+            cipher,
+            ..EnvelopeOptions::default()
+        };
+        write_envelope_header(&[recipient], &options, &data_key, output)?;
+        std::io::copy(input, output)?;
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Reads the header of an envelope and unwraps its data key with this Private Key.
+    ///
+    /// Every wrapped data key is read, so `input` is left at the first segment.
+    ///
+    /// # Errors
+    /// Same as [`Key::decode_envelope()`], the error of unwrapping the last data key
+    /// is returned if none was wrapped for this Key.
+    pub(crate) fn read_envelope_header<R: Read>(&self, input: &mut R) -> RsaResult<SegmentCipher> {
+        let (cipher, data_key) = self.unwrap_envelope_header(input)?;
+        SegmentCipher::new(cipher, &data_key)
+    }
+
+    /// Same as [`Key::read_envelope_header()`], but returns the cipher and the data key.
+    fn unwrap_envelope_header<R: Read>(
+        &self,
+        input: &mut R,
+    ) -> RsaResult<(EnvelopeCipher, Zeroizing<Vec<u8>>)> {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        let header = EnvelopeHeader::read(input)?;
+        let mut data_key = Err(RsaError::WrongRecipient);
+        for wrapped_key in &header.wrapped_keys {
+            data_key = self
+                .decode_bytes(wrapped_key, PaddingScheme::Oaep)
+                .map(Zeroizing::new);
+            if data_key.is_ok() {
+                break;
+            }
+        }
+        Ok((header.cipher, data_key?))
+    }
+}
+
+/// Header of an envelope, with its data key still wrapped for every recipient.
+pub(crate) struct EnvelopeHeader {
+    pub(crate) cipher: EnvelopeCipher,
+    pub(crate) wrapped_keys: Vec<Vec<u8>>,
+}
+
+impl EnvelopeHeader {
+    /// Reads the header at the start of `input`, without any Key,
+    /// so `input` is left at the first segment.
+    ///
+    /// # Errors
+    /// - [`RsaError::InvalidEnvelope`] if the header is truncated or invalid.
+    /// - [`RsaError::UnsupportedVersion`] if it was written by a newer version.
+    /// - Propagates [`std::io::Error`].
+    pub(crate) fn read<R: Read>(input: &mut R) -> RsaResult<Self> {
+        let mut magic = [0u8; ENVELOPE_MAGIC.len()];
+        if read_chunk(input, &mut magic)? < magic.len() || !is_envelope(&magic) {
+            return Err(RsaError::InvalidEnvelope("missing header".into()));
+        }
+        let version = read_u32(input, "version")?;
+        version::check_supported("envelope", &version.to_string(), ENVELOPE_VERSION)?;
+        let cipher = if version <= 2 {
+            EnvelopeCipher::Aes256Gcm
+        } else {
+            let mut byte = [0u8; 1];
+            if read_chunk(input, &mut byte)? < byte.len() {
+                return Err(RsaError::InvalidEnvelope("truncated cipher".into()));
+            }
+            EnvelopeCipher::from_byte(byte[0])?
+        };
+        let recipients_amount = if version == 1 {
+            1
+        } else {
+            read_u32(input, "number of recipients")?
+        };
+        if !(1..=MAX_RECIPIENTS).contains(&recipients_amount) {
+            return Err(RsaError::InvalidEnvelope(format!(
+                "it has {recipients_amount} recipients"
+            )));
+        }
+
+        let mut wrapped_keys = Vec::new();
+        for _ in 0..recipients_amount {
+            let wrapped_key_len = usize::try_from(read_u32(input, "wrapped key length")?)
+                .ok()
+                .filter(|&len| len <= MAX_WRAPPED_KEY_LEN)
+                .ok_or_else(|| RsaError::InvalidEnvelope("the wrapped key is too long".into()))?;
+            let mut wrapped_key = vec![0u8; wrapped_key_len];
+            if read_chunk(input, &mut wrapped_key)? < wrapped_key_len {
+                return Err(RsaError::InvalidEnvelope("truncated wrapped key".into()));
+            }
+            wrapped_keys.push(wrapped_key);
+        }
+        Ok(Self {
+            cipher,
+            wrapped_keys,
+        })
+    }
+}
+
+/// Writes the header of an envelope of the current version, with `data_key` wrapped
+/// for every one of `recipients`, then for the escrow Key of `options` if any.
+///
+/// # Errors
+/// Same as [`Key::encode_envelope_for()`].
+fn write_envelope_header<W: Write>(
+    recipients: &[&Key],
+    options: &EnvelopeOptions,
+    data_key: &[u8],
+    output: &mut W,
+) -> RsaResult<()> {
+    if recipients.is_empty() {
+        return Err(RsaError::InvalidEnvelope(
+            "it must have at least 1 recipient".into(),
+        ));
+    }
+    let recipients: Vec<&Key> = recipients.iter().copied().chain(options.escrow).collect();
+    if recipients
+        .iter()
+        .any(|recipient| recipient.variant != KeyVariant::PublicKey)
+    {
+        return Err(RsaError::WrongKeyVariant);
+    }
+    let recipients_amount = u32::try_from(recipients.len())
+        .ok()
+        .filter(|amount| (1..=MAX_RECIPIENTS).contains(amount))
+        .ok_or_else(|| {
+            RsaError::InvalidEnvelope(format!(
+                "it must have between 1 and {MAX_RECIPIENTS} recipients"
+            ))
+        })?;
+
+    let wrapped_keys = recipients
+        .iter()
+        .map(|recipient| {
+            if options.key_hint {
+                recipient.encode_bytes(data_key, PaddingScheme::Oaep)
+            } else {
+                let mut wrapped_key = Vec::new();
+                recipient.encode_without_key_hint(
+                    &mut &data_key[..],
+                    &mut wrapped_key,
+                    PaddingScheme::Oaep,
+                )?;
+                Ok(wrapped_key)
+            }
+        })
+        .collect::<RsaResult<Vec<_>>>()?;
+    output.write_all(ENVELOPE_MAGIC)?;
+    output.write_all(&ENVELOPE_VERSION.to_be_bytes())?;
+    output.write_all(&[options.cipher.to_byte()])?;
+    output.write_all(&recipients_amount.to_be_bytes())?;
+    for wrapped_key in wrapped_keys {
+        let wrapped_key_len =
+            u32::try_from(wrapped_key.len()).map_err(|_| RsaError::EncodingError)?;
+        output.write_all(&wrapped_key_len.to_be_bytes())?;
+        output.write_all(&wrapped_key)?;
+    }
+    Ok(())
+}
+
+/// Reads a big endian `u32` of the envelope header.
+///
+/// # Errors
+/// [`RsaError::InvalidEnvelope`] if `input` ends before the `field`.
+fn read_u32<R: Read>(input: &mut R, field: &str) -> RsaResult<u32> {
+    let mut bytes = [0u8; 4];
+    if read_chunk(input, &mut bytes)? < bytes.len() {
+        return Err(RsaError::InvalidEnvelope(format!("truncated {field}")));
+    }
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Nonce of the segment number `counter`: zeros, the counter and whether it is the last segment.
+fn segment_nonce(counter: u32, last: bool) -> Nonce<<Aes256Gcm as aes_gcm::AeadCore>::NonceSize> {
+    let mut nonce = [0u8; 12];
+    nonce[7..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = u8::from(last);
+    nonce.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::key::{
+        tests::{pair_4096, test_pair},
+        KeyPair,
+    };
+
+    #[test]
+    fn test_envelope() {
+        let pair = KeyPair::demo();
+        for len in [0, 1, ENVELOPE_SEGMENT_LEN, 2 * ENVELOPE_SEGMENT_LEN + 7] {
+            let original: Vec<u8> = (0..len).map(|i| u8::try_from(i % 251).unwrap()).collect();
+            let mut sealed = Vec::new();
+            pair.public_key
+                .encode_envelope(&mut original.as_slice(), &mut sealed)
+                .unwrap();
+            assert!(is_envelope(&sealed));
+
+            let mut opened = Vec::new();
+            pair.private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut opened)
+                .unwrap();
+            assert_eq!(opened, original, "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn test_envelope_errors() {
+        let pair = KeyPair::demo();
+        let original = vec![42u8; ENVELOPE_SEGMENT_LEN + 10];
+        let mut sealed = Vec::new();
+        pair.public_key
+            .encode_envelope(&mut original.as_slice(), &mut sealed)
+            .unwrap();
+
+        // modified
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        assert!(matches!(
+            pair.private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut Vec::new()),
+            Err(RsaError::ChecksumMismatch(1))
+        ));
+        sealed[last] ^= 0x01;
+
+        // truncated at a segment boundary
+        let truncated = &sealed[..sealed.len() - (10 + ENVELOPE_TAG_LEN)];
+        assert!(matches!(
+            pair.private_key
+                .decode_envelope(&mut &truncated[..], &mut Vec::new()),
+            Err(RsaError::ChecksumMismatch(1))
+        ));
+        for key in [&pair.public_key, &pair.private_key] {
+            assert_eq!(key.verify_envelope(&mut &truncated[..]).unwrap(), [1]);
+            assert!(key
+                .verify_envelope(&mut sealed.as_slice())
+                .unwrap()
+                .is_empty());
+        }
+
+        assert!(matches!(
+            pair.private_key
+                .decode_envelope(&mut &b"RRSA-EN"[..], &mut Vec::new()),
+            Err(RsaError::InvalidEnvelope(_))
+        ));
+        assert!(pair
+            .public_key
+            .decode_envelope(&mut sealed.as_slice(), &mut Vec::new())
+            .is_err());
+        // too small to wrap the data key with OAEP
+        assert!(matches!(
+            test_pair()
+                .public_key
+                .encode_envelope(&mut &b"data"[..], &mut Vec::new()),
+            Err(RsaError::KeyTooSmallForPadding)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_recipients() {
+        let (alice, bob) = (KeyPair::demo(), pair_4096());
+        let mut sealed = Vec::new();
+        Key::encode_envelope_for(
+            &[&alice.public_key, &bob.public_key],
+            &mut &b"for both"[..],
+            &mut sealed,
+        )
+        .unwrap();
+        for recipient in [alice, &bob] {
+            let mut opened = Vec::new();
+            recipient
+                .private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut opened)
+                .unwrap();
+            assert_eq!(opened, b"for both");
+        }
+        assert!(matches!(
+            test_pair()
+                .private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut Vec::new()),
+            Err(RsaError::WrongRecipient)
+        ));
+
+        assert!(matches!(
+            Key::encode_envelope_for(&[], &mut &b"data"[..], &mut Vec::new()),
+            Err(RsaError::InvalidEnvelope(_))
+        ));
+        assert!(matches!(
+            Key::encode_envelope_for(
+                &[&alice.public_key, &bob.private_key],
+                &mut &b"data"[..],
+                &mut Vec::new()
+            ),
+            Err(RsaError::WrongKeyVariant)
+        ));
+
+        // version 2 envelopes have no cipher, and version 1 no number of recipients either
+        for (version, removed) in [(2u32, 1), (1, 5)] {
+            let mut sealed = Vec::new();
+            alice
+                .public_key
+                .encode_envelope(&mut &b"older"[..], &mut sealed)
+                .unwrap();
+            let cipher_start = ENVELOPE_MAGIC.len() + 4;
+            sealed.drain(cipher_start..cipher_start + removed);
+            sealed[ENVELOPE_MAGIC.len()..cipher_start].copy_from_slice(&version.to_be_bytes());
+            let mut opened = Vec::new();
+            alice
+                .private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut opened)
+                .unwrap();
+            assert_eq!(opened, b"older", "version {version}");
+        }
+    }
+
+    #[test]
+    fn test_envelope_escrow() {
+        let (alice, escrow) = (KeyPair::demo(), pair_4096());
+        let mut sealed = Vec::new();
+        Key::encode_envelope_escrowed(
+            &[&alice.public_key],
+            &escrow.public_key,
+            EnvelopeCipher::default(),
+            &mut &b"escrowed"[..],
+            &mut sealed,
+        )
+        .unwrap();
+        let recipients_start = ENVELOPE_MAGIC.len() + 4 + 1;
+        assert_eq!(
+            sealed[recipients_start..recipients_start + 4],
+            2u32.to_be_bytes()
+        );
+        for pair in [alice, &escrow] {
+            let mut opened = Vec::new();
+            pair.private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut opened)
+                .unwrap();
+            assert_eq!(opened, b"escrowed");
+        }
+
+        // the escrow agent is not a recipient on its own
+        assert!(matches!(
+            Key::encode_envelope_escrowed(
+                &[],
+                &escrow.public_key,
+                EnvelopeCipher::default(),
+                &mut &b"data"[..],
+                &mut Vec::new()
+            ),
+            Err(RsaError::InvalidEnvelope(_))
+        ));
+        assert!(matches!(
+            Key::encode_envelope_escrowed(
+                &[&alice.public_key],
+                &escrow.private_key,
+                EnvelopeCipher::default(),
+                &mut &b"data"[..],
+                &mut Vec::new()
+            ),
+            Err(RsaError::WrongKeyVariant)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_without_key_hint() {
+        let (alice, bob) = (KeyPair::demo(), pair_4096());
+        let options = EnvelopeOptions {
+            key_hint: false,
+            ..EnvelopeOptions::default()
+        };
+        let mut sealed = Vec::new();
+        Key::encode_envelope_with_options(
+            &[&alice.public_key, &bob.public_key],
+            &options,
+            &mut &b"anonymous"[..],
+            &mut sealed,
+        )
+        .unwrap();
+        assert!(!Key::has_key_hint(&mut Cursor::new(&sealed)).unwrap());
+        for fingerprint in [alice.public_key.fingerprint(), bob.public_key.fingerprint()] {
+            assert!(!sealed.windows(32).any(|window| window == fingerprint));
+        }
+        for recipient in [alice, &bob] {
+            let mut opened = Vec::new();
+            recipient
+                .private_key
+                .decode_envelope(&mut sealed.as_slice(), &mut opened)
+                .unwrap();
+            assert_eq!(opened, b"anonymous");
+        }
+        assert!(test_pair()
+            .private_key
+            .decode_envelope(&mut sealed.as_slice(), &mut Vec::new())
+            .is_err());
+
+        let mut hinted = Vec::new();
+        alice
+            .public_key
+            .encode_envelope(&mut &b"hinted"[..], &mut hinted)
+            .unwrap();
+        assert!(Key::has_key_hint(&mut Cursor::new(&hinted)).unwrap());
+    }
+
+    #[test]
+    fn test_reencrypt_envelope() {
+        let (alice, bob) = (KeyPair::demo(), pair_4096());
+        let original = vec![3u8; ENVELOPE_SEGMENT_LEN + 5];
+        let mut sealed = Vec::new();
+        Key::encode_envelope_with(
+            &[&alice.public_key],
+            EnvelopeCipher::ChaCha20Poly1305,
+            &mut original.as_slice(),
+            &mut sealed,
+        )
+        .unwrap();
+        let mut rekeyed = Vec::new();
+        alice
+            .private_key
+            .reencrypt_envelope(&bob.public_key, &mut sealed.as_slice(), &mut rekeyed)
+            .unwrap();
+
+        // only the header changed
+        let segments_len = original.len() + 2 * ENVELOPE_TAG_LEN;
+        assert_eq!(
+            sealed[sealed.len() - segments_len..],
+            rekeyed[rekeyed.len() - segments_len..]
+        );
+        let mut opened = Vec::new();
+        bob.private_key
+            .decode_envelope(&mut rekeyed.as_slice(), &mut opened)
+            .unwrap();
+        assert_eq!(opened, original);
+        assert!(matches!(
+            alice
+                .private_key
+                .decode_envelope(&mut rekeyed.as_slice(), &mut Vec::new()),
+            Err(RsaError::WrongRecipient)
+        ));
+
+        assert!(matches!(
+            test_pair().private_key.reencrypt_envelope(
+                &bob.public_key,
+                &mut sealed.as_slice(),
+                &mut Vec::new()
+            ),
+            Err(RsaError::WrongRecipient)
+        ));
+        assert!(matches!(
+            alice.private_key.reencrypt_envelope(
+                &bob.private_key,
+                &mut sealed.as_slice(),
+                &mut Vec::new()
+            ),
+            Err(RsaError::WrongKeyVariant)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_ciphers() {
+        let pair = KeyPair::demo();
+        let original = vec![7u8; ENVELOPE_SEGMENT_LEN + 3];
+        let sealed_with = |cipher| {
+            let mut sealed = Vec::new();
+            Key::encode_envelope_with(
+                &[&pair.public_key],
+                cipher,
+                &mut original.as_slice(),
+                &mut sealed,
+            )
+            .unwrap();
+            sealed
+        };
+        let aes = sealed_with(EnvelopeCipher::Aes256Gcm);
+        let mut chacha = sealed_with(EnvelopeCipher::ChaCha20Poly1305);
+        let cipher_byte = ENVELOPE_MAGIC.len() + 4;
+        assert_eq!((aes[cipher_byte], chacha[cipher_byte]), (0, 1));
+        assert_eq!(aes.len(), chacha.len());
+
+        let mut opened = Vec::new();
+        pair.private_key
+            .decode_envelope(&mut chacha.as_slice(), &mut opened)
+            .unwrap();
+        assert_eq!(opened, original);
+
+        // a modified cipher fails to open the first segment
+        chacha[cipher_byte] = 0;
+        assert!(matches!(
+            pair.private_key
+                .decode_envelope(&mut chacha.as_slice(), &mut Vec::new()),
+            Err(RsaError::ChecksumMismatch(0))
+        ));
+        chacha[cipher_byte] = 2;
+        assert!(matches!(
+            pair.private_key
+                .decode_envelope(&mut chacha.as_slice(), &mut Vec::new()),
+            Err(RsaError::InvalidEnvelope(_))
+        ));
+    }
+}
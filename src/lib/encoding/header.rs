@@ -0,0 +1,242 @@
+//! Header written before the chunks of [`Key::encode()`], so decoding with the wrong key
+//! or the wrong padding scheme is reported as such instead of as corrupted chunks.
+//!
+//! ## Format
+//! 1. [`CIPHERTEXT_MAGIC`] followed by [`CIPHERTEXT_VERSION`] as a big endian `u32`.
+//! 2. The [`PaddingScheme`] as one byte, `0` for none, `1` for OAEP and `2` for PKCS#1 v1.5.
+//! 3. The size in bits of the modulus as a big endian `u32`.
+//! 4. The [`Key::fingerprint()`] of the Public Key, or zeros if the input is anonymous,
+//!    see [`CiphertextHeader::anonymized()`].
+//!
+//! Input without the magic is rejected with [`RsaError::MissingHeader`]. Files written
+//! by older versions, which did not have a header, can only be decoded by [`Key::decode_legacy()`].
+
+use std::io::Read;
+
+use super::{envelope, padding::PaddingScheme, read_chunk};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::version;
+
+/// First bytes of every header.
+pub const CIPHERTEXT_MAGIC: &[u8; 4] = b"RRSA";
+/// Current version of the header, bumped on any incompatible change.
+pub const CIPHERTEXT_VERSION: u32 = 1;
+/// Size in bytes of the whole header.
+pub const CIPHERTEXT_HEADER_LEN: usize = CIPHERTEXT_MAGIC.len() + 4 + 1 + 4 + 32;
+/// Fingerprint stored in the header of anonymous inputs.
+const ANONYMOUS_FINGERPRINT: [u8; 32] = [0u8; 32];
+
+/// Parameters of an encoded input, stored in its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CiphertextHeader {
+    pub padding: PaddingScheme,
+    /// Size in bits of the modulus of the Key.
+    pub modulus_bits: u32,
+    /// [`Key::fingerprint()`] of the Key, or zeros if anonymous.
+    pub fingerprint: [u8; 32],
+}
+
+impl CiphertextHeader {
+    /// Header of an input encoded by `key` with `padding`.
+    #[must_use]
+    pub fn new(key: &Key, padding: PaddingScheme) -> Self {
+        Self {
+            padding,
+            modulus_bits: u32::try_from(key.modulus.bits()).unwrap_or(u32::MAX),
+            fingerprint: key.fingerprint(),
+        }
+    }
+
+    /// Removes the fingerprint of the Key, so the header does not reveal who can decode the input.
+    ///
+    /// The input is still framed as usual, so every Private Key with the same modulus size
+    /// passes [`CiphertextHeader::verify()`], and only the right one decodes it.
+    #[must_use]
+    pub fn anonymized(mut self) -> Self {
+        self.fingerprint = ANONYMOUS_FINGERPRINT;
+        self
+    }
+
+    /// Returns `true` if this header has no fingerprint, see [`CiphertextHeader::anonymized()`].
+    #[must_use]
+    pub fn is_anonymous(&self) -> bool {
+        self.fingerprint == ANONYMOUS_FINGERPRINT
+    }
+
+    /// Formats this header as bytes, see the [module documentation](self).
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; CIPHERTEXT_HEADER_LEN] {
+        let mut bytes = [0u8; CIPHERTEXT_HEADER_LEN];
+        let (magic, rest) = bytes.split_at_mut(CIPHERTEXT_MAGIC.len());
+        magic.copy_from_slice(CIPHERTEXT_MAGIC);
+        rest[..4].copy_from_slice(&CIPHERTEXT_VERSION.to_be_bytes());
+        rest[4] = match self.padding {
+            PaddingScheme::None => 0,
+            PaddingScheme::Oaep => 1,
+            PaddingScheme::Pkcs1v15 => 2,
+        };
+        rest[5..9].copy_from_slice(&self.modulus_bits.to_be_bytes());
+        rest[9..].copy_from_slice(&self.fingerprint);
+        bytes
+    }
+
+    /// Parses the header at the start of `bytes`.
+    ///
+    /// # Returns
+    /// `None` if `bytes` do not start with [`CIPHERTEXT_MAGIC`], or start like an envelope.
+    ///
+    /// # Errors
+    /// - If the header is truncated or its padding scheme unknown.
+    /// - [`RsaError::UnsupportedVersion`] if it was written by a newer version.
+    ///
+    /// # Panics
+    /// Never, the fields are sliced from a header of checked length.
+    pub fn parse(bytes: &[u8]) -> RsaResult<Option<Self>> {
+        if !bytes.starts_with(CIPHERTEXT_MAGIC) || envelope::is_envelope(bytes) {
+            return Ok(None);
+        }
+        let invalid = |reason: &str| RsaError::ImproperlyFormattedStr(format!("header, {reason}"));
+        let rest = bytes
+            .get(CIPHERTEXT_MAGIC.len()..CIPHERTEXT_HEADER_LEN)
+            .ok_or_else(|| invalid("it is truncated"))?;
+        let version = u32::from_be_bytes(rest[..4].try_into().expect("sliced 4 bytes"));
+        version::check_supported("ciphertext", &version.to_string(), CIPHERTEXT_VERSION)?;
+        let padding = match rest[4] {
+            0 => PaddingScheme::None,
+            1 => PaddingScheme::Oaep,
+            2 => PaddingScheme::Pkcs1v15,
+            _ => return Err(invalid("the padding scheme is unknown")),
+        };
+        Ok(Some(Self {
+            padding,
+            modulus_bits: u32::from_be_bytes(rest[5..9].try_into().expect("sliced 4 bytes")),
+            fingerprint: rest[9..].try_into().expect("sliced 32 bytes"),
+        }))
+    }
+
+    /// Checks the input was encoded for `key` with `padding`.
+    ///
+    /// # Errors
+    /// - [`RsaError::WrongRecipient`] if it was encoded by a Key of another pair,
+    ///   or of another modulus size if the header is anonymous.
+    /// - [`RsaError::PaddingMismatch`] if it was encoded with another padding scheme.
+    pub fn verify(&self, key: &Key, padding: PaddingScheme) -> RsaResult<()> {
+        let expected = CiphertextHeader::new(key, padding);
+        if self.modulus_bits != expected.modulus_bits
+            || (!self.is_anonymous() && self.fingerprint != expected.fingerprint)
+        {
+            return Err(RsaError::WrongRecipient);
+        }
+        if self.padding != padding {
+            return Err(RsaError::PaddingMismatch {
+                expected: padding,
+                found: self.padding,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Key {
+    /// Reads the header at the start of `input`.
+    ///
+    /// # Errors
+    /// - [`RsaError::MissingHeader`] if `input` does not start with [`CIPHERTEXT_MAGIC`],
+    ///   such input can only be decoded by [`Key::decode_legacy()`].
+    /// - Same as [`CiphertextHeader::parse()`], and propagates [`std::io::Error`].
+    pub(crate) fn read_header<R: Read>(input: &mut R) -> RsaResult<CiphertextHeader> {
+        let mut bytes = [0u8; CIPHERTEXT_HEADER_LEN];
+        let bytes_amount_read = read_chunk(input, &mut bytes)?;
+        CiphertextHeader::parse(&bytes[..bytes_amount_read])?.ok_or(RsaError::MissingHeader)
+    }
+
+    /// Same as [`Key::read_header()`], but also verifies the header was written for this Key
+    /// and `padding`, see [`CiphertextHeader::verify()`].
+    pub(crate) fn read_verified_header<R: Read>(
+        &self,
+        input: &mut R,
+        padding: PaddingScheme,
+    ) -> RsaResult<CiphertextHeader> {
+        let header = Key::read_header(input)?;
+        header.verify(self, padding)?;
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, KeyPair};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ciphertext_header() {
+        let pair = test_pair();
+        let header = CiphertextHeader::new(&pair.public_key, PaddingScheme::Pkcs1v15);
+        let bytes = header.to_bytes();
+        assert_eq!(CiphertextHeader::parse(&bytes).unwrap(), Some(header));
+        assert!(header
+            .verify(&pair.private_key, PaddingScheme::Pkcs1v15)
+            .is_ok());
+        assert!(matches!(
+            header.verify(&KeyPair::demo().private_key, PaddingScheme::Pkcs1v15),
+            Err(RsaError::WrongRecipient)
+        ));
+        assert!(matches!(
+            header.verify(&pair.private_key, PaddingScheme::None),
+            Err(RsaError::PaddingMismatch {
+                expected: PaddingScheme::None,
+                found: PaddingScheme::Pkcs1v15
+            })
+        ));
+
+        assert_eq!(CiphertextHeader::parse(b"RRSA-ENV").unwrap(), None);
+        assert_eq!(CiphertextHeader::parse(&[0u8; 64]).unwrap(), None);
+        assert!(CiphertextHeader::parse(&bytes[..20]).is_err());
+        let mut newer = bytes;
+        newer[7] = 2;
+        assert!(matches!(
+            CiphertextHeader::parse(&newer),
+            Err(RsaError::UnsupportedVersion { found: 2, .. })
+        ));
+        let mut unknown_padding = bytes;
+        unknown_padding[8] = 3;
+        assert!(CiphertextHeader::parse(&unknown_padding).is_err());
+
+        let anonymous = header.anonymized();
+        assert!(anonymous.is_anonymous() && !header.is_anonymous());
+        assert_eq!(
+            CiphertextHeader::parse(&anonymous.to_bytes()).unwrap(),
+            Some(anonymous)
+        );
+        assert!(anonymous
+            .verify(&pair.private_key, PaddingScheme::Pkcs1v15)
+            .is_ok());
+        assert!(matches!(
+            anonymous.verify(&KeyPair::demo().private_key, PaddingScheme::Pkcs1v15),
+            Err(RsaError::WrongRecipient)
+        ));
+
+        // input without header is rejected, however short
+        for headerless in [&b""[..], b"RR", b"RRSA-ENV", &[0u8; 64]] {
+            assert!(matches!(
+                Key::read_header(&mut Cursor::new(headerless)),
+                Err(RsaError::MissingHeader)
+            ));
+        }
+        let mut input = Cursor::new([&bytes[..], b"chunks"].concat());
+        assert_eq!(
+            pair.private_key
+                .read_verified_header(&mut input, PaddingScheme::Pkcs1v15)
+                .unwrap(),
+            header
+        );
+        assert_eq!(input.position(), CIPHERTEXT_HEADER_LEN as u64);
+        assert!(matches!(
+            pair.private_key
+                .read_verified_header(&mut Cursor::new(b"RR"), PaddingScheme::Pkcs1v15),
+            Err(RsaError::MissingHeader)
+        ));
+    }
+}
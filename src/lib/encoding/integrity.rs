@@ -0,0 +1,92 @@
+//! Integrity tag encrypted as the last chunk of [`Key::encode()`]: the SHA-256 of the
+//! whole plaintext, truncated to fit a single block of the Key.
+//!
+//! The chunk checksums only detect damaged chunks, the tag also detects chunks
+//! that were dropped or reordered, and chunks decrypted into garbage.
+//! Envelopes do not need it, since every segment is authenticated by AES-256-GCM.
+
+use sha2::{Digest, Sha256};
+
+use super::padding::{PaddingScheme, HASH_LEN};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+
+impl Key {
+    /// Length of the integrity tag, at most the 32 bytes of a SHA-256 digest.
+    fn integrity_tag_len(&self, padding: PaddingScheme) -> RsaResult<usize> {
+        Ok(self.max_message_bytes(padding)?.min(HASH_LEN))
+    }
+
+    /// Encrypts the integrity tag of the plaintext hashed by `hasher`,
+    /// followed by its checksum like any other chunk.
+    pub(crate) fn encrypt_integrity_tag(
+        &self,
+        hasher: Sha256,
+        padding: PaddingScheme,
+    ) -> RsaResult<Vec<u8>> {
+        let digest = hasher.finalize();
+        let mut chunk =
+            self.encrypt_block(&digest[..self.integrity_tag_len(padding)?], padding, true)?;
+        let checksum = crc32fast::hash(&chunk);
+        chunk.extend_from_slice(&checksum.to_le_bytes());
+        Ok(chunk)
+    }
+
+    /// Checks `tag`, the last decrypted chunk, is the integrity tag
+    /// of the plaintext hashed by `hasher`.
+    ///
+    /// # Errors
+    /// [`RsaError::IntegrityTagMismatch`] if it is not, or `tag` is `None`.
+    pub(crate) fn verify_integrity_tag(
+        &self,
+        hasher: Sha256,
+        tag: Option<&[u8]>,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        let digest = hasher.finalize();
+        match tag {
+            Some(tag) if *tag == digest[..self.integrity_tag_len(padding)?] => Ok(()),
+            _ => Err(RsaError::IntegrityTagMismatch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+
+    #[test]
+    fn test_integrity_tag() {
+        let pair = test_pair();
+        let mut hasher = Sha256::new();
+        hasher.update(b"tagged");
+        let chunk = pair
+            .public_key
+            .encrypt_integrity_tag(hasher.clone(), PaddingScheme::None)
+            .unwrap();
+        assert!(Key::chunk_checksum_matches(&chunk));
+        let tag = pair
+            .private_key
+            .decrypt_block(
+                &chunk[..chunk.len() - Key::CHUNK_CHECKSUM_SIZE],
+                PaddingScheme::None,
+                true,
+            )
+            .unwrap();
+        assert_eq!(tag.len(), 3);
+        assert!(pair
+            .private_key
+            .verify_integrity_tag(hasher.clone(), Some(&tag), PaddingScheme::None)
+            .is_ok());
+        assert!(matches!(
+            pair.private_key
+                .verify_integrity_tag(Sha256::new(), Some(&tag), PaddingScheme::None),
+            Err(RsaError::IntegrityTagMismatch)
+        ));
+        assert!(pair
+            .private_key
+            .verify_integrity_tag(hasher, None, PaddingScheme::None)
+            .is_err());
+    }
+}
@@ -1,80 +1,702 @@
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Seek, Write};
 
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
 
 use crate::error::{RsaError, RsaResult};
 use crate::key::{Key, KeyVariant};
+use crate::math::wipe;
+pub use buffers::{Ciphertext, Plaintext};
+#[cfg(feature = "fuzz")]
+pub use container::{parse_container, Container};
+use header::CiphertextHeader;
+use padding::PaddingScheme;
+pub use stream::{DecryptingReader, EncryptingWriter};
+
+pub mod armor;
+#[cfg(feature = "tokio")]
+mod asynchronous;
+mod buffers;
+#[cfg(feature = "fuzz")]
+pub mod container;
+pub mod envelope;
+pub mod header;
+mod integrity;
+pub mod padding;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod recovery;
+mod stream;
 
 impl Key {
     const ENCRYPTION_BYTE_OFFSET: usize = 1;
+    /// Byte appended to every block encrypted with [`PaddingScheme::None`],
+    /// it always fits since [`Key::max_message_bytes()`] leaves a spare byte below the modulus.
+    const BLOCK_END_MARKER: u8 = 0x01;
+    /// Size of the CRC32 stored after every encrypted chunk.
+    const CHUNK_CHECKSUM_SIZE: usize = 4;
 
     /// Encodes a [`Read`] implementor to a [`Write`] implementor
-    /// using this Public Key.
+    /// using this Public Key and the given [`PaddingScheme`].
+    ///
+    /// The chunks are preceded by a [`header::CiphertextHeader`], so decoding with the wrong key
+    /// or padding scheme is detected, and every encrypted chunk is followed by the CRC32
+    /// of its bytes, so corruption can be localized when decoding.
+    /// With [`PaddingScheme::None`], an end marker is encrypted after every chunk,
+    /// so chunks ending with zero bytes are decoded exactly.
+    /// The last chunk is an integrity tag, the SHA-256 of the whole input truncated to fit
+    /// a single block, so dropped or reordered chunks are also detected when decoding.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut encoded = Vec::new();
+    /// pair.public_key
+    ///     .encode(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    ///
+    /// let mut decoded = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert_eq!(decoded, b"attack at dawn");
+    /// ```
     ///
     /// # Errors
     /// - If `self` is not a [`KeyVariant::PublicKey`].
+    /// - If the key is too small for the padding scheme.
     /// - If any [`std::io::Error`] occurs.
-    pub fn encode<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> RsaResult<()> {
+    pub fn encode<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!("encode", ?padding).entered();
+        self.encode_blocks(input, output, padding, true)
+    }
+
+    /// Same as [`Key::encode()`], but the header does not hold the fingerprint of this Key,
+    /// so the output does not reveal who can decode it, see [`CiphertextHeader::anonymized()`].
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    /// use std::io::Cursor;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut encoded = Vec::new();
+    /// pair.public_key
+    ///     .encode_without_key_hint(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert!(!rrsa_lib::key::Key::has_key_hint(&mut Cursor::new(&encoded)).unwrap());
+    ///
+    /// let mut decoded = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert_eq!(decoded, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encode_without_key_hint<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!("encode_without_key_hint", ?padding).entered();
+        let header = CiphertextHeader::new(self, padding).anonymized();
+        self.encode_chunks(input, output, padding, Some(header))
+    }
+
+    /// Same as [`Key::encode()`], but the output is wrapped in ASCII armor
+    /// (see [`armor::armor()`]) so it can be pasted in emails or chats.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut armored = Vec::new();
+    /// pair.public_key
+    ///     .encode_armored(&mut &b"attack at dawn"[..], &mut armored, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert!(armored.starts_with(b"-----BEGIN RRSA MESSAGE-----"));
+    ///
+    /// // decoding detects the armor
+    /// let mut decoded = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut armored.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert_eq!(decoded, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encode_armored<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        let mut armored = armor::ArmorWriter::new(output)?;
+        self.encode(input, &mut armored, padding)?;
+        armored.finish()?;
+        Ok(())
+    }
+
+    pub(crate) fn encode_blocks<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+        with_checksums: bool,
+    ) -> RsaResult<()> {
+        let header = with_checksums.then(|| CiphertextHeader::new(self, padding));
+        self.encode_chunks(input, output, padding, header)
+    }
+
+    /// Encodes the chunks of [`Key::encode()`] after `header`,
+    /// or the legacy chunks without checksums if there is no header.
+    fn encode_chunks<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+        header: Option<CiphertextHeader>,
+    ) -> RsaResult<()> {
         if self.variant != KeyVariant::PublicKey {
             return Err(RsaError::WrongKeyVariant);
         }
 
-        let max_bytes_read = self.modulus.size_in_bytes_floored() - Key::ENCRYPTION_BYTE_OFFSET;
-        let max_bytes_write = self.modulus.size_in_bytes_floored() + Key::ENCRYPTION_BYTE_OFFSET;
-        let mut source_bytes = vec![0u8; max_bytes_read];
-        let mut destiny_bytes = Vec::<u8>::with_capacity(max_bytes_read);
+        let max_bytes_read = self.max_message_bytes(padding)?;
+        let with_checksums = header.is_some();
+        if let Some(header) = header {
+            output.write_all(&header.to_bytes())?;
+        }
+        let mut source_bytes = Zeroizing::new(vec![0u8; max_bytes_read]);
         let mut bytes_amount_read = max_bytes_read;
+        let mut hasher = Sha256::new();
 
         while bytes_amount_read == max_bytes_read {
             source_bytes.fill(0u8);
-            bytes_amount_read = input.read(&mut source_bytes)?;
+            bytes_amount_read = read_chunk(input, &mut source_bytes)?;
             if bytes_amount_read == 0 {
                 break;
             }
-            let message = BigUint::from_bytes_le(&source_bytes);
-            let encrypted = message.modpow(&self.exponent, &self.modulus);
-            destiny_bytes.clear();
-            let _ = destiny_bytes.write(&encrypted.to_bytes_le())?;
-            let size_diff = (max_bytes_write) - destiny_bytes.len();
-            destiny_bytes.append(&mut vec![0u8; size_diff]);
-            let _bytes_amount_written = output.write(&destiny_bytes)?;
+            hasher.update(&source_bytes[..bytes_amount_read]);
+            let mut destiny_bytes =
+                self.encrypt_block(&source_bytes[..bytes_amount_read], padding, with_checksums)?;
+            if with_checksums {
+                let checksum = crc32fast::hash(&destiny_bytes);
+                destiny_bytes.extend_from_slice(&checksum.to_le_bytes());
+            }
+            output.write_all(&destiny_bytes)?;
+        }
+        if with_checksums {
+            output.write_all(&self.encrypt_integrity_tag(hasher, padding)?)?;
         }
         output.flush()?;
         Ok(())
     }
 
     /// Decodes a [`Read`] implementor to a [`Write`] implementor
+    /// using this Private Key and the given [`PaddingScheme`].
+    ///
+    /// Input produced by [`Key::encode_armored()`] is detected and unwrapped first,
+    /// and envelopes produced by [`Key::encode_envelope()`] are detected too,
+    /// in which case `padding` is not used.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - [`RsaError::WrongRecipient`] or [`RsaError::PaddingMismatch`] if the header
+    ///   was written for another pair or padding scheme.
+    /// - [`RsaError::MissingHeader`] if the input has no header,
+    ///   as written by older versions, see [`Key::decode_legacy()`].
+    /// - If the checksum of any chunk does not match its contents.
+    /// - If the padding of any chunk is invalid.
+    /// - [`RsaError::IntegrityTagMismatch`] if the decoded data does not match the integrity tag
+    ///   after the last chunk, in which case it was already written to `output`.
+    /// - If any [`std::io::Error`] occurs.
+    pub fn decode<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        let _span = tracing::debug_span!("decode", ?padding).entered();
+        let mut prefix = vec![0u8; armor::ARMOR_BEGIN.len()];
+        let prefix_len = read_chunk(input, &mut prefix)?;
+        prefix.truncate(prefix_len);
+
+        if armor::is_armored(&prefix) {
+            tracing::debug!("Input is ASCII armor");
+            let mut dearmored = armor::ArmorReader::new(Cursor::new(prefix).chain(input))?;
+            let mut magic = vec![0u8; envelope::ENVELOPE_MAGIC.len()];
+            let magic_len = read_chunk(&mut dearmored, &mut magic)?;
+            magic.truncate(magic_len);
+            let is_envelope = envelope::is_envelope(&magic);
+            let mut encoded = Cursor::new(magic).chain(dearmored);
+            if is_envelope {
+                self.decode_envelope(&mut encoded, output)
+            } else {
+                self.decode_blocks(&mut encoded, output, padding, true)
+            }
+        } else if envelope::is_envelope(&prefix) {
+            tracing::debug!("Input is an envelope");
+            self.decode_envelope(&mut Cursor::new(prefix).chain(input), output)
+        } else {
+            self.decode_blocks(&mut Cursor::new(prefix).chain(input), output, padding, true)
+        }
+    }
+
+    /// Decodes a [`Read`] implementor produced by the legacy `.cypher` chunking
+    /// (bare blocks of `N + 1` bytes, without any header) to a [`Write`] implementor
     /// using this Private Key.
     ///
+    /// Kept so files encrypted by older versions can still be decrypted.
+    ///
     /// # Errors
     /// - If `self` is not a [`KeyVariant::PrivateKey`].
     /// - If any [`std::io::Error`] occurs.
-    pub fn decode<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> RsaResult<()> {
+    pub fn decode_legacy<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> RsaResult<()> {
+        self.decode_blocks(input, output, PaddingScheme::None, false)
+    }
+
+    /// Decodes the chunks of [`Key::decode()`] if `with_checksums`: after a header, followed by
+    /// their checksum, framed and ending with the integrity tag.
+    /// Otherwise decodes the bare blocks of [`Key::decode_legacy()`].
+    fn decode_blocks<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+        with_checksums: bool,
+    ) -> RsaResult<()> {
         if self.variant != KeyVariant::PrivateKey {
             return Err(RsaError::WrongKeyVariant);
         }
 
-        let max_bytes = self.modulus.size_in_bytes_floored() + Key::ENCRYPTION_BYTE_OFFSET;
+        if with_checksums {
+            self.read_verified_header(input, padding)?;
+        }
+        let block_bytes = self.encrypted_block_bytes(padding);
+        let max_bytes = if with_checksums {
+            block_bytes + Key::CHUNK_CHECKSUM_SIZE
+        } else {
+            block_bytes
+        };
         let mut source_bytes = vec![0u8; max_bytes];
-        let mut destiny_bytes = Vec::<u8>::with_capacity(max_bytes);
         let mut bytes_amount_read = max_bytes;
+        let mut chunk_index = 0usize;
+        // the last checksummed chunk is the integrity tag, so every chunk is held back
+        // until the next one is read
+        let mut hasher = Sha256::new();
+        let mut held_back: Option<Zeroizing<Vec<u8>>> = None;
 
         while bytes_amount_read == max_bytes {
             source_bytes.fill(0u8);
-            bytes_amount_read = input.read(&mut source_bytes)?;
+            bytes_amount_read = read_chunk(input, &mut source_bytes)?;
             if bytes_amount_read == 0 {
                 break;
             }
-            let encrypted = BigUint::from_bytes_le(&source_bytes);
-            let message = encrypted.modpow(&self.exponent, &self.modulus);
-            destiny_bytes.clear();
-            let _ = destiny_bytes.write(&message.to_bytes_le())?;
-            let _bytes_amount_written = output.write(&destiny_bytes)?;
+            if with_checksums && !Key::chunk_checksum_matches(&source_bytes[..bytes_amount_read]) {
+                return Err(RsaError::ChecksumMismatch(chunk_index));
+            }
+            let message = Zeroizing::new(self.decrypt_block(
+                &source_bytes[..block_bytes],
+                padding,
+                with_checksums,
+            )?);
+            if !with_checksums {
+                output.write_all(&message)?;
+            } else if let Some(previous) = held_back.replace(message) {
+                hasher.update(&previous);
+                output.write_all(&previous)?;
+            }
+            chunk_index += 1;
         }
         output.flush()?;
+        if with_checksums {
+            self.verify_integrity_tag(hasher, held_back.as_deref().map(Vec::as_slice), padding)?;
+        }
         Ok(())
     }
+
+    /// Verifies the checksum of every chunk of an encoded [`Read`] implementor,
+    /// without decoding it, so either key of the pair can be used.
+    ///
+    /// ASCII armor and envelopes are detected as in [`Key::decode()`], the segments of
+    /// an envelope are verified by [`Key::verify_envelope()`], so only a Private Key
+    /// detects modified segments.
+    ///
+    /// # Returns
+    /// The indexes of the damaged chunks, or segments, empty if the whole input is intact.
+    ///
+    /// # Errors
+    /// - [`RsaError::MissingHeader`] if the input is neither an envelope nor has a header.
+    /// - If the header was written for another pair or padding scheme,
+    ///   see [`header::CiphertextHeader::verify()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn verify_chunks<R: Read>(
+        &self,
+        input: &mut R,
+        padding: PaddingScheme,
+    ) -> RsaResult<Vec<usize>> {
+        let mut prefix = vec![0u8; armor::ARMOR_BEGIN.len()];
+        let prefix_len = read_chunk(input, &mut prefix)?;
+        prefix.truncate(prefix_len);
+
+        if armor::is_armored(&prefix) {
+            let mut dearmored = armor::ArmorReader::new(Cursor::new(prefix).chain(input))?;
+            let mut magic = vec![0u8; envelope::ENVELOPE_MAGIC.len()];
+            let magic_len = read_chunk(&mut dearmored, &mut magic)?;
+            magic.truncate(magic_len);
+            let is_envelope = envelope::is_envelope(&magic);
+            let mut encoded = Cursor::new(magic).chain(dearmored);
+            if is_envelope {
+                self.verify_envelope(&mut encoded)
+            } else {
+                self.verify_blocks(&mut encoded, padding)
+            }
+        } else if envelope::is_envelope(&prefix) {
+            self.verify_envelope(&mut Cursor::new(prefix).chain(input))
+        } else {
+            self.verify_blocks(&mut Cursor::new(prefix).chain(input), padding)
+        }
+    }
+
+    /// Verifies the chunks of [`Key::verify_chunks()`] once dearmored, after a header.
+    fn verify_blocks<R: Read>(
+        &self,
+        input: &mut R,
+        padding: PaddingScheme,
+    ) -> RsaResult<Vec<usize>> {
+        self.read_verified_header(input, padding)?;
+        let max_bytes = self.encrypted_block_bytes(padding) + Key::CHUNK_CHECKSUM_SIZE;
+        let mut source_bytes = vec![0u8; max_bytes];
+        let mut damaged = Vec::new();
+        let mut chunk_index = 0usize;
+
+        loop {
+            let bytes_amount_read = read_chunk(input, &mut source_bytes)?;
+            if bytes_amount_read == 0 {
+                break;
+            }
+            if bytes_amount_read < max_bytes || !Key::chunk_checksum_matches(&source_bytes) {
+                damaged.push(chunk_index);
+            }
+            if bytes_amount_read < max_bytes {
+                break;
+            }
+            chunk_index += 1;
+        }
+        Ok(damaged)
+    }
+
+    /// Returns `true` if the first chunk of an encoded [`Read`] implementor
+    /// can be decoded by this Private Key, e.g. to find which key an encoded file is for.
+    ///
+    /// The fingerprint and padding scheme stored in the header are compared first.
+    /// If the header is anonymous, see [`header::CiphertextHeader::anonymized()`],
+    /// the first chunk is also decrypted and its padding checked. With [`PaddingScheme::None`]
+    /// there is no padding to check, so any key of the right size is accepted.
+    /// For envelopes, the key is accepted if it unwraps the data key, whatever `padding` is.
+    ///
+    /// `input` is rewound before and after reading, so it can be decoded afterwards.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    /// use std::io::Cursor;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut encoded = Vec::new();
+    /// pair.public_key
+    ///     .encode(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    ///
+    /// let mut input = Cursor::new(encoded);
+    /// assert!(pair.private_key.decodes_first_chunk(&mut input, PaddingScheme::Oaep).unwrap());
+    /// assert!(!KeyPair::demo_tiny().private_key.decodes_first_chunk(&mut input, PaddingScheme::Oaep).unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - [`RsaError::MissingHeader`] if the input is neither an envelope nor has a header.
+    /// - Propagates [`std::io::Error`].
+    pub fn decodes_first_chunk<R: Read + Seek>(
+        &self,
+        input: &mut R,
+        padding: PaddingScheme,
+    ) -> RsaResult<bool> {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        input.rewind()?;
+        let mut prefix = vec![0u8; armor::ARMOR_BEGIN.len()];
+        let bytes_amount_read = read_chunk(input, &mut prefix)?;
+        prefix.truncate(bytes_amount_read);
+        let decodes = if armor::is_armored(&prefix) {
+            let mut dearmored = armor::ArmorReader::new(Cursor::new(prefix).chain(&mut *input))?;
+            self.first_chunk_decodes(&mut dearmored, padding)?
+        } else {
+            self.first_chunk_decodes(&mut Cursor::new(prefix).chain(&mut *input), padding)?
+        };
+        input.rewind()?;
+        Ok(decodes)
+    }
+
+    /// Returns `false` if an encoded [`Read`] implementor does not reveal who can decode it:
+    /// its header is anonymous, or for envelopes, the headers of all of its wrapped data keys,
+    /// see [`Key::encode_without_key_hint()`].
+    ///
+    /// `input` is rewound before and after reading, so it can be decoded afterwards.
+    ///
+    /// # Errors
+    /// - [`RsaError::MissingHeader`] if the input is neither an envelope nor has a header.
+    /// - If the armor or the header is invalid.
+    /// - Propagates [`std::io::Error`].
+    pub fn has_key_hint<R: Read + Seek>(input: &mut R) -> RsaResult<bool> {
+        input.rewind()?;
+        let mut prefix = vec![0u8; armor::ARMOR_BEGIN.len()];
+        let bytes_amount_read = read_chunk(input, &mut prefix)?;
+        prefix.truncate(bytes_amount_read);
+        let has_key_hint = if armor::is_armored(&prefix) {
+            let mut dearmored = armor::ArmorReader::new(Cursor::new(prefix).chain(&mut *input))?;
+            Key::dearmored_has_key_hint(&mut dearmored)?
+        } else {
+            Key::dearmored_has_key_hint(&mut Cursor::new(prefix).chain(&mut *input))?
+        };
+        input.rewind()?;
+        Ok(has_key_hint)
+    }
+
+    /// Checks the header of a dearmored input for [`Key::has_key_hint()`].
+    fn dearmored_has_key_hint<R: Read>(input: &mut R) -> RsaResult<bool> {
+        let mut prefix = vec![0u8; envelope::ENVELOPE_MAGIC.len()];
+        let bytes_amount_read = read_chunk(input, &mut prefix)?;
+        prefix.truncate(bytes_amount_read);
+        let is_envelope = envelope::is_envelope(&prefix);
+        let mut input = Cursor::new(prefix).chain(input);
+        if is_envelope {
+            let header = envelope::EnvelopeHeader::read(&mut input)?;
+            return Ok(header.wrapped_keys.iter().any(|wrapped_key| {
+                CiphertextHeader::parse(wrapped_key)
+                    .is_ok_and(|header| header.is_some_and(|header| !header.is_anonymous()))
+            }));
+        }
+        Ok(!Key::read_header(&mut input)?.is_anonymous())
+    }
+
+    /// Checks the first chunk of a dearmored input for [`Key::decodes_first_chunk()`],
+    /// or the wrapped data key if it is an envelope.
+    fn first_chunk_decodes<R: Read>(
+        &self,
+        input: &mut R,
+        padding: PaddingScheme,
+    ) -> RsaResult<bool> {
+        let mut prefix = vec![0u8; envelope::ENVELOPE_MAGIC.len()];
+        let bytes_amount_read = read_chunk(input, &mut prefix)?;
+        prefix.truncate(bytes_amount_read);
+        let is_envelope = envelope::is_envelope(&prefix);
+        let mut input = Cursor::new(prefix).chain(input);
+        if is_envelope {
+            return Ok(self.read_envelope_header(&mut input).is_ok());
+        }
+        let header = Key::read_header(&mut input)?;
+        if header.verify(self, padding).is_err() {
+            return Ok(false);
+        } else if !header.is_anonymous() {
+            return Ok(true);
+        }
+
+        let max_bytes = self.encrypted_block_bytes(padding) + Key::CHUNK_CHECKSUM_SIZE;
+        let mut chunk = vec![0u8; max_bytes];
+        let bytes_amount_read = read_chunk(&mut input, &mut chunk)?;
+        Ok(bytes_amount_read == max_bytes
+            && Key::chunk_checksum_matches(&chunk)
+            && self
+                .decrypt_block(
+                    &chunk[..max_bytes - Key::CHUNK_CHECKSUM_SIZE],
+                    padding,
+                    true,
+                )
+                .is_ok())
+    }
+
+    /// Amount of input bytes in every chunk read by [`Key::encode()`] with a Public Key,
+    /// or by [`Key::decode()`] with a Private Key, e.g. to report progress in chunks.
+    ///
+    /// # Errors
+    /// If the key is too small for the padding scheme.
+    pub fn chunk_len(&self, padding: PaddingScheme) -> RsaResult<usize> {
+        match self.variant {
+            KeyVariant::PublicKey => self.max_message_bytes(padding),
+            KeyVariant::PrivateKey => {
+                Ok(self.encrypted_block_bytes(padding) + Key::CHUNK_CHECKSUM_SIZE)
+            }
+        }
+    }
+
+    /// Maximum amount of plaintext bytes encrypted in a single block.
+    ///
+    /// # Errors
+    /// If the key is too small for the padding scheme.
+    pub(crate) fn max_message_bytes(&self, padding: PaddingScheme) -> RsaResult<usize> {
+        match padding {
+            PaddingScheme::None => {
+                Ok(self.modulus.size_in_bytes_floored() - Key::ENCRYPTION_BYTE_OFFSET)
+            }
+            PaddingScheme::Oaep => padding::oaep_max_message_len(self.modulus.size_in_bytes()),
+            PaddingScheme::Pkcs1v15 => {
+                padding::pkcs1v15_max_message_len(self.modulus.size_in_bytes())
+            }
+        }
+    }
+
+    /// Size in bytes of a single encrypted block, without its checksum.
+    pub(crate) fn encrypted_block_bytes(&self, padding: PaddingScheme) -> usize {
+        encrypted_block_bytes_for(self.modulus.bits(), padding)
+    }
+
+    /// Pads and encrypts a single block of at most [`Key::max_message_bytes`] bytes.
+    ///
+    /// With [`PaddingScheme::None`] and `framed`, [`Key::BLOCK_END_MARKER`] is appended
+    /// to `message`, so its trailing zero bytes survive the conversion to a number.
+    /// Only input without a [`CiphertextHeader`] is not framed.
+    pub(crate) fn encrypt_block(
+        &self,
+        message: &[u8],
+        padding: PaddingScheme,
+        framed: bool,
+    ) -> RsaResult<Vec<u8>> {
+        match padding {
+            PaddingScheme::None => {
+                let message = if framed {
+                    BigUint::from_bytes_le(&Zeroizing::new(
+                        [message, &[Key::BLOCK_END_MARKER]].concat(),
+                    ))
+                } else {
+                    BigUint::from_bytes_le(message)
+                };
+                let encrypted = message.modpow(&self.exponent, &self.modulus);
+                let mut block = encrypted.to_bytes_le();
+                block.resize(self.encrypted_block_bytes(padding), 0u8);
+                Ok(block)
+            }
+            PaddingScheme::Oaep | PaddingScheme::Pkcs1v15 => {
+                let k = self.modulus.size_in_bytes();
+                let em = Zeroizing::new(if padding == PaddingScheme::Oaep {
+                    padding::oaep_pad(message, k)?
+                } else {
+                    padding::pkcs1v15_pad(message, k)?
+                });
+                let encrypted = BigUint::from_bytes_be(&em).modpow(&self.exponent, &self.modulus);
+                i2osp(&encrypted, k)
+            }
+        }
+    }
+
+    /// Decrypts and unpads a single block of [`Key::encrypted_block_bytes`] bytes,
+    /// removing the [`Key::BLOCK_END_MARKER`] if `framed`, see [`Key::encrypt_block()`].
+    ///
+    /// # Errors
+    /// - If the block value is not smaller than the modulus.
+    /// - If the padding of the block is invalid, or its end marker missing.
+    pub(crate) fn decrypt_block(
+        &self,
+        block: &[u8],
+        padding: PaddingScheme,
+        framed: bool,
+    ) -> RsaResult<Vec<u8>> {
+        let encrypted = match padding {
+            PaddingScheme::None => BigUint::from_bytes_le(block),
+            PaddingScheme::Oaep | PaddingScheme::Pkcs1v15 => BigUint::from_bytes_be(block),
+        };
+        if encrypted >= self.modulus {
+            return Err(RsaError::EncodingError);
+        }
+        let mut message = self.apply_exponent(&encrypted);
+
+        let decrypted = match padding {
+            PaddingScheme::None => {
+                let mut bytes = Zeroizing::new(message.to_bytes_le());
+                if framed && bytes.pop() != Some(Key::BLOCK_END_MARKER) {
+                    Err(RsaError::InvalidPadding)
+                } else {
+                    Ok(bytes.to_vec())
+                }
+            }
+            PaddingScheme::Oaep | PaddingScheme::Pkcs1v15 => {
+                i2osp(&message, self.modulus.size_in_bytes()).and_then(|em| {
+                    let em = Zeroizing::new(em);
+                    if padding == PaddingScheme::Oaep {
+                        padding::oaep_unpad(&em)
+                    } else {
+                        padding::pkcs1v15_unpad(&em)
+                    }
+                })
+            }
+        };
+        wipe(&mut message);
+        decrypted
+    }
+
+    /// Returns `true` if the trailing CRC32 of `chunk` matches the bytes before it.
+    fn chunk_checksum_matches(chunk: &[u8]) -> bool {
+        if chunk.len() < Key::CHUNK_CHECKSUM_SIZE {
+            return false;
+        }
+        let (block, checksum) = chunk.split_at(chunk.len() - Key::CHUNK_CHECKSUM_SIZE);
+        let mut stored = [0u8; Key::CHUNK_CHECKSUM_SIZE];
+        stored.copy_from_slice(checksum);
+        crc32fast::hash(block) == u32::from_le_bytes(stored)
+    }
+}
+
+/// Converts `value` to a big endian byte string of exactly `len` bytes (I2OSP).
+///
+/// # Errors
+/// If `value` does not fit in `len` bytes.
+pub(crate) fn i2osp(value: &BigUint, len: usize) -> RsaResult<Vec<u8>> {
+    let bytes = value.to_bytes_be();
+    if bytes.len() > len {
+        return Err(RsaError::EncodingError);
+    }
+    let mut output = vec![0u8; len - bytes.len()];
+    output.extend_from_slice(&bytes);
+    Ok(output)
+}
+
+/// Reads from `input` until `buf` is full or the end of input is reached.
+///
+/// # Returns
+/// The amount of bytes read.
+fn read_chunk<R: Read>(input: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        match input.read(&mut buf[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
+
+/// Size in bytes of a single encrypted block, without its checksum,
+/// for a modulus of `modulus_bits` bits, so the chunks can be split without the Key.
+pub(crate) fn encrypted_block_bytes_for(modulus_bits: u64, padding: PaddingScheme) -> usize {
+    let bytes = match padding {
+        PaddingScheme::None => modulus_bits / 8 + Key::ENCRYPTION_BYTE_OFFSET as u64,
+        PaddingScheme::Oaep | PaddingScheme::Pkcs1v15 => modulus_bits.div_ceil(8),
+    };
+    bytes.to_usize().unwrap_or(0usize)
 }
 
 pub trait SizeInBytes {
@@ -91,14 +713,7 @@ impl SizeInBytes for BigUint {
     /// For a number that needs `14` bits,
     /// at least `2` bytes are necessary to correctly represent it.
     fn size_in_bytes(&self) -> usize {
-        // We can safelly cast this u64 to f64 here
-        // because the maximum number of bits a BigUint will
-        // take in this application is 4096 at any given time
-        #[allow(clippy::cast_precision_loss)]
-        (self.bits() as f64 / 8.0)
-            .ceil()
-            .to_usize()
-            .unwrap_or(0usize)
+        self.bits().div_ceil(8).to_usize().unwrap_or(0usize)
     }
 
     /// The exact number of bytes necessary to represent `self`,
@@ -116,9 +731,38 @@ impl SizeInBytes for BigUint {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::key::KeyPair;
+    use crate::key::tests::{pair_4096, test_pair};
+    use header::CIPHERTEXT_HEADER_LEN;
     use lipsum::lipsum;
-    use std::{io::Cursor, str::FromStr};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_size_in_bytes() {
+        for (bits, bytes, floored) in [
+            (0, 0, 0),
+            (1, 1, 0),
+            (7, 1, 0),
+            (8, 1, 1),
+            (9, 2, 1),
+            (14, 2, 1),
+            (16, 2, 2),
+            (17, 3, 2),
+            (4095, 512, 511),
+            (4096, 512, 512),
+            (4097, 513, 512),
+            (16_384, 2048, 2048),
+            (16_385, 2049, 2048),
+        ] {
+            let value = if bits == 0 {
+                BigUint::default()
+            } else {
+                BigUint::from(1u8) << (bits - 1)
+            };
+            assert_eq!(value.bits(), bits);
+            assert_eq!(value.size_in_bytes(), bytes, "{bits} bits");
+            assert_eq!(value.size_in_bytes_floored(), floored, "{bits} bits");
+        }
+    }
 
     #[test]
     fn test_encode_decode() {
@@ -133,7 +777,9 @@ mod tests {
         let mut input1 = Cursor::new(input1_s);
         let mut output1 = Cursor::new(output1_s);
 
-        pair.public_key.encode(&mut input1, &mut output1).unwrap();
+        pair.public_key
+            .encode(&mut input1, &mut output1, PaddingScheme::None)
+            .unwrap();
 
         // decode
         let input2_s = output1.into_inner().clone();
@@ -142,25 +788,333 @@ mod tests {
         let mut input2 = Cursor::new(input2_s);
         let mut output2 = Cursor::new(output2_s);
 
-        pair.private_key.decode(&mut input2, &mut output2).unwrap();
+        pair.private_key
+            .decode(&mut input2, &mut output2, PaddingScheme::None)
+            .unwrap();
 
         pretty_assertions::assert_eq!(original, output2.into_inner());
     }
 
-    fn pair_4096() -> KeyPair {
-        let pub_str = r"rrsa 8a171c456a76fa677632c86d79e76a08e9bd619d877b665195fb1d8e506c5fb93277da524842690e855d860644e6050da582f0fe632763a120e0d316cfbccc3e44cf6c8a2d3906690d8ab6133466f210e100213762f1a7b674307f491c6eba0f120a59fd9a8084ca43dfc43988837546fa0cf5e471703f6588d12a35607b20a8604bd989573ca3fea13637dfe31d77efc4f2919b6a8afc5dd58f78cb77a2e000210a636a8240a59c37eebda30adfe85025643f0592bafcb47e6d01d9a50132e23944044af48ded1e5c1517cbcb3bfb4f3ed488a778503ddf4d8de19ae2919ca3c6a78fd9338fe75d5800c45d4c7f9fe5a49967d285fe872063155ce41915e68728a2bc61fe33202d446c19a1a2a685e05cc006b9722c2c58287880f4ebe541f07feb5088290b1ddfce91aeddcd2d051bf33a02144ea6ecc6c1248d8de0702678d85edf7d6a82bc02d6d6523a87abc6c8dbf965a87e410dadff0a62fefded77f0dc4a0b1a65587c2c546d35e4b7ef85a159b2359d32e56df33cce92fb2a287fd1ee39cb940de89c30cd29b8eeb483ad5ff3d948bcbf17a4641876c55b1ba2026f4b08b96716c8b1038252d84610e491f14d5e4994025918aa5ea083e42d767eb8ee3e4e78c4f3a6afd69642f4f2704525a69141762f7448c9bd4e6d42c9b18358d6e405115579f7834869a9e68f8b0ce9ccbc7cf46119ce464b244d5b58458f8b
-";
-        let priv_str = r"-----BEGIN RSA-RUST PRIVATE KEY-----
-8a171c456a76fa677632c86d79e76a08e9bd619d877b665195fb1d8e506c5fb93277da524842690e855d860644e6050da582f0fe632763a120e0d316cfbccc3e44cf6c8a2d3906690d8ab6133466f210e100213762f1a7b674307f491c6eba0f120a59fd9a8084ca43dfc43988837546fa0cf5e471703f6588d12a35607b20a8604bd989573ca3fea13637dfe31d77efc4f2919b6a8afc5dd58f78cb77a2e000210a636a8240a59c37eebda30adfe85025643f0592bafcb47e6d01d9a50132e23944044af48ded1e5c1517cbcb3bfb4f3ed488a778503ddf4d8de19ae2919ca3c6a78fd9338fe75d5800c45d4c7f9fe5a49967d285fe872063155ce41915e68728a2bc61fe33202d446c19a1a2a685e05cc006b9722c2c58287880f4ebe541f07feb5088290b1ddfce91aeddcd2d051bf33a02144ea6ecc6c1248d8de0702678d85edf7d6a82bc02d6d6523a87abc6c8dbf965a87e410dadff0a62fefded77f0dc4a0b1a65587c2c546d35e4b7ef85a159b2359d32e56df33cce92fb2a287fd1ee39cb940de89c30cd29b8eeb483ad5ff3d948bcbf17a4641876c55b1ba2026f4b08b96716c8b1038252d84610e491f14d5e4994025918aa5ea083e42d767eb8ee3e4e78c4f3a6afd69642f4f2704525a69141762f7448c9bd4e6d42c9b18358d6e405115579f7834869a9e68f8b0ce9ccbc7cf46119ce464b244d5b58458f8b
-29e6a54f72e4b34a9d94ff3828db4d537309620b58c6dadf3ab13de0a70a9b6928a5317bf22d248fa16c2574d5872e555bb985c2caf772c5bba23cab1951e26faa957e0bd7790c36e84304c8830811bf89666eadcdba21f7bcfdd241aefcf23c0c6f53ab1e2c8d1e8ac5e556c7d38bcc83a7571d80465d164413a3c91a8381ff5568ee933c034c87c10720a130db0a3f98f539b57cf8bb67059c493d040a4a09fffc94fa0697f32899d83976b5a0076ffa4896ceec1d0cfcffb7b7ee00a1827d1e7f4306337ab54e97065778212d0c2e999407fb3908b01d87fcdb4e121db8f801196b0eaf14a551af985bfd2b6f36678a307a4e6916388e5d42683356614cd7951c694730d55a7e139e6e1bd0ee36042c1358c704141abe95fd3ab8ab3a7a4c54183dbc1c6c70cafc815263fe1f8e020b4a169e0303376c30c2adc987b68c28996fcd9da0ba83fe52ee2d2fea92145e9ac66c79f753133ba2d52738aaa08e40b7566eb618c10f19b3df04e6cc5f2d3ba9fc7efc7884565a6ef161a737769d5125a76ba2044119a6950e9ccfbfcd4c294a2aa2665d8819a31b50210e4033cd194e0b9d828e684aeada7e68c2f2e8edd1cd5dbbd08ea94da100f1a8c407a8c12b35f0ec004ee592d51946f74ead50e7ba73bab3f75bd197a757c76373f8e1a5c0d7b09e30572751e1084a165f7ccdf82d45c9de1401b4870821012e79e6744431
------END RSA-RUST PRIVATE KEY-----
-";
-        let public_key = Key::from_str(pub_str).unwrap();
-        let private_key = Key::from_str(priv_str).unwrap();
+    #[test]
+    fn test_encode_decode_trailing_zeros() {
+        let pair = test_pair();
+        // every chunk of 3 bytes ends with zeros, and so does the last partial one
+        let original = [1u8, 0, 0, 0, 0, 0, 2, 0, 0, 3, 0].to_vec();
+        let mut encoded = Vec::new();
+        pair.public_key
+            .encode(
+                &mut Cursor::new(original.clone()),
+                &mut encoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode(
+                &mut Cursor::new(&encoded),
+                &mut decoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        assert_eq!(decoded, original);
+
+        // without header the blocks are not framed, so the zeros are lost
+        let mut legacy = Vec::new();
+        pair.public_key
+            .encode_blocks(
+                &mut Cursor::new(original.clone()),
+                &mut legacy,
+                PaddingScheme::None,
+                false,
+            )
+            .unwrap();
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode_legacy(&mut Cursor::new(legacy), &mut decoded)
+            .unwrap();
+        assert_eq!(decoded, [1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_encode_decode_oaep() {
+        let pair = pair_4096();
+        let original = lipsum(2_000).as_bytes().to_vec();
+
+        let mut encoded = Vec::new();
+        pair.public_key
+            .encode(
+                &mut Cursor::new(original.clone()),
+                &mut encoded,
+                PaddingScheme::Oaep,
+            )
+            .unwrap();
+        // plus the integrity tag
+        let blocks = original.len().div_ceil(446);
+        assert_eq!(
+            encoded.len(),
+            CIPHERTEXT_HEADER_LEN + (blocks + 1) * (512 + Key::CHUNK_CHECKSUM_SIZE)
+        );
+
+        // randomized padding, so encoding twice gives different ciphertexts
+        let mut encoded_again = Vec::new();
+        pair.public_key
+            .encode(
+                &mut Cursor::new(original.clone()),
+                &mut encoded_again,
+                PaddingScheme::Oaep,
+            )
+            .unwrap();
+        assert_ne!(encoded, encoded_again);
+
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode(&mut Cursor::new(encoded), &mut decoded, PaddingScheme::Oaep)
+            .unwrap();
+        pretty_assertions::assert_eq!(original, decoded);
+
+        // fixed seed block
+        let em = padding::oaep_pad_with_seed(b"rrsa", 512, &[0xAA; padding::HASH_LEN]).unwrap();
+        let encrypted =
+            BigUint::from_bytes_be(&em).modpow(&pair.public_key.exponent, &pair.public_key.modulus);
+        let block = i2osp(&encrypted, 512).unwrap();
+        assert_eq!(
+            pair.private_key
+                .decrypt_block(&block, PaddingScheme::Oaep, true)
+                .unwrap(),
+            b"rrsa"
+        );
+
+        assert!(matches!(
+            test_pair().public_key.encode(
+                &mut Cursor::new(b"rrsa"),
+                &mut Vec::new(),
+                PaddingScheme::Oaep
+            ),
+            Err(RsaError::KeyTooSmallForPadding)
+        ));
+    }
+
+    #[test]
+    fn test_encode_decode_pkcs1v15() {
+        let pair = pair_4096();
+        let original = lipsum(2_000).as_bytes().to_vec();
+
+        let mut encoded = Vec::new();
+        pair.public_key
+            .encode(
+                &mut Cursor::new(original.clone()),
+                &mut encoded,
+                PaddingScheme::Pkcs1v15,
+            )
+            .unwrap();
+        // plus the integrity tag
+        let blocks = original.len().div_ceil(501);
+        assert_eq!(
+            encoded.len(),
+            CIPHERTEXT_HEADER_LEN + (blocks + 1) * (512 + Key::CHUNK_CHECKSUM_SIZE)
+        );
+
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode(
+                &mut Cursor::new(encoded.clone()),
+                &mut decoded,
+                PaddingScheme::Pkcs1v15,
+            )
+            .unwrap();
+        pretty_assertions::assert_eq!(original, decoded);
+
+        // the header tells the wrong scheme or key apart from corrupted chunks
+        assert!(matches!(
+            pair.private_key.decode(
+                &mut Cursor::new(encoded.clone()),
+                &mut Vec::new(),
+                PaddingScheme::Oaep
+            ),
+            Err(RsaError::PaddingMismatch {
+                expected: PaddingScheme::Oaep,
+                found: PaddingScheme::Pkcs1v15
+            })
+        ));
+        assert!(matches!(
+            crate::key::KeyPair::demo().private_key.decode(
+                &mut Cursor::new(encoded.clone()),
+                &mut Vec::new(),
+                PaddingScheme::Pkcs1v15
+            ),
+            Err(RsaError::WrongRecipient)
+        ));
 
-        KeyPair {
-            public_key,
-            private_key,
+        // checksummed chunks without the header are rejected, not decoded as legacy blocks
+        let headerless = &encoded[CIPHERTEXT_HEADER_LEN..];
+        assert!(matches!(
+            pair.private_key.decode(
+                &mut Cursor::new(headerless),
+                &mut Vec::new(),
+                PaddingScheme::Pkcs1v15
+            ),
+            Err(RsaError::MissingHeader)
+        ));
+        assert!(matches!(
+            pair.public_key
+                .verify_chunks(&mut Cursor::new(headerless), PaddingScheme::Pkcs1v15),
+            Err(RsaError::MissingHeader)
+        ));
+    }
+
+    #[test]
+    fn test_encode_decode_armored() {
+        let pair = pair_4096();
+        let original = lipsum(1_000).as_bytes().to_vec();
+
+        let mut armored = Vec::new();
+        pair.public_key
+            .encode_armored(
+                &mut Cursor::new(original.clone()),
+                &mut armored,
+                PaddingScheme::Oaep,
+            )
+            .unwrap();
+        assert!(armor::is_armored(&armored));
+        assert!(armored.is_ascii());
+
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode(&mut Cursor::new(armored), &mut decoded, PaddingScheme::Oaep)
+            .unwrap();
+        pretty_assertions::assert_eq!(original, decoded);
+
+        // binary input shorter than the armor marker is neither armor nor a header
+        assert!(matches!(
+            test_pair().private_key.decode(
+                &mut Cursor::new(b"RR"),
+                &mut Vec::new(),
+                PaddingScheme::None
+            ),
+            Err(RsaError::MissingHeader)
+        ));
+    }
+
+    #[test]
+    fn test_chunk_checksums() {
+        let mut encoded = Vec::new();
+        test_pair()
+            .public_key
+            .encode(
+                &mut Cursor::new(b"checksummed chunks".to_vec()),
+                &mut encoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        assert!(test_pair()
+            .public_key
+            .verify_chunks(&mut Cursor::new(encoded.clone()), PaddingScheme::None)
+            .unwrap()
+            .is_empty());
+
+        // reordered or dropped chunks keep their checksums, but not the integrity tag
+        let mut reordered = encoded.clone();
+        reordered[CIPHERTEXT_HEADER_LEN..CIPHERTEXT_HEADER_LEN + 2 * 9].rotate_left(9);
+        let mut dropped = encoded.clone();
+        dropped.drain(CIPHERTEXT_HEADER_LEN + 9..CIPHERTEXT_HEADER_LEN + 2 * 9);
+        for tampered in [reordered, dropped] {
+            assert!(matches!(
+                test_pair().private_key.decode(
+                    &mut Cursor::new(tampered),
+                    &mut Vec::new(),
+                    PaddingScheme::None
+                ),
+                Err(RsaError::IntegrityTagMismatch)
+            ));
         }
+
+        // flip a bit of the third chunk
+        encoded[CIPHERTEXT_HEADER_LEN + 2 * 9 + 1] ^= 0x01;
+        assert_eq!(
+            test_pair()
+                .private_key
+                .verify_chunks(&mut Cursor::new(encoded.clone()), PaddingScheme::None)
+                .unwrap(),
+            vec![2]
+        );
+        assert!(matches!(
+            test_pair().private_key.decode(
+                &mut Cursor::new(encoded),
+                &mut Vec::new(),
+                PaddingScheme::None
+            ),
+            Err(RsaError::ChecksumMismatch(2))
+        ));
+    }
+
+    #[test]
+    fn test_decodes_first_chunk() {
+        let demo = crate::key::KeyPair::demo();
+        let mut encoded = Vec::new();
+        demo.public_key
+            .encode_armored(
+                &mut Cursor::new(b"which key?".to_vec()),
+                &mut encoded,
+                PaddingScheme::Oaep,
+            )
+            .unwrap();
+        let mut input = Cursor::new(encoded);
+        assert!(demo
+            .private_key
+            .decodes_first_chunk(&mut input, PaddingScheme::Oaep)
+            .unwrap());
+        assert_eq!(input.position(), 0);
+        assert!(!test_pair()
+            .private_key
+            .decodes_first_chunk(&mut input, PaddingScheme::None)
+            .unwrap());
+        assert!(!pair_4096()
+            .private_key
+            .decodes_first_chunk(&mut input, PaddingScheme::Oaep)
+            .unwrap());
+        assert!(demo
+            .public_key
+            .decodes_first_chunk(&mut input, PaddingScheme::Oaep)
+            .is_err());
+        let mut headerless = Vec::new();
+        test_pair()
+            .public_key
+            .encode_blocks(
+                &mut Cursor::new(b"which key?".to_vec()),
+                &mut headerless,
+                PaddingScheme::None,
+                false,
+            )
+            .unwrap();
+        assert!(matches!(
+            test_pair()
+                .private_key
+                .decodes_first_chunk(&mut Cursor::new(headerless), PaddingScheme::None),
+            Err(RsaError::MissingHeader)
+        ));
+
+        let mut decoded = Vec::new();
+        demo.private_key
+            .decode(&mut input, &mut decoded, PaddingScheme::Oaep)
+            .unwrap();
+        assert_eq!(decoded, b"which key?");
+    }
+
+    #[test]
+    fn test_decode_legacy() {
+        let ciphertext = std::fs::read("./testvectors/ciphertext_legacy.encoded").unwrap();
+        let plaintext = std::fs::read("./testvectors/plaintext.txt").unwrap();
+        let mut output = Vec::new();
+
+        test_pair()
+            .private_key
+            .decode_legacy(&mut Cursor::new(ciphertext), &mut output)
+            .unwrap();
+        pretty_assertions::assert_eq!(plaintext, output);
+
+        assert!(test_pair()
+            .public_key
+            .decode_legacy(&mut Cursor::new(vec![0u8; 5]), &mut output)
+            .is_err());
     }
 }
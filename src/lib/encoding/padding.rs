@@ -0,0 +1,243 @@
+//! Padding schemes applied to every block before encryption.
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{RsaError, RsaResult};
+use crate::rng;
+
+/// Padding scheme used when encoding and decoding blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingScheme {
+    /// Textbook RSA, blocks are only zero padded.
+    ///
+    /// Deterministic and malleable, kept for compatibility and for learning purposes.
+    #[default]
+    None,
+    /// RSAES-OAEP with SHA-256 and MGF1 (SHA-256), with an empty label.
+    Oaep,
+    /// RSAES-PKCS1-v1_5, with random nonzero padding bytes.
+    Pkcs1v15,
+}
+
+/// Length in bytes of a SHA-256 digest.
+pub(crate) const HASH_LEN: usize = 32;
+/// Minimum amount of random padding bytes in a PKCS#1 v1.5 encoded block.
+const PKCS1V15_MIN_PADDING_LEN: usize = 8;
+
+/// Mask generation function MGF1 with SHA-256, as defined by RFC 8017.
+pub(crate) fn mgf1(seed: &[u8], mask_len: usize) -> Vec<u8> {
+    let mut mask = Vec::with_capacity(mask_len + HASH_LEN);
+    let mut counter = 0u32;
+    while mask.len() < mask_len {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(counter.to_be_bytes());
+        mask.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    mask.truncate(mask_len);
+    mask
+}
+
+pub(crate) fn xor_in_place(target: &mut [u8], mask: &[u8]) {
+    for (byte, mask_byte) in target.iter_mut().zip(mask) {
+        *byte ^= mask_byte;
+    }
+}
+
+/// Maximum message length in bytes for an OAEP encoded block of `k` bytes.
+pub(crate) fn oaep_max_message_len(k: usize) -> RsaResult<usize> {
+    k.checked_sub(2 * HASH_LEN + 2)
+        .filter(|len| *len > 0)
+        .ok_or(RsaError::KeyTooSmallForPadding)
+}
+
+/// EME-OAEP encoding of `message` into a `k` bytes block, using a random seed.
+pub(crate) fn oaep_pad(message: &[u8], k: usize) -> RsaResult<Vec<u8>> {
+    let mut seed = [0u8; HASH_LEN];
+    rng::fill_bytes(&mut seed);
+    oaep_pad_with_seed(message, k, &seed)
+}
+
+/// EME-OAEP encoding of `message` into a `k` bytes block, using the given `seed`.
+pub(crate) fn oaep_pad_with_seed(
+    message: &[u8],
+    k: usize,
+    seed: &[u8; HASH_LEN],
+) -> RsaResult<Vec<u8>> {
+    if message.len() > oaep_max_message_len(k)? {
+        return Err(RsaError::EncodingError);
+    }
+
+    // DB = lHash || PS || 0x01 || M
+    let mut db = Vec::with_capacity(k - HASH_LEN - 1);
+    db.extend_from_slice(&Sha256::digest(b""));
+    db.resize(k - message.len() - HASH_LEN - 2, 0u8);
+    db.push(0x01);
+    db.extend_from_slice(message);
+    let db_mask = mgf1(seed, db.len());
+    xor_in_place(&mut db, &db_mask);
+
+    let mut masked_seed = seed.to_vec();
+    xor_in_place(&mut masked_seed, &mgf1(&db, HASH_LEN));
+
+    // EM = 0x00 || maskedSeed || maskedDB
+    let mut em = Vec::with_capacity(k);
+    em.push(0x00);
+    em.extend_from_slice(&masked_seed);
+    em.extend_from_slice(&db);
+    Ok(em)
+}
+
+/// EME-OAEP decoding of a `k` bytes block, returning the message.
+pub(crate) fn oaep_unpad(em: &[u8]) -> RsaResult<Vec<u8>> {
+    if em.len() < 2 * HASH_LEN + 2 {
+        return Err(RsaError::InvalidPadding);
+    }
+    let (leading, rest) = em.split_at(1);
+    let (masked_seed, masked_db) = rest.split_at(HASH_LEN);
+
+    let mut seed = masked_seed.to_vec();
+    xor_in_place(&mut seed, &mgf1(masked_db, HASH_LEN));
+    let mut db = masked_db.to_vec();
+    xor_in_place(&mut db, &mgf1(&seed, masked_db.len()));
+
+    let (l_hash, padded_message) = db.split_at(HASH_LEN);
+    let separator = padded_message.iter().position(|byte| *byte != 0x00);
+    match separator {
+        Some(index)
+            if leading[0] == 0x00
+                && l_hash == Sha256::digest(b"").as_slice()
+                && padded_message[index] == 0x01 =>
+        {
+            Ok(padded_message[index + 1..].to_vec())
+        }
+        _ => Err(RsaError::InvalidPadding),
+    }
+}
+
+/// Maximum message length in bytes for a PKCS#1 v1.5 encoded block of `k` bytes.
+pub(crate) fn pkcs1v15_max_message_len(k: usize) -> RsaResult<usize> {
+    k.checked_sub(PKCS1V15_MIN_PADDING_LEN + 3)
+        .filter(|len| *len > 0)
+        .ok_or(RsaError::KeyTooSmallForPadding)
+}
+
+/// EME-PKCS1-v1_5 encoding of `message` into a `k` bytes block.
+pub(crate) fn pkcs1v15_pad(message: &[u8], k: usize) -> RsaResult<Vec<u8>> {
+    if message.len() > pkcs1v15_max_message_len(k)? {
+        return Err(RsaError::EncodingError);
+    }
+
+    // PS must not contain any zero byte
+    let mut padding_string = vec![0u8; k - message.len() - 3];
+    rng::fill_nonzero_bytes(&mut padding_string);
+
+    // EM = 0x00 || 0x02 || PS || 0x00 || M
+    let mut em = Vec::with_capacity(k);
+    em.extend_from_slice(&[0x00, 0x02]);
+    em.extend_from_slice(&padding_string);
+    em.push(0x00);
+    em.extend_from_slice(message);
+    Ok(em)
+}
+
+/// EME-PKCS1-v1_5 decoding of a `k` bytes block, returning the message.
+pub(crate) fn pkcs1v15_unpad(em: &[u8]) -> RsaResult<Vec<u8>> {
+    if em.len() < PKCS1V15_MIN_PADDING_LEN + 3 || em[0] != 0x00 || em[1] != 0x02 {
+        return Err(RsaError::InvalidPadding);
+    }
+    match em[2..].iter().position(|byte| *byte == 0x00) {
+        Some(padding_len) if padding_len >= PKCS1V15_MIN_PADDING_LEN => {
+            Ok(em[2 + padding_len + 1..].to_vec())
+        }
+        _ => Err(RsaError::InvalidPadding),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_mgf1() {
+        // MGF1-SHA256("abc", 40)
+        assert_eq!(
+            mgf1(b"abc", 40),
+            [
+                0xcf, 0x2d, 0xb1, 0xac, 0x98, 0x67, 0xde, 0xbd, 0xf8, 0xce, 0x91, 0xf9, 0x9f, 0x14,
+                0x1e, 0x55, 0x44, 0xbf, 0x26, 0xca, 0x36, 0xb3, 0xfd, 0x4f, 0x8e, 0x40, 0x35, 0xee,
+                0xc4, 0x2c, 0xab, 0x0d, 0x46, 0xc3, 0x86, 0xeb, 0xcc, 0xef, 0x82, 0xba
+            ]
+        );
+    }
+
+    #[test]
+    fn test_oaep_fixed_vector() {
+        let seed = [0xAAu8; HASH_LEN];
+        let em = oaep_pad_with_seed(b"rrsa", 128, &seed).unwrap();
+        assert_eq!(em.len(), 128);
+        assert_eq!(em[0], 0x00);
+        assert_eq!(&em[1..9], &[0x77, 0x26, 0x60, 0x66, 0x92, 0x77, 0xde, 0xab]);
+        assert_eq!(
+            &em[120..],
+            &[0x7e, 0x93, 0x5b, 0x1a, 0xed, 0xc4, 0x49, 0x32]
+        );
+        assert_eq!(oaep_unpad(&em).unwrap(), b"rrsa");
+    }
+
+    #[test]
+    fn test_oaep_invalid() {
+        let mut em = oaep_pad(b"rrsa", 128).unwrap();
+        em[100] ^= 0x01;
+        assert!(oaep_unpad(&em).is_err());
+        assert!(oaep_max_message_len(66).is_err());
+        assert!(oaep_pad(&[0u8; 63], 128).is_err());
+        assert_eq!(oaep_pad(&[0u8; 62], 128).unwrap().len(), 128);
+    }
+
+    #[test]
+    fn test_pkcs1v15() {
+        let em = pkcs1v15_pad(b"rrsa", 64).unwrap();
+        assert_eq!(em.len(), 64);
+        assert_eq!(&em[..2], &[0x00, 0x02]);
+        assert!(em[2..59].iter().all(|byte| *byte != 0x00));
+        assert_eq!(em[59], 0x00);
+        assert_eq!(pkcs1v15_unpad(&em).unwrap(), b"rrsa");
+
+        assert!(pkcs1v15_pad(&[1u8; 54], 64).is_err());
+        assert_eq!(pkcs1v15_pad(&[1u8; 53], 64).unwrap().len(), 64);
+        assert!(pkcs1v15_max_message_len(11).is_err());
+    }
+
+    #[test]
+    fn test_pkcs1v15_strict_unpad() {
+        let mut em = vec![0x00, 0x02];
+        em.extend_from_slice(&[0xFF; 8]);
+        em.push(0x00);
+        em.extend_from_slice(b"msg");
+        assert_eq!(pkcs1v15_unpad(&em).unwrap(), b"msg");
+
+        // wrong block type
+        let mut wrong_type = em.clone();
+        wrong_type[1] = 0x01;
+        assert!(pkcs1v15_unpad(&wrong_type).is_err());
+
+        // leading byte not zero
+        let mut wrong_lead = em.clone();
+        wrong_lead[0] = 0x01;
+        assert!(pkcs1v15_unpad(&wrong_lead).is_err());
+
+        // padding string shorter than 8 bytes
+        let mut short_padding = em.clone();
+        short_padding[9] = 0x00;
+        assert!(pkcs1v15_unpad(&short_padding).is_err());
+
+        // no separator
+        let no_separator = vec![
+            0x00, 0x02, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ];
+        assert!(pkcs1v15_unpad(&no_separator).is_err());
+    }
+}
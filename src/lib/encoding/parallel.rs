@@ -0,0 +1,137 @@
+//! Encoding of every chunk across a thread pool, only available with the `rayon` feature.
+
+use std::io::{Read, Write};
+
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::{header::CiphertextHeader, padding::PaddingScheme, read_chunk};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+
+impl Key {
+    /// Number of chunks read and encrypted together by [`Key::par_encode()`].
+    const PARALLEL_BATCH_CHUNKS: usize = 256;
+
+    /// Same as [`Key::encode()`], but the chunks of every batch read from `input`
+    /// are encrypted across the [`rayon`] thread pool, then written in order.
+    ///
+    /// The output can be decoded by [`Key::decode()`]. Random padding bytes are drawn
+    /// from the default source of each worker thread, so a source set with
+    /// [`crate::rng::with_source()`] is not used.
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let original = vec![42u8; 10_000];
+    /// let mut encoded = Vec::new();
+    /// pair.public_key
+    ///     .par_encode(&mut original.as_slice(), &mut encoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    ///
+    /// let mut decoded = Vec::new();
+    /// pair.private_key
+    ///     .decode(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert_eq!(decoded, original);
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn par_encode<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<()> {
+        if self.variant != KeyVariant::PublicKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        let max_bytes_read = self.max_message_bytes(padding)?;
+        output.write_all(&CiphertextHeader::new(self, padding).to_bytes())?;
+        let mut batch = Zeroizing::new(vec![0u8; max_bytes_read * Key::PARALLEL_BATCH_CHUNKS]);
+        let mut hasher = Sha256::new();
+        loop {
+            let bytes_amount_read = read_chunk(input, &mut batch)?;
+            hasher.update(&batch[..bytes_amount_read]);
+            let blocks = batch[..bytes_amount_read]
+                .par_chunks(max_bytes_read)
+                .map(|chunk| {
+                    let mut block = self.encrypt_block(chunk, padding, true)?;
+                    let checksum = crc32fast::hash(&block);
+                    block.extend_from_slice(&checksum.to_le_bytes());
+                    Ok(block)
+                })
+                .collect::<RsaResult<Vec<_>>>()?;
+            for block in blocks {
+                output.write_all(&block)?;
+            }
+            if bytes_amount_read < batch.len() {
+                break;
+            }
+        }
+        output.write_all(&self.encrypt_integrity_tag(hasher, padding)?)?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::header::CIPHERTEXT_HEADER_LEN;
+    use crate::key::tests::{pair_4096, test_pair};
+    use lipsum::lipsum;
+
+    #[test]
+    fn test_par_encode() {
+        let pair = pair_4096();
+        let original = lipsum(40_000).into_bytes();
+
+        // textbook RSA is deterministic, so the output matches the sequential encoder,
+        // across several batches
+        let mut sequential = Vec::new();
+        let mut parallel = Vec::new();
+        pair.public_key
+            .encode(
+                &mut original.as_slice(),
+                &mut sequential,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        pair.public_key
+            .par_encode(&mut original.as_slice(), &mut parallel, PaddingScheme::None)
+            .unwrap();
+        assert_eq!(parallel, sequential);
+
+        // decoding with the 4096 bits Private Key is slow, so only a few chunks
+        let original = &original[..4000];
+        let mut encoded = Vec::new();
+        pair.public_key
+            .par_encode(&mut &original[..], &mut encoded, PaddingScheme::Oaep)
+            .unwrap();
+        let mut decoded = Vec::new();
+        pair.private_key
+            .decode(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+            .unwrap();
+        assert_eq!(decoded, original);
+
+        let mut empty = Vec::new();
+        pair.public_key
+            .par_encode(&mut [0u8; 0].as_slice(), &mut empty, PaddingScheme::Oaep)
+            .unwrap();
+        // only the integrity tag
+        assert_eq!(
+            empty.len(),
+            CIPHERTEXT_HEADER_LEN + 512 + Key::CHUNK_CHECKSUM_SIZE
+        );
+
+        assert!(test_pair()
+            .private_key
+            .par_encode(&mut &original[..], &mut encoded, PaddingScheme::None)
+            .is_err());
+    }
+}
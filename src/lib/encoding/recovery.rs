@@ -0,0 +1,138 @@
+use std::{
+    io::{Read, Write},
+    ops::Range,
+};
+
+use super::{padding::PaddingScheme, read_chunk};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+
+impl Key {
+    /// Byte written in place of every plaintext byte lost to a damaged chunk.
+    pub const CORRUPT_CHUNK_MARKER: u8 = b'?';
+
+    /// Decodes a [`Read`] implementor to a [`Write`] implementor
+    /// using this Private Key and the given [`PaddingScheme`],
+    /// skipping damaged chunks instead of aborting.
+    ///
+    /// A chunk is considered damaged if it is truncated, if its checksum does not
+    /// match, if it cannot be decrypted and unpadded, or if it decodes to more
+    /// bytes than a plaintext chunk can hold. Each damaged chunk is replaced by a full
+    /// plaintext chunk of [`Key::CORRUPT_CHUNK_MARKER`] bytes.
+    ///
+    /// The integrity tag is dropped without being checked,
+    /// since the damaged chunks could not be hashed anyway.
+    ///
+    /// # Returns
+    /// The byte ranges of the output that were filled with markers.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If the header is missing, damaged or was written for another Key or [`PaddingScheme`].
+    /// - If any [`std::io::Error`] occurs.
+    pub fn decode_best_effort<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<Vec<Range<usize>>> {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+
+        let block_bytes = self.encrypted_block_bytes(padding);
+        let max_bytes = block_bytes + Key::CHUNK_CHECKSUM_SIZE;
+        let max_plain_bytes = self.max_message_bytes(padding)?;
+        let mut source_bytes = vec![0u8; max_bytes];
+        let mut lost = Vec::new();
+        let mut written = 0usize;
+        self.read_verified_header(input, padding)?;
+        // outcome of the last chunk, only written once the next one is read,
+        // since it may be the integrity tag
+        let mut held_back = None;
+
+        loop {
+            source_bytes.fill(0u8);
+            let bytes_amount_read = read_chunk(input, &mut source_bytes)?;
+            if bytes_amount_read == 0 {
+                break;
+            }
+
+            let intact =
+                bytes_amount_read == max_bytes && Key::chunk_checksum_matches(&source_bytes);
+            let message = intact
+                .then_some(&source_bytes[..block_bytes])
+                .and_then(|block| self.decrypt_block(block, padding, true).ok())
+                .filter(|message| message.len() <= max_plain_bytes);
+            match held_back.replace(message) {
+                Some(Some(message)) => {
+                    output.write_all(&message)?;
+                    written += message.len();
+                }
+                Some(None) => {
+                    output.write_all(&vec![Key::CORRUPT_CHUNK_MARKER; max_plain_bytes])?;
+                    match lost.last_mut() {
+                        Some(Range { end, .. }) if *end == written => *end += max_plain_bytes,
+                        _ => lost.push(written..written + max_plain_bytes),
+                    }
+                    written += max_plain_bytes;
+                }
+                None => {}
+            }
+
+            if bytes_amount_read < max_bytes {
+                break;
+            }
+        }
+        output.flush()?;
+        Ok(lost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::header::CIPHERTEXT_HEADER_LEN;
+    use crate::key::tests::test_pair;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_decode_best_effort() {
+        let original = b"The quick brown fox jumps over the lazy dog.\n".to_vec();
+        let mut encoded = Vec::new();
+        test_pair()
+            .public_key
+            .encode(
+                &mut Cursor::new(original.clone()),
+                &mut encoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+
+        // undamaged input decodes completely
+        let mut decoded = Vec::new();
+        let lost = test_pair()
+            .private_key
+            .decode_best_effort(
+                &mut Cursor::new(encoded.clone()),
+                &mut decoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        assert!(lost.is_empty());
+        assert_eq!(original, decoded);
+
+        // second chunk damaged, integrity tag truncated
+        encoded[CIPHERTEXT_HEADER_LEN + 9..CIPHERTEXT_HEADER_LEN + 14].fill(0xFF);
+        encoded.truncate(encoded.len() - 2);
+        let mut decoded = Vec::new();
+        let lost = test_pair()
+            .private_key
+            .decode_best_effort(&mut Cursor::new(encoded), &mut decoded, PaddingScheme::None)
+            .unwrap();
+        assert_eq!(lost, vec![3..6]);
+        assert_eq!(&decoded[..3], &original[..3]);
+        assert_eq!(&decoded[3..6], b"???");
+        assert_eq!(&decoded[6..], &original[6..]);
+    }
+}
@@ -0,0 +1,340 @@
+//! [`Write`] and [`Read`] adapters that encode and decode on the fly,
+//! so RSA encryption can be plugged into [`std::io::copy()`] or any pipeline.
+
+use std::io::{self, Read, Write};
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::{header::CiphertextHeader, padding::PaddingScheme, read_chunk};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+
+/// Encodes everything written to it into `W`, in the format of [`Key::encode()`].
+///
+/// The header is written by [`EncryptingWriter::new()`], then bytes are buffered until
+/// a whole chunk can be encrypted, so the last partial chunk and the integrity tag are only
+/// written by [`EncryptingWriter::finish()`], or on a best effort basis when dropped.
+#[derive(Debug)]
+pub struct EncryptingWriter<'k, W: Write> {
+    key: &'k Key,
+    inner: Option<W>,
+    padding: PaddingScheme,
+    chunk_len: usize,
+    buffer: Zeroizing<Vec<u8>>,
+    hasher: Sha256,
+}
+
+/// Decodes the output of [`Key::encode()`] read from `R`, chunk by chunk.
+///
+/// ASCII armor is not detected, use [`Key::decode()`] for armored input.
+/// Reading fails at the end of the input if the integrity tag does not match,
+/// see [`Key::decode()`].
+#[derive(Debug)]
+pub struct DecryptingReader<'k, R: Read> {
+    key: &'k Key,
+    inner: R,
+    padding: PaddingScheme,
+    /// Whether the header was read and verified.
+    header_read: bool,
+    /// Hash of the decoded data, `None` once the integrity tag was verified.
+    hasher: Option<Sha256>,
+    /// Last decoded chunk, only returned once the next one is read, since it may be the tag.
+    held_back: Option<Zeroizing<Vec<u8>>>,
+    block: Vec<u8>,
+    decoded: Zeroizing<Vec<u8>>,
+    position: usize,
+    chunk_index: usize,
+}
+
+impl<'k, W: Write> EncryptingWriter<'k, W> {
+    /// Wraps `inner`, encoding with the Public Key `key` and the given [`PaddingScheme`].
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::{padding::PaddingScheme, DecryptingReader, EncryptingWriter};
+    /// use rrsa_lib::key::KeyPair;
+    /// use std::io::{copy, Read};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut writer = EncryptingWriter::new(&pair.public_key, Vec::new(), PaddingScheme::Oaep).unwrap();
+    /// copy(&mut &b"attack at dawn"[..], &mut writer).unwrap();
+    /// let encoded = writer.finish().unwrap();
+    ///
+    /// let mut reader = DecryptingReader::new(&pair.private_key, encoded.as_slice(), PaddingScheme::Oaep).unwrap();
+    /// let mut decoded = Vec::new();
+    /// reader.read_to_end(&mut decoded).unwrap();
+    /// assert_eq!(decoded, b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// - If `key` is not a [`KeyVariant::PublicKey`].
+    /// - If the key is too small for the padding scheme.
+    /// - If writing the header fails.
+    pub fn new(key: &'k Key, mut inner: W, padding: PaddingScheme) -> RsaResult<Self> {
+        if key.variant != KeyVariant::PublicKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        let chunk_len = key.max_message_bytes(padding)?;
+        inner.write_all(&CiphertextHeader::new(key, padding).to_bytes())?;
+        Ok(Self {
+            key,
+            inner: Some(inner),
+            padding,
+            chunk_len,
+            buffer: Zeroizing::new(Vec::with_capacity(chunk_len)),
+            hasher: Sha256::new(),
+        })
+    }
+
+    /// Encodes the last partial chunk and the integrity tag, then flushes the inner writer.
+    ///
+    /// # Returns
+    /// The inner writer.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] and encoding errors.
+    ///
+    /// # Panics
+    /// Never, the inner writer is only taken here, which consumes `self`.
+    pub fn finish(mut self) -> RsaResult<W> {
+        self.write_end()?;
+        let mut inner = self.inner.take().expect("inner writer is only taken once");
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Encodes and writes the buffered bytes as a single chunk, if any.
+    fn write_buffered(&mut self) -> RsaResult<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.hasher.update(&*self.buffer);
+        let mut block = self.key.encrypt_block(&self.buffer, self.padding, true)?;
+        let checksum = crc32fast::hash(&block);
+        block.extend_from_slice(&checksum.to_le_bytes());
+        if let Some(inner) = self.inner.as_mut() {
+            inner.write_all(&block)?;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Writes the buffered bytes, then the integrity tag of everything written.
+    fn write_end(&mut self) -> RsaResult<()> {
+        self.write_buffered()?;
+        let tag = self
+            .key
+            .encrypt_integrity_tag(std::mem::take(&mut self.hasher), self.padding)?;
+        if let Some(inner) = self.inner.as_mut() {
+            inner.write_all(&tag)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() == self.chunk_len {
+            self.write_buffered().map_err(io::Error::other)?;
+        }
+        let len = buf.len().min(self.chunk_len - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    /// Flushes the inner writer, the last partial chunk stays buffered.
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for EncryptingWriter<'_, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_end();
+        }
+    }
+}
+
+impl<'k, R: Read> DecryptingReader<'k, R> {
+    /// Wraps `inner`, decoding with the Private Key `key` and the given [`PaddingScheme`].
+    ///
+    /// # Errors
+    /// If `key` is not a [`KeyVariant::PrivateKey`].
+    pub fn new(key: &'k Key, inner: R, padding: PaddingScheme) -> RsaResult<Self> {
+        if key.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        Ok(Self {
+            key,
+            inner,
+            padding,
+            header_read: false,
+            hasher: Some(Sha256::new()),
+            held_back: None,
+            block: vec![0u8; key.encrypted_block_bytes(padding) + Key::CHUNK_CHECKSUM_SIZE],
+            decoded: Zeroizing::new(Vec::new()),
+            position: 0,
+            chunk_index: 0,
+        })
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decodes the next chunk.
+    ///
+    /// # Returns
+    /// `false` at the end of the input.
+    fn decode_next(&mut self) -> RsaResult<bool> {
+        if !self.header_read {
+            self.key
+                .read_verified_header(&mut self.inner, self.padding)?;
+            self.header_read = true;
+        }
+        let bytes_amount_read = read_chunk(&mut self.inner, &mut self.block)?;
+        if bytes_amount_read == 0 {
+            if let Some(hasher) = self.hasher.take() {
+                let tag = self.held_back.take();
+                self.key.verify_integrity_tag(
+                    hasher,
+                    tag.as_deref().map(Vec::as_slice),
+                    self.padding,
+                )?;
+            }
+            return Ok(false);
+        }
+        // a short chunk would be decrypted along with the rest of the previous one
+        if bytes_amount_read != self.block.len()
+            || !Key::chunk_checksum_matches(&self.block[..bytes_amount_read])
+        {
+            return Err(RsaError::ChecksumMismatch(self.chunk_index));
+        }
+        let block_bytes = self.block.len() - Key::CHUNK_CHECKSUM_SIZE;
+        let message = Zeroizing::new(self.key.decrypt_block(
+            &self.block[..block_bytes],
+            self.padding,
+            true,
+        )?);
+        if let Some(previous) = self.held_back.replace(message) {
+            if let Some(hasher) = self.hasher.as_mut() {
+                hasher.update(&*previous);
+            }
+            self.decoded = previous;
+        } else {
+            self.decoded.clear();
+        }
+        self.position = 0;
+        self.chunk_index += 1;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if !self.decode_next().map_err(io::Error::other)? {
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.decoded.len() - self.position);
+        buf[..len].copy_from_slice(&self.decoded[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::header::CIPHERTEXT_HEADER_LEN;
+    use crate::key::{tests::test_pair, KeyPair};
+    use std::io::copy;
+
+    #[test]
+    fn test_streaming_adapters() {
+        let pair = KeyPair::demo();
+        for padding in [
+            PaddingScheme::None,
+            PaddingScheme::Oaep,
+            PaddingScheme::Pkcs1v15,
+        ] {
+            let chunk_len = pair.public_key.max_message_bytes(padding).unwrap();
+            for len in [0, 1, chunk_len, chunk_len + 1, 3 * chunk_len] {
+                let original: Vec<u8> = (0..len)
+                    .map(|i| u8::try_from(i % 251).unwrap() + 1)
+                    .collect();
+
+                let mut writer =
+                    EncryptingWriter::new(&pair.public_key, Vec::new(), padding).unwrap();
+                // small writes, crossing chunk boundaries
+                for piece in original.chunks(7) {
+                    writer.write_all(piece).unwrap();
+                }
+                let encoded = writer.finish().unwrap();
+
+                let mut expected = Vec::new();
+                pair.public_key
+                    .encode(&mut original.as_slice(), &mut expected, padding)
+                    .unwrap();
+                assert_eq!(encoded.len(), expected.len(), "{padding:?} {len} bytes");
+
+                let mut reader =
+                    DecryptingReader::new(&pair.private_key, encoded.as_slice(), padding).unwrap();
+                let mut decoded = Vec::new();
+                copy(&mut reader, &mut decoded).unwrap();
+                assert_eq!(decoded, original, "{padding:?} {len} bytes");
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_adapters_errors() {
+        let pair = KeyPair::demo();
+        assert!(EncryptingWriter::new(&pair.private_key, Vec::new(), PaddingScheme::None).is_err());
+        assert!(DecryptingReader::new(&pair.public_key, &[][..], PaddingScheme::None).is_err());
+        assert!(
+            EncryptingWriter::new(&test_pair().public_key, Vec::new(), PaddingScheme::Oaep)
+                .is_err()
+        );
+
+        // dropping the writer still writes the last chunk
+        let mut encoded = Vec::new();
+        {
+            let mut writer =
+                EncryptingWriter::new(&pair.public_key, &mut encoded, PaddingScheme::Oaep).unwrap();
+            writer.write_all(b"attack at dawn").unwrap();
+        }
+        assert!(encoded.len() > CIPHERTEXT_HEADER_LEN);
+
+        // the last byte is in the checksum of the integrity tag, after the only chunk
+        let last = encoded.len() - 1;
+        encoded[last] ^= 0x01;
+        let mut reader =
+            DecryptingReader::new(&pair.private_key, encoded.as_slice(), PaddingScheme::Oaep)
+                .unwrap();
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("checksum mismatch in encrypted chunk 1"));
+
+        // a truncated last chunk is rejected, even with a checksum matching what is left of it
+        encoded[last] ^= 0x01;
+        let chunk_len = (encoded.len() - CIPHERTEXT_HEADER_LEN) / 2;
+        let mut truncated = encoded[..CIPHERTEXT_HEADER_LEN + chunk_len + 10].to_vec();
+        let checksum = crc32fast::hash(&truncated[CIPHERTEXT_HEADER_LEN + chunk_len..]);
+        truncated.extend_from_slice(&checksum.to_le_bytes());
+        let mut reader =
+            DecryptingReader::new(&pair.private_key, truncated.as_slice(), PaddingScheme::Oaep)
+                .unwrap();
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("checksum mismatch in encrypted chunk 1"));
+    }
+}
@@ -3,6 +3,8 @@
 use num_bigint::ParseBigIntError;
 use thiserror::Error;
 
+use crate::encoding::padding::PaddingScheme;
+
 /// Type alias for [`RsaError`] type.
 pub type RsaResult<T> = std::result::Result<T, RsaError>;
 
@@ -27,8 +29,61 @@ pub enum RsaError {
         #[source]
         ParseBigIntError,
     ),
+    #[error("checksum mismatch in encrypted chunk {0}")]
+    ChecksumMismatch(usize),
+    #[error("the decoded data does not match its integrity tag, it was truncated, reordered or decoded with the wrong key")]
+    IntegrityTagMismatch,
+    #[error("the input has no ciphertext header, it is not encrypted or was encrypted by an older version without one")]
+    MissingHeader,
+    #[error("checksum mismatch in key file, it was truncated or modified")]
+    KeyChecksumMismatch,
+    #[error("the key is too small for the chosen padding scheme")]
+    KeyTooSmallForPadding,
+    #[error("invalid padding, wrong key or corrupted data")]
+    InvalidPadding,
+    #[error("the digest length does not match the hash algorithm")]
+    InvalidDigest,
+    #[error("the peer public value is out of range")]
+    InvalidPeerValue,
+    #[error("the prime factors of the key could not be recovered")]
+    MissingKeyFactors,
+    #[error("the signature is not valid")]
+    InvalidSignature,
+    #[error("the token has expired")]
+    TokenExpired,
+    #[error("the message was encrypted to a different key")]
+    WrongRecipient,
+    #[error("the {format} was produced by a newer version of rrsa (format version {found}, this version supports up to {supported})")]
+    UnsupportedVersion {
+        format: &'static str,
+        found: u32,
+        supported: u32,
+    },
+    #[error("invalid number {0}")]
+    InvalidNumber(String),
+    #[error("key size {0} is not supported, it must be even and in (32..=4096)")]
+    InvalidKeySize(u32),
+    #[error("key generation failed: {0}")]
+    GenerationFailed(String),
+    #[error("the data was encoded with the {found:?} padding scheme, not {expected:?}")]
+    PaddingMismatch {
+        expected: PaddingScheme,
+        found: PaddingScheme,
+    },
+    #[error("invalid key, {0}")]
+    InvalidKey(String),
+    #[error("invalid envelope, {0}")]
+    InvalidEnvelope(String),
+    #[error("daemon protocol error, {0}")]
+    DaemonProtocol(String),
+    #[error("the daemon holds no key named `{0}`")]
+    DaemonUnknownKey(String),
+    #[error("the daemon failed the request: {0}")]
+    DaemonRequest(String),
     #[error("the wrong type of Key Variant was providaded")]
     WrongKeyVariant,
+    #[error("{} already exists, it would be overwritten", .0.display())]
+    WouldOverwrite(std::path::PathBuf),
     #[error("{0}")]
     UnknownError(String),
 }
@@ -0,0 +1,127 @@
+//! Module with a builder of temporary directories holding keys and sample files,
+//! so tests never share paths and can run in parallel.
+//!
+//! Used by the tests of this crate, and available to the tests
+//! of crates built on top of it with the `fixtures` feature.
+
+use crate::error::RsaResult;
+use crate::key::{Key, KeyPair};
+use std::{
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// Builder of a [`Fixture`], every entry is created when [`FixtureBuilder::build()`] is called.
+#[derive(Debug, Default)]
+pub struct FixtureBuilder {
+    dirs: Vec<String>,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+/// Temporary directory created by a [`FixtureBuilder`], removed on drop.
+#[derive(Debug)]
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl FixtureBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an empty directory, creating its parents.
+    #[must_use]
+    pub fn dir(mut self, name: &str) -> Self {
+        self.dirs.push(name.to_string());
+        self
+    }
+
+    /// Adds a file with `contents`, creating its parent directories.
+    #[must_use]
+    pub fn file(mut self, name: &str, contents: impl Into<Vec<u8>>) -> Self {
+        self.files.push((name.to_string(), contents.into()));
+        self
+    }
+
+    /// Adds a [`Key`] file, formatted as by [`Key::write_to_path()`].
+    #[must_use]
+    pub fn key(self, name: &str, key: &Key) -> Self {
+        self.file(name, key.to_string())
+    }
+
+    /// Adds both files of a [`KeyPair`], formatted as by [`KeyPair::write_to_path()`],
+    /// so the Public Key is `name` with the [`Key::DEFAULT_PUBLIC_KEY_EXTENSION`].
+    #[must_use]
+    pub fn key_pair(self, name: &str, pair: &KeyPair) -> Self {
+        let pub_name = Path::new(name).with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION);
+        self.key(&pub_name.to_string_lossy(), &pair.public_key)
+            .key(name, &pair.private_key)
+    }
+
+    /// Creates the temporary directory and every entry inside it.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn build(self) -> RsaResult<Fixture> {
+        let fixture = Fixture {
+            dir: TempDir::new()?,
+        };
+        for name in &self.dirs {
+            create_dir_all(fixture.join(name))?;
+        }
+        for (name, contents) in &self.files {
+            let path = fixture.join(name);
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+            write(path, contents)?;
+        }
+        Ok(fixture)
+    }
+}
+
+impl Fixture {
+    /// Path of the temporary directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Path of `name` inside the temporary directory.
+    #[must_use]
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path().join(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+
+    #[test]
+    fn test_fixture_builder() {
+        let fixture = FixtureBuilder::new()
+            .dir("empty/nested")
+            .file("messages/msg.txt", "rrsa")
+            .key_pair("keys/pair", test_pair())
+            .build()
+            .unwrap();
+
+        assert!(fixture.join("empty/nested").is_dir());
+        assert_eq!(
+            std::fs::read(fixture.join("messages/msg.txt")).unwrap(),
+            b"rrsa"
+        );
+        assert_eq!(
+            KeyPair::read_from_path(&fixture.join("keys/pair")).unwrap(),
+            *test_pair()
+        );
+
+        let path = fixture.path().to_path_buf();
+        drop(fixture);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,155 @@
+//! Module to sign and verify RS256 JSON Web Tokens (RFC 7519) with this crate's keys,
+//! using RSASSA-PKCS1-v1_5 with SHA-256.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::signature::{HashAlgorithm, Signature, SignatureScheme};
+
+/// JOSE header of every token signed by this crate.
+const HEADER: &str = r#"{"alg":"RS256","typ":"JWT"}"#;
+const SCHEME: SignatureScheme = SignatureScheme::Pkcs1v15(HashAlgorithm::Sha256);
+
+fn improperly_formatted(reason: &str) -> RsaError {
+    RsaError::ImproperlyFormattedStr(format!("because the token {reason}"))
+}
+
+/// Parses a base64url encoded JSON object.
+///
+/// # Returns
+/// The JSON as it was encoded, and the parsed object.
+fn decode_json_object(encoded: &str) -> RsaResult<(String, serde_json::Map<String, Value>)> {
+    let json = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .ok()
+        .and_then(|json| String::from_utf8(json).ok())
+        .ok_or_else(|| improperly_formatted("is not valid base64url"))?;
+    match serde_json::from_str(&json) {
+        Ok(Value::Object(object)) => Ok((json, object)),
+        _ => Err(improperly_formatted("does not contain a JSON object")),
+    }
+}
+
+/// Signs `claims`, a JSON object, as an RS256 JWT using a Private Key.
+///
+/// The claims are encoded exactly as given, without being reformatted.
+///
+/// ```
+/// use rrsa_lib::{jwt, key::KeyPair};
+///
+/// let pair = KeyPair::demo();
+/// let token = jwt::sign_rs256(&pair.private_key, r#"{"sub":"rrsa"}"#).unwrap();
+/// assert_eq!(jwt::verify_rs256(&pair.public_key, &token).unwrap(), r#"{"sub":"rrsa"}"#);
+/// ```
+///
+/// # Errors
+/// - If `claims` is not a JSON object.
+/// - If `key` is not a Private Key large enough for RS256.
+pub fn sign_rs256(key: &Key, claims: &str) -> RsaResult<String> {
+    let claims = claims.trim();
+    if !matches!(serde_json::from_str(claims), Ok(Value::Object(_))) {
+        return Err(RsaError::ImproperlyFormattedStr(
+            "because the claims are not a JSON object".into(),
+        ));
+    }
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(HEADER),
+        URL_SAFE_NO_PAD.encode(claims)
+    );
+    let digest = SCHEME.hash().digest(signing_input.as_bytes());
+    let signature = key.sign(&digest, SCHEME)?;
+    Ok(format!(
+        "{signing_input}.{}",
+        URL_SAFE_NO_PAD.encode(signature.as_bytes())
+    ))
+}
+
+/// Verifies an RS256 JWT using a Public Key, rejecting it if it has expired.
+///
+/// # Returns
+/// The claims of the token, as the JSON encoded in it.
+///
+/// # Errors
+/// - If `token` is not a well formed RS256 JWT.
+/// - If the signature is not valid, or the `exp` claim is in the past.
+/// - If `key` is not a Public Key.
+pub fn verify_rs256(key: &Key, token: &str) -> RsaResult<String> {
+    let token = token.trim();
+    let (signing_input, encoded_signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| improperly_formatted("does not have three parts"))?;
+    let Some((encoded_header, encoded_claims)) = signing_input.split_once('.') else {
+        return Err(improperly_formatted("does not have three parts"));
+    };
+
+    let (_, header) = decode_json_object(encoded_header)?;
+    if header.get("alg").and_then(Value::as_str) != Some("RS256") {
+        return Err(improperly_formatted("is not signed with RS256"));
+    }
+    let (claims_json, claims) = decode_json_object(encoded_claims)?;
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(encoded_signature)
+        .map_err(|_| improperly_formatted("signature is not valid base64url"))?;
+    let digest = SCHEME.hash().digest(signing_input.as_bytes());
+    if !key.verify(&digest, &Signature::from_bytes(signature), SCHEME)? {
+        return Err(RsaError::InvalidSignature);
+    }
+
+    if let Some(expiration) = claims.get("exp").and_then(Value::as_u64) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        if now >= expiration {
+            return Err(RsaError::TokenExpired);
+        }
+    }
+    Ok(claims_json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::{pair_4096, test_pair};
+    use pretty_assertions::assert_eq;
+
+    const CLAIMS: &str = r#"{"sub":"rrsa","name":"toy"}"#;
+
+    #[test]
+    fn test_sign_verify_rs256() {
+        let pair = pair_4096();
+        let token = sign_rs256(&pair.private_key, CLAIMS).unwrap();
+        // deterministic, verified with other JWT implementations
+        assert!(token.starts_with(
+            "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJycnNhIiwibmFtZSI6InRveSJ9."
+        ));
+        assert!(token.ends_with("XY-nfrSr-QFxjQnOL3CFdWDxAL3XS4yS9n0jq9Go"));
+        assert_eq!(verify_rs256(&pair.public_key, &token).unwrap(), CLAIMS);
+
+        let tampered = token.replacen("eyJzdWIiOiJycnNh", "eyJzdWIiOiJyc2Fh", 1);
+        assert!(verify_rs256(&pair.public_key, &tampered).is_err());
+
+        let expired = sign_rs256(&pair.private_key, r#"{"sub":"rrsa","exp":1}"#).unwrap();
+        assert!(verify_rs256(&pair.public_key, &expired).is_err());
+        let valid = sign_rs256(&pair.private_key, r#"{"exp":99999999999}"#).unwrap();
+        assert!(verify_rs256(&pair.public_key, &valid).is_ok());
+    }
+
+    #[test]
+    fn test_sign_verify_rs256_error() {
+        let pair = pair_4096();
+        assert!(sign_rs256(&pair.private_key, "[1, 2]").is_err());
+        assert!(sign_rs256(&pair.public_key, CLAIMS).is_err());
+        assert!(sign_rs256(&test_pair().private_key, CLAIMS).is_err());
+
+        assert!(verify_rs256(&pair.public_key, "not.a-token").is_err());
+        // "alg": "none"
+        assert!(
+            verify_rs256(&pair.public_key, "eyJhbGciOiJub25lIn0.eyJzdWIiOiJycnNhIn0.").is_err()
+        );
+    }
+}
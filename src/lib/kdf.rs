@@ -0,0 +1,153 @@
+//! HKDF-SHA256 key derivation (RFC 5869), and symmetric session keys
+//! derived from a Private Key for protocols built on top of a Key Pair.
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+
+/// Length in bytes of a SHA-256 digest, and of the keys from [`Key::derive_session_key()`].
+pub const SESSION_KEY_LEN: usize = 32;
+/// Block size of SHA-256, used by HMAC.
+const BLOCK_LEN: usize = 64;
+/// Label of the salt, so session keys never collide with other uses of the fingerprint.
+const SALT_PREFIX: &[u8] = b"rrsa session key v1";
+
+/// Symmetric key bound to a Key Pair and a label, wiped on drop.
+pub type SessionKey = Zeroizing<[u8; SESSION_KEY_LEN]>;
+
+/// HMAC-SHA256 of `message`.
+fn hmac_sha256(key: &[u8], message: &[&[u8]]) -> Zeroizing<[u8; SESSION_KEY_LEN]> {
+    let mut block = Zeroizing::new([0u8; BLOCK_LEN]);
+    if key.len() > BLOCK_LEN {
+        block[..SESSION_KEY_LEN].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    for part in message {
+        inner.update(part);
+    }
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    Zeroizing::new(outer.finalize().into())
+}
+
+/// Fills `okm` with key material derived from `ikm`, as HKDF-SHA256 extract and expand.
+///
+/// ```
+/// use rrsa_lib::kdf::hkdf_sha256;
+///
+/// let mut first = [0u8; 16];
+/// let mut second = [0u8; 16];
+/// hkdf_sha256(b"secret", b"salt", b"first", &mut first).unwrap();
+/// hkdf_sha256(b"secret", b"salt", b"second", &mut second).unwrap();
+/// assert_ne!(first, second);
+/// ```
+///
+/// # Errors
+/// If `okm` is longer than `255 * 32` bytes.
+pub fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8], okm: &mut [u8]) -> RsaResult<()> {
+    if okm.len() > 255 * SESSION_KEY_LEN {
+        return Err(RsaError::UnknownError(format!(
+            "HKDF output must be at most {} bytes",
+            255 * SESSION_KEY_LEN
+        )));
+    }
+    let prk = hmac_sha256(salt, &[ikm]);
+
+    let mut previous = Zeroizing::new(Vec::new());
+    for (counter, chunk) in (1u8..).zip(okm.chunks_mut(SESSION_KEY_LEN)) {
+        let block = hmac_sha256(prk.as_slice(), &[previous.as_slice(), info, &[counter]]);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        *previous = block.to_vec();
+    }
+    Ok(())
+}
+
+impl Key {
+    /// Derives a [`SessionKey`] bound to this Key Pair and `label`.
+    ///
+    /// The private exponent is the input key material and the [`Key::fingerprint()`]
+    /// is part of the salt, so only the owner of the Private Key can derive it.
+    /// Different labels give independent keys.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let private_key = &KeyPair::demo().private_key;
+    /// let backups = private_key.derive_session_key("backups").unwrap();
+    /// assert_eq!(backups, private_key.derive_session_key("backups").unwrap());
+    /// assert_ne!(backups, private_key.derive_session_key("logs").unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// If `self` is not a [`crate::key::KeyVariant::PrivateKey`].
+    pub fn derive_session_key(&self, label: &str) -> RsaResult<SessionKey> {
+        if !self.is_private() {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        let ikm = Zeroizing::new(self.exponent.to_bytes_be());
+        let salt = [SALT_PREFIX, &self.fingerprint()].concat();
+        let mut key = Zeroizing::new([0u8; SESSION_KEY_LEN]);
+        hkdf_sha256(&ikm, &salt, label.as_bytes(), key.as_mut_slice())?;
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_hkdf_sha256() {
+        // RFC 5869, test cases 1 and 3
+        let mut okm = [0u8; 42];
+        hkdf_sha256(
+            &[0x0b; 22],
+            &from_hex("000102030405060708090a0b0c"),
+            &from_hex("f0f1f2f3f4f5f6f7f8f9"),
+            &mut okm,
+        )
+        .unwrap();
+        assert_eq!(
+            okm.to_vec(),
+            from_hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+        hkdf_sha256(&[0x0b; 22], &[], &[], &mut okm).unwrap();
+        assert_eq!(
+            okm.to_vec(),
+            from_hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8")
+        );
+
+        assert!(hkdf_sha256(b"ikm", b"salt", b"info", &mut vec![0; 255 * 32 + 1]).is_err());
+    }
+
+    #[test]
+    fn test_derive_session_key() {
+        let pair = test_pair();
+        let key = pair.private_key.derive_session_key("label").unwrap();
+        assert_eq!(key, pair.private_key.derive_session_key("label").unwrap());
+        assert_ne!(key, pair.private_key.derive_session_key("other").unwrap());
+        assert_ne!(
+            key,
+            crate::key::KeyPair::demo()
+                .private_key
+                .derive_session_key("label")
+                .unwrap()
+        );
+        assert!(pair.public_key.derive_session_key("label").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Post-generation audit of a [`KeyPair`] against a subset of the FIPS 186-4
+//! criteria for RSA key pairs (section 5.1 and appendix B.3.1).
+//!
+//! The generator of this crate is meant for learning, so it does not enforce any of them,
+//! the audit only shows what a key pair generated by a standard compliant tool would satisfy.
+
+use num_bigint::BigUint;
+use num_traits::One;
+use std::fmt;
+
+use super::KeyPair;
+use crate::error::{RsaError, RsaResult};
+use crate::math::{euclides_extended, factor_modulus};
+
+/// Result of a single criterion of the audit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditCheck {
+    /// Short name of the criterion, e.g. `p - q distance`.
+    pub name: &'static str,
+    /// What FIPS 186-4 requires.
+    pub requirement: String,
+    pub passed: bool,
+}
+
+/// Every [`AuditCheck`] of a [`KeyPair::audit()`], displayed as one line per check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    pub checks: Vec<AuditCheck>,
+}
+
+impl AuditReport {
+    /// Returns `true` if every check passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Returns the checks that did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &AuditCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let result = if check.passed { "PASS" } else { "FAIL" };
+            writeln!(f, "[{result}] {}: {}", check.name, check.requirement)?;
+        }
+        let passed = self.checks.len() - self.failures().count();
+        write!(f, "{passed}/{} checks passed", self.checks.len())
+    }
+}
+
+impl KeyPair {
+    /// Bit lengths of the modulus allowed for generated key pairs (appendix B.3.1).
+    const FIPS_MODULUS_BITS: [u64; 2] = [2048, 3072];
+    /// Minimum `|P - Q|` is `2^(nlen/2 - FIPS_PRIME_DISTANCE_SHIFT)`.
+    const FIPS_PRIME_DISTANCE_SHIFT: u64 = 100;
+    /// The public exponent must be in `(2^16, 2^256)`.
+    const FIPS_EXPONENT_BITS: std::ops::Range<u64> = 17..257;
+
+    /// Audits this pair against a subset of the FIPS 186-4 criteria:
+    /// - The modulus has 2048 or 3072 bits.
+    /// - The public exponent is odd and in `(2^16, 2^256)`.
+    /// - Both primes are in `[sqrt(2) * 2^(nlen/2 - 1), 2^(nlen/2) - 1]`.
+    /// - The primes are more than `2^(nlen/2 - 100)` apart.
+    /// - The private exponent is in `(2^(nlen/2), LCM(P-1, Q-1))`.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let report = KeyPair::demo().audit().unwrap();
+    /// // 1024 bit keys are too small for FIPS 186-4
+    /// assert!(!report.passed());
+    /// println!("{report}");
+    /// ```
+    ///
+    /// # Errors
+    /// - If the Keys are not a Public and a Private Key.
+    /// - If the prime factors can not be recovered, which means the pair is not valid.
+    pub fn audit(&self) -> RsaResult<AuditReport> {
+        if !self.public_key.is_public() || !self.private_key.is_private() {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        let modulus = &self.public_key.modulus;
+        let public_exponent = &self.public_key.exponent;
+        let private_exponent = &self.private_key.exponent;
+        let (p, q) = match &self.private_key.crt {
+            Some(crt) => (crt.p.clone(), crt.q.clone()),
+            None => factor_modulus(modulus, public_exponent, private_exponent)
+                .ok_or(RsaError::MissingKeyFactors)?,
+        };
+
+        let nlen = modulus.bits();
+        let half = nlen / 2;
+        let one = BigUint::one();
+        let distance = if p > q { &p - &q } else { &q - &p };
+        let p_minus_one = &p - 1u8;
+        let q_minus_one = &q - 1u8;
+        let (gcd, _, _) = euclides_extended(&p_minus_one, &q_minus_one);
+        let lcm = gcd
+            .to_biguint()
+            .map_or_else(BigUint::default, |gcd| &p_minus_one * &q_minus_one / gcd);
+
+        let checks = vec![
+            AuditCheck {
+                name: "modulus size",
+                requirement: format!("nlen is 2048 or 3072 bits, got {nlen}"),
+                passed: Self::FIPS_MODULUS_BITS.contains(&nlen),
+            },
+            AuditCheck {
+                name: "public exponent",
+                requirement: format!("e is odd and 2^16 < e < 2^256, got {public_exponent}"),
+                passed: public_exponent.bit(0)
+                    && Self::FIPS_EXPONENT_BITS.contains(&public_exponent.bits()),
+            },
+            AuditCheck {
+                name: "prime bounds",
+                requirement: format!(
+                    "sqrt(2) * 2^{} <= p, q <= 2^{half} - 1",
+                    half.saturating_sub(1)
+                ),
+                passed: within_fips_prime_bounds(&p, half) && within_fips_prime_bounds(&q, half),
+            },
+            AuditCheck {
+                name: "p - q distance",
+                requirement: format!(
+                    "|p - q| > 2^{}",
+                    half.saturating_sub(Self::FIPS_PRIME_DISTANCE_SHIFT)
+                ),
+                passed: distance > &one << half.saturating_sub(Self::FIPS_PRIME_DISTANCE_SHIFT),
+            },
+            AuditCheck {
+                name: "private exponent bounds",
+                requirement: format!("2^{half} < d < LCM(p - 1, q - 1)"),
+                passed: private_exponent > &(&one << half) && private_exponent < &lcm,
+            },
+        ];
+        Ok(AuditReport { checks })
+    }
+}
+
+/// Returns `true` if `sqrt(2) * 2^(half - 1) <= prime <= 2^half - 1`.
+///
+/// Squaring both sides, the lower bound is `prime^2 >= 2^(2*half - 1)`.
+fn within_fips_prime_bounds(prime: &BigUint, half: u64) -> bool {
+    half > 0 && prime.bits() <= half && prime * prime >= BigUint::one() << (2 * half - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, Key};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_audit() {
+        let pair = test_pair();
+        let report = pair.audit().unwrap();
+        assert_eq!(report.checks.len(), 5);
+        assert!(!report.passed());
+        assert!(report.failures().any(|check| check.name == "modulus size"));
+        assert!(report.to_string().contains("[FAIL] modulus size"));
+
+        // primes recovered from the modulus give the same report
+        let reparsed = KeyPair {
+            public_key: Key::from_str(&pair.public_key.to_string()).unwrap(),
+            private_key: Key::from_str(&pair.private_key.to_string()).unwrap(),
+        };
+        assert_eq!(reparsed.audit().unwrap(), report);
+
+        let swapped = KeyPair {
+            public_key: Key::from_str(&pair.private_key.to_string()).unwrap(),
+            private_key: Key::from_str(&pair.public_key.to_string()).unwrap(),
+        };
+        assert!(matches!(swapped.audit(), Err(RsaError::WrongKeyVariant)));
+
+        assert!(within_fips_prime_bounds(&BigUint::from(0xF1u8), 8));
+        // 0xB5 < sqrt(2) * 2^7 < 0xB6
+        assert!(!within_fips_prime_bounds(&BigUint::from(0xB5u8), 8));
+        assert!(within_fips_prime_bounds(&BigUint::from(0xB6u8), 8));
+        assert!(!within_fips_prime_bounds(&BigUint::from(0x101u16), 8));
+    }
+}
@@ -0,0 +1,196 @@
+use num_bigint::BigUint;
+use std::fmt;
+
+use super::{Key, KeyGenOptions, KeyPair, KeySize};
+use crate::error::RsaResult;
+use crate::rng::{SecureRandom, ThreadSource};
+
+/// Public exponent of a [`KeyPair`] built by a [`KeyPairBuilder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Exponent {
+    /// The classic 65537, which Public Key files leave implicit.
+    #[default]
+    Default,
+    /// A fixed odd exponent, at least 3, recorded in the Public Key file.
+    Fixed(u32),
+    /// A random prime exponent, recorded in the Public Key file.
+    Random,
+}
+
+/// Callback receiving every step of the generation, see [`KeyPairBuilder::progress()`].
+type ProgressFn<'p> = Box<dyn FnMut(&str) + 'p>;
+
+/// Fluent configuration of [`KeyPair`] generation, returned by [`KeyPair::builder()`].
+///
+/// ```
+/// use rrsa_lib::key::{Exponent, KeyPair, KeySize};
+///
+/// let mut steps = 0;
+/// let pair = KeyPair::builder()
+///     .size(KeySize::custom(256).unwrap())
+///     .exponent(Exponent::Fixed(17))
+///     .progress(|_step: &str| steps += 1)
+///     .build()
+///     .unwrap();
+/// assert!(pair.is_valid());
+/// assert!(steps > 0);
+/// ```
+#[must_use]
+pub struct KeyPairBuilder<'p> {
+    size: KeySize,
+    exponent: Exponent,
+    print_results: bool,
+    progress: Option<ProgressFn<'p>>,
+}
+
+impl fmt::Debug for KeyPairBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPairBuilder")
+            .field("size", &self.size)
+            .field("exponent", &self.exponent)
+            .field("print_results", &self.print_results)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl<'p> KeyPairBuilder<'p> {
+    /// Size of the modulus, 4096 bits unless set.
+    pub fn size(mut self, size: KeySize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Public exponent, [`Exponent::Default`] unless set.
+    pub fn exponent(mut self, exponent: Exponent) -> Self {
+        self.exponent = exponent;
+        self
+    }
+
+    /// Logs the internal values of the generation as `tracing` INFO events, `false` unless set.
+    pub fn print_results(mut self, print_results: bool) -> Self {
+        self.print_results = print_results;
+        self
+    }
+
+    /// Calls `progress` with every step of the generation, as human readable text.
+    pub fn progress(mut self, progress: impl FnMut(&str) + 'p) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Logs every line of the steps of the generation as a `tracing` INFO event,
+    /// see [`KeyPairBuilder::progress()`].
+    pub(super) fn print_progress(self, print_progress: bool) -> Self {
+        if print_progress {
+            let mut line = String::new();
+            self.progress(move |step: &str| log_step(&mut line, step))
+        } else {
+            self
+        }
+    }
+
+    /// Generates the [`KeyPair`], see [`KeyPair::generate()`] for how it works.
+    ///
+    /// # Errors
+    /// - If a [`Exponent::Fixed`] exponent is invalid, see [`KeyGenOptions::validate()`].
+    /// - [`crate::error::RsaError::GenerationFailed`] if the generated pair is not valid.
+    pub fn build(self) -> RsaResult<KeyPair> {
+        self.build_with_rng(&mut ThreadSource)
+    }
+
+    /// Same as [`KeyPairBuilder::build()`], but drawing every prime from `rng`,
+    /// see [`KeyPair::generate_with_rng()`].
+    ///
+    /// # Errors
+    /// Same as [`KeyPairBuilder::build()`].
+    pub fn build_with_rng<R: SecureRandom + ?Sized>(mut self, rng: &mut R) -> RsaResult<KeyPair> {
+        let default_exponent = match self.exponent {
+            Exponent::Default => Some(BigUint::from(Key::DEFAULT_EXPONENT)),
+            Exponent::Fixed(exponent) => {
+                KeyGenOptions {
+                    key_size_bits: Some(self.size),
+                    default_exponent: exponent,
+                    use_default_exponent: true,
+                }
+                .validate()?;
+                Some(BigUint::from(exponent))
+            }
+            Exponent::Random => None,
+        };
+        let mut progress = self.progress.take();
+        KeyPair::generate_inner(
+            rng,
+            Some(self.size),
+            default_exponent.as_ref(),
+            self.print_results,
+            &mut |step: &str| {
+                if let Some(progress) = progress.as_mut() {
+                    progress(step);
+                }
+            },
+        )
+    }
+}
+
+impl KeyPair {
+    /// Returns a [`KeyPairBuilder`] for a 4096 bits pair with the default exponent.
+    pub fn builder<'p>() -> KeyPairBuilder<'p> {
+        KeyPairBuilder {
+            size: KeySize::default(),
+            exponent: Exponent::default(),
+            print_results: false,
+            progress: None,
+        }
+    }
+}
+
+/// Appends `step` to `line`, logging the line once it is complete,
+/// since steps such as `Generating P...` are only completed by a later `DONE`.
+fn log_step(line: &mut String, step: &str) {
+    line.push_str(step);
+    while let Some(end) = line.find('\n') {
+        let complete: String = line.drain(..=end).collect();
+        let complete = complete.trim_end();
+        if !complete.is_empty() {
+            tracing::info!("{complete}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::IsDefaultExponent;
+
+    #[test]
+    fn test_builder() {
+        let size = KeySize::custom(128).unwrap();
+        let pair = KeyPair::builder().size(size).build().unwrap();
+        assert!(pair.is_valid());
+        assert!(pair.public_key.exponent.is_default_exponent());
+
+        let mut steps = Vec::new();
+        let pair = KeyPair::builder()
+            .size(size)
+            .exponent(Exponent::Fixed(3))
+            .progress(|step: &str| steps.push(step.to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(pair.public_key.exponent, BigUint::from(3u8));
+        assert_eq!(steps[0], "Generating key with 128 bits\n");
+        assert_eq!(steps.last().unwrap(), "\nKey Pair successfully generated\n");
+
+        let pair = KeyPair::builder()
+            .size(size)
+            .exponent(Exponent::Random)
+            .build()
+            .unwrap();
+        assert!(pair.is_valid());
+        assert!(KeyPair::builder()
+            .size(size)
+            .exponent(Exponent::Fixed(4))
+            .build()
+            .is_err());
+    }
+}
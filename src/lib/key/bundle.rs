@@ -0,0 +1,268 @@
+//! Bundles of labeled Public Keys, e.g. the keys of every member of a team,
+//! so a file can be encrypted for all of them at once.
+//!
+//! Example of a bundle file:
+//! ```text
+//! # comments and empty lines are ignored
+//! alice = rrsa 9668f701
+//! bob = rrsa-ndex 5b97 11c68c75
+//! ```
+
+use std::{fmt, fs::read_to_string, path::Path, str::FromStr};
+
+use super::{file::lock::FileLock, Key};
+use crate::atomic::write_atomic;
+use crate::error::{RsaError, RsaResult};
+
+/// Public Keys, each with a unique label, read from and written to a `.pubs` file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct KeyBundle {
+    /// Label and Public Key of every recipient, in the order of the file.
+    pub keys: Vec<(String, Key)>,
+}
+
+impl KeyBundle {
+    /// Extension of bundle files.
+    pub const DEFAULT_EXTENSION: &'static str = "pubs";
+    const LABEL_SPLIT_CHAR: char = '=';
+
+    /// Reads a bundle from the file at `path`.
+    ///
+    /// # Errors
+    /// - If the file is not a valid bundle, see [`KeyBundle::from_str()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn read_from_path(path: &Path) -> RsaResult<Self> {
+        read_to_string(path)?.parse()
+    }
+
+    /// Writes this bundle to the file at `path`, replacing it if it exists.
+    ///
+    /// The file is replaced atomically, see [`write_atomic()`], so an interrupted
+    /// `bundle add` or `bundle remove` never truncates the only copy of a bundle.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn write_to_path(&self, path: &Path) -> RsaResult<()> {
+        write_atomic(path, self.to_string())
+    }
+
+    /// Reads the bundle at `path`, empty if the file does not exist, applies `update` to it
+    /// and writes it back, unless `update` fails.
+    ///
+    /// The file is locked meanwhile, so concurrent `bundle add` or `bundle remove`
+    /// never lose each other's changes.
+    ///
+    /// # Returns
+    /// What `update` returns.
+    ///
+    /// # Errors
+    /// - Same as [`KeyBundle::read_from_path()`].
+    /// - Propagates the error of `update`, the file is left untouched then.
+    pub fn update_path<T>(
+        path: &Path,
+        update: impl FnOnce(&mut KeyBundle) -> RsaResult<T>,
+    ) -> RsaResult<T> {
+        let _lock = FileLock::acquire(path)?;
+        let mut bundle = if path.is_file() {
+            KeyBundle::read_from_path(path)?
+        } else {
+            KeyBundle::default()
+        };
+        let updated = update(&mut bundle)?;
+        bundle.write_to_path(path)?;
+        Ok(updated)
+    }
+
+    /// Adds `key` to this bundle under `label`.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyBundle};
+    /// use std::str::FromStr;
+    ///
+    /// let mut bundle = KeyBundle::default();
+    /// bundle.add("alice", Key::from_str(Key::DEMO_PUBLIC_KEY).unwrap()).unwrap();
+    /// assert!(bundle.add("alice", Key::from_str(Key::DEMO_PUBLIC_KEY).unwrap()).is_err());
+    /// assert!(bundle.add("bob", Key::from_str(Key::DEMO_PRIVATE_KEY).unwrap()).is_err());
+    /// assert_eq!(bundle.labels().collect::<Vec<_>>(), ["alice"]);
+    /// ```
+    ///
+    /// # Errors
+    /// - If `label` is empty, spans more than one line, contains `=` or is already used.
+    /// - If `key` is not a Public Key.
+    pub fn add(&mut self, label: &str, key: Key) -> RsaResult<()> {
+        let label = label.trim();
+        if label.is_empty() || label.contains(['\r', '\n', Self::LABEL_SPLIT_CHAR]) {
+            return Err(RsaError::ImproperlyFormattedStr(format!(
+                "because the label `{label}` is empty, spans more than one line or contains `{}`",
+                Self::LABEL_SPLIT_CHAR
+            )));
+        }
+        if !key.is_public() {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        if self.get(label).is_some() {
+            return Err(RsaError::UnknownError(format!(
+                "the label `{label}` is already in the bundle"
+            )));
+        }
+        self.keys.push((label.to_string(), key));
+        Ok(())
+    }
+
+    /// Removes the Key labeled `label`.
+    ///
+    /// # Returns
+    /// The removed Key.
+    ///
+    /// # Errors
+    /// If no Key has that label.
+    pub fn remove(&mut self, label: &str) -> RsaResult<Key> {
+        let index = self
+            .keys
+            .iter()
+            .position(|(key_label, _)| key_label == label)
+            .ok_or_else(|| {
+                RsaError::UnknownError(format!("the label `{label}` is not in the bundle"))
+            })?;
+        Ok(self.keys.remove(index).1)
+    }
+
+    /// Returns the Key labeled `label`, if any.
+    #[must_use]
+    pub fn get(&self, label: &str) -> Option<&Key> {
+        self.keys
+            .iter()
+            .find_map(|(key_label, key)| (key_label == label).then_some(key))
+    }
+
+    /// Returns the labels of every Key, in order.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|(label, _)| label.as_str())
+    }
+
+    /// Returns every Key, in order, e.g. for [`Key::encode_envelope_for()`].
+    #[must_use]
+    pub fn public_keys(&self) -> Vec<&Key> {
+        self.keys.iter().map(|(_, key)| key).collect()
+    }
+}
+
+impl FromStr for KeyBundle {
+    type Err = RsaError;
+
+    /// Parses `label = public key` lines, the Public Keys being single line,
+    /// as written by [`Key::to_string()`] or in the OpenSSH format.
+    ///
+    /// # Errors
+    /// - If a line is not a `label = public key` line.
+    /// - If a label is invalid or repeated, see [`KeyBundle::add()`].
+    /// - If a Key is invalid or not a Public Key.
+    fn from_str(s: &str) -> RsaResult<Self> {
+        let mut bundle = Self::default();
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (label, key) = line.split_once(Self::LABEL_SPLIT_CHAR).ok_or_else(|| {
+                RsaError::ImproperlyFormattedStr(format!("because `{line}` has no label"))
+            })?;
+            bundle.add(label, Key::from_str(key.trim())?)?;
+        }
+        Ok(bundle)
+    }
+}
+
+impl fmt::Display for KeyBundle {
+    /// Formats the given [`KeyBundle`] as the file content of it,
+    /// the label taking the place of the [`crate::key::KeyMetadata`] of each Key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, key) in &self.keys {
+            writeln!(
+                f,
+                "{label} {} {}",
+                Self::LABEL_SPLIT_CHAR,
+                key.to_string().lines().next().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, KeyPair};
+
+    fn owned(key: &Key) -> Key {
+        Key::from_str(&key.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_key_bundle() {
+        let mut bundle = KeyBundle::default();
+        bundle
+            .add(" alice ", owned(&test_pair().public_key))
+            .unwrap();
+        bundle
+            .add("bob", owned(&KeyPair::demo().public_key))
+            .unwrap();
+        let contents = bundle.to_string();
+        assert!(contents.starts_with("alice = rrsa 9668f701\nbob = rrsa"));
+        assert_eq!(
+            KeyBundle::from_str(&format!("# team\n\n{contents}")).unwrap(),
+            bundle
+        );
+        assert_eq!(bundle.get("alice"), Some(&test_pair().public_key));
+        assert_eq!(bundle.public_keys().len(), 2);
+
+        assert_eq!(bundle.remove("alice").unwrap(), test_pair().public_key);
+        assert!(bundle.remove("alice").is_err());
+        assert_eq!(bundle.labels().collect::<Vec<_>>(), ["bob"]);
+
+        for invalid in [
+            "rrsa 9668f701",
+            "alice = not a key",
+            " = rrsa 9668f701",
+            "alice = rrsa 9668f701\nalice = rrsa 9668f701",
+        ] {
+            assert!(KeyBundle::from_str(invalid).is_err(), "{invalid}");
+        }
+        assert!(bundle.add("a=b", owned(&test_pair().public_key)).is_err());
+    }
+
+    #[test]
+    fn test_update_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("team.pubs");
+        KeyBundle::update_path(&path, |bundle| {
+            bundle.add("alice", owned(&test_pair().public_key))
+        })
+        .unwrap();
+        // a failed update leaves the file untouched
+        assert!(KeyBundle::update_path(&path, |bundle| bundle.remove("bob")).is_err());
+        assert_eq!(
+            KeyBundle::read_from_path(&path)
+                .unwrap()
+                .labels()
+                .collect::<Vec<_>>(),
+            ["alice"]
+        );
+
+        // concurrent updates are serialized, none is lost
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    KeyBundle::update_path(&path, |bundle| {
+                        bundle.add(&format!("member{i}"), owned(&KeyPair::demo().public_key))
+                    })
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap().unwrap();
+        }
+        assert_eq!(KeyBundle::read_from_path(&path).unwrap().keys.len(), 9);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+use num_bigint::BigUint;
+
+use super::{Key, KeyVariant};
+use crate::error::{RsaError, RsaResult};
+use crate::math::{factor_modulus, mod_inverse, wipe};
+
+/// Every component of a Private Key that formats like PKCS#1 or JWK require,
+/// and that speed up private operations through the Chinese Remainder Theorem.
+#[derive(Debug, Clone)]
+pub(crate) struct CrtComponents {
+    pub(crate) public_exponent: BigUint,
+    /// Largest prime factor of the modulus.
+    pub(crate) p: BigUint,
+    pub(crate) q: BigUint,
+    /// `D mod (P-1)`
+    pub(crate) exponent_p: BigUint,
+    /// `D mod (Q-1)`
+    pub(crate) exponent_q: BigUint,
+    /// `Q^-1 mod P`
+    pub(crate) coefficient: BigUint,
+}
+
+impl Drop for CrtComponents {
+    fn drop(&mut self) {
+        for secret in [
+            &mut self.p,
+            &mut self.q,
+            &mut self.exponent_p,
+            &mut self.exponent_q,
+            &mut self.coefficient,
+        ] {
+            wipe(secret);
+        }
+    }
+}
+
+impl CrtComponents {
+    /// Derives the components from the prime factors of the modulus,
+    /// in any order, and the exponents of the pair.
+    ///
+    /// Returns [`None`] if the factors are equal.
+    pub(crate) fn from_factors(
+        public_exponent: &BigUint,
+        private_exponent: &BigUint,
+        p: &BigUint,
+        q: &BigUint,
+    ) -> Option<Self> {
+        let (p, q) = if p > q { (p, q) } else { (q, p) };
+        let coefficient = mod_inverse(q, p)?;
+        Some(CrtComponents {
+            public_exponent: public_exponent.clone(),
+            p: p.clone(),
+            q: q.clone(),
+            exponent_p: private_exponent % (p - 1u8),
+            exponent_q: private_exponent % (q - 1u8),
+            coefficient,
+        })
+    }
+
+    /// Checks the components stored in a format 2 Private Key file against `key`,
+    /// deriving the public exponent from them.
+    ///
+    /// # Errors
+    /// - If `p` and `q` do not multiply to the modulus of `key`.
+    /// - If any of the other components does not match `p`, `q` and the exponent of `key`.
+    pub(crate) fn from_stored(
+        key: &Key,
+        p: &BigUint,
+        q: &BigUint,
+        exponent_p: &BigUint,
+        exponent_q: &BigUint,
+        coefficient: &BigUint,
+    ) -> RsaResult<Self> {
+        if p * q != key.modulus {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the stored primes do not multiply to the modulus".into(),
+            ));
+        }
+        let inconsistent = || {
+            RsaError::ImproperlyFormattedStr(
+                "because the stored CRT components do not match the key".into(),
+            )
+        };
+        let public_exponent =
+            mod_inverse(&key.exponent, &((p - 1u8) * (q - 1u8))).ok_or_else(inconsistent)?;
+        let crt = CrtComponents::from_factors(&public_exponent, &key.exponent, p, q)
+            .ok_or_else(inconsistent)?;
+        if crt.p != *p
+            || crt.exponent_p != *exponent_p
+            || crt.exponent_q != *exponent_q
+            || crt.coefficient != *coefficient
+        {
+            return Err(inconsistent());
+        }
+        Ok(crt)
+    }
+
+    /// Calculates `value^D mod P*Q` as two half size exponentiations (Garner's formula).
+    fn pow(&self, value: &BigUint) -> BigUint {
+        let m_p = value.modpow(&self.exponent_p, &self.p);
+        let m_q = value.modpow(&self.exponent_q, &self.q);
+        let h = (&self.coefficient * (m_p + &self.p - (&m_q % &self.p))) % &self.p;
+        m_q + h * &self.q
+    }
+}
+
+impl Key {
+    /// Recovers the [`CrtComponents`] of this Private Key.
+    ///
+    /// If they were not kept since generation, the public exponent
+    /// is assumed to be the default one to factor the modulus.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If the components are not kept and the public exponent of the pair is not the default one.
+    pub(crate) fn crt_components(&self) -> RsaResult<CrtComponents> {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        if let Some(crt) = &self.crt {
+            return Ok(crt.clone());
+        }
+        let public_exponent = BigUint::from(Key::DEFAULT_EXPONENT);
+        let (p, q) = factor_modulus(&self.modulus, &public_exponent, &self.exponent)
+            .ok_or(RsaError::MissingKeyFactors)?;
+        CrtComponents::from_factors(&public_exponent, &self.exponent, &p, &q)
+            .ok_or(RsaError::MissingKeyFactors)
+    }
+
+    /// Returns `true` if this Private Key carries the CRT components
+    /// that speed up decoding and signing.
+    #[must_use]
+    pub fn has_crt(&self) -> bool {
+        self.crt.is_some()
+    }
+
+    /// Recovers and keeps the CRT components of this Private Key,
+    /// e.g. after reading it from a file that only stores `N` and `D`.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::str::FromStr;
+    ///
+    /// let mut key = Key::from_str(&KeyPair::demo_tiny().private_key.to_string()).unwrap();
+    /// assert!(!key.has_crt());
+    /// key.precompute_crt().unwrap();
+    /// assert!(key.has_crt());
+    /// ```
+    ///
+    /// # Errors
+    /// Same as recovering them for PKCS#1 or JWK:
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If the public exponent of the pair is not the default one.
+    pub fn precompute_crt(&mut self) -> RsaResult<()> {
+        self.crt = Some(self.crt_components()?);
+        Ok(())
+    }
+
+    /// Calculates `value^exponent mod N`, using the CRT components when present.
+    pub(crate) fn apply_exponent(&self, value: &BigUint) -> BigUint {
+        match &self.crt {
+            Some(crt) => crt.pow(value),
+            None => value.modpow(&self.exponent, &self.modulus),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::{pair_4096, test_pair};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_apply_exponent_crt() {
+        let pair = pair_4096();
+        let mut private_key = Key::from_str(&pair.private_key.to_string()).unwrap();
+        assert!(!private_key.has_crt());
+        private_key.precompute_crt().unwrap();
+        assert!(private_key.has_crt());
+        assert_eq!(private_key, pair.private_key);
+
+        let value = BigUint::from(0x147B_7F71_9668_F701_u64).pow(40) % &pair.public_key.modulus;
+        assert_eq!(
+            private_key.apply_exponent(&value),
+            pair.private_key.apply_exponent(&value)
+        );
+
+        let crt = test_pair().private_key.crt_components().unwrap();
+        let value = BigUint::from(0x1234_5678_u32);
+        assert_eq!(
+            crt.pow(&value),
+            value.modpow(
+                &test_pair().private_key.exponent,
+                &test_pair().private_key.modulus
+            )
+        );
+
+        assert!(matches!(
+            Key::from_str(&test_pair().public_key.to_string())
+                .unwrap()
+                .precompute_crt(),
+            Err(RsaError::WrongKeyVariant)
+        ));
+    }
+}
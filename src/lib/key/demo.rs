@@ -0,0 +1,125 @@
+//! Fixed demo Key Pairs, used by the documentation examples.
+//!
+//! Their Private Keys are published with the source code, so they protect nothing.
+
+use num_bigint::BigUint;
+use std::{str::FromStr, sync::OnceLock};
+
+use super::{Key, KeyPair, KeyVariant};
+
+static DEMO_TINY: OnceLock<KeyPair> = OnceLock::new();
+static DEMO: OnceLock<KeyPair> = OnceLock::new();
+
+impl Key {
+    /// Public Key of [`KeyPair::demo()`], as written to a file.
+    pub const DEMO_PUBLIC_KEY: &'static str = "rrsa d35d52addb74a65de5aa18d83a1aec1da077c0bd892da34082c4101854e4af447127f402e2adfd457355af064c98da426267b805c25fc23e34c58458d55e36188f72a2640fae3708cdebaed2c4166a067c766c983c79ac509424965aa22e6a258ab70d76431897bbfc0a5be661c39131864828795fad906a96677d9c7e023b31\n";
+    /// Private Key of [`KeyPair::demo()`], as written to a file.
+    pub const DEMO_PRIVATE_KEY: &'static str = r"-----BEGIN RSA-RUST PRIVATE KEY-----
+Format: 2
+Producer: rrsa 0.2.0
+d35d52addb74a65de5aa18d83a1aec1da077c0bd892da34082c4101854e4af447127f402e2adfd457355af064c98da426267b805c25fc23e34c58458d55e36188f72a2640fae3708cdebaed2c4166a067c766c983c79ac509424965aa22e6a258ab70d76431897bbfc0a5be661c39131864828795fad906a96677d9c7e023b31
+24951f6d45f17fa5facd20f5b3c0db231996007f0d921f9257cfb57570a4100dc983ffc0cc190f2009214864cfb718f7aa6e87343ffb30f4714fca6351b2b7abe1d52d25ea72448a6b6ea55ccbb9854ee55be59404021f932db8736a40cf9586f9d338d0a5898522d6e72f34f55c75bf724630d87c144c379aaa1d9fa9c310dd
+f9f4677fe90869b4ab2e3d76f79445f53752281f57bba574da16d48d9e71bf62deb418683a234536561511d896cf1f47a19f31b4c1b9abb40331abdec5ff754f
+d879fccd4671770264360e5c6dc778d9883e72ebd2fa3482305e6498fcecc10035a7c39bf0d9d17121d6ba777e3bc2b0ae2a02bca6df538d3b38572c8618277f
+56b8b75827afe58ceb7f658feb22248b89a0158f9a4ede7f0653b977512bc1ed39bb5e647b1755a21e87bce35a9f8e08ba489364557a67da52e95eea31e409fd
+1715e344cce75ac6b1832642eb1e23a0272c69bb0673d257b3eacd15979b73f59ec3365411fa58a094f83e4871f8d3895df972f3456479978d223401b6872ae7
+e3ab52ba9e153a173bec9b17bbbe8b56cf7c01b4de0b3f51f3ee64fdb4eb8b1e2d1b0cca69c841da3783f1ac7d19ee9933b78d48f1269708cb8cf972ccad93c8
+-----END RSA-RUST PRIVATE KEY-----
+";
+
+    /// Textbook RSA, raises `value` to the exponent of this Key modulo its modulus.
+    ///
+    /// Nothing is padded and `value` is expected to be smaller than the modulus,
+    /// so it is only useful to show the math with tiny keys.
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo_tiny();
+    /// let message = BigUint::from(42u8);
+    /// let encrypted = pair.public_key.apply_raw(&message);
+    /// assert_ne!(encrypted, message);
+    /// assert_eq!(pair.private_key.apply_raw(&encrypted), message);
+    /// ```
+    #[must_use]
+    pub fn apply_raw(&self, value: &BigUint) -> BigUint {
+        self.apply_exponent(value)
+    }
+}
+
+impl KeyPair {
+    /// A 32 bits [`KeyPair`] with the default exponent, small enough to follow by hand.
+    ///
+    /// It only fits [`crate::encoding::padding::PaddingScheme::None`] and [`Key::apply_raw()`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo_tiny();
+    /// assert!(pair.is_valid());
+    /// assert_eq!(pair.public_key.to_string(), "rrsa 9668f701\n");
+    /// ```
+    ///
+    /// # Panics
+    /// Never, the demo keys are checked by the tests.
+    #[must_use]
+    pub fn demo_tiny() -> &'static KeyPair {
+        DEMO_TINY.get_or_init(|| KeyPair {
+            public_key: Key::try_new(
+                BigUint::from(Key::DEFAULT_EXPONENT),
+                BigUint::from(0x9668_F701u64),
+                KeyVariant::PublicKey,
+            )
+            .expect("demo tiny public key is valid"),
+            private_key: Key::try_new(
+                BigUint::from(0x147B_7F71u32),
+                BigUint::from(0x9668_F701u64),
+                KeyVariant::PrivateKey,
+            )
+            .expect("demo tiny private key is valid"),
+        })
+    }
+
+    /// A 1024 bits [`KeyPair`] with the default exponent and CRT components,
+    /// large enough for every padding and signature scheme.
+    ///
+    /// Parsed from [`Key::DEMO_PUBLIC_KEY`] and [`Key::DEMO_PRIVATE_KEY`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo();
+    /// assert!(pair.is_valid());
+    /// assert!(pair.private_key.has_crt());
+    /// ```
+    ///
+    /// # Panics
+    /// Never, the demo keys are checked by the tests.
+    #[must_use]
+    pub fn demo() -> &'static KeyPair {
+        DEMO.get_or_init(|| KeyPair {
+            public_key: Key::from_str(Key::DEMO_PUBLIC_KEY).expect("demo public key is valid"),
+            private_key: Key::from_str(Key::DEMO_PRIVATE_KEY).expect("demo private key is valid"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_pairs() {
+        for pair in [KeyPair::demo_tiny(), KeyPair::demo()] {
+            assert!(pair.is_valid());
+        }
+        let pair = KeyPair::demo();
+        assert_eq!(pair.public_key.modulus.bits(), 1024);
+        assert_eq!(
+            pair.private_key.to_string(),
+            Key::DEMO_PRIVATE_KEY.replace("rrsa 0.2.0", &crate::version::producer())
+        );
+        assert_eq!(pair.public_key.to_string(), Key::DEMO_PUBLIC_KEY);
+    }
+}
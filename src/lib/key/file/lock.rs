@@ -0,0 +1,160 @@
+use fs4::fs_std::FileExt;
+use same_file::Handle;
+use std::{
+    fs::{remove_file, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use crate::error::RsaResult;
+use crate::key::Key;
+
+/// Exclusive advisory lock over a keys directory, released when dropped.
+///
+/// Only cooperating `rrsa` processes are stopped from mutating the directory
+/// at the same time, other programs can still freely write to it.
+#[derive(Debug)]
+pub(crate) struct DirLock {
+    file: File,
+}
+
+impl DirLock {
+    /// Name of the lock file created inside the locked directory.
+    pub(crate) const FILE_NAME: &'static str = ".rrsa.lock";
+
+    /// Blocks until the exclusive lock over `dir` is acquired.
+    ///
+    /// Locks are not reentrant, the same directory must not be locked twice
+    /// by the same process without dropping the first [`DirLock`].
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub(crate) fn acquire(dir: &Path) -> RsaResult<Self> {
+        let file = open_lock_file(&dir.join(DirLock::FILE_NAME))?;
+        FileExt::lock_exclusive(&file)?;
+        Ok(Self { file })
+    }
+
+    /// Same as [`DirLock::acquire()`], but only if `dir` is a keys directory managed by `rrsa`,
+    /// [`Key::default_dir()`] or where the default keys are stored, so no lock file is left
+    /// in any other directory a Key is written to.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub(crate) fn acquire_if_managed(dir: &Path) -> RsaResult<Option<Self>> {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let Ok(dir) = dir.canonicalize() else {
+            return Ok(None);
+        };
+        let is_managed = [Key::default_dir(), Key::storage_dir()]
+            .iter()
+            .any(|managed| managed.canonicalize().is_ok_and(|managed| managed == dir));
+        is_managed.then(|| DirLock::acquire(&dir)).transpose()
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Exclusive advisory lock over a single file, e.g. a [`crate::key::KeyBundle`],
+/// held through a `<name>.lock` sibling which is removed when dropped.
+#[derive(Debug)]
+pub(crate) struct FileLock {
+    handle: Handle,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Extension appended to the name of the locked file, for its lock file.
+    pub(crate) const EXTENSION: &'static str = "lock";
+
+    /// Blocks until the exclusive lock over `path` is acquired, `path` itself may not exist.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub(crate) fn acquire(path: &Path) -> RsaResult<Self> {
+        let mut lock_name = path.file_name().unwrap_or_default().to_os_string();
+        lock_name.push(".");
+        lock_name.push(FileLock::EXTENSION);
+        let lock_path = path.with_file_name(lock_name);
+        loop {
+            let file = open_lock_file(&lock_path)?;
+            FileExt::lock_exclusive(&file)?;
+            let handle = Handle::from_file(file)?;
+            // the previous holder removes the lock file, after this process opened it
+            if Handle::from_path(&lock_path).is_ok_and(|current| current == handle) {
+                return Ok(Self {
+                    handle,
+                    path: lock_path,
+                });
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // removed while still locked, so waiters notice it is stale and open a new one
+        let _ = remove_file(&self.path);
+        let _ = FileExt::unlock(self.handle.as_file());
+    }
+}
+
+/// Opens the lock file at `path`, creating it if missing, without truncating it.
+fn open_lock_file(path: &Path) -> RsaResult<File> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::FixtureBuilder;
+
+    #[test]
+    fn test_dir_lock() {
+        let fixture = FixtureBuilder::new().build().unwrap();
+        let dir = fixture.path();
+
+        let lock = DirLock::acquire(dir).unwrap();
+        let other = File::open(dir.join(DirLock::FILE_NAME)).unwrap();
+        assert!(!FileExt::try_lock_exclusive(&other).unwrap());
+
+        drop(lock);
+        assert!(FileExt::try_lock_exclusive(&other).unwrap());
+
+        // any other directory is left untouched
+        let unmanaged = FixtureBuilder::new().build().unwrap();
+        assert!(DirLock::acquire_if_managed(unmanaged.path())
+            .unwrap()
+            .is_none());
+        assert!(!unmanaged.join(DirLock::FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_file_lock() {
+        let fixture = FixtureBuilder::new().build().unwrap();
+        let path = fixture.join("team.pubs");
+        let lock_path = fixture.join("team.pubs.lock");
+
+        let lock = FileLock::acquire(&path).unwrap();
+        let other = File::open(&lock_path).unwrap();
+        assert!(!FileExt::try_lock_exclusive(&other).unwrap());
+
+        // a waiting process acquires it once released, on a new lock file
+        let waiter = std::thread::spawn(move || FileLock::acquire(&path).map(drop));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(lock);
+        waiter.join().unwrap().unwrap();
+        assert!(!lock_path.exists());
+    }
+}
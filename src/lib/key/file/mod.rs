@@ -1,18 +1,73 @@
-use std::{fs::create_dir_all, path::PathBuf};
+use std::{
+    fmt,
+    fs::{copy, create_dir_all, read_dir, read_to_string, remove_file, rename},
+    path::PathBuf,
+    str::FromStr,
+};
 
 use clap::crate_name;
 use directories::ProjectDirs;
 
 use super::Key;
+use crate::atomic::write_atomic;
+use crate::config::Config;
+use crate::error::{RsaError, RsaResult};
+use lock::DirLock;
 
+pub(super) mod lock;
 mod reading;
 mod writing;
 
+/// Base directory where the default keys directory is placed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StorageLocation {
+    /// See [`ProjectDirs::config_dir()`].
+    #[default]
+    Config,
+    /// See [`ProjectDirs::data_dir()`].
+    Data,
+}
+
+impl StorageLocation {
+    const CONFIG_STR: &'static str = "config";
+    const DATA_STR: &'static str = "data";
+}
+
+impl FromStr for StorageLocation {
+    type Err = RsaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::CONFIG_STR => Ok(StorageLocation::Config),
+            Self::DATA_STR => Ok(StorageLocation::Data),
+            _ => Err(RsaError::UnknownError(format!(
+                "invalid storage location `{s}`, expected `{}` or `{}`",
+                Self::CONFIG_STR,
+                Self::DATA_STR
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StorageLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StorageLocation::Config => Self::CONFIG_STR,
+            StorageLocation::Data => Self::DATA_STR,
+        })
+    }
+}
+
 impl Key {
     pub(super) const DEFAULT_DIR: &'static str = crate_name!();
     pub const DEFAULT_PUBLIC_KEY_EXTENSION: &'static str = "pub";
     pub const DEFAULT_PUBLIC_KEY_NAME: &'static str = "rrsa_key.pub";
     pub const DEFAULT_PRIVATE_KEY_NAME: &'static str = "rrsa_key";
+    /// Name of the file, inside the config directory, pointing to the keys directory
+    /// when keys were migrated to [`StorageLocation::Data`].
+    pub const STORAGE_POINTER_NAME: &'static str = "keys_location";
+    /// Environment variable overriding [`Key::default_dir()`].
+    pub const HOME_VAR: &'static str = "RRSA_HOME";
 
     /// Returns the default keys directory, or `cwd` if it cannot be retrived.
     ///
@@ -24,24 +79,189 @@ impl Key {
     ///
     /// On Windows this is: `{FOLDERID_RoamingAppData}\rrsa\config`
     ///
+    /// If the keys were migrated with [`Key::migrate_storage()`],
+    /// the directory written to the [`Key::STORAGE_POINTER_NAME`] file is used instead.
+    ///
+    /// If the [`Key::HOME_VAR`] environment variable is set, it takes precedence over both,
+    /// and the keys in it are never migrated, see [`Key::ensure_storage()`].
+    ///
     /// See the documentation of [`ProjectDirs::config_dir()`] for more information.
     #[must_use]
     pub fn default_dir() -> PathBuf {
+        if let Some(home_dir) = Key::home_dir() {
+            return home_dir;
+        }
+        Key::storage_dir()
+    }
+
+    /// Directory of [`Key::HOME_VAR`], if set.
+    fn home_dir() -> Option<PathBuf> {
+        let home_dir =
+            PathBuf::from(std::env::var_os(Key::HOME_VAR).filter(|home| !home.is_empty())?);
+        create_dir_all(&home_dir).is_ok().then_some(home_dir)
+    }
+
+    /// Keys directory of [`Key::current_storage()`], ignoring [`Key::HOME_VAR`].
+    fn storage_dir() -> PathBuf {
+        let config_dir = Key::default_dir_for(StorageLocation::Config);
+        if let Ok(pointer) = read_to_string(config_dir.join(Key::STORAGE_POINTER_NAME)) {
+            let pointed_dir = PathBuf::from(pointer.trim());
+            if !pointer.trim().is_empty() && create_dir_all(&pointed_dir).is_ok() {
+                return pointed_dir;
+            }
+        }
+        config_dir
+    }
+
+    /// Returns where the default keys are stored, [`StorageLocation::Data`]
+    /// once migrated there, which leaves a [`Key::STORAGE_POINTER_NAME`] file.
+    #[must_use]
+    pub fn current_storage() -> StorageLocation {
+        let pointer = Key::default_dir_for(StorageLocation::Config).join(Key::STORAGE_POINTER_NAME);
+        if pointer.is_file() {
+            StorageLocation::Data
+        } else {
+            StorageLocation::Config
+        }
+    }
+
+    /// Migrates the default keys to `location`, e.g. the `storage` of the [`Config`],
+    /// if they are stored elsewhere, see [`Key::migrate_storage()`].
+    ///
+    /// Nothing is migrated while [`Key::HOME_VAR`] is set, since the keys are then
+    /// read from that directory instead of either location.
+    ///
+    /// # Returns
+    /// The new default keys directory, if the keys were migrated.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn ensure_storage(location: StorageLocation) -> RsaResult<Option<PathBuf>> {
+        if Key::home_dir().is_some() || Key::current_storage() == location {
+            return Ok(None);
+        }
+        Key::migrate_storage(location).map(Some)
+    }
+
+    /// Returns the keys directory inside the given [`StorageLocation`],
+    /// or `cwd` if it cannot be retrived.
+    ///
+    /// On Linux the data directory is: `$XDG_DATA_HOME/rrsa/`
+    ///
+    /// See the documentation of [`ProjectDirs::data_dir()`] for more information.
+    #[must_use]
+    pub fn default_dir_for(location: StorageLocation) -> PathBuf {
         if let Some(project_dirs) = ProjectDirs::from("", "", Key::DEFAULT_DIR) {
-            let default_dir = project_dirs.config_dir();
+            let default_dir = match location {
+                StorageLocation::Config => project_dirs.config_dir(),
+                StorageLocation::Data => project_dirs.data_dir(),
+            };
             if create_dir_all(default_dir).is_ok() {
                 return default_dir.to_path_buf();
             }
         }
         PathBuf::new()
     }
+
+    /// Moves every file in the keys directory of [`Key::current_storage()`] to the
+    /// keys directory of `location`, keys, bundles and signatures alike.
+    ///
+    /// The [`Config::FILE_NAME`] file always stays in the config directory.
+    /// The directory of [`Key::HOME_VAR`] is neither read nor written.
+    ///
+    /// When migrating to [`StorageLocation::Data`], a [`Key::STORAGE_POINTER_NAME`] file
+    /// is left in the config directory, so [`Key::default_dir()`] keeps finding the keys.
+    /// A [`Config::key_path`] pointing at the moved directory, as written on first run,
+    /// is pointed at the new one.
+    ///
+    /// Both directories are locked for the duration of the migration.
+    ///
+    /// # Returns
+    /// The new default keys directory.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn migrate_storage(location: StorageLocation) -> RsaResult<PathBuf> {
+        let from_dir = Key::storage_dir();
+        let to_dir = Key::default_dir_for(location);
+
+        let _from_lock = DirLock::acquire(&from_dir)?;
+        let _to_lock = if from_dir == to_dir {
+            None
+        } else {
+            Some(DirLock::acquire(&to_dir)?)
+        };
+
+        if from_dir != to_dir {
+            for entry in read_dir(&from_dir)? {
+                let path = entry?.path();
+                let Some(file_name) = path.file_name() else {
+                    continue;
+                };
+                let is_kept = [
+                    Config::FILE_NAME,
+                    Key::STORAGE_POINTER_NAME,
+                    DirLock::FILE_NAME,
+                ]
+                .iter()
+                .any(|kept| file_name == *kept);
+                if is_kept {
+                    continue;
+                }
+                let destination = to_dir.join(file_name);
+                if rename(&path, &destination).is_err() {
+                    if !path.is_file() {
+                        return Err(RsaError::UnknownError(format!(
+                            "could not move {} to {}",
+                            path.display(),
+                            destination.display()
+                        )));
+                    }
+                    copy(&path, &destination)?;
+                    remove_file(&path)?;
+                }
+            }
+        }
+
+        let pointer = Key::default_dir_for(StorageLocation::Config).join(Key::STORAGE_POINTER_NAME);
+        match location {
+            StorageLocation::Config if pointer.is_file() => remove_file(pointer)?,
+            StorageLocation::Config => {}
+            StorageLocation::Data => write_atomic(&pointer, to_dir.to_string_lossy().as_bytes())?,
+        }
+        let config_path = Config::file_path();
+        if from_dir != to_dir && config_path.is_file() {
+            let mut config = Config::load_from_path(&config_path)?;
+            if config.key_path.as_ref() == Some(&from_dir) {
+                config.key_path = Some(to_dir.clone());
+                config.write_to_path(&config_path)?;
+            }
+        }
+        Ok(to_dir)
+    }
 }
 
 #[cfg(test)]
-pub(crate) mod tests {
-    pub(crate) const PUB_KEY_PATH: &str = "./keys/tests/test_key.pub";
-    pub(crate) const PRIV_KEY_PATH: &str = "./keys/tests/test_key";
-    pub(crate) const KEY_DIR_PATH: &str = "./keys/tests/key/";
-    pub(crate) const PAIR_KEY_PATH: &str = "./keys/tests/test_pair";
-    pub(crate) const PAIR_DIR_PATH: &str = "./keys/tests/pair";
+mod tests {
+    use super::{Key, StorageLocation};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_default_dir_for() {
+        let config_dir = Key::default_dir_for(StorageLocation::Config);
+        let data_dir = Key::default_dir_for(StorageLocation::Data);
+        assert!(config_dir.is_dir());
+        assert!(data_dir.is_dir());
+    }
+
+    #[test]
+    fn test_storage_location_from_str() {
+        for location in [StorageLocation::Config, StorageLocation::Data] {
+            assert_eq!(
+                StorageLocation::from_str(&location.to_string()).unwrap(),
+                location
+            );
+        }
+        assert!(StorageLocation::from_str("cache").is_err());
+    }
 }
@@ -2,7 +2,10 @@ use crate::{
     error::{RsaError, RsaResult},
     key::{Key, KeyPair},
 };
-use std::{fs::read_to_string, path::Path, str::FromStr};
+use std::{
+    fs::{read, read_dir},
+    path::{Path, PathBuf},
+};
 
 impl KeyPair {
     /// Reads a [`KeyPair`] from two files or a directory path.
@@ -61,19 +64,21 @@ impl Key {
     /// [`Key::DEFAULT_PUBLIC_KEY_NAME`] are used,
     /// in this order of priority.
     ///
+    /// The format of the file is detected by its contents, see [`Key::from_format()`].
+    ///
     /// # Errors
     /// Propagates [`std::io::Error`].
     pub fn read_from_path(path: &Path) -> RsaResult<Self> {
         if path.is_dir() {
             if path.join(Key::DEFAULT_PRIVATE_KEY_NAME).is_file() {
-                Key::from_str(&read_to_string(path.join(Key::DEFAULT_PRIVATE_KEY_NAME))?)
+                Key::from_format(&read(path.join(Key::DEFAULT_PRIVATE_KEY_NAME))?, None)
             } else if path.join(Key::DEFAULT_PUBLIC_KEY_NAME).is_file() {
-                Key::from_str(&read_to_string(path.join(Key::DEFAULT_PUBLIC_KEY_NAME))?)
+                Key::from_format(&read(path.join(Key::DEFAULT_PUBLIC_KEY_NAME))?, None)
             } else {
                 Err(RsaError::MissingKeyFromDirError)
             }
         } else {
-            Key::from_str(&read_to_string(path)?)
+            Key::from_format(&read(path)?, None)
         }
     }
 
@@ -89,47 +94,98 @@ impl Key {
     pub fn read_from_default() -> RsaResult<Self> {
         Key::read_from_path(&Key::default_dir())
     }
+
+    /// Reads every Private Key file directly inside `dir`, sorted by path,
+    /// e.g. to find which key can decrypt an anonymous message.
+    ///
+    /// Files that are not Private Keys are skipped.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`] from reading the directory.
+    pub fn read_private_keys_from_dir(dir: &Path) -> RsaResult<Vec<(PathBuf, Self)>> {
+        let mut keys = Vec::new();
+        for entry in read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Ok(key) = Key::read_from_path(&path) {
+                if key.is_private() {
+                    keys.push((path, key));
+                }
+            }
+        }
+        keys.sort_by(|(first, _), (second, _)| first.cmp(second));
+        Ok(keys)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{super::writing::tests::test_write_key_to_file, *};
-    use crate::key::{
-        file::{
-            tests::{KEY_DIR_PATH, PAIR_DIR_PATH, PAIR_KEY_PATH, PRIV_KEY_PATH, PUB_KEY_PATH},
-            writing::tests::{test_write_key_pair_to_default, test_write_key_pair_to_file},
-        },
-        tests::test_pair,
-    };
-    use std::path::PathBuf;
+    use super::*;
+    use crate::fixtures::FixtureBuilder;
+    use crate::key::{file::writing::tests::test_write_key_pair_to_default, tests::test_pair};
 
     #[test]
     fn test_read_key_from_file() {
-        let pub_path = PathBuf::from(PUB_KEY_PATH);
-        let priv_path = PathBuf::from(PRIV_KEY_PATH);
-        let dir_path = PathBuf::from(KEY_DIR_PATH);
-        test_write_key_to_file();
+        let fixture = FixtureBuilder::new()
+            .key_pair("test_key", test_pair())
+            .key_pair(
+                &format!("key/{}", Key::DEFAULT_PRIVATE_KEY_NAME),
+                test_pair(),
+            )
+            .file("test_key.der", test_pair().private_key.to_der().unwrap())
+            .build()
+            .unwrap();
 
-        let key = Key::read_from_path(&pub_path).unwrap();
+        let key = Key::read_from_path(&fixture.join("test_key.pub")).unwrap();
         assert_eq!(key, test_pair().public_key);
 
-        let key = Key::read_from_path(&priv_path).unwrap();
+        let key = Key::read_from_path(&fixture.join("test_key")).unwrap();
         assert_eq!(key, test_pair().private_key);
 
         // reads the private key
-        let key = Key::read_from_path(&dir_path).unwrap();
+        let key = Key::read_from_path(&fixture.join("key")).unwrap();
+        assert_eq!(key, test_pair().private_key);
+
+        // binary DER is detected
+        let key = Key::read_from_path(&fixture.join("test_key.der")).unwrap();
         assert_eq!(key, test_pair().private_key);
     }
 
+    #[test]
+    fn test_read_private_keys_from_dir() {
+        let fixture = FixtureBuilder::new()
+            .key_pair("b_key", test_pair())
+            .key_pair("a_key", test_pair())
+            .key_pair("nested/c_key", test_pair())
+            .file("notes.txt", "not a key")
+            .build()
+            .unwrap();
+
+        let keys = Key::read_private_keys_from_dir(fixture.path()).unwrap();
+        let names: Vec<_> = keys
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a_key", "b_key"]);
+        assert!(keys.iter().all(|(_, key)| *key == test_pair().private_key));
+    }
+
     #[test]
     fn test_read_key_pair_to_file() {
-        let file_path = PathBuf::from(PAIR_KEY_PATH);
-        let dir_path = PathBuf::from(PAIR_DIR_PATH);
-        test_write_key_pair_to_file();
+        let fixture = FixtureBuilder::new()
+            .key_pair("test_pair", test_pair())
+            .key_pair(
+                &format!("pair/{}", Key::DEFAULT_PRIVATE_KEY_NAME),
+                test_pair(),
+            )
+            .build()
+            .unwrap();
 
-        let pair = KeyPair::read_from_path(&file_path).unwrap();
+        let pair = KeyPair::read_from_path(&fixture.join("test_pair")).unwrap();
         assert_eq!(pair, *test_pair());
-        let pair = KeyPair::read_from_path(&dir_path).unwrap();
+        let pair = KeyPair::read_from_path(&fixture.join("pair")).unwrap();
         assert_eq!(pair, *test_pair());
     }
 
@@ -1,39 +1,86 @@
+use super::lock::DirLock;
+use crate::atomic::write_atomic;
 use crate::error::RsaResult;
 use crate::key::{Key, KeyPair, KeyVariant};
+use crate::paths::refuse_overwrite;
 use std::{
     fs::create_dir_all,
     path::{Path, PathBuf},
 };
 
 impl KeyPair {
-    /// Writes this [`KeyPair`] to a file or dir path.
+    /// Writes this [`KeyPair`] to a file or dir path, replacing existing key files.
     ///
     /// If it is a directory, it must have already been created.
     /// The Public Key will have the extension added automatically.
     ///
+    /// Both Keys are written while holding the lock of their directory if `rrsa` manages it,
+    /// so concurrent writers never leave a mismatched pair behind.
+    ///
     /// # Errors
     /// Propagates [`std::io::Error`].
     pub fn write_to_path(&self, path: &Path) -> RsaResult<()> {
-        if path.is_dir() {
-            self.public_key.write_to_path(path)?;
-            self.private_key.write_to_path(path)?;
+        self.write_pair_to_path(path, true)
+    }
+
+    /// Same as [`KeyPair::write_to_path()`], but refuses to replace existing key files.
+    ///
+    /// # Errors
+    /// - [`RsaError::WouldOverwrite`] if either key file exists, nothing is written then.
+    /// - Propagates [`std::io::Error`].
+    pub fn write_new_to_path(&self, path: &Path) -> RsaResult<()> {
+        self.write_pair_to_path(path, false)
+    }
+
+    fn write_pair_to_path(&self, path: &Path, overwrite: bool) -> RsaResult<()> {
+        let pub_path = self.public_key.resolve_write_path(if path.is_dir() {
+            path.to_path_buf()
         } else {
-            self.public_key
-                .write_to_path(&path.with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION))?;
-            self.private_key.write_to_path(path)?;
-        }
+            path.with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION)
+        })?;
+        let priv_path = self.private_key.resolve_write_path(path.to_path_buf())?;
 
-        Ok(())
+        let _lock = DirLock::acquire_if_managed(parent_dir(&priv_path))?;
+        self.write_pair(&pub_path, &priv_path, overwrite)
     }
 
     /// Writes this [`KeyPair`] to the default keys directory,
-    /// or `cwd` if default keys directory cannot be created or accessed.
+    /// or `cwd` if default keys directory cannot be created or accessed,
+    /// replacing existing key files.
     ///
     /// # Errors
     /// Propagates [`std::io::Error`].
     pub fn write_to_default(&self) -> RsaResult<()> {
-        self.public_key.write_to_default()?;
-        self.private_key.write_to_default()?;
+        self.write_pair_to_default(true)
+    }
+
+    /// Same as [`KeyPair::write_to_default()`], but refuses to replace existing key files.
+    ///
+    /// # Errors
+    /// - [`RsaError::WouldOverwrite`] if either key file exists, nothing is written then.
+    /// - Propagates [`std::io::Error`].
+    pub fn write_new_to_default(&self) -> RsaResult<()> {
+        self.write_pair_to_default(false)
+    }
+
+    fn write_pair_to_default(&self, overwrite: bool) -> RsaResult<()> {
+        let default_dir = Key::default_dir();
+        let _lock = DirLock::acquire(&default_dir)?;
+        self.write_pair(
+            &default_dir.join(Key::DEFAULT_PUBLIC_KEY_NAME),
+            &default_dir.join(Key::DEFAULT_PRIVATE_KEY_NAME),
+            overwrite,
+        )
+    }
+
+    /// Writes both Keys, the lock of their directory must be held.
+    fn write_pair(&self, pub_path: &Path, priv_path: &Path, overwrite: bool) -> RsaResult<()> {
+        if !overwrite {
+            refuse_overwrite(pub_path)?;
+            refuse_overwrite(priv_path)?;
+        }
+        self.public_key.write_file(pub_path)?;
+        self.private_key.write_file(priv_path)?;
         Ok(())
     }
 }
@@ -46,26 +93,39 @@ impl Key {
     /// [`Key::DEFAULT_PRIVATE_KEY_NAME`] or
     /// [`Key::DEFAULT_PUBLIC_KEY_NAME`] are used.
     ///
+    /// The file is written while holding the lock of its directory if `rrsa` manages it.
+    ///
     /// # Returns
     /// The final filepath written to.
     ///
     /// # Errors
     /// Propagates [`std::io::Error`].
     pub fn write_to_path(&self, path: &Path) -> RsaResult<PathBuf> {
-        let filepath = if path.is_dir() {
-            if self.variant == KeyVariant::PublicKey {
+        let filepath = self.resolve_write_path(path.to_path_buf())?;
+        let _lock = DirLock::acquire_if_managed(parent_dir(&filepath))?;
+        self.write_file(&filepath)?;
+        Ok(filepath)
+    }
+
+    /// Returns the filepath this [`Key`] is written to for `path`,
+    /// creating its parent directories if needed.
+    fn resolve_write_path(&self, path: PathBuf) -> RsaResult<PathBuf> {
+        if path.is_dir() {
+            Ok(if self.variant == KeyVariant::PublicKey {
                 path.join(Key::DEFAULT_PUBLIC_KEY_NAME)
             } else {
                 path.join(Key::DEFAULT_PRIVATE_KEY_NAME)
-            }
+            })
         } else {
-            let parent = path.parent().unwrap_or(Path::new(""));
-            create_dir_all(parent)?;
-            path.to_path_buf()
-        };
+            create_dir_all(parent_dir(&path))?;
+            Ok(path)
+        }
+    }
 
-        std::fs::write(&filepath, self.to_string())?;
-        Ok(filepath)
+    /// Writes this [`Key`] with its checksum to a temporary file renamed over `filepath`,
+    /// so readers never observe a partially written key, see [`write_atomic()`].
+    fn write_file(&self, filepath: &Path) -> RsaResult<()> {
+        write_atomic(filepath, self.to_string_with_checksum())
     }
 
     /// Writes this [`Key`] to the default keys directory,
@@ -87,21 +147,23 @@ impl Key {
     }
 }
 
+/// Directory containing `path`, `cwd` being represented by an empty path.
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
 #[cfg(test)]
 pub(super) mod tests {
     use super::*;
-    use crate::key::{
-        file::tests::{KEY_DIR_PATH, PAIR_DIR_PATH, PAIR_KEY_PATH, PRIV_KEY_PATH, PUB_KEY_PATH},
-        tests::test_pair,
-    };
-    use std::path::PathBuf;
+    use crate::fixtures::FixtureBuilder;
+    use crate::key::tests::test_pair;
 
     #[test]
-    pub(crate) fn test_write_key_to_file() {
-        let pub_path = PathBuf::from(PUB_KEY_PATH);
-        let priv_path = PathBuf::from(PRIV_KEY_PATH);
-        let dir_path = PathBuf::from(KEY_DIR_PATH);
-        create_dir_all(&dir_path).unwrap();
+    fn test_write_key_to_file() {
+        let fixture = FixtureBuilder::new().dir("key").build().unwrap();
+        let pub_path = fixture.join("test_key.pub");
+        let priv_path = fixture.join("test_key");
+        let dir_path = fixture.join("key");
 
         test_pair().public_key.write_to_path(&pub_path).unwrap();
         assert!(pub_path.is_file());
@@ -117,10 +179,10 @@ pub(super) mod tests {
     }
 
     #[test]
-    pub(crate) fn test_write_key_pair_to_file() {
-        let file_path = PathBuf::from(PAIR_KEY_PATH);
-        let dir_path = PathBuf::from(PAIR_DIR_PATH);
-        create_dir_all(&dir_path).unwrap();
+    fn test_write_key_pair_to_file() {
+        let fixture = FixtureBuilder::new().dir("pair").build().unwrap();
+        let file_path = fixture.join("test_pair");
+        let dir_path = fixture.join("pair");
 
         test_pair().write_to_path(&dir_path).unwrap();
         assert!(dir_path.join(Key::DEFAULT_PUBLIC_KEY_NAME).is_file());
@@ -131,6 +193,31 @@ pub(super) mod tests {
         assert!(file_path
             .with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION)
             .is_file());
+        // only the keys directories of `rrsa` are locked, leaving no lock file elsewhere
+        assert!(!fixture.join(DirLock::FILE_NAME).exists());
+        assert!(!dir_path.join(DirLock::FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_write_new_key_pair() {
+        let fixture = FixtureBuilder::new().build().unwrap();
+        let file_path = fixture.join("new_pair");
+
+        test_pair().write_new_to_path(&file_path).unwrap();
+        assert!(matches!(
+            test_pair().write_new_to_path(&file_path),
+            Err(crate::error::RsaError::WouldOverwrite(_))
+        ));
+        test_pair().write_to_path(&file_path).unwrap();
+
+        // a lone Public Key is enough to refuse, and the Private Key is not written
+        let pub_only = fixture.join("pub_only");
+        test_pair()
+            .public_key
+            .write_to_path(&pub_only.with_extension(Key::DEFAULT_PUBLIC_KEY_EXTENSION))
+            .unwrap();
+        assert!(test_pair().write_new_to_path(&pub_only).is_err());
+        assert!(!pub_only.exists());
     }
 
     #[test]
@@ -0,0 +1,155 @@
+use super::{Key, KeyVariant};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+impl Key {
+    /// Width and height of [`Key::fingerprint_randomart()`], as in OpenSSH.
+    const RANDOMART_SIZE: (usize, usize) = (17, 9);
+    /// Symbol of a cell by its number of visits, the last two marking the start and the end.
+    const RANDOMART_SYMBOLS: &'static [u8] = b" .o+=*BOX@%&#/^SE";
+
+    /// Returns the SHA-256 digest of the modulus followed by the public exponent,
+    /// both encoded as big endian bytes prefixed by their length as a big endian `u32`.
+    ///
+    /// The public exponent of a Private Key is the one of its CRT components, or the
+    /// default one if it has none, so both Keys of a pair share the same fingerprint.
+    #[must_use]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let public_exponent = match (&self.variant, &self.crt) {
+            (KeyVariant::PublicKey, _) => self.exponent.clone(),
+            (KeyVariant::PrivateKey, Some(crt)) => crt.public_exponent.clone(),
+            (KeyVariant::PrivateKey, None) => BigUint::from(Key::DEFAULT_EXPONENT),
+        };
+        let mut hasher = Sha256::new();
+        for value in [&self.modulus, &public_exponent] {
+            let bytes = value.to_bytes_be();
+            // values will never be anywhere near 2^32 bytes long
+            #[allow(clippy::cast_possible_truncation)]
+            hasher.update((bytes.len() as u32).to_be_bytes());
+            hasher.update(&bytes);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Returns the [`Key::fingerprint()`] formatted as `SHA256:` followed by lowercase hex.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let pair = KeyPair::demo();
+    /// assert_eq!(pair.public_key.fingerprint_hex(), pair.private_key.fingerprint_hex());
+    /// ```
+    #[must_use]
+    pub fn fingerprint_hex(&self) -> String {
+        self.fingerprint()
+            .iter()
+            .fold(String::from("SHA256:"), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            })
+    }
+
+    /// Returns the [`Key::fingerprint()`] drawn as randomart, with the drunken bishop
+    /// walk of OpenSSH, so fingerprints can be compared at a glance, e.g. over the phone.
+    ///
+    /// The art differs from the one `ssh-keygen` draws for the same Key,
+    /// since OpenSSH hashes the whole key blob.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let art = KeyPair::demo().public_key.fingerprint_randomart();
+    /// assert!(art.starts_with("+---[RSA 1024]----+"));
+    /// assert_eq!(art.lines().count(), 11);
+    /// ```
+    #[must_use]
+    pub fn fingerprint_randomart(&self) -> String {
+        let (width, height) = Key::RANDOMART_SIZE;
+        let start = Key::RANDOMART_SYMBOLS.len() - 2;
+        let mut field = vec![vec![0usize; width]; height];
+        let (mut x, mut y) = (width / 2, height / 2);
+        for byte in self.fingerprint() {
+            // every pair of bits, least significant first, moves one step diagonally
+            for step in 0..4 {
+                let bits = byte >> (2 * step);
+                x = if bits & 1 == 1 {
+                    (x + 1).min(width - 1)
+                } else {
+                    x.saturating_sub(1)
+                };
+                y = if bits & 2 == 2 {
+                    (y + 1).min(height - 1)
+                } else {
+                    y.saturating_sub(1)
+                };
+                if field[y][x] < start - 1 {
+                    field[y][x] += 1;
+                }
+            }
+        }
+        field[height / 2][width / 2] = start;
+        field[y][x] = start + 1;
+
+        let mut art = randomart_border(&format!("[RSA {}]", self.modulus.bits()), width);
+        art.push('\n');
+        for row in field {
+            art.push('|');
+            art.extend(
+                row.into_iter()
+                    .map(|visits| char::from(Key::RANDOMART_SYMBOLS[visits])),
+            );
+            art.push_str("|\n");
+        }
+        art.push_str(&randomart_border("[SHA256]", width));
+        art
+    }
+}
+
+/// Top or bottom border of a randomart `width` cells wide, with `title` centered.
+fn randomart_border(title: &str, width: usize) -> String {
+    let left = width.saturating_sub(title.len()) / 2;
+    let right = width.saturating_sub(left + title.len());
+    format!("+{}{title}{}+", "-".repeat(left), "-".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::key::{tests::test_pair, Key, KeyVariant};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_fingerprint() {
+        let pair = test_pair();
+        assert_eq!(
+            pair.public_key.fingerprint(),
+            pair.private_key.fingerprint()
+        );
+        assert_eq!(
+            pair.public_key.fingerprint_hex(),
+            "SHA256:a91882fd52c5ccad642fe450a19a90a2d57cf9e597e476b6527bd97678bc6037"
+        );
+        // same modulus with another public exponent
+        let other_exponent = Key::try_new(
+            BigUint::from(3u8),
+            pair.public_key.modulus.clone(),
+            KeyVariant::PublicKey,
+        )
+        .unwrap();
+        assert_ne!(other_exponent.fingerprint(), pair.public_key.fingerprint());
+        assert_eq!(
+            pair.private_key.fingerprint_randomart(),
+            "+----[RSA 32]-----+
+|      o.         |
+| . o * o         |
+|+ . = @ . . .    |
+|o= o O + + o .   |
+|o = o + S . = +  |
+|   + o o   o + =o|
+|  . o .     .o+EB|
+|   .        ..o++|
+|               . |
++----[SHA256]-----+"
+        );
+    }
+}
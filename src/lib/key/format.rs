@@ -0,0 +1,148 @@
+//! Conversion of a [`Key`] between every supported format,
+//! detecting the format of the input.
+
+use std::str::FromStr;
+
+use super::Key;
+use crate::error::{RsaError, RsaResult};
+
+/// Format a [`Key`] can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// The native format, see [`Key::from_str()`].
+    Rrsa,
+    /// PKCS#1 PEM, see [`Key::to_pkcs1_pem()`].
+    Pem,
+    /// PKCS#1 DER, see [`Key::to_der()`].
+    Der,
+    /// OpenSSH public key line, see [`Key::to_openssh()`],
+    /// with the comment of the [`crate::key::KeyMetadata`].
+    OpenSsh,
+    /// JSON Web Key, see [`Key::to_jwk()`].
+    #[cfg(feature = "jwk")]
+    Jwk,
+}
+
+impl KeyFormat {
+    /// Detects the format of the contents of a key file from their first bytes.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyFormat;
+    ///
+    /// assert_eq!(KeyFormat::detect(b"rrsa 9668f701\n").unwrap(), KeyFormat::Rrsa);
+    /// assert!(KeyFormat::detect(b"not a key").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// If `contents` do not start like any [`KeyFormat`].
+    pub fn detect(contents: &[u8]) -> RsaResult<Self> {
+        if Key::is_der(contents) {
+            return Ok(KeyFormat::Der);
+        }
+        let text = std::str::from_utf8(contents)
+            .unwrap_or_default()
+            .trim_start();
+        if text.starts_with(Key::PUBLIC_KEY_NDEX_HEADER)
+            || text.starts_with(Key::PUBLIC_KEY_NORMAL_HEADER)
+            || text.starts_with(Key::PRIVATE_KEY_HEADER)
+        {
+            Ok(KeyFormat::Rrsa)
+        } else if text.starts_with(Key::PKCS1_PUBLIC_KEY_HEADER)
+            || text.starts_with(Key::PKCS1_PRIVATE_KEY_HEADER)
+        {
+            Ok(KeyFormat::Pem)
+        } else if text.starts_with(Key::OPENSSH_KEY_TYPE) {
+            Ok(KeyFormat::OpenSsh)
+        } else {
+            #[cfg(feature = "jwk")]
+            if text.starts_with('{') {
+                return Ok(KeyFormat::Jwk);
+            }
+            Err(RsaError::ImproperlyFormattedStr(
+                "because it is not in any supported key format".into(),
+            ))
+        }
+    }
+}
+
+impl Key {
+    /// Extracts a [`Key`] from `contents` in the given [`KeyFormat`],
+    /// or in the one detected by [`KeyFormat::detect()`] if `None`.
+    ///
+    /// # Errors
+    /// If `contents` are not a valid key in that format.
+    pub fn from_format(contents: &[u8], format: Option<KeyFormat>) -> RsaResult<Self> {
+        let format = match format {
+            Some(format) => format,
+            None => KeyFormat::detect(contents)?,
+        };
+        let text = || {
+            std::str::from_utf8(contents).map_err(|_| {
+                RsaError::ImproperlyFormattedStr("because it is not valid UTF-8 text".into())
+            })
+        };
+        match format {
+            KeyFormat::Rrsa => Key::from_str(text()?),
+            KeyFormat::Pem => Key::from_pkcs1_pem(text()?),
+            KeyFormat::Der => Key::from_der(contents),
+            KeyFormat::OpenSsh => Key::from_openssh_keeping_comment(text()?),
+            #[cfg(feature = "jwk")]
+            KeyFormat::Jwk => Key::from_jwk(text()?),
+        }
+    }
+
+    /// Formats this [`Key`] in the given [`KeyFormat`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyFormat, KeyPair};
+    ///
+    /// let public_key = &KeyPair::demo().public_key;
+    /// let line = public_key.to_format(KeyFormat::OpenSsh).unwrap();
+    /// assert_eq!(Key::from_format(&line, None).unwrap(), *public_key);
+    /// ```
+    ///
+    /// # Errors
+    /// - If `self` is a Private Key and the format is [`KeyFormat::OpenSsh`].
+    /// - If `self` is a Private Key whose public exponent is not the default one,
+    ///   and the format needs it, see [`Key::to_pkcs1_der()`].
+    pub fn to_format(&self, format: KeyFormat) -> RsaResult<Vec<u8>> {
+        Ok(match format {
+            KeyFormat::Rrsa => self.to_string().into_bytes(),
+            KeyFormat::Pem => self.to_pkcs1_pem()?.into_bytes(),
+            KeyFormat::Der => self.to_der()?,
+            KeyFormat::OpenSsh => self
+                .to_openssh(self.metadata.comment.as_deref().unwrap_or_default())?
+                .into_bytes(),
+            #[cfg(feature = "jwk")]
+            KeyFormat::Jwk => self.to_jwk()?.into_bytes(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+
+    #[test]
+    fn test_convert_formats() {
+        let pair = test_pair();
+        #[cfg_attr(not(feature = "jwk"), allow(unused_mut))]
+        let mut formats = vec![KeyFormat::Rrsa, KeyFormat::Pem, KeyFormat::Der];
+        #[cfg(feature = "jwk")]
+        formats.push(KeyFormat::Jwk);
+        for format in formats {
+            for key in [&pair.public_key, &pair.private_key] {
+                let contents = key.to_format(format).unwrap();
+                assert_eq!(KeyFormat::detect(&contents).unwrap(), format);
+                assert_eq!(Key::from_format(&contents, None).unwrap(), *key);
+                assert_eq!(Key::from_format(&contents, Some(format)).unwrap(), *key);
+            }
+        }
+
+        let line = pair.public_key.to_format(KeyFormat::OpenSsh).unwrap();
+        assert_eq!(KeyFormat::detect(&line).unwrap(), KeyFormat::OpenSsh);
+        assert!(pair.private_key.to_format(KeyFormat::OpenSsh).is_err());
+        assert!(Key::from_format(&line, Some(KeyFormat::Pem)).is_err());
+    }
+}
@@ -1,19 +1,25 @@
-use super::{Key, KeyPair};
-use crate::math::{euclides_extended, PrimeGenerator};
+use super::{
+    CrtComponents, Exponent, IsDefaultExponent, Key, KeyGenOptions, KeyMetadata, KeyPair, KeySize,
+};
+use crate::error::{RsaError, RsaResult};
+use crate::math::{mod_inverse, wipe, PrimeGenerator};
+use crate::rng::SecureRandom;
 use num_bigint::BigUint;
-use num_traits::{CheckedMul, One, Signed};
-use std::{io::Write, ops::RangeInclusive};
+use num_traits::CheckedMul;
 
 impl Key {
-    const DEFAULT_KEY_SIZE: u16 = 4096;
-    const KEY_SIZE_RANGE: RangeInclusive<u16> = (32..=4096);
-    pub(super) const DEFAULT_EXPONENT: u32 = 65_537u32;
+    /// Classic default exponent, the only one public key files can leave implicit.
+    pub(crate) const DEFAULT_EXPONENT: u32 = 65_537u32;
+    /// Miller-Rabin bases used by [`KeyPair::generate_insecure_for_tests()`].
+    const INSECURE_MILLER_RABIN_ROUNDS: usize = 2;
 }
 
 impl KeyPair {
     /// Generates the values of P, Q, N Phi(N), E and D and
     /// returns a `KeyPair` with a Public and a Private Key.
     ///
+    /// Shorthand for [`KeyPair::builder()`], which is clearer than a row of booleans.
+    ///
     /// ## How it works
     /// 1. Select two big prime numbers `P` and `Q`
     /// 2. Calculate `N = P * Q`
@@ -21,116 +27,275 @@ impl KeyPair {
     /// 4. Find a `E` such that `gcd(e, λ(N)) = 1` and `1 < E < λ(N)`
     /// 5. Calculate `D` such that `E*D = 1 (mod λ(N))`
     ///
-    /// # Panics
-    /// Panics if `key_size` is not in (32, 4096) interval
-    #[allow(clippy::many_single_char_names)]
-    #[must_use]
+    /// `P`, `Q`, `λ(N)` and `D` are wiped before returning, only the copy of `D`
+    /// and the CRT components derived from `P` and `Q` are kept in the Private Key.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{KeyPair, KeySize};
+    ///
+    /// let pair = KeyPair::generate(Some(KeySize::custom(256).unwrap()), true, false, false).unwrap();
+    /// assert!(pair.is_valid());
+    /// assert!(pair.private_key.has_crt());
+    /// ```
+    ///
+    /// # Errors
+    /// [`RsaError::GenerationFailed`] if the generated pair is not valid.
     pub fn generate(
-        maybe_key_size_bits: Option<u16>,
+        maybe_key_size: Option<KeySize>,
         use_default_exponent: bool,
         print_results: bool,
         print_progress: bool,
-    ) -> KeyPair {
-        let pp = print_progress;
-        let key_size = maybe_key_size_bits.unwrap_or(Key::DEFAULT_KEY_SIZE);
-        assert!(
-            Key::KEY_SIZE_RANGE.contains(&key_size),
-            "Key size not supported!"
-        );
-        printf!(pp, "Generating key with {key_size} bits\n");
+    ) -> RsaResult<KeyPair> {
+        KeyPair::builder()
+            .size(maybe_key_size.unwrap_or_default())
+            .exponent(if use_default_exponent {
+                Exponent::Default
+            } else {
+                Exponent::Random
+            })
+            .print_results(print_results)
+            .print_progress(print_progress)
+            .build()
+    }
+
+    /// Same as [`KeyPair::generate()`], using the key size and the default exponent
+    /// of `options`, e.g. read from a config file with [`KeyGenOptions::load()`].
+    ///
+    /// When the exponent differs from 65537 the Public Key file records it explicitly.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{KeyGenOptions, KeyPair, KeySize};
+    ///
+    /// let options = KeyGenOptions {
+    ///     key_size_bits: Some(KeySize::custom(256).unwrap()),
+    ///     default_exponent: 3,
+    ///     use_default_exponent: true,
+    /// };
+    /// let pair = KeyPair::generate_with_options(&options, false, false).unwrap();
+    /// assert!(pair.is_valid());
+    /// assert!(pair.public_key.to_string().starts_with("rrsa-ndex "));
+    /// ```
+    ///
+    /// # Errors
+    /// - If `options` are invalid, see [`KeyGenOptions::validate()`].
+    /// - [`RsaError::GenerationFailed`] if the generated pair is not valid.
+    pub fn generate_with_options(
+        options: &KeyGenOptions,
+        print_results: bool,
+        print_progress: bool,
+    ) -> RsaResult<KeyPair> {
+        options.validate()?;
+        KeyPair::builder()
+            .size(options.key_size_bits.unwrap_or_default())
+            .exponent(if options.use_default_exponent {
+                Exponent::Fixed(options.default_exponent)
+            } else {
+                Exponent::Random
+            })
+            .print_results(print_results)
+            .print_progress(print_progress)
+            .build()
+    }
+
+    /// Generates a [`KeyPair`] with the fixed exponent `default_exponent`,
+    /// or a random prime exponent if absent, drawing primes from `rng`
+    /// and reporting every step to `progress`.
+    #[allow(clippy::many_single_char_names)]
+    pub(super) fn generate_inner<R: SecureRandom + ?Sized>(
+        rng: &mut R,
+        maybe_key_size: Option<KeySize>,
+        default_exponent: Option<&BigUint>,
+        print_results: bool,
+        progress: &mut dyn FnMut(&str),
+    ) -> RsaResult<KeyPair> {
+        let key_size = maybe_key_size.unwrap_or_default().bits();
+        let _span = tracing::info_span!("keygen", bits = key_size).entered();
+        progress(&format!("Generating key with {key_size} bits\n"));
 
         let max_bits = key_size / 2;
         let mut attempts = 0u32;
         let (mut p, mut q, mut n, mut totn, mut e, mut d);
-        let mut gen = PrimeGenerator::new();
+        let mut gen = PrimeGenerator::with_rng(rng);
 
         loop {
             attempts += 1;
-            printf!(pp, "\nAttempt number {attempts}\nGenerating P...");
-            p = gen.random_prime(max_bits);
-            printf!(pp, "DONE\nGenerating Q...");
-            q = gen.random_prime(max_bits);
+            progress(&format!("\nAttempt number {attempts}\nGenerating P..."));
+            p = gen.random_prime_of_bits(max_bits);
+            progress("DONE\nGenerating Q...");
+            q = gen.random_prime_of_bits(max_bits);
             while p == q {
-                q = gen.random_prime(max_bits);
+                q = gen.random_prime_of_bits(max_bits);
             }
-            printf!(pp, "DONE\nCalculating Public/Private Key's Modulus (N)...");
-            n = p
-                .checked_mul(&q)
-                .expect("Checked multiplication of Big Integers failed.");
-            printf!(pp, "DONE\n");
+            progress("DONE\nCalculating Public/Private Key's Modulus (N)...");
+            n = p.checked_mul(&q).ok_or_else(|| {
+                RsaError::GenerationFailed("multiplication of P and Q overflowed".into())
+            })?;
+            progress("DONE\n");
             totn = (&p - 1u8) * (&q - 1u8);
 
-            if use_default_exponent {
-                printf!(pp, "Using default exponent...DONE\n");
-                e = BigUint::from(Key::DEFAULT_EXPONENT);
-                assert!(e < totn, "Tot(N) is smaller than the default exponent");
+            if let Some(default_exponent) = default_exponent {
+                progress("Using default exponent...DONE\n");
+                e = default_exponent.clone();
+                if e >= totn {
+                    for secret in [&mut p, &mut q, &mut totn] {
+                        wipe(secret);
+                    }
+                    return Err(RsaError::GenerationFailed(
+                        "Tot(N) is smaller than the default exponent".into(),
+                    ));
+                }
             } else {
-                printf!(pp, "Calculating Public Key's Exponent (E)...");
+                progress("Calculating Public Key's Exponent (E)...");
                 e = gen.random_prime(max_bits);
                 while e >= totn {
                     e = gen.random_prime(max_bits);
                 }
-                printf!(pp, "DONE\n");
+                progress("DONE\n");
             }
 
-            printf!(pp, "Calculating Private Key's Exponent (D)...");
-            let (_, d_tmp, _) = euclides_extended(&e, &totn);
-            d = d_tmp.abs().to_biguint().unwrap();
-            d = (d % &totn + &totn) % &totn;
-
-            if (&e * &d % &totn) == One::one() {
-                printf!(pp, "DONE\n");
+            progress("Calculating Private Key's Exponent (D)...");
+            if let Some(inverse) = mod_inverse(&e, &totn) {
+                d = inverse;
+                progress("DONE\n");
                 break;
             }
-            printf!(pp, "\nCould not find a valid Private Key...RETRYING\n");
+            progress("\nCould not find a valid Private Key...RETRYING\n");
+            for secret in [&mut p, &mut q, &mut totn] {
+                wipe(secret);
+            }
         }
-        printf!(pp, "\nKey Pair successfully generated\n");
+        progress("\nKey Pair successfully generated\n");
 
         let key_pair = KeyPair {
             public_key: Key {
                 exponent: e.clone(),
                 modulus: n.clone(),
                 variant: crate::key::KeyVariant::PublicKey,
+                crt: None,
+                metadata: KeyMetadata::generated_now(),
             },
             private_key: Key {
                 exponent: d.clone(),
                 modulus: n.clone(),
                 variant: crate::key::KeyVariant::PrivateKey,
+                crt: CrtComponents::from_factors(&e, &d, &p, &q),
+                metadata: KeyMetadata::generated_now(),
             },
         };
 
-        assert!(key_pair.is_valid());
+        if !key_pair.is_valid() {
+            for secret in [&mut p, &mut q, &mut totn, &mut d] {
+                wipe(secret);
+            }
+            return Err(RsaError::GenerationFailed(
+                "the generated Key Pair is not valid".into(),
+            ));
+        }
 
+        tracing::debug!(attempts, "Key Pair generated");
         if print_results {
-            println!("Max bits for N: {key_size}");
-            println!("Max bits for P and Q: {max_bits}");
-            println!("Attempts needed: {attempts}");
-            println!("The values calculated were:");
-            println!("P = {p}");
-            println!("Q = {q}");
-            println!("N = {n}");
-            println!("Tot(N) = {totn}");
-            if !use_default_exponent {
-                println!("E (Non default) = {e}");
+            tracing::info!("Max bits for N: {key_size}");
+            tracing::info!("Max bits for P and Q: {max_bits}");
+            tracing::info!("Attempts needed: {attempts}");
+            tracing::info!("The values calculated were:");
+            tracing::info!("P = {p}");
+            tracing::info!("Q = {q}");
+            tracing::info!("N = {n}");
+            tracing::info!("Tot(N) = {totn}");
+            if !e.is_default_exponent() {
+                tracing::info!("E (Non default) = {e}");
             }
-            println!("D = {d}");
+            tracing::info!("D = {d}");
+        }
+        for secret in [&mut p, &mut q, &mut totn, &mut d] {
+            wipe(secret);
         }
 
-        key_pair
+        Ok(key_pair)
     }
-}
 
-/// If first expression is `true`, does a `print!()` with arguments
-/// and then flushes STDOUT.
-macro_rules! printf {
-    ($should_print:expr, $( $string:expr),+ ) => {
-        if $should_print {
-            print!($($string,)*);
-            std::io::stdout().flush().expect("Could not flush stdout");
+    /// Same as [`KeyPair::generate()`], without printing anything,
+    /// but drawing every prime from `rng` instead of the source of this thread,
+    /// e.g. a hardware RNG, or a seeded generator to make the pair reproducible.
+    ///
+    /// `rng` is only borrowed for the generation, see [`crate::rng::with_source()`]
+    /// to also swap the source of padding and signatures.
+    ///
+    /// ```
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use rrsa_lib::key::{KeyPair, KeySize};
+    ///
+    /// let size = Some(KeySize::custom(256).unwrap());
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let first = KeyPair::generate_with_rng(&mut rng, size, true).unwrap();
+    /// let second = KeyPair::generate_with_rng(&mut StdRng::seed_from_u64(7), size, true).unwrap();
+    /// assert_eq!(first, second);
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`KeyPair::generate()`].
+    pub fn generate_with_rng<R: SecureRandom + ?Sized>(
+        rng: &mut R,
+        maybe_key_size: Option<KeySize>,
+        use_default_exponent: bool,
+    ) -> RsaResult<KeyPair> {
+        KeyPair::builder()
+            .size(maybe_key_size.unwrap_or_default())
+            .exponent(if use_default_exponent {
+                Exponent::Default
+            } else {
+                Exponent::Random
+            })
+            .build_with_rng(rng)
+    }
+
+    /// Quickly generates a [`KeyPair`] with the default exponent, **for tests only**.
+    ///
+    /// Candidate primes get only two Miller-Rabin rounds and the pair is not checked
+    /// with [`KeyPair::is_valid()`], so in rare cases a factor is composite and the pair
+    /// does not round trip. Combined with small sizes the keys offer no security at all.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{KeyPair, KeySize};
+    ///
+    /// let pair = KeyPair::generate_insecure_for_tests(KeySize::custom(128).unwrap());
+    /// assert!(pair.public_key.is_public());
+    /// ```
+    #[must_use]
+    pub fn generate_insecure_for_tests(size: KeySize) -> KeyPair {
+        let bits = size.bits();
+        let e = BigUint::from(Key::DEFAULT_EXPONENT);
+        let mut gen = PrimeGenerator::new();
+
+        loop {
+            let p = gen.random_prime_with_rounds(bits / 2, Key::INSECURE_MILLER_RABIN_ROUNDS);
+            let q = gen.random_prime_with_rounds(bits / 2, Key::INSECURE_MILLER_RABIN_ROUNDS);
+            let totn = (&p - 1u8) * (&q - 1u8);
+            if p == q || e >= totn {
+                continue;
+            }
+            if let Some(d) = mod_inverse(&e, &totn) {
+                let n = p * q;
+                return KeyPair {
+                    public_key: Key {
+                        exponent: e,
+                        modulus: n.clone(),
+                        variant: crate::key::KeyVariant::PublicKey,
+                        crt: None,
+                        metadata: KeyMetadata::default(),
+                    },
+                    private_key: Key {
+                        exponent: d,
+                        modulus: n,
+                        variant: crate::key::KeyVariant::PrivateKey,
+                        crt: None,
+                        metadata: KeyMetadata::default(),
+                    },
+                };
+            }
         }
-    };
+    }
 }
-use printf;
 
 #[cfg(test)]
 mod tests {
@@ -139,31 +304,126 @@ mod tests {
 
     #[test]
     fn test_key_validation() {
-        let key_pair = KeyPair {
-            public_key: Key {
-                exponent: BigUint::from(0x1_0001u32), // default exponent
-                modulus: BigUint::from(0x9668_F701u64),
-                variant: KeyVariant::PublicKey,
-            },
-            private_key: Key {
-                exponent: BigUint::from(0x147B_7F71u32),
-                modulus: BigUint::from(0x9668_F701u64),
-                variant: KeyVariant::PrivateKey,
-            },
+        for (modulus, public_exponent, private_exponent) in [
+            // default exponent
+            (0x9668_F701u64, 0x1_0001u64, 0x147B_7F71u64),
+            (0x11C6_8C75u64, 0x5B97u64, 0x37A_21E7u64),
+        ] {
+            let key_pair = KeyPair {
+                public_key: Key::try_new(
+                    BigUint::from(public_exponent),
+                    BigUint::from(modulus),
+                    KeyVariant::PublicKey,
+                )
+                .unwrap(),
+                private_key: Key::try_new(
+                    BigUint::from(private_exponent),
+                    BigUint::from(modulus),
+                    KeyVariant::PrivateKey,
+                )
+                .unwrap(),
+            };
+            assert!(key_pair.is_valid());
+        }
+    }
+
+    #[test]
+    fn test_generate_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let size = Some(KeySize::custom(256).unwrap());
+        let first = KeyPair::generate_with_rng(&mut StdRng::seed_from_u64(7), size, false).unwrap();
+        let second =
+            KeyPair::generate_with_rng(&mut StdRng::seed_from_u64(7), size, false).unwrap();
+        assert!(first.is_valid());
+        assert_eq!(first, second);
+        assert_ne!(
+            first,
+            KeyPair::generate_with_rng(&mut StdRng::seed_from_u64(8), size, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_generate_nominal_size() {
+        for size in [KeySize::custom(258).unwrap(), KeySize::B2048] {
+            let key_pair = KeyPair::generate_with_options(
+                &KeyGenOptions {
+                    key_size_bits: Some(size),
+                    ..KeyGenOptions::default()
+                },
+                false,
+                false,
+            )
+            .unwrap();
+            assert_eq!(key_pair.public_key.modulus.bits(), u64::from(size.bits()));
+            // a 2048 bits pair is not reported as weak
+            if size == KeySize::B2048 {
+                assert!(key_pair.public_key.warnings().is_empty());
+                assert!(key_pair.private_key.warnings().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_with_options() {
+        let options = KeyGenOptions {
+            key_size_bits: Some(KeySize::custom(128).unwrap()),
+            default_exponent: 17,
+            use_default_exponent: true,
         };
+        let key_pair = KeyPair::generate_with_options(&options, false, false).unwrap();
         assert!(key_pair.is_valid());
-        let key_pair = KeyPair {
-            public_key: Key {
-                exponent: BigUint::from(0x5B97u64),
-                modulus: BigUint::from(0x11C6_8C75u64),
-                variant: KeyVariant::PublicKey,
-            },
-            private_key: Key {
-                exponent: BigUint::from(0x37A_21E7u64),
-                modulus: BigUint::from(0x11C6_8C75u64),
-                variant: KeyVariant::PrivateKey,
+        assert_eq!(key_pair.public_key.exponent, BigUint::from(17u8));
+        // the exponent is recorded, so it survives a round trip
+        let contents = key_pair.public_key.to_string();
+        assert!(contents.starts_with("rrsa-ndex "));
+        assert_eq!(
+            <Key as std::str::FromStr>::from_str(&contents).unwrap(),
+            key_pair.public_key
+        );
+
+        let key_pair =
+            KeyPair::generate_with_options(&KeyGenOptions::default(), false, false).unwrap();
+        assert!(key_pair.public_key.exponent.is_default_exponent());
+        assert!(KeyPair::generate_with_options(
+            &KeyGenOptions {
+                default_exponent: 2,
+                ..options
             },
-        };
-        assert!(key_pair.is_valid());
+            false,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_generate_keeps_crt() {
+        let key_pair =
+            KeyPair::generate(Some(KeySize::custom(256).unwrap()), true, false, false).unwrap();
+        assert!(key_pair.private_key.has_crt());
+        assert!(!key_pair.public_key.has_crt());
+
+        let value = BigUint::from(0x147B_7F71_9668_F701_u64);
+        assert_eq!(
+            key_pair.private_key.apply_exponent(&value),
+            value.modpow(
+                &key_pair.private_key.exponent,
+                &key_pair.private_key.modulus
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_insecure_for_tests() {
+        let key_pair = KeyPair::generate_insecure_for_tests(KeySize::custom(64).unwrap());
+        assert!(!key_pair.private_key.has_crt());
+        assert!(key_pair.public_key.is_public());
+        assert!(key_pair.private_key.is_private());
+        assert_eq!(key_pair.public_key.modulus, key_pair.private_key.modulus);
+        assert!(key_pair.public_key.modulus.bits() <= 64);
+        assert_eq!(
+            key_pair.public_key.exponent,
+            BigUint::from(Key::DEFAULT_EXPONENT)
+        );
     }
 }
@@ -0,0 +1,124 @@
+//! RSA JSON Web Keys, as defined by RFC 7517 and RFC 7518.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use super::{Key, KeyVariant};
+use crate::error::{RsaError, RsaResult};
+
+fn encode_member(value: &BigUint) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_bytes_be())
+}
+
+fn decode_member(jwk: &Value, name: &str) -> RsaResult<Option<BigUint>> {
+    jwk.get(name)
+        .map(|member| {
+            member
+                .as_str()
+                .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+                .map(|bytes| BigUint::from_bytes_be(&bytes))
+                .ok_or_else(|| {
+                    RsaError::ImproperlyFormattedStr(format!(
+                        "because the JWK member `{name}` is not valid base64url"
+                    ))
+                })
+        })
+        .transpose()
+}
+
+impl Key {
+    /// Formats this [`Key`] as an RSA JSON Web Key.
+    ///
+    /// Private Keys also include `e` and the CRT members `p`, `q`, `dp`, `dq` and `qi`,
+    /// recovered assuming the default public exponent.
+    ///
+    /// # Errors
+    /// If `self` is a Private Key whose public exponent is not the default one.
+    pub fn to_jwk(&self) -> RsaResult<String> {
+        let jwk = match self.variant {
+            KeyVariant::PublicKey => json!({
+                "kty": "RSA",
+                "n": encode_member(&self.modulus),
+                "e": encode_member(&self.exponent),
+            }),
+            KeyVariant::PrivateKey => {
+                let crt = self.crt_components()?;
+                json!({
+                    "kty": "RSA",
+                    "n": encode_member(&self.modulus),
+                    "e": encode_member(&crt.public_exponent),
+                    "d": encode_member(&self.exponent),
+                    "p": encode_member(&crt.p),
+                    "q": encode_member(&crt.q),
+                    "dp": encode_member(&crt.exponent_p),
+                    "dq": encode_member(&crt.exponent_q),
+                    "qi": encode_member(&crt.coefficient),
+                })
+            }
+        };
+        Ok(jwk.to_string())
+    }
+
+    /// Extracts a [`Key`] from an RSA JSON Web Key,
+    /// a Private Key if the `d` member is present or a Public Key otherwise.
+    ///
+    /// # Errors
+    /// If `s` is not a JSON object with `kty` `RSA` and valid `n` and `e` members,
+    /// see [`Key::try_new()`].
+    pub fn from_jwk(s: &str) -> RsaResult<Self> {
+        let jwk: Value = serde_json::from_str(s).map_err(|_| {
+            RsaError::ImproperlyFormattedStr("because the JWK is not valid JSON".into())
+        })?;
+        if jwk.get("kty").and_then(Value::as_str) != Some("RSA") {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the JWK is not an RSA key".into(),
+            ));
+        }
+
+        let (Some(modulus), Some(public_exponent)) =
+            (decode_member(&jwk, "n")?, decode_member(&jwk, "e")?)
+        else {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the JWK is missing the `n` and/or `e` members".into(),
+            ));
+        };
+        match decode_member(&jwk, "d")? {
+            Some(private_exponent) => {
+                Key::try_new(private_exponent, modulus, KeyVariant::PrivateKey)
+            }
+            None => Key::try_new(public_exponent, modulus, KeyVariant::PublicKey),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_jwk() {
+        let pair = test_pair();
+        let public_jwk = pair.public_key.to_jwk().unwrap();
+        assert_eq!(public_jwk, r#"{"e":"AQAB","kty":"RSA","n":"lmj3AQ"}"#);
+        assert_eq!(Key::from_jwk(&public_jwk).unwrap(), pair.public_key);
+
+        let private_jwk = pair.private_key.to_jwk().unwrap();
+        assert_eq!(
+            private_jwk,
+            r#"{"d":"FHt_cQ","dp":"YPU","dq":"CX0","e":"AQAB","kty":"RSA","n":"lmj3AQ","p":"zOU","q":"u-0","qi":"LMM"}"#
+        );
+        assert_eq!(Key::from_jwk(&private_jwk).unwrap(), pair.private_key);
+    }
+
+    #[test]
+    fn test_jwk_error() {
+        assert!(Key::from_jwk("not json").is_err());
+        assert!(Key::from_jwk(r#"{"kty":"EC","n":"lmj3AQ","e":"AQAB"}"#).is_err());
+        assert!(Key::from_jwk(r#"{"kty":"RSA","n":"lmj3AQ"}"#).is_err());
+        assert!(Key::from_jwk(r#"{"kty":"RSA","n":"lmj3AQ","e":"AQ+B"}"#).is_err());
+        assert!(Key::from_jwk(r#"{"kty":"RSA","n":"lmj3AQ","e":1}"#).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Optional metadata of a [`Key`], kept in its file next to the values,
+//! like the comment of an OpenSSH key.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Key;
+use crate::error::{RsaError, RsaResult};
+use crate::version;
+
+/// Metadata of a [`Key`], which does not change its values, so it is ignored when comparing Keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyMetadata {
+    /// Free text describing the Key, written on a single line.
+    pub comment: Option<String>,
+    /// Seconds since the Unix epoch when the Key was generated.
+    pub created_at: Option<u64>,
+    /// Name and version of the program that generated the Key, e.g. `rrsa 0.5.0`.
+    pub generator: Option<String>,
+}
+
+impl KeyMetadata {
+    pub(crate) const COMMENT_FIELD: &'static str = "Comment";
+    pub(crate) const CREATED_FIELD: &'static str = "Created";
+    pub(crate) const GENERATOR_FIELD: &'static str = "Generator";
+
+    /// Metadata of a Key generated now by this version of the crate.
+    #[must_use]
+    pub fn generated_now() -> Self {
+        KeyMetadata {
+            comment: None,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs()),
+            generator: Some(version::producer()),
+        }
+    }
+
+    /// Returns `true` if no field is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == KeyMetadata::default()
+    }
+
+    /// Sets the field `name` to `value`, returning `false` if `name` is not a metadata field.
+    ///
+    /// # Errors
+    /// If the creation time is not a number of seconds.
+    pub(crate) fn set_field(&mut self, name: &str, value: &str) -> RsaResult<bool> {
+        match name {
+            KeyMetadata::COMMENT_FIELD => self.comment = Some(value.to_string()),
+            KeyMetadata::CREATED_FIELD => {
+                self.created_at = Some(value.trim().parse().map_err(|_| {
+                    RsaError::ImproperlyFormattedStr(format!(
+                        "because the creation time `{value}` is not in seconds"
+                    ))
+                })?);
+            }
+            KeyMetadata::GENERATOR_FIELD => self.generator = Some(value.to_string()),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl fmt::Display for KeyMetadata {
+    /// Formats the set fields as `Field: value` lines, as written in a key file.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let split_str = Key::FIELD_SPLIT_STR;
+        if let Some(comment) = &self.comment {
+            // a line break would end the field early
+            let comment = comment.replace(['\r', '\n'], " ");
+            writeln!(f, "{}{split_str}{comment}", KeyMetadata::COMMENT_FIELD)?;
+        }
+        if let Some(created_at) = self.created_at {
+            writeln!(f, "{}{split_str}{created_at}", KeyMetadata::CREATED_FIELD)?;
+        }
+        if let Some(generator) = &self.generator {
+            writeln!(f, "{}{split_str}{generator}", KeyMetadata::GENERATOR_FIELD)?;
+        }
+        Ok(())
+    }
+}
+
+impl Key {
+    /// Metadata of this Key, empty unless read from a file with it or generated.
+    #[must_use]
+    pub fn metadata(&self) -> &KeyMetadata {
+        &self.metadata
+    }
+
+    /// Mutable metadata of this Key, written along with it.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::str::FromStr;
+    ///
+    /// let mut public_key = Key::from_str(&KeyPair::demo_tiny().public_key.to_string()).unwrap();
+    /// public_key.metadata_mut().comment = Some("work laptop".into());
+    /// assert_eq!(public_key.to_string(), "rrsa 9668f701\nComment: work laptop\n");
+    ///
+    /// let read = Key::from_str(&public_key.to_string()).unwrap();
+    /// assert_eq!(read.metadata().comment.as_deref(), Some("work laptop"));
+    /// ```
+    pub fn metadata_mut(&mut self) -> &mut KeyMetadata {
+        &mut self.metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, KeyPair};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let metadata = KeyMetadata {
+            comment: Some("ci\ndeploy key".into()),
+            created_at: Some(1_700_000_000),
+            generator: Some("rrsa 0.2.0".into()),
+        };
+        for key in [&test_pair().public_key, &test_pair().private_key] {
+            let mut key = Key::from_str(&key.to_string()).unwrap();
+            assert!(key.metadata().is_empty());
+            *key.metadata_mut() = metadata.clone();
+
+            let contents = key.to_string_with_checksum();
+            assert!(contents.contains("Comment: ci deploy key\nCreated: 1700000000\n"));
+            let read = Key::from_str(&contents).unwrap();
+            assert_eq!(read, key);
+            assert_eq!(read.metadata().comment.as_deref(), Some("ci deploy key"));
+            assert_eq!(read.metadata().created_at, metadata.created_at);
+            assert_eq!(read.metadata().generator, metadata.generator);
+        }
+
+        let mut pair = KeyPair::from_str(&format!(
+            "{}{}",
+            test_pair().public_key,
+            test_pair().private_key
+        ))
+        .unwrap();
+        pair.public_key.metadata_mut().comment = Some("pair".into());
+        let read = KeyPair::from_str(&format!("{}{}", pair.public_key, pair.private_key)).unwrap();
+        assert_eq!(read.public_key.metadata().comment.as_deref(), Some("pair"));
+
+        assert!(Key::from_str("rrsa 9668f701\nCreated: yesterday\n").is_err());
+        assert!(Key::from_str("rrsa 9668f701\nnot a field\n").is_err());
+
+        let generated = KeyMetadata::generated_now();
+        assert!(generated.created_at.is_some());
+        assert_eq!(generated.generator, Some(version::producer()));
+    }
+}
@@ -2,15 +2,43 @@
 //! formatting as string, parsing from string,
 //! writting and reading from files and validating.
 
-use crate::math::mod_pow;
+use crate::error::{RsaError, RsaResult};
+use crate::math::{mod_pow, wipe, PrimeGenerator};
 use num_bigint::BigUint;
+use std::ops::RangeInclusive;
 
+mod audit;
+mod builder;
+mod bundle;
+mod crt;
+mod demo;
 mod file;
+mod fingerprint;
+mod format;
 mod generation;
+#[cfg(feature = "jwk")]
+mod jwk;
+mod metadata;
+mod openssh;
+mod options;
+mod pkcs1;
+mod report;
+mod size;
 mod str;
 
+pub use audit::{AuditCheck, AuditReport};
+pub use builder::{Exponent, KeyPairBuilder};
+pub use bundle::KeyBundle;
+use crt::CrtComponents;
+pub use file::StorageLocation;
+pub use format::KeyFormat;
+pub use metadata::KeyMetadata;
+pub use options::KeyGenOptions;
+pub use report::{KeyReport, ValidationReport};
+pub use size::KeySize;
+
 /// Enum to dictate if Key is a Public or Private key.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyVariant {
     /// Has a modulus, and can also have a non default exponent.
     PublicKey,
@@ -23,15 +51,31 @@ pub enum KeyVariant {
 /// In the case of a Public key with a default exponent, it is still present in the struct,
 /// but can be recognized via the [`IsDefaultExponent`] trait, which is
 /// implemented for [`BigUint`].
-#[derive(Debug, PartialEq, Eq)]
+///
+/// A Private Key can also carry its CRT components, which only speed up its operations,
+/// so they are ignored when comparing Keys, as is its [`KeyMetadata`].
+#[derive(Debug)]
 pub struct Key {
     /// `D` or `E` part of the key.
     pub(crate) exponent: BigUint,
     /// `N` part of the key.
     pub(crate) modulus: BigUint,
     pub(crate) variant: KeyVariant,
+    /// Kept from generation, or recovered by [`Key::precompute_crt()`].
+    pub(crate) crt: Option<CrtComponents>,
+    pub(crate) metadata: KeyMetadata,
 }
 
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.exponent == other.exponent
+            && self.modulus == other.modulus
+            && self.variant == other.variant
+    }
+}
+
+impl Eq for Key {}
+
 /// Contains both the Public and Private keys.
 #[derive(Debug, PartialEq, Eq)]
 pub struct KeyPair {
@@ -65,7 +109,70 @@ impl KeyPair {
     }
 }
 
+impl Drop for Key {
+    /// Wipes the exponent of a Private Key, so it does not linger in freed memory.
+    fn drop(&mut self) {
+        if self.is_private() {
+            wipe(&mut self.exponent);
+        }
+    }
+}
+
 impl Key {
+    /// Sizes of the modulus accepted by [`Key::try_new()`], wider than [`KeySize`]
+    /// so Keys generated by other tools can be imported too.
+    const MODULUS_BITS: RangeInclusive<u64> = 16..=16_384;
+
+    /// Builds a Key from its exponent and modulus, checking they look like RSA values.
+    ///
+    /// The checks are not a proof the Key is valid, which needs the other Key of the pair,
+    /// see [`KeyPair::is_valid()`].
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    /// use rrsa_lib::key::{Key, KeyVariant};
+    ///
+    /// let modulus = BigUint::from(0x9668_F701u32);
+    /// let key = Key::try_new(BigUint::from(65_537u32), modulus.clone(), KeyVariant::PublicKey).unwrap();
+    /// assert_eq!(key.to_string(), "rrsa 9668f701\n");
+    ///
+    /// // exponent larger than the modulus
+    /// assert!(Key::try_new(&modulus + 2u8, modulus, KeyVariant::PublicKey).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// [`RsaError::InvalidKey`] if:
+    /// - The modulus is not in the 16 to 16384 bits range, is even or is prime.
+    /// - The exponent is lower than 2 or not lower than the modulus.
+    pub fn try_new(exponent: BigUint, modulus: BigUint, variant: KeyVariant) -> RsaResult<Key> {
+        let bits = modulus.bits();
+        if !Key::MODULUS_BITS.contains(&bits) {
+            return Err(RsaError::InvalidKey(format!(
+                "the modulus has {bits} bits, it must be in ({}..={})",
+                Key::MODULUS_BITS.start(),
+                Key::MODULUS_BITS.end()
+            )));
+        }
+        if !modulus.bit(0) {
+            return Err(RsaError::InvalidKey("the modulus is even".into()));
+        }
+        if PrimeGenerator::miller_rabin(&modulus) {
+            return Err(RsaError::InvalidKey("the modulus is prime".into()));
+        }
+        if exponent < BigUint::from(2u8) || exponent >= modulus {
+            return Err(RsaError::InvalidKey(
+                "the exponent must be at least 2 and lower than the modulus".into(),
+            ));
+        }
+        Ok(Key {
+            exponent,
+            modulus,
+            variant,
+            crt: None,
+            metadata: KeyMetadata::default(),
+        })
+    }
+
     #[must_use]
     pub fn is_public(&self) -> bool {
         self.variant == KeyVariant::PublicKey
@@ -78,13 +185,16 @@ impl Key {
 }
 
 /// Trait to determine if something is equal to the default exponent.
+///
+/// This is always the classic 65537, regardless of [`KeyGenOptions::default_exponent`],
+/// so Public Key files written by any version keep being read the same way.
 pub trait IsDefaultExponent {
     /// Returns if something is equal to the default exponent.
+    #[must_use]
     fn is_default_exponent(&self) -> bool;
 }
 
 impl IsDefaultExponent for BigUint {
-    #[must_use]
     fn is_default_exponent(&self) -> bool {
         *self == BigUint::from(Key::DEFAULT_EXPONENT)
     }
@@ -93,25 +203,63 @@ impl IsDefaultExponent for BigUint {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::{Key, KeyPair, KeyVariant};
+    use crate::error::RsaError;
     use num_bigint::BigUint;
-    use std::sync::OnceLock;
-
-    static PAIR: OnceLock<KeyPair> = OnceLock::new();
+    use std::str::FromStr;
 
     pub(crate) fn test_pair() -> &'static KeyPair {
-        PAIR.get_or_init(|| {
-            KeyPair {
-                public_key: Key {
-                    exponent: BigUint::from(0x1_0001u32), // default exponent
-                    modulus: BigUint::from(0x9668_F701u64),
-                    variant: KeyVariant::PublicKey,
-                },
-                private_key: Key {
-                    exponent: BigUint::from(0x147B_7F71u32),
-                    modulus: BigUint::from(0x9668_F701u64),
-                    variant: KeyVariant::PrivateKey,
-                },
-            }
-        })
+        KeyPair::demo_tiny()
+    }
+
+    pub(crate) fn pair_4096() -> KeyPair {
+        let pair_str = r"rrsa 8a171c456a76fa677632c86d79e76a08e9bd619d877b665195fb1d8e506c5fb93277da524842690e855d860644e6050da582f0fe632763a120e0d316cfbccc3e44cf6c8a2d3906690d8ab6133466f210e100213762f1a7b674307f491c6eba0f120a59fd9a8084ca43dfc43988837546fa0cf5e471703f6588d12a35607b20a8604bd989573ca3fea13637dfe31d77efc4f2919b6a8afc5dd58f78cb77a2e000210a636a8240a59c37eebda30adfe85025643f0592bafcb47e6d01d9a50132e23944044af48ded1e5c1517cbcb3bfb4f3ed488a778503ddf4d8de19ae2919ca3c6a78fd9338fe75d5800c45d4c7f9fe5a49967d285fe872063155ce41915e68728a2bc61fe33202d446c19a1a2a685e05cc006b9722c2c58287880f4ebe541f07feb5088290b1ddfce91aeddcd2d051bf33a02144ea6ecc6c1248d8de0702678d85edf7d6a82bc02d6d6523a87abc6c8dbf965a87e410dadff0a62fefded77f0dc4a0b1a65587c2c546d35e4b7ef85a159b2359d32e56df33cce92fb2a287fd1ee39cb940de89c30cd29b8eeb483ad5ff3d948bcbf17a4641876c55b1ba2026f4b08b96716c8b1038252d84610e491f14d5e4994025918aa5ea083e42d767eb8ee3e4e78c4f3a6afd69642f4f2704525a69141762f7448c9bd4e6d42c9b18358d6e405115579f7834869a9e68f8b0ce9ccbc7cf46119ce464b244d5b58458f8b
+-----BEGIN RSA-RUST PRIVATE KEY-----
+8a171c456a76fa677632c86d79e76a08e9bd619d877b665195fb1d8e506c5fb93277da524842690e855d860644e6050da582f0fe632763a120e0d316cfbccc3e44cf6c8a2d3906690d8ab6133466f210e100213762f1a7b674307f491c6eba0f120a59fd9a8084ca43dfc43988837546fa0cf5e471703f6588d12a35607b20a8604bd989573ca3fea13637dfe31d77efc4f2919b6a8afc5dd58f78cb77a2e000210a636a8240a59c37eebda30adfe85025643f0592bafcb47e6d01d9a50132e23944044af48ded1e5c1517cbcb3bfb4f3ed488a778503ddf4d8de19ae2919ca3c6a78fd9338fe75d5800c45d4c7f9fe5a49967d285fe872063155ce41915e68728a2bc61fe33202d446c19a1a2a685e05cc006b9722c2c58287880f4ebe541f07feb5088290b1ddfce91aeddcd2d051bf33a02144ea6ecc6c1248d8de0702678d85edf7d6a82bc02d6d6523a87abc6c8dbf965a87e410dadff0a62fefded77f0dc4a0b1a65587c2c546d35e4b7ef85a159b2359d32e56df33cce92fb2a287fd1ee39cb940de89c30cd29b8eeb483ad5ff3d948bcbf17a4641876c55b1ba2026f4b08b96716c8b1038252d84610e491f14d5e4994025918aa5ea083e42d767eb8ee3e4e78c4f3a6afd69642f4f2704525a69141762f7448c9bd4e6d42c9b18358d6e405115579f7834869a9e68f8b0ce9ccbc7cf46119ce464b244d5b58458f8b
+29e6a54f72e4b34a9d94ff3828db4d537309620b58c6dadf3ab13de0a70a9b6928a5317bf22d248fa16c2574d5872e555bb985c2caf772c5bba23cab1951e26faa957e0bd7790c36e84304c8830811bf89666eadcdba21f7bcfdd241aefcf23c0c6f53ab1e2c8d1e8ac5e556c7d38bcc83a7571d80465d164413a3c91a8381ff5568ee933c034c87c10720a130db0a3f98f539b57cf8bb67059c493d040a4a09fffc94fa0697f32899d83976b5a0076ffa4896ceec1d0cfcffb7b7ee00a1827d1e7f4306337ab54e97065778212d0c2e999407fb3908b01d87fcdb4e121db8f801196b0eaf14a551af985bfd2b6f36678a307a4e6916388e5d42683356614cd7951c694730d55a7e139e6e1bd0ee36042c1358c704141abe95fd3ab8ab3a7a4c54183dbc1c6c70cafc815263fe1f8e020b4a169e0303376c30c2adc987b68c28996fcd9da0ba83fe52ee2d2fea92145e9ac66c79f753133ba2d52738aaa08e40b7566eb618c10f19b3df04e6cc5f2d3ba9fc7efc7884565a6ef161a737769d5125a76ba2044119a6950e9ccfbfcd4c294a2aa2665d8819a31b50210e4033cd194e0b9d828e684aeada7e68c2f2e8edd1cd5dbbd08ea94da100f1a8c407a8c12b35f0ec004ee592d51946f74ead50e7ba73bab3f75bd197a757c76373f8e1a5c0d7b09e30572751e1084a165f7ccdf82d45c9de1401b4870821012e79e6744431
+-----END RSA-RUST PRIVATE KEY-----
+";
+        KeyPair::from_str(pair_str).unwrap()
+    }
+
+    #[test]
+    fn test_try_new() {
+        let pair = test_pair();
+        let key = Key::try_new(
+            pair.private_key.exponent.clone(),
+            pair.private_key.modulus.clone(),
+            KeyVariant::PrivateKey,
+        )
+        .unwrap();
+        assert_eq!(&key, &pair.private_key);
+
+        for (exponent, modulus) in [
+            // too small, even and prime moduli
+            (3u64, 0x7FFFu64),
+            (65_537, 0x9668_F700),
+            (65_537, 0x9668_F711),
+            // exponent out of range
+            (1, 0x9668_F701),
+            (0x9668_F701, 0x9668_F701),
+        ] {
+            assert!(matches!(
+                Key::try_new(
+                    BigUint::from(exponent),
+                    BigUint::from(modulus),
+                    KeyVariant::PublicKey
+                ),
+                Err(RsaError::InvalidKey(_))
+            ));
+        }
+        // strong pseudoprimes to base 2, which are products of two primes
+        for modulus in [1_373_653u32, 25_326_001] {
+            assert!(Key::try_new(
+                BigUint::from(65_537u32),
+                BigUint::from(modulus),
+                KeyVariant::PublicKey
+            )
+            .is_ok());
+        }
+        let too_large = (BigUint::from(1u8) << 16_384u32) + 1u8;
+        assert!(Key::try_new(BigUint::from(3u8), too_large, KeyVariant::PublicKey).is_err());
     }
 }
@@ -0,0 +1,171 @@
+//! OpenSSH one line public keys, as found in `~/.ssh/id_rsa.pub`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use num_bigint::BigUint;
+
+use super::{Key, KeyVariant};
+use crate::error::{RsaError, RsaResult};
+
+impl Key {
+    /// Key type of RSA keys, both as the line prefix and inside the encoded blob.
+    pub(crate) const OPENSSH_KEY_TYPE: &'static str = "ssh-rsa";
+
+    /// Formats this Public Key as an OpenSSH public key line,
+    /// e.g. `ssh-rsa AAAAB3NzaC1yc2E... user@host`.
+    ///
+    /// The comment is omitted if empty.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    ///
+    /// let public_key = &KeyPair::demo().public_key;
+    /// let line = public_key.to_openssh("demo@rrsa").unwrap();
+    /// assert!(line.starts_with("ssh-rsa "));
+    /// let (key, comment) = Key::from_openssh(&line).unwrap();
+    /// assert_eq!((&key, comment.as_str()), (public_key, "demo@rrsa"));
+    /// ```
+    ///
+    /// # Errors
+    /// If `self` is not a [`KeyVariant::PublicKey`].
+    pub fn to_openssh(&self, comment: &str) -> RsaResult<String> {
+        if self.variant != KeyVariant::PublicKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        let mut blob = Vec::new();
+        write_string(&mut blob, Key::OPENSSH_KEY_TYPE.as_bytes());
+        write_mpint(&mut blob, &self.exponent);
+        write_mpint(&mut blob, &self.modulus);
+
+        let mut line = format!("{} {}", Key::OPENSSH_KEY_TYPE, STANDARD.encode(blob));
+        if !comment.is_empty() {
+            line.push(' ');
+            line.push_str(comment);
+        }
+        line.push('\n');
+        Ok(line)
+    }
+
+    /// Extracts a Public Key and its comment from an OpenSSH public key line.
+    ///
+    /// # Returns
+    /// The [`Key`] and the comment, empty if there is none.
+    ///
+    /// # Errors
+    /// If `s` is not a properly formatted OpenSSH RSA public key,
+    /// or its values are rejected by [`Key::try_new()`].
+    pub fn from_openssh(s: &str) -> RsaResult<(Self, String)> {
+        let mut pieces = s.trim().splitn(3, char::is_whitespace);
+        if pieces.next() != Some(Key::OPENSSH_KEY_TYPE) {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because it is not an OpenSSH RSA public key".into(),
+            ));
+        }
+        let blob = pieces
+            .next()
+            .and_then(|encoded| STANDARD.decode(encoded).ok())
+            .ok_or_else(|| {
+                RsaError::ImproperlyFormattedStr(
+                    "because the OpenSSH key blob is not valid base64".into(),
+                )
+            })?;
+        let comment = pieces.next().unwrap_or_default().trim().to_string();
+
+        let mut rest = blob.as_slice();
+        let key_type = read_string(&mut rest)?;
+        let exponent = BigUint::from_bytes_be(read_string(&mut rest)?);
+        let modulus = BigUint::from_bytes_be(read_string(&mut rest)?);
+        if key_type != Key::OPENSSH_KEY_TYPE.as_bytes() || !rest.is_empty() {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the OpenSSH key blob does not hold an RSA key".into(),
+            ));
+        }
+
+        Ok((
+            Key::try_new(exponent, modulus, KeyVariant::PublicKey)?,
+            comment,
+        ))
+    }
+
+    /// Same as [`Key::from_openssh()`], keeping the comment in [`Key::metadata()`].
+    pub(crate) fn from_openssh_keeping_comment(s: &str) -> RsaResult<Self> {
+        let (mut key, comment) = Key::from_openssh(s)?;
+        key.metadata.comment = Some(comment).filter(|comment| !comment.is_empty());
+        Ok(key)
+    }
+}
+
+/// Writes an SSH `string`, prefixed by its length as a big endian `u32`.
+fn write_string(output: &mut Vec<u8>, bytes: &[u8]) {
+    // keys will never be anywhere near 2^32 bytes long
+    #[allow(clippy::cast_possible_truncation)]
+    output.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    output.extend_from_slice(bytes);
+}
+
+/// Writes an SSH `mpint`, with a leading zero byte if the high bit is set.
+fn write_mpint(output: &mut Vec<u8>, value: &BigUint) {
+    let mut bytes = value.to_bytes_be();
+    if bytes == [0x00] {
+        bytes.clear();
+    } else if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0x00);
+    }
+    write_string(output, &bytes);
+}
+
+/// Reads an SSH `string`, advancing `input` past it.
+fn read_string<'a>(input: &mut &'a [u8]) -> RsaResult<&'a [u8]> {
+    let truncated =
+        || RsaError::ImproperlyFormattedStr("because the OpenSSH key blob is truncated".into());
+    let len_bytes = input.get(..4).ok_or_else(truncated)?;
+    let len = u32::from_be_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]);
+    let len = usize::try_from(len).map_err(|_| truncated())?;
+    let bytes = input.get(4..4 + len).ok_or_else(truncated)?;
+    *input = &input[4 + len..];
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+    use pretty_assertions::assert_eq;
+
+    // produced by OpenSSH from the test pair
+    const OPENSSH_KEY: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAABQCWaPcB";
+
+    #[test]
+    fn test_openssh() {
+        let public_key = &test_pair().public_key;
+        assert_eq!(
+            public_key.to_openssh("user@host").unwrap(),
+            format!("{OPENSSH_KEY} user@host\n")
+        );
+        assert_eq!(
+            public_key.to_openssh("").unwrap(),
+            format!("{OPENSSH_KEY}\n")
+        );
+
+        let (key, comment) = Key::from_openssh(&format!("{OPENSSH_KEY} my key\n")).unwrap();
+        assert_eq!(&key, public_key);
+        assert_eq!(comment, "my key");
+        let (_, comment) = Key::from_openssh(OPENSSH_KEY).unwrap();
+        assert_eq!(comment, "");
+
+        let (ndex_key, _) =
+            Key::from_openssh("ssh-rsa AAAAB3NzaC1yc2EAAAACW5cAAAAEEcaMdQ==").unwrap();
+        assert_eq!(ndex_key.exponent, BigUint::from(0x5B97u32));
+        assert_eq!(ndex_key.modulus, BigUint::from(0x11C6_8C75u64));
+    }
+
+    #[test]
+    fn test_openssh_error() {
+        assert!(test_pair().private_key.to_openssh("").is_err());
+        assert!(Key::from_openssh("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5").is_err());
+        assert!(Key::from_openssh("ssh-rsa AAAA?3NzaC1yc2E").is_err());
+        // truncated blob
+        assert!(Key::from_openssh("ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAABQCW").is_err());
+        // blob of another key type
+        assert!(Key::from_openssh("ssh-rsa AAAAB3NzaC1kc3MAAAADAQABAAAABQCWaPcB").is_err());
+    }
+}
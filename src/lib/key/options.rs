@@ -0,0 +1,126 @@
+use super::{Key, KeySize};
+use crate::config::Config;
+use crate::error::{RsaError, RsaResult};
+
+/// Options of [`super::KeyPair::generate_with_options()`],
+/// which can be read from the `key_size` and `default_exponent` fields of a [`Config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyGenOptions {
+    /// Key size in bits, 4096 if absent.
+    pub key_size_bits: Option<KeySize>,
+    /// Exponent used when `use_default_exponent` is set, 65537 unless configured.
+    pub default_exponent: u32,
+    /// Uses `default_exponent` instead of a random prime exponent.
+    pub use_default_exponent: bool,
+}
+
+impl Default for KeyGenOptions {
+    fn default() -> Self {
+        Self {
+            key_size_bits: None,
+            default_exponent: Key::DEFAULT_EXPONENT,
+            use_default_exponent: true,
+        }
+    }
+}
+
+impl KeyGenOptions {
+    /// Reads the options from the config file and environment, see [`Config::load()`].
+    ///
+    /// # Errors
+    /// - If the config is not valid, see [`Config::from_config_str()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn load() -> RsaResult<Self> {
+        Self::from_config(&Config::load()?)
+    }
+
+    /// Options from the fields of `config`, missing fields keep their default value.
+    ///
+    /// ```
+    /// use rrsa_lib::config::Config;
+    /// use rrsa_lib::key::{KeyGenOptions, KeySize};
+    ///
+    /// let config = Config::from_config_str("key_size = 2048\ndefault_exponent = 3\n").unwrap();
+    /// let options = KeyGenOptions::from_config(&config).unwrap();
+    /// assert_eq!(options.key_size_bits, Some(KeySize::B2048));
+    /// assert_eq!(options.default_exponent, 3);
+    /// ```
+    ///
+    /// # Errors
+    /// If the options are invalid, see [`KeyGenOptions::validate()`].
+    pub fn from_config(config: &Config) -> RsaResult<Self> {
+        let options = Self {
+            key_size_bits: config.key_size,
+            default_exponent: config.default_exponent.unwrap_or(Key::DEFAULT_EXPONENT),
+            ..Self::default()
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Checks the default exponent can be a public exponent for the key size.
+    ///
+    /// # Errors
+    /// - If the default exponent is not odd and at least 3.
+    /// - If the default exponent is too large for the key size.
+    pub fn validate(&self) -> RsaResult<()> {
+        let key_size = self.key_size_bits.unwrap_or_default().bits();
+        if self.default_exponent < 3 || self.default_exponent.is_multiple_of(2) {
+            return Err(RsaError::UnknownError(format!(
+                "the default exponent {} must be odd and at least 3",
+                self.default_exponent
+            )));
+        }
+        // Tot(N) has at least `key_size - 2` bits
+        let exponent_bits = u32::BITS - self.default_exponent.leading_zeros();
+        if exponent_bits + 2 > u32::from(key_size) {
+            return Err(RsaError::UnknownError(format!(
+                "the default exponent {} is too large for {key_size} bit keys",
+                self.default_exponent
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keygen_options_from_config() {
+        assert_eq!(
+            KeyGenOptions::from_config(&Config::default()).unwrap(),
+            KeyGenOptions::default()
+        );
+        let config = Config {
+            key_size: Some(KeySize::B512),
+            default_exponent: Some(17),
+            ..Config::default()
+        };
+        assert_eq!(
+            KeyGenOptions::from_config(&config).unwrap(),
+            KeyGenOptions {
+                key_size_bits: Some(KeySize::B512),
+                default_exponent: 17,
+                use_default_exponent: true,
+            }
+        );
+
+        for (key_size, default_exponent) in [
+            (None, 1),
+            (None, 65536),
+            (Some(KeySize::custom(32).unwrap()), u32::MAX),
+        ] {
+            let config = Config {
+                key_size,
+                default_exponent: Some(default_exponent),
+                ..Config::default()
+            };
+            assert!(
+                KeyGenOptions::from_config(&config).is_err(),
+                "{default_exponent}"
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Minimal ASN.1 DER encoding and decoding, only supporting
+//! a `SEQUENCE` of non negative `INTEGER`s, which is all PKCS#1 keys need.
+
+use crate::error::{RsaError, RsaResult};
+use num_bigint::BigUint;
+
+pub(super) const SEQUENCE_TAG: u8 = 0x30;
+const INTEGER_TAG: u8 = 0x02;
+
+fn improperly_formatted(reason: &str) -> RsaError {
+    RsaError::ImproperlyFormattedStr(format!("because the DER {reason}"))
+}
+
+fn encode_length(len: usize, output: &mut Vec<u8>) {
+    if len < 0x80 {
+        // short form, fits in 7 bits
+        #[allow(clippy::cast_possible_truncation)]
+        output.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let significant = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+        #[allow(clippy::cast_possible_truncation)]
+        output.push(0x80 | significant.len() as u8);
+        output.extend_from_slice(significant);
+    }
+}
+
+fn encode_integer(value: &BigUint, output: &mut Vec<u8>) {
+    let mut content = value.to_bytes_be();
+    // a set high bit would make the integer negative
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0x00);
+    }
+    output.push(INTEGER_TAG);
+    encode_length(content.len(), output);
+    output.extend_from_slice(&content);
+}
+
+/// Encodes `integers` as a DER `SEQUENCE` of `INTEGER`s.
+pub(super) fn encode_sequence(integers: &[&BigUint]) -> Vec<u8> {
+    let mut content = Vec::new();
+    for integer in integers {
+        encode_integer(integer, &mut content);
+    }
+    let mut output = vec![SEQUENCE_TAG];
+    encode_length(content.len(), &mut output);
+    output.extend_from_slice(&content);
+    output
+}
+
+/// Reads a tag and length, returning the content and the remaining input.
+fn decode_element(input: &[u8], tag: u8) -> RsaResult<(&[u8], &[u8])> {
+    let (&found_tag, rest) = input
+        .split_first()
+        .ok_or_else(|| improperly_formatted("ended unexpectedly"))?;
+    if found_tag != tag {
+        return Err(improperly_formatted("had an unexpected tag"));
+    }
+    let (&first, mut rest) = rest
+        .split_first()
+        .ok_or_else(|| improperly_formatted("ended unexpectedly"))?;
+
+    let len = if first < 0x80 {
+        usize::from(first)
+    } else {
+        let len_bytes = usize::from(first & 0x7F);
+        if len_bytes == 0 || len_bytes > std::mem::size_of::<usize>() || rest.len() < len_bytes {
+            return Err(improperly_formatted("had an invalid length"));
+        }
+        let (len, after_len) = rest.split_at(len_bytes);
+        rest = after_len;
+        len.iter()
+            .fold(0usize, |len, byte| (len << 8) | usize::from(*byte))
+    };
+
+    if rest.len() < len {
+        return Err(improperly_formatted("ended unexpectedly"));
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Decodes a DER `SEQUENCE` of non negative `INTEGER`s, which must span all of `input`.
+pub(super) fn decode_sequence(input: &[u8]) -> RsaResult<Vec<BigUint>> {
+    let (mut content, rest) = decode_element(input, SEQUENCE_TAG)?;
+    if !rest.is_empty() {
+        return Err(improperly_formatted("had trailing data"));
+    }
+
+    let mut integers = Vec::new();
+    while !content.is_empty() {
+        let (integer, rest) = decode_element(content, INTEGER_TAG)?;
+        match integer.first() {
+            None => return Err(improperly_formatted("had an empty integer")),
+            Some(byte) if byte & 0x80 != 0 => {
+                return Err(improperly_formatted("had a negative integer"))
+            }
+            Some(_) => integers.push(BigUint::from_bytes_be(integer)),
+        }
+        content = rest;
+    }
+    Ok(integers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_der_sequence() {
+        let small = BigUint::from(0x7Fu8);
+        let high_bit = BigUint::from(0x80u8);
+        let zero = BigUint::from(0u8);
+        let der = encode_sequence(&[&small, &high_bit, &zero]);
+        assert_eq!(
+            der,
+            [0x30, 0x0A, 0x02, 0x01, 0x7F, 0x02, 0x02, 0x00, 0x80, 0x02, 0x01, 0x00]
+        );
+        assert_eq!(decode_sequence(&der).unwrap(), [small, high_bit, zero]);
+
+        // long form length
+        let big = BigUint::from_bytes_be(&[0x01; 200]);
+        let der = encode_sequence(&[&big]);
+        assert_eq!(&der[..7], &[0x30, 0x81, 0xCB, 0x02, 0x81, 0xC8, 0x01]);
+        assert_eq!(decode_sequence(&der).unwrap(), [big]);
+
+        // negative integer, truncated, trailing data and wrong tag
+        assert!(decode_sequence(&[0x30, 0x03, 0x02, 0x01, 0xFF]).is_err());
+        assert!(decode_sequence(&[0x30, 0x03, 0x02, 0x01]).is_err());
+        assert!(decode_sequence(&[0x30, 0x03, 0x02, 0x01, 0x01, 0x00]).is_err());
+        assert!(decode_sequence(&[0x31, 0x03, 0x02, 0x01, 0x01]).is_err());
+    }
+}
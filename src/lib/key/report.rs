@@ -0,0 +1,260 @@
+//! Reports describing Keys and their validation, displayed as text
+//! or formatted as JSON, e.g. to embed key metadata in documentation.
+
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+use std::fmt;
+
+use super::{Key, KeyMetadata, KeyPair, KeyVariant};
+
+/// Metadata of a [`Key`], returned by [`Key::report()`].
+///
+/// Never holds the private exponent, so it can be shared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyReport {
+    pub variant: KeyVariant,
+    /// Size of the modulus in bits.
+    pub bits: u64,
+    /// Exponent of a Public Key, or of the pair of a Private Key carrying its CRT components,
+    /// `None` for any other Private Key.
+    pub public_exponent: Option<BigUint>,
+    /// See [`Key::fingerprint_hex()`].
+    pub fingerprint: String,
+    /// Whether a Private Key carries its CRT components.
+    pub crt: bool,
+    /// See [`Key::metadata()`].
+    pub metadata: KeyMetadata,
+}
+
+impl Key {
+    /// Returns the [`KeyReport`] of this Key.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    ///
+    /// let report = KeyPair::demo().public_key.report();
+    /// assert_eq!(report.bits, 1024);
+    /// assert_eq!(report.to_json()["variant"], "public");
+    /// println!("{report}");
+    /// ```
+    #[must_use]
+    pub fn report(&self) -> KeyReport {
+        KeyReport {
+            variant: self.variant,
+            bits: self.modulus.bits(),
+            public_exponent: if self.is_public() {
+                Some(self.exponent.clone())
+            } else {
+                self.crt.as_ref().map(|crt| crt.public_exponent.clone())
+            },
+            fingerprint: self.fingerprint_hex(),
+            crt: self.crt.is_some(),
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+impl KeyReport {
+    /// Whether the public exponent is [`Key::DEFAULT_EXPONENT`], `None` if it is unknown.
+    #[must_use]
+    pub fn default_exponent(&self) -> Option<bool> {
+        self.public_exponent
+            .as_ref()
+            .map(|exponent| *exponent == BigUint::from(Key::DEFAULT_EXPONENT))
+    }
+
+    /// Formats this [`KeyReport`] as a JSON object, with the same member names as the fields
+    /// plus `default_exponent`, and the fields of the metadata instead of `metadata`.
+    ///
+    /// The variant is `public` or `private`, and the public exponent a decimal string
+    /// (or `null`), as it can be larger than JSON numbers.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "variant": match self.variant {
+                KeyVariant::PublicKey => "public",
+                KeyVariant::PrivateKey => "private",
+            },
+            "bits": self.bits,
+            "public_exponent": self.public_exponent.as_ref().map(ToString::to_string),
+            "default_exponent": self.default_exponent(),
+            "fingerprint": self.fingerprint,
+            "crt": self.crt,
+            "comment": self.metadata.comment,
+            "created_at": self.metadata.created_at,
+            "generator": self.metadata.generator,
+        })
+    }
+}
+
+impl fmt::Display for KeyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variant {
+            KeyVariant::PublicKey => writeln!(f, "Variant: Public Key")?,
+            KeyVariant::PrivateKey => writeln!(f, "Variant: Private Key")?,
+        }
+        writeln!(f, "Size: {} bits", self.bits)?;
+        match (&self.public_exponent, self.default_exponent()) {
+            (Some(exponent), Some(true)) => writeln!(f, "Public exponent: {exponent} (default)")?,
+            (Some(exponent), _) => writeln!(f, "Public exponent: {exponent} (not default)")?,
+            (None, _) => writeln!(f, "Public exponent: unknown")?,
+        }
+        if self.variant == KeyVariant::PrivateKey {
+            writeln!(f, "CRT components: {}", if self.crt { "yes" } else { "no" })?;
+        }
+        write!(f, "{}", self.metadata)?;
+        write!(f, "Fingerprint: {}", self.fingerprint)
+    }
+}
+
+/// Result of validating a Public Key, a Private Key, or both as a pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub public_key: Option<KeyReport>,
+    pub private_key: Option<KeyReport>,
+    /// Why the Keys are not valid, `None` if they are.
+    pub problem: Option<String>,
+}
+
+impl ValidationReport {
+    /// Checks each Key is of the expected [`KeyVariant`],
+    /// and if both are given, that they form a valid [`KeyPair`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::{KeyPair, KeySize, ValidationReport};
+    ///
+    /// let pair = KeyPair::builder().size(KeySize::B512).build().unwrap();
+    /// let report = ValidationReport::new(Some(pair.public_key), Some(pair.private_key));
+    /// assert!(report.is_valid());
+    /// assert_eq!(report.to_string(), "Key Pair is valid!");
+    /// ```
+    #[must_use]
+    pub fn new(public_key: Option<Key>, private_key: Option<Key>) -> Self {
+        let mut report = ValidationReport {
+            public_key: public_key.as_ref().map(Key::report),
+            private_key: private_key.as_ref().map(Key::report),
+            problem: None,
+        };
+        report.problem = match (public_key, private_key) {
+            (None, None) => Some("No Key to validate".into()),
+            (Some(public_key), None) => {
+                (!public_key.is_public()).then(|| "Public Key is actually a Private Key".into())
+            }
+            (None, Some(private_key)) => {
+                (!private_key.is_private()).then(|| "Private Key is actually a Public Key".into())
+            }
+            (Some(public_key), Some(private_key)) => {
+                let pair = KeyPair {
+                    public_key,
+                    private_key,
+                };
+                (!pair.is_valid()).then(|| "Key Pair is not valid!".into())
+            }
+        };
+        report
+    }
+
+    /// Returns `true` if the Keys are valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.problem.is_none()
+    }
+
+    /// Formats this [`ValidationReport`] as a JSON object, with the same member names
+    /// as the fields plus `valid`, and the Keys formatted by [`KeyReport::to_json()`].
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "valid": self.is_valid(),
+            "problem": self.problem,
+            "public_key": self.public_key.as_ref().map(KeyReport::to_json),
+            "private_key": self.private_key.as_ref().map(KeyReport::to_json),
+        })
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// Either the problem, or which Keys are valid.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.problem, &self.public_key, &self.private_key) {
+            (Some(problem), _, _) => write!(f, "{problem}"),
+            (None, Some(_), Some(_)) => write!(f, "Key Pair is valid!"),
+            (None, Some(_), None) => write!(f, "Public Key is valid!"),
+            (None, None, _) => write!(f, "Private Key is valid!"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+    use std::str::FromStr;
+
+    fn reparse(key: &Key) -> Key {
+        Key::from_str(&key.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_reports() {
+        let pair = test_pair();
+        let public_report = pair.public_key.report();
+        assert_eq!(public_report.bits, pair.public_key.modulus.bits());
+        assert_eq!(
+            public_report.public_exponent.as_ref(),
+            Some(&pair.public_key.exponent)
+        );
+        assert_eq!(
+            public_report.fingerprint,
+            pair.private_key.fingerprint_hex()
+        );
+        let private_report = pair.private_key.report();
+        assert_eq!(private_report.public_exponent, None);
+        assert_eq!(private_report.to_json()["public_exponent"], Value::Null);
+        assert_eq!(private_report.default_exponent(), None);
+        assert!(private_report.to_string().contains("CRT components: no"));
+        assert_eq!(public_report.default_exponent(), Some(true));
+        assert_eq!(public_report.to_json()["default_exponent"], true);
+        assert!(!public_report.to_string().contains("CRT components"));
+        assert_eq!(public_report.to_json()["comment"], Value::Null);
+
+        let mut commented = reparse(&pair.public_key);
+        commented.metadata_mut().comment = Some("laptop".into());
+        commented.metadata_mut().created_at = Some(1_700_000_000);
+        let commented_report = commented.report();
+        assert!(commented_report
+            .to_string()
+            .contains("Comment: laptop\nCreated: 1700000000\n"));
+        assert_eq!(commented_report.to_json()["comment"], "laptop");
+        assert_eq!(commented_report.to_json()["created_at"], 1_700_000_000);
+
+        let demo_report = KeyPair::demo().private_key.report();
+        assert!(demo_report.crt);
+        assert_eq!(
+            demo_report.public_exponent,
+            Some(BigUint::from(Key::DEFAULT_EXPONENT))
+        );
+
+        let report = ValidationReport::new(
+            Some(reparse(&pair.public_key)),
+            Some(reparse(&pair.private_key)),
+        );
+        assert!(report.is_valid());
+        assert_eq!(report.to_json()["valid"], true);
+        assert_eq!(report.to_json()["public_key"], public_report.to_json());
+        assert_eq!(
+            ValidationReport::new(Some(reparse(&pair.public_key)), None).to_string(),
+            "Public Key is valid!"
+        );
+
+        let report = ValidationReport::new(Some(reparse(&pair.private_key)), None);
+        assert!(!report.is_valid());
+        assert_eq!(report.to_string(), "Public Key is actually a Private Key");
+        let report = ValidationReport::new(
+            Some(reparse(&pair.private_key)),
+            Some(reparse(&pair.public_key)),
+        );
+        assert_eq!(report.to_json()["problem"], "Key Pair is not valid!");
+        assert!(!ValidationReport::new(None, None).is_valid());
+    }
+}
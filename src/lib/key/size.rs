@@ -0,0 +1,98 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::{RsaError, RsaResult};
+
+/// Size in bits of the modulus of a generated [`super::KeyPair`].
+///
+/// Always even and in the (32, 4096) interval, so both primes get the same amount of bits.
+///
+/// ```
+/// use rrsa_lib::key::KeySize;
+///
+/// assert_eq!(KeySize::B2048.bits(), 2048);
+/// assert_eq!(KeySize::custom(768).unwrap().bits(), 768);
+/// assert!(KeySize::custom(767).is_err());
+/// assert_eq!("1024".parse::<KeySize>().unwrap(), KeySize::B1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeySize(u16);
+
+impl KeySize {
+    /// Smallest supported size.
+    pub const MIN: KeySize = KeySize(32);
+    /// Largest supported size.
+    pub const MAX: KeySize = KeySize(4096);
+    pub const B512: KeySize = KeySize(512);
+    pub const B1024: KeySize = KeySize(1024);
+    pub const B2048: KeySize = KeySize(2048);
+    pub const B3072: KeySize = KeySize(3072);
+    pub const B4096: KeySize = KeySize(4096);
+
+    /// Validates a size in bits.
+    ///
+    /// # Errors
+    /// [`RsaError::InvalidKeySize`] if `bits` is odd or not in (32, 4096) interval.
+    pub fn custom(bits: u32) -> RsaResult<Self> {
+        match u16::try_from(bits) {
+            Ok(size)
+                if (KeySize::MIN.0..=KeySize::MAX.0).contains(&size) && size.is_multiple_of(2) =>
+            {
+                Ok(KeySize(size))
+            }
+            _ => Err(RsaError::InvalidKeySize(bits)),
+        }
+    }
+
+    /// Returns the size in bits.
+    #[must_use]
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+}
+
+impl Default for KeySize {
+    fn default() -> Self {
+        KeySize::B4096
+    }
+}
+
+impl fmt::Display for KeySize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for KeySize {
+    type Err = RsaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bits = s
+            .trim()
+            .parse()
+            .map_err(|_| RsaError::UnknownError(format!("`{s}` is not a key size in bits")))?;
+        KeySize::custom(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_size() {
+        for bits in [32, 34, 768, 4096] {
+            assert_eq!(u32::from(KeySize::custom(bits).unwrap().bits()), bits);
+        }
+        for bits in [0, 30, 33, 4095, 4098, 65_568, u32::MAX] {
+            assert!(matches!(
+                KeySize::custom(bits),
+                Err(RsaError::InvalidKeySize(size)) if size == bits
+            ));
+        }
+        assert_eq!(KeySize::default(), KeySize::B4096);
+        assert_eq!(KeySize::B3072.to_string(), "3072");
+        assert_eq!(" 512 ".parse::<KeySize>().unwrap(), KeySize::B512);
+        assert!("big".parse::<KeySize>().is_err());
+        assert!("-2048".parse::<KeySize>().is_err());
+    }
+}
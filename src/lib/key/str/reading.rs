@@ -1,5 +1,7 @@
 use crate::error::{RsaError, RsaResult};
-use crate::key::{Key, KeyVariant};
+use crate::key::{CrtComponents, Key, KeyMetadata, KeyPair, KeyVariant};
+use crate::math::wipe;
+use crate::version;
 use num_bigint::BigUint;
 use num_traits::Num;
 use regex::Regex;
@@ -10,13 +12,39 @@ impl FromStr for Key {
 
     /// Extracts a [`Key`] from the given string slice,
     /// that represented the file content of it.
+    ///
+    /// PKCS#1 PEM keys and OpenSSH public keys are also accepted,
+    /// see [`Key::from_pkcs1_pem()`] and [`Key::from_openssh()`].
+    ///
+    /// If the last line is a checksum, as written by [`Key::to_string_with_checksum()`],
+    /// it must match the rest of the string. The values must pass [`Key::try_new()`].
+    ///
+    /// The [`KeyMetadata`] fields are read into [`Key::metadata()`],
+    /// as is the comment of an OpenSSH public key.
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::str::FromStr;
+    ///
+    /// let key = Key::from_str("rrsa 9668f701\n").unwrap();
+    /// assert_eq!(key, KeyPair::demo_tiny().public_key);
+    /// assert_eq!(key.to_string(), "rrsa 9668f701\n");
+    /// assert!(Key::from_str("not a key").is_err());
+    /// ```
     fn from_str(s: &str) -> RsaResult<Self> {
+        let s = Key::strip_checksum(s)?;
         if s.starts_with(Key::PUBLIC_KEY_NDEX_HEADER) {
-            Key::public_ndex_key_from_str(s)
+            Key::with_public_metadata(s, Key::public_ndex_key_from_str)
         } else if s.starts_with(Key::PUBLIC_KEY_NORMAL_HEADER) {
-            Key::public_dex_key_from_str(s)
+            Key::with_public_metadata(s, Key::public_dex_key_from_str)
         } else if s.starts_with(Key::PRIVATE_KEY_HEADER) {
             Key::private_key_from_str(s)
+        } else if s.starts_with(Key::PKCS1_PUBLIC_KEY_HEADER)
+            || s.starts_with(Key::PKCS1_PRIVATE_KEY_HEADER)
+        {
+            Key::from_pkcs1_pem(s)
+        } else if s.starts_with(Key::OPENSSH_KEY_TYPE) {
+            Key::from_openssh_keeping_comment(s)
         } else {
             Err(RsaError::ImproperlyFormattedStr(
                 "because it did not start with a correct header".into(),
@@ -25,7 +53,92 @@ impl FromStr for Key {
     }
 }
 
+impl FromStr for KeyPair {
+    type Err = RsaError;
+
+    /// Extracts a [`KeyPair`] from a Public Key followed by its Private Key,
+    /// each in any format accepted by [`Key::from_str()`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::str::FromStr;
+    ///
+    /// let contents = format!("{}{}", Key::DEMO_PUBLIC_KEY, Key::DEMO_PRIVATE_KEY);
+    /// assert_eq!(KeyPair::from_str(&contents).unwrap(), *KeyPair::demo());
+    /// assert!(KeyPair::from_str(Key::DEMO_PRIVATE_KEY).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// - If either Key is missing or not valid.
+    /// - If the Keys are not a valid pair, see [`KeyPair::is_valid()`].
+    fn from_str(s: &str) -> RsaResult<Self> {
+        let private_start = [Key::PRIVATE_KEY_HEADER, Key::PKCS1_PRIVATE_KEY_HEADER]
+            .iter()
+            .filter_map(|header| s.find(header))
+            .min()
+            .ok_or_else(|| {
+                RsaError::ImproperlyFormattedStr("because it had no private key".into())
+            })?;
+        let pair = KeyPair {
+            public_key: Key::from_str(s[..private_start].trim_start())?,
+            private_key: Key::from_str(&s[private_start..])?,
+        };
+        if !pair.is_valid() {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the public and private keys are not a valid pair".into(),
+            ));
+        }
+        Ok(pair)
+    }
+}
+
 impl Key {
+    /// Returns `s` without its checksum line, if it has one.
+    ///
+    /// # Errors
+    /// If the checksum is not valid hex, or does not match the CRC32 of the rest of `s`.
+    fn strip_checksum(s: &str) -> RsaResult<&str> {
+        let trimmed = s.trim_end();
+        let line_start = trimmed.rfind('\n').map_or(0, |i| i + 1);
+        let Some(checksum) = trimmed[line_start..]
+            .strip_prefix(Key::CHECKSUM_FIELD)
+            .and_then(|rest| rest.strip_prefix(Key::FIELD_SPLIT_STR))
+        else {
+            return Ok(s);
+        };
+        let body = &s[..line_start];
+        match u32::from_str_radix(checksum.trim(), 16) {
+            Ok(checksum) if checksum == crc32fast::hash(body.as_bytes()) => Ok(body),
+            Ok(_) => Err(RsaError::KeyChecksumMismatch),
+            Err(_) => Err(RsaError::ImproperlyFormattedStr(
+                "because the checksum is not valid hex".into(),
+            )),
+        }
+    }
+
+    /// Parses the first line of `s` with `parse`, and the following lines as [`KeyMetadata`].
+    ///
+    /// # Errors
+    /// If a following line is not a `Field: value` line, or a field is not valid.
+    fn with_public_metadata(s: &str, parse: fn(&str) -> RsaResult<Self>) -> RsaResult<Self> {
+        let (key_line, fields) = s.split_once('\n').unwrap_or((s, ""));
+        let mut key = parse(key_line)?;
+        for line in fields
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+        {
+            let (name, value) = line.split_once(Key::FIELD_SPLIT_STR).ok_or_else(|| {
+                RsaError::ImproperlyFormattedStr(format!(
+                    "because `{line}` is not a field of a public key"
+                ))
+            })?;
+            // unknown fields are skipped, for files written by newer versions
+            key.metadata.set_field(name, value)?;
+        }
+        Ok(key)
+    }
+
     fn public_ndex_key_from_str(s: &str) -> RsaResult<Self> {
         let reg = Regex::new(Key::KEY_FILE_STR_RADIX_REGEX).unwrap();
         let pieces: Vec<_> = s.split(Key::PUBLIC_KEY_SPLIT_CHAR).collect();
@@ -42,11 +155,11 @@ impl Key {
             ));
         }
 
-        Ok(Key {
-            exponent: BigUint::from_str_radix(pieces[2].trim(), Key::BIGUINT_STR_RADIX)?,
-            modulus: BigUint::from_str_radix(pieces[1].trim(), Key::BIGUINT_STR_RADIX)?,
-            variant: KeyVariant::PublicKey,
-        })
+        Key::try_new(
+            BigUint::from_str_radix(pieces[2].trim(), Key::BIGUINT_STR_RADIX)?,
+            BigUint::from_str_radix(pieces[1].trim(), Key::BIGUINT_STR_RADIX)?,
+            KeyVariant::PublicKey,
+        )
     }
 
     fn public_dex_key_from_str(s: &str) -> RsaResult<Self> {
@@ -65,50 +178,117 @@ impl Key {
             ));
         }
 
-        Ok(Key {
-            exponent: BigUint::from(Key::DEFAULT_EXPONENT),
-            modulus: BigUint::from_str_radix(pieces[1].trim(), Key::BIGUINT_STR_RADIX)?,
-            variant: KeyVariant::PublicKey,
-        })
+        Key::try_new(
+            BigUint::from(Key::DEFAULT_EXPONENT),
+            BigUint::from_str_radix(pieces[1].trim(), Key::BIGUINT_STR_RADIX)?,
+            KeyVariant::PublicKey,
+        )
     }
 
     fn private_key_from_str(s: &str) -> RsaResult<Self> {
         let reg = Regex::new(Key::KEY_FILE_STR_RADIX_REGEX).unwrap();
-        let pieces: Vec<_> = s.split(Key::PRIVATE_KEY_SPLIT_CHAR).collect();
+        let mut pieces: Vec<_> = s.split(Key::PRIVATE_KEY_SPLIT_CHAR).collect();
 
         // example: r"
         // -----BEGIN RSA-RUST PRIVATE KEY-----
+        // Format: 1
+        // Producer: rrsa 0.2.0
         // 9668f701
         // 147b7f71
         // -----END RSA-RUST PRIVATE KEY-----
         // "
-        if pieces.len() != 5 {
+        // format 2 keys have the CRT components after the private exponent
+        // keys written before the format was versioned have no fields
+        let fields: Vec<&str> = pieces
+            .iter()
+            .skip(1)
+            .take_while(|piece| piece.contains(Key::FIELD_SPLIT_STR))
+            .copied()
+            .collect();
+        let mut metadata = KeyMetadata::default();
+        for field in &fields {
+            match field.split_once(Key::FIELD_SPLIT_STR) {
+                Some((Key::FORMAT_FIELD, found)) => {
+                    version::check_supported("private key", found, Key::FORMAT_VERSION)?;
+                }
+                Some((name, value)) => {
+                    metadata.set_field(name, value)?;
+                }
+                None => {}
+            }
+        }
+        pieces.drain(1..=fields.len());
+
+        // P, Q, D mod (P-1), D mod (Q-1) and Q^-1 mod P
+        let value_count = pieces.len().saturating_sub(3);
+        if value_count != 2 && value_count != 7 {
             return Err(RsaError::ImproperlyFormattedStr(
                 "because it had the wrong number of pieces for a private key".into(),
             ));
         }
-        if pieces[0] != Key::PRIVATE_KEY_HEADER || pieces[3] != Key::PRIVATE_KEY_FOOTER {
+        let footer_index = value_count + 1;
+        if pieces[0] != Key::PRIVATE_KEY_HEADER || pieces[footer_index] != Key::PRIVATE_KEY_FOOTER {
             return Err(RsaError::ImproperlyFormattedStr(
                 "because it didn't have correct header and/or footer for a private key".into(),
             ));
         }
-        if !reg.is_match(pieces[1].trim()) || !reg.is_match(pieces[2].trim()) {
+        if pieces[1..footer_index]
+            .iter()
+            .any(|piece| !reg.is_match(piece.trim()))
+        {
             return Err(RsaError::ImproperlyFormattedStr(
                 "because the exponent and/or modulus values had invalid characters".into(),
             ));
         }
 
-        Ok(Key {
-            exponent: BigUint::from_str_radix(pieces[2].trim(), Key::BIGUINT_STR_RADIX)?,
-            modulus: BigUint::from_str_radix(pieces[1].trim(), Key::BIGUINT_STR_RADIX)?,
-            variant: KeyVariant::PrivateKey,
-        })
+        let mut values = pieces[1..footer_index]
+            .iter()
+            .map(|piece| BigUint::from_str_radix(piece.trim(), Key::BIGUINT_STR_RADIX))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut key = Key::try_new(values[1].clone(), values[0].clone(), KeyVariant::PrivateKey)?;
+        if let [_, _, p, q, exponent_p, exponent_q, coefficient] = values.as_slice() {
+            key.crt = Some(CrtComponents::from_stored(
+                &key,
+                p,
+                q,
+                exponent_p,
+                exponent_q,
+                coefficient,
+            )?);
+        }
+        values.iter_mut().for_each(wipe);
+        key.metadata = metadata;
+        Ok(key)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::key::tests::test_pair;
+
+    #[test]
+    fn test_key_pair_from_str() {
+        let pair = test_pair();
+        let contents = format!("{}{}", pair.public_key, pair.private_key);
+        assert_eq!(KeyPair::from_str(&contents).unwrap(), *pair);
+        let pkcs1 = format!(
+            "{}{}",
+            pair.public_key.to_string_with_checksum(),
+            pair.private_key.to_pkcs1_pem().unwrap()
+        );
+        assert_eq!(KeyPair::from_str(&pkcs1).unwrap(), *pair);
+
+        // missing, swapped or unrelated keys
+        for invalid in [
+            pair.public_key.to_string(),
+            pair.private_key.to_string(),
+            format!("{}{}", pair.private_key, pair.public_key),
+            format!("{}{}", KeyPair::demo().public_key, pair.private_key),
+        ] {
+            assert!(KeyPair::from_str(&invalid).is_err(), "{invalid}");
+        }
+    }
 
     #[test]
     fn test_key_from_str_error() {
@@ -152,19 +332,104 @@ mod tests {
         assert!(Key::from_str(key_str).is_err());
 
         // correct public ndex
-        let key_str = "rrsa-ndex 23424 14143\n";
+        let key_str = "rrsa-ndex 11c68c75 5b97\n";
         assert!(Key::from_str(key_str).is_ok());
 
         // correct public dex
-        let key_str = "rrsa 23424\n";
+        let key_str = "rrsa 9668f701\n";
+        assert!(Key::from_str(key_str).is_ok());
+
+        // well formatted, but with an even modulus
+        assert!(matches!(
+            Key::from_str("rrsa-ndex 23424 14143\n"),
+            Err(RsaError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            Key::from_str("rrsa 23424\n"),
+            Err(RsaError::InvalidKey(_))
+        ));
+
+        // correct PKCS#1 public
+        let key_str =
+            "-----BEGIN RSA PUBLIC KEY-----\nMAwCBQCWaPcBAgMBAAE=\n-----END RSA PUBLIC KEY-----\n";
+        assert!(Key::from_str(key_str).is_ok());
+
+        // correct OpenSSH public
+        let key_str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAAABQCWaPcB user@host\n";
+        assert!(Key::from_str(key_str).is_ok());
+
+        // correct private, written before the format was versioned
+        let key_str = r"-----BEGIN RSA-RUST PRIVATE KEY-----
+9668f701
+147b7f71
+-----END RSA-RUST PRIVATE KEY-----
+";
         assert!(Key::from_str(key_str).is_ok());
 
         // correct private
         let key_str = r"-----BEGIN RSA-RUST PRIVATE KEY-----
+Format: 1
+Producer: rrsa 0.2.0
 9668f701
 147b7f71
 -----END RSA-RUST PRIVATE KEY-----
 ";
         assert!(Key::from_str(key_str).is_ok());
+
+        // private produced by a newer version
+        assert!(matches!(
+            Key::from_str(&key_str.replace("Format: 1", "Format: 3")),
+            Err(RsaError::UnsupportedVersion { found: 3, .. })
+        ));
+
+        // correct private with CRT components
+        let key_str = r"-----BEGIN RSA-RUST PRIVATE KEY-----
+Format: 2
+Producer: rrsa 0.2.0
+9668f701
+147b7f71
+cce5
+bbed
+60f5
+97d
+2cc3
+-----END RSA-RUST PRIVATE KEY-----
+";
+        assert!(Key::from_str(key_str).unwrap().has_crt());
+
+        // primes do not multiply to the modulus
+        assert!(Key::from_str(&key_str.replace("cce5", "cce7")).is_err());
+
+        // inconsistent CRT exponent
+        assert!(Key::from_str(&key_str.replace("60f5", "60f7")).is_err());
+
+        // wrong qnt of pieces for CRT components
+        assert!(Key::from_str(&key_str.replace("2cc3\n", "")).is_err());
+    }
+
+    #[test]
+    fn test_key_from_str_checksum() {
+        for key in [&test_pair().public_key, &test_pair().private_key] {
+            let contents = key.to_string_with_checksum();
+            assert!(contents.lines().last().unwrap().starts_with("Checksum: "));
+            assert_eq!(Key::from_str(&contents).unwrap(), *key);
+            // trailing whitespace is ignored
+            assert_eq!(Key::from_str(&format!("{contents}\n\n")).unwrap(), *key);
+
+            // modified
+            assert!(matches!(
+                Key::from_str(&contents.replacen("9668f701", "9668f711", 1)),
+                Err(RsaError::KeyChecksumMismatch)
+            ));
+            // invalid checksum
+            assert!(Key::from_str(&format!("{key}Checksum: xyz\n")).is_err());
+        }
+
+        // truncated
+        let contents = test_pair().private_key.to_string_with_checksum();
+        assert!(matches!(
+            Key::from_str(&contents.replacen("147b7f71\n", "", 1)),
+            Err(RsaError::KeyChecksumMismatch)
+        ));
     }
 }
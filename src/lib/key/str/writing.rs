@@ -1,4 +1,5 @@
 use crate::key::{IsDefaultExponent, Key, KeyVariant};
+use crate::version;
 use std::fmt;
 
 impl Key {
@@ -12,11 +13,48 @@ impl Key {
     pub(crate) const PRIVATE_KEY_HEADER: &'static str = "-----BEGIN RSA-RUST PRIVATE KEY-----";
     pub(crate) const PRIVATE_KEY_FOOTER: &'static str = "-----END RSA-RUST PRIVATE KEY-----";
     pub(crate) const PRIVATE_KEY_SPLIT_CHAR: char = '\n';
+    /// Latest version of the Private Key format, bumped on any incompatible change.
+    ///
+    /// Version `2` adds the CRT components, Private Keys without them are still written as version `1`.
+    /// Public Keys are single line and unversioned.
+    pub const FORMAT_VERSION: u32 = 2;
+    pub(crate) const FORMAT_FIELD: &'static str = "Format";
+    pub(crate) const PRODUCER_FIELD: &'static str = "Producer";
+    pub(crate) const FIELD_SPLIT_STR: &'static str = ": ";
+    /// Optional last line of a key file, with the CRC32 of everything before it.
+    pub(crate) const CHECKSUM_FIELD: &'static str = "Checksum";
+
+    /// Same as [`Key::to_string()`], followed by a `Checksum` line
+    /// with the CRC32 of the key in hex, checked by [`Key::from_str()`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::{Key, KeyPair};
+    /// use std::str::FromStr;
+    ///
+    /// let public_key = &KeyPair::demo_tiny().public_key;
+    /// let contents = public_key.to_string_with_checksum();
+    /// assert_eq!(contents, "rrsa 9668f701\nChecksum: 1606aede\n");
+    /// assert_eq!(Key::from_str(&contents).unwrap(), *public_key);
+    /// assert!(Key::from_str(&contents.replace('f', "e")).is_err());
+    /// ```
+    #[must_use]
+    pub fn to_string_with_checksum(&self) -> String {
+        let body = self.to_string();
+        let checksum = crc32fast::hash(body.as_bytes());
+        format!(
+            "{body}{}{}{checksum:08x}\n",
+            Key::CHECKSUM_FIELD,
+            Key::FIELD_SPLIT_STR
+        )
+    }
 }
 
 impl fmt::Display for Key {
     /// Formats the given [`Key`] as a string,
     /// which can represent the file content of it.
+    ///
+    /// Its [`crate::key::KeyMetadata`] is written as field lines,
+    /// after the key line of a Public Key and after the header fields of a Private Key.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.variant {
             KeyVariant::PublicKey => {
@@ -27,7 +65,7 @@ impl fmt::Display for Key {
                         Key::PUBLIC_KEY_NORMAL_HEADER,
                         Key::PUBLIC_KEY_SPLIT_CHAR,
                         self.modulus.to_str_radix(Key::BIGUINT_STR_RADIX)
-                    )
+                    )?;
                 } else {
                     writeln!(
                         f,
@@ -37,21 +75,48 @@ impl fmt::Display for Key {
                         &self.modulus.to_str_radix(Key::BIGUINT_STR_RADIX),
                         Key::PUBLIC_KEY_SPLIT_CHAR,
                         &self.exponent.to_str_radix(Key::BIGUINT_STR_RADIX)
-                    )
+                    )?;
                 }
+                write!(f, "{}", self.metadata)
             }
             KeyVariant::PrivateKey => {
-                writeln!(
+                let (split_char, split_str) = (Key::PRIVATE_KEY_SPLIT_CHAR, Key::FIELD_SPLIT_STR);
+                write!(f, "{}{split_char}", Key::PRIVATE_KEY_HEADER)?;
+                let format_version = if self.crt.is_some() { 2 } else { 1 };
+                write!(
+                    f,
+                    "{}{split_str}{format_version}{split_char}",
+                    Key::FORMAT_FIELD,
+                )?;
+                write!(
                     f,
-                    "{}{}{}{}{}{}{}",
-                    Key::PRIVATE_KEY_HEADER,
-                    Key::PRIVATE_KEY_SPLIT_CHAR,
+                    "{}{split_str}{}{split_char}",
+                    Key::PRODUCER_FIELD,
+                    version::producer()
+                )?;
+                write!(f, "{}", self.metadata)?;
+                write!(
+                    f,
+                    "{}{split_char}{}{split_char}",
                     self.modulus.to_str_radix(Key::BIGUINT_STR_RADIX),
-                    Key::PRIVATE_KEY_SPLIT_CHAR,
-                    self.exponent.to_str_radix(Key::BIGUINT_STR_RADIX),
-                    Key::PRIVATE_KEY_SPLIT_CHAR,
-                    Key::PRIVATE_KEY_FOOTER
-                )
+                    self.exponent.to_str_radix(Key::BIGUINT_STR_RADIX)
+                )?;
+                if let Some(crt) = &self.crt {
+                    for value in [
+                        &crt.p,
+                        &crt.q,
+                        &crt.exponent_p,
+                        &crt.exponent_q,
+                        &crt.coefficient,
+                    ] {
+                        write!(
+                            f,
+                            "{}{split_char}",
+                            value.to_str_radix(Key::BIGUINT_STR_RADIX)
+                        )?;
+                    }
+                }
+                writeln!(f, "{}", Key::PRIVATE_KEY_FOOTER)
             }
         }
     }
@@ -63,28 +128,58 @@ mod tests {
     use crate::key::tests::test_pair;
     use num_bigint::BigUint;
     use pretty_assertions::assert_eq;
+    use std::str::FromStr;
 
     #[test]
     fn test_public_key_writing() {
         assert_eq!("rrsa 9668f701\n", test_pair().public_key.to_string());
 
-        let public_ndex_key = Key {
-            exponent: BigUint::from(0x5b97_u64),
-            modulus: BigUint::from(0x11c6_8c75_u64),
-            variant: KeyVariant::PublicKey,
-        };
+        let public_ndex_key = Key::try_new(
+            BigUint::from(0x5b97_u64),
+            BigUint::from(0x11c6_8c75_u64),
+            KeyVariant::PublicKey,
+        )
+        .unwrap();
         assert_eq!("rrsa-ndex 11c68c75 5b97\n", public_ndex_key.to_string());
     }
 
     #[test]
     fn test_private_key_writing() {
         assert_eq!(
-            r"-----BEGIN RSA-RUST PRIVATE KEY-----
+            format!(
+                "-----BEGIN RSA-RUST PRIVATE KEY-----
+Format: 1
+Producer: rrsa {}
 9668f701
 147b7f71
 -----END RSA-RUST PRIVATE KEY-----
 ",
+                version::CRATE_VERSION
+            ),
             test_pair().private_key.to_string()
         );
+
+        let mut crt_key = Key::from_str(&test_pair().private_key.to_string()).unwrap();
+        crt_key.precompute_crt().unwrap();
+        let formatted = crt_key.to_string();
+        assert_eq!(
+            format!(
+                "-----BEGIN RSA-RUST PRIVATE KEY-----
+Format: 2
+Producer: rrsa {}
+9668f701
+147b7f71
+cce5
+bbed
+60f5
+97d
+2cc3
+-----END RSA-RUST PRIVATE KEY-----
+",
+                version::CRATE_VERSION
+            ),
+            formatted
+        );
+        assert!(Key::from_str(&formatted).unwrap().has_crt());
     }
 }
@@ -1,11 +1,56 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(dead_code)]
-//! This crate is a toy implementation of RSA key generation, encryption and decryption.
+//! This crate is a toy implementation of RSA key generation, encryption, decryption and signatures.
 //!
 //! It should not be used for real world applications, given it has many security flaws and shortcomings.
+//!
+//! The examples use the fixed [`key::KeyPair::demo()`] keys, whose Private Keys are public.
+//!
+//! ```
+//! use rrsa_lib::encoding::{padding::PaddingScheme, Plaintext};
+//! use rrsa_lib::key::KeyPair;
+//! use rrsa_lib::signature::SignatureScheme;
+//!
+//! let pair = KeyPair::demo();
+//! let plaintext = Plaintext::from_bytes(b"Hello, rrsa!".to_vec());
+//! let ciphertext = pair.public_key.encrypt(&plaintext, PaddingScheme::Oaep).unwrap();
+//! assert_eq!(pair.private_key.decrypt(&ciphertext, PaddingScheme::Oaep).unwrap(), plaintext);
+//!
+//! let digest = SignatureScheme::Pss.hash().digest(plaintext.as_bytes());
+//! let signature = pair.private_key.sign(&digest, SignatureScheme::Pss).unwrap();
+//! assert!(pair.public_key.verify(&digest, &signature, SignatureScheme::Pss).unwrap());
+//! ```
 
+pub mod archive;
+pub mod atomic;
+pub mod capabilities;
+#[cfg(feature = "cli-test")]
+pub mod cli_test;
+pub mod config;
+pub mod corpus;
+#[cfg(unix)]
+pub mod daemon;
+pub mod dh;
+#[cfg(feature = "extras")]
+pub mod elgamal;
 pub mod encoding;
 pub mod error;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod jwt;
+pub mod kdf;
 pub mod key;
-mod math;
+pub mod math;
+pub mod message;
+pub mod ops;
+pub mod paths;
+pub mod progress;
+pub mod rng;
+pub mod secret;
+pub mod signature;
+pub mod testvectors;
+pub mod version;
+pub mod warning;
+
+pub use capabilities::capabilities;
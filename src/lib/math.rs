@@ -1,11 +1,18 @@
+//! Module with the number theory behind RSA: prime generation, modular exponentiation
+//! and the extended euclidean algorithm.
+
+use crate::error::{RsaError, RsaResult};
+use crate::rng::{SecureRandom, ThreadSource};
 use num_bigint::{BigInt, BigUint, RandBigInt};
 use num_traits::{One, Zero};
-use rand::prelude::ThreadRng;
+use std::sync::atomic::{compiler_fence, Ordering};
 
-pub struct PrimeGenerator {
+/// Generates random prime numbers, tested with Miller-Rabin,
+/// drawing candidates from `R`, the source of this thread unless given.
+pub struct PrimeGenerator<R = ThreadSource> {
+    rng: R,
     prime: BigUint,
     odd: BigUint,
-    rng: ThreadRng,
 }
 
 impl Default for PrimeGenerator {
@@ -15,13 +22,13 @@ impl Default for PrimeGenerator {
 }
 
 impl PrimeGenerator {
-    /// Returns new `PrimeGenerator` instance with `rng` member properly initialized.
+    /// Bases of the Miller-Rabin test, the first twelve primes.
+    const MILLER_RABIN_BASES: [u8; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    /// Returns new `PrimeGenerator` instance, drawing candidates from [`ThreadSource`].
     #[must_use]
     pub fn new() -> Self {
-        let prime = Zero::zero();
-        let odd = Zero::zero();
-        let rng = rand::thread_rng();
-        Self { prime, odd, rng }
+        Self::with_rng(ThreadSource)
     }
 
     #[allow(clippy::many_single_char_names)]
@@ -47,20 +54,33 @@ impl PrimeGenerator {
     /// Miller-Rabin primality test.
     ///
     /// **Returns** true if `n` is likely to be prime.
-    fn miller_rabin(n: &BigUint) -> bool {
+    ///
+    /// ```
+    /// use num_bigint::BigUint;
+    /// use rrsa_lib::math::PrimeGenerator;
+    ///
+    /// assert!(PrimeGenerator::miller_rabin(&BigUint::from(65_537u32)));
+    /// assert!(!PrimeGenerator::miller_rabin(&BigUint::from(561u16)));
+    /// ```
+    #[must_use]
+    pub fn miller_rabin(n: &BigUint) -> bool {
+        PrimeGenerator::miller_rabin_rounds(n, PrimeGenerator::MILLER_RABIN_BASES.len())
+    }
+
+    /// Miller-Rabin primality test, with only the first `rounds` bases.
+    pub(crate) fn miller_rabin_rounds(n: &BigUint, rounds: usize) -> bool {
         if *n < BigUint::from(2u8) {
             return false;
         }
 
         let mut r: BigUint = Zero::zero();
         let mut d: BigUint = n - 1u8;
-        let first_primes: [u8; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
 
         while !d.bit(0) {
             d >>= 1u8;
             r += 1u8;
         }
-        for a in first_primes {
+        for &a in PrimeGenerator::MILLER_RABIN_BASES.iter().take(rounds) {
             if *n == a.into() {
                 return true;
             }
@@ -70,15 +90,45 @@ impl PrimeGenerator {
         }
         true
     }
+}
 
+impl<R: SecureRandom> PrimeGenerator<R> {
+    /// Returns new `PrimeGenerator` instance, drawing candidates from `rng`,
+    /// e.g. `&mut rng` to borrow a generator of the caller.
+    ///
+    /// ```
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use rrsa_lib::math::PrimeGenerator;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let first = PrimeGenerator::with_rng(&mut rng).random_prime(64);
+    /// let second = PrimeGenerator::with_rng(StdRng::seed_from_u64(7)).random_prime(64);
+    /// assert_eq!(first, second);
+    /// assert!(PrimeGenerator::miller_rabin(&first));
+    /// ```
+    pub fn with_rng(rng: R) -> Self {
+        Self {
+            rng,
+            prime: Zero::zero(),
+            odd: Zero::zero(),
+        }
+    }
+
+    /// Returns a random probable prime of at most `max_bits` bits.
     pub fn random_prime(&mut self, max_bits: u16) -> BigUint {
+        self.random_prime_with_rounds(max_bits, PrimeGenerator::MILLER_RABIN_BASES.len())
+    }
+
+    /// Same as [`PrimeGenerator::random_prime()`], but testing candidates
+    /// with only `rounds` Miller-Rabin bases, so it may return a composite.
+    pub(crate) fn random_prime_with_rounds(&mut self, max_bits: u16, rounds: usize) -> BigUint {
         let low = BigUint::from(2u8);
         let max_num: BigUint = (BigUint::from(1u8) << max_bits) - 1u8;
         self.prime = self.rng.gen_biguint_range(&low, &max_num);
         // No even numbers are primes (except 2), saves rng.gen overhead
         self.prime.set_bit(0, true);
 
-        while !PrimeGenerator::miller_rabin(&self.prime) {
+        while !PrimeGenerator::miller_rabin_rounds(&self.prime, rounds) {
             self.prime += 2u8;
             if self.prime > max_num {
                 self.prime = self.rng.gen_biguint_range(&low, &max_num);
@@ -88,6 +138,39 @@ impl PrimeGenerator {
         self.prime.clone()
     }
 
+    /// Returns a random probable prime of exactly `bits` bits, with its two highest bits set,
+    /// so the product of two of them has exactly `2 * bits` bits, as RSA primes need.
+    ///
+    /// ```
+    /// use rrsa_lib::math::PrimeGenerator;
+    ///
+    /// let mut gen = PrimeGenerator::new();
+    /// let (p, q) = (gen.random_prime_of_bits(64), gen.random_prime_of_bits(64));
+    /// assert_eq!((p.bits(), q.bits()), (64, 64));
+    /// assert_eq!((p * q).bits(), 128);
+    /// ```
+    ///
+    /// # Panics
+    /// If `bits` is lower than 2.
+    pub fn random_prime_of_bits(&mut self, bits: u16) -> BigUint {
+        assert!(
+            bits >= 2,
+            "a prime with its two highest bits set has at least 2 bits"
+        );
+        let low = BigUint::from(3u8) << (bits - 2);
+        let high = BigUint::from(1u8) << bits;
+        loop {
+            self.prime = self.rng.gen_biguint_range(&low, &high);
+            self.prime.set_bit(0, true);
+            while self.prime < high {
+                if PrimeGenerator::miller_rabin(&self.prime) {
+                    return self.prime.clone();
+                }
+                self.prime += 2u8;
+            }
+        }
+    }
+
     #[allow(dead_code)]
     fn random_odd(&mut self, max_bits: u16) -> BigUint {
         let low = BigUint::from(3u8);
@@ -99,9 +182,20 @@ impl PrimeGenerator {
     }
 }
 
+/// Smallest modulus, in bits, for which [`mod_pow`] uses [`Montgomery`] multiplication,
+/// below it the conversions to and from Montgomery form cost more than they save.
+const MONTGOMERY_MIN_BITS: u64 = 256;
+
 /// Calculates Modular Exponent for given `base`, `exponent` and `modulus`.
+///
+/// Large odd moduli, which every RSA modulus is, use [`Montgomery`] multiplication.
 #[must_use]
 pub fn mod_pow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    if modulus.bits() >= MONTGOMERY_MIN_BITS {
+        if let Some(montgomery) = Montgomery::new(modulus) {
+            return montgomery.pow(base, exponent);
+        }
+    }
     let mut result = BigUint::from(1u8);
     let mut base_ = base % modulus;
     let mut exp = exponent.clone();
@@ -113,7 +207,76 @@ pub fn mod_pow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint
         exp >>= 1u8;
         base_ = (base_.pow(2)) % modulus;
     }
-    result
+    // reduced for a zero exponent, as everything is zero modulo 1
+    result % modulus
+}
+
+/// Montgomery arithmetic modulo an odd `N`, with `R = 2^k` for the smallest `k`
+/// multiple of `64` such that `R > N`.
+///
+/// Numbers are kept in Montgomery form `a*R mod N`, where a product is reduced
+/// by [`Montgomery::reduce()`] with only multiplications, masks and shifts,
+/// instead of the long division behind `%`.
+struct Montgomery {
+    modulus: BigUint,
+    /// `k`, so that `R = 2^k`.
+    shift: u64,
+    /// `R - 1`, to calculate `mod R` as a mask.
+    mask: BigUint,
+    /// `-N^-1 mod R`
+    modulus_inverse: BigUint,
+    /// `R^2 mod N`, to convert into Montgomery form.
+    r_squared: BigUint,
+}
+
+impl Montgomery {
+    /// Returns [`None`] if `modulus` is even or smaller than `3`,
+    /// since then it has no inverse modulo `R`.
+    fn new(modulus: &BigUint) -> Option<Self> {
+        if modulus.bits() < 2 || !modulus.bit(0) {
+            return None;
+        }
+        let shift = modulus.bits().div_ceil(64) * 64;
+        let r = BigUint::one() << shift;
+        let inverse = mod_inverse(&(modulus & (&r - 1u8)), &r)?;
+        Some(Self {
+            modulus: modulus.clone(),
+            shift,
+            mask: &r - 1u8,
+            modulus_inverse: &r - inverse,
+            r_squared: (&r * &r) % modulus,
+        })
+    }
+
+    /// Calculates `t * R^-1 mod N` for a `t < N*R` (REDC).
+    fn reduce(&self, t: &BigUint) -> BigUint {
+        let m = ((t & &self.mask) * &self.modulus_inverse) & &self.mask;
+        let u = (t + m * &self.modulus) >> self.shift;
+        if u >= self.modulus {
+            u - &self.modulus
+        } else {
+            u
+        }
+    }
+
+    /// Calculates the product of two numbers in Montgomery form, also in Montgomery form.
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        self.reduce(&(a * b))
+    }
+
+    /// Calculates `base^exponent mod N`, with every operation in Montgomery form.
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let mut base_ = self.reduce(&((base % &self.modulus) * &self.r_squared));
+        let mut result = self.reduce(&self.r_squared);
+
+        for bit in 0..exponent.bits() {
+            if exponent.bit(bit) {
+                result = self.mul(&result, &base_);
+            }
+            base_ = self.mul(&base_, &base_);
+        }
+        self.reduce(&result)
+    }
 }
 
 /// Calculates extended euclides algorithm for give `a` and  `b`.
@@ -134,6 +297,119 @@ pub fn euclides_extended(a: &BigUint, b: &BigUint) -> (BigInt, BigInt, BigInt) {
     (old_r, old_s, old_t)
 }
 
+/// Calculates the modular inverse of `a` modulo `modulus`,
+/// or [`None`] if they are not coprime.
+#[must_use]
+pub fn mod_inverse(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let (gcd, coeff, _) = euclides_extended(a, modulus);
+    if !gcd.is_one() {
+        return None;
+    }
+    let modulus = BigInt::from(modulus.clone());
+    ((coeff % &modulus + &modulus) % &modulus).to_biguint()
+}
+
+/// Recovers the prime factors `(P, Q)` of `modulus`, with `P > Q`,
+/// from a matching pair of public and private exponents.
+///
+/// Since `E*D - 1` is a multiple of `λ(N)`, a non trivial square root of `1`
+/// modulo `N` is quickly found, and its `gcd` with `N` is one of the factors.
+///
+/// Returns [`None`] if the exponents do not match the modulus.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn factor_modulus(
+    modulus: &BigUint,
+    public_exponent: &BigUint,
+    private_exponent: &BigUint,
+) -> Option<(BigUint, BigUint)> {
+    let k = public_exponent * private_exponent - 1u8;
+    let twos = k.trailing_zeros()?;
+    let odd_part = &k >> twos;
+    let minus_one = modulus - 1u8;
+    let candidates: [u8; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    for base in candidates {
+        let mut x = mod_pow(&BigUint::from(base), &odd_part, modulus);
+        if x.is_one() || x == minus_one {
+            continue;
+        }
+        for _ in 0..twos {
+            let y = &x * &x % modulus;
+            if y.is_one() {
+                let (gcd, _, _) = euclides_extended(&(&x - 1u8), modulus);
+                let p = gcd.to_biguint()?;
+                let q = modulus / &p;
+                return (p > BigUint::one() && q > BigUint::one()).then(|| {
+                    if p > q {
+                        (p, q)
+                    } else {
+                        (q, p)
+                    }
+                });
+            }
+            if y == minus_one {
+                break;
+            }
+            x = y;
+        }
+    }
+    None
+}
+
+/// Overwrites the digits of `value` with zeros, leaving it equal to zero.
+///
+/// [`BigUint`] does not implement [`zeroize::Zeroize`], but
+/// [`BigUint::assign_from_slice()`] reuses the current allocation,
+/// so the secret digits are overwritten in place.
+/// Copies left behind by earlier arithmetic are out of reach and not wiped.
+pub(crate) fn wipe(value: &mut BigUint) {
+    let digits = usize::try_from(value.bits().div_ceil(32)).unwrap_or(0);
+    value.assign_from_slice(&vec![0u32; digits]);
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Parses a number written in decimal, or in hexadecimal when prefixed by `0x`,
+/// with optional `_` separators between digits.
+///
+/// Unlike Key files, which are always hexadecimal, the radix is never assumed.
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rrsa_lib::math::parse_number;
+///
+/// assert_eq!(parse_number("65_537").unwrap(), BigUint::from(65_537u32));
+/// assert_eq!(parse_number("0x1_0001").unwrap(), BigUint::from(65_537u32));
+/// assert!(parse_number("1f").is_err());
+/// ```
+///
+/// # Errors
+/// [`RsaError::InvalidNumber`] if there are no digits, a digit is not valid in the radix,
+/// or a separator is not between two digits.
+pub fn parse_number(s: &str) -> RsaResult<BigUint> {
+    let trimmed = s.trim();
+    let (digits, radix, radix_name) = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(hex) => (hex, 16, "hexadecimal"),
+        None => (trimmed, 10, "decimal"),
+    };
+    let invalid = |reason: String| RsaError::InvalidNumber(format!("`{trimmed}`: {reason}"));
+
+    if digits.is_empty() {
+        return Err(invalid("no digits".into()));
+    }
+    if let Some(digit) = digits.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+        return Err(invalid(format!("`{digit}` is not a {radix_name} digit")));
+    }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(invalid("`_` must separate two digits".into()));
+    }
+    let digits: String = digits.chars().filter(|&c| c != '_').collect();
+    BigUint::parse_bytes(digits.as_bytes(), radix).ok_or_else(|| invalid("no digits".into()))
+}
+
 fn update_step(a: &mut BigInt, old_a: &mut BigInt, quotient: &BigInt) {
     let tmp = a.clone();
     *a = &*old_a - quotient * &tmp;
@@ -192,6 +468,98 @@ mod tests {
             ),
             BigUint::from(result)
         );
+        assert!(mod_pow(&BigUint::from(5u8), &BigUint::zero(), &BigUint::one()).is_zero());
+    }
+
+    #[test]
+    fn test_mod_pow_montgomery() {
+        // 2^521 - 1, a Mersenne prime
+        let prime = (BigUint::one() << 521u16) - 1u8;
+        let base = BigUint::from(0x147B_7F71_9668_F701_u64).pow(9);
+        let exponent = BigUint::from(0x1_0001u32).pow(31);
+        assert!(prime.bits() >= MONTGOMERY_MIN_BITS);
+        assert_eq!(
+            mod_pow(&base, &exponent, &prime),
+            base.modpow(&exponent, &prime)
+        );
+        // Fermat's little theorem
+        assert!(mod_pow(&base, &(&prime - 1u8), &prime).is_one());
+
+        // even moduli fall back to the plain implementation
+        let even = &prime + 1u8;
+        assert!(Montgomery::new(&even).is_none());
+        assert_eq!(
+            mod_pow(&base, &exponent, &even),
+            base.modpow(&exponent, &even)
+        );
+
+        let montgomery = Montgomery::new(&BigUint::from(497u16)).unwrap();
+        assert_eq!(
+            montgomery.pow(&BigUint::from(4u8), &BigUint::from(13u8)),
+            BigUint::from(445u16)
+        );
+        assert!(montgomery
+            .pow(&BigUint::from(4u8), &BigUint::zero())
+            .is_one());
+    }
+
+    #[test]
+    fn test_factor_modulus() {
+        let modulus = BigUint::from(0x9668_F701u64);
+        let (p, q) = factor_modulus(
+            &modulus,
+            &BigUint::from(65_537u32),
+            &BigUint::from(0x147B_7F71u32),
+        )
+        .unwrap();
+        assert_eq!(
+            (p.clone(), q.clone()),
+            (BigUint::from(52_453u32), BigUint::from(48_109u32))
+        );
+        assert_eq!(mod_inverse(&q, &p), Some(BigUint::from(11_459u32)));
+        assert_eq!(mod_inverse(&BigUint::from(6u8), &BigUint::from(9u8)), None);
+        assert_eq!(
+            factor_modulus(&modulus, &BigUint::from(3u8), &BigUint::from(7u8)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        for (s, expected) in [
+            ("0", 0u32),
+            ("65537", 65_537),
+            (" 65_537 ", 65_537),
+            ("0x10001", 65_537),
+            ("0X1_00_01", 65_537),
+            ("0xdeadBEEF", 0xDEAD_BEEF),
+        ] {
+            assert_eq!(parse_number(s).unwrap(), BigUint::from(expected), "{s}");
+        }
+        for (s, reason) in [
+            ("", "no digits"),
+            ("0x", "no digits"),
+            ("ff", "`f` is not a decimal digit"),
+            ("0xfg", "`g` is not a hexadecimal digit"),
+            ("-1", "`-` is not a decimal digit"),
+            ("_1", "`_` must separate two digits"),
+            ("1__0", "`_` must separate two digits"),
+            ("0x10_", "`_` must separate two digits"),
+        ] {
+            let error = parse_number(s).unwrap_err().to_string();
+            assert!(error.ends_with(reason), "{s}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_wipe() {
+        let mut value = BigUint::from(0x147B_7F71_9668_F701_u64) << 100u8;
+        wipe(&mut value);
+        assert!(value.is_zero());
+
+        let mut value = BigUint::zero();
+        wipe(&mut value);
+        assert!(value.is_zero());
     }
 
     #[test]
@@ -0,0 +1,333 @@
+//! Module with a MIME like encrypted message format, showing how email encryption
+//! containers such as S/MIME are structured: cleartext routing headers
+//! followed by a base64 encoded hybrid ciphertext body, an envelope of [`Key::encode_envelope()`].
+//!
+//! As in S/MIME, the headers (including the subject) are not encrypted.
+//! The `To` header can be left out with [`EncryptedMessage::encrypt_without_key_hint()`],
+//! so the message does not reveal its recipient.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{fmt, io::Cursor, str::FromStr};
+
+use crate::encoding::envelope::EnvelopeOptions;
+use crate::encoding::padding::PaddingScheme;
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::version;
+
+/// An encrypted message, addressed to the Key Pair with the `to` fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedMessage {
+    /// [`Key::fingerprint_hex()`] of the recipient, or [`None`] for anonymous messages.
+    pub to: Option<String>,
+    pub subject: String,
+    /// Envelope written by [`Key::encode_envelope()`],
+    /// or for version 1 messages, encoded with [`PaddingScheme::Oaep`].
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedMessage {
+    /// Current version of the format, bumped on any incompatible change.
+    pub const VERSION: u32 = 2;
+    const CONTENT_TYPE: &'static str = "application/x-rrsa-encrypted; format=envelope";
+    /// Content type of version 1 messages, still read.
+    const LEGACY_CONTENT_TYPE: &'static str = "application/x-rrsa-encrypted; padding=oaep";
+    /// Maximum length of a base64 line, as required by MIME.
+    const LINE_LEN: usize = 76;
+
+    /// Encrypts `plaintext` to the owner of the Public Key `to`.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::message::EncryptedMessage;
+    /// use std::str::FromStr;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let message = EncryptedMessage::encrypt(&pair.public_key, "Plans", b"attack at dawn").unwrap();
+    /// let received = EncryptedMessage::from_str(&message.to_string()).unwrap();
+    /// assert_eq!(received.decrypt(&pair.private_key).unwrap(), b"attack at dawn");
+    /// ```
+    ///
+    /// # Errors
+    /// - If `subject` spans more than one line.
+    /// - If `to` is not a Public Key large enough to wrap the data key with OAEP.
+    pub fn encrypt(to: &Key, subject: &str, plaintext: &[u8]) -> RsaResult<Self> {
+        EncryptedMessage::encrypt_with_key_hint(to, subject, plaintext, true)
+    }
+
+    /// Same as [`EncryptedMessage::encrypt()`], but without the fingerprint of the recipient,
+    /// neither in the `To` header nor in the envelope, so the message does not reveal
+    /// who can read it, see [`EnvelopeOptions::key_hint`].
+    ///
+    /// Anonymous messages are decrypted by trying every key, see [`EncryptedMessage::decrypt_with_any()`].
+    ///
+    /// # Errors
+    /// Same as [`EncryptedMessage::encrypt()`].
+    pub fn encrypt_without_key_hint(to: &Key, subject: &str, plaintext: &[u8]) -> RsaResult<Self> {
+        EncryptedMessage::encrypt_with_key_hint(to, subject, plaintext, false)
+    }
+
+    fn encrypt_with_key_hint(
+        to: &Key,
+        subject: &str,
+        plaintext: &[u8],
+        key_hint: bool,
+    ) -> RsaResult<Self> {
+        if subject.contains(['\r', '\n']) {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the subject spans more than one line".into(),
+            ));
+        }
+        let mut ciphertext = Vec::new();
+        Key::encode_envelope_with_options(
+            &[to],
+            &EnvelopeOptions {
+                key_hint,
+                ..EnvelopeOptions::default()
+            },
+            &mut Cursor::new(plaintext),
+            &mut ciphertext,
+        )?;
+        Ok(Self {
+            to: key_hint.then(|| to.fingerprint_hex()),
+            subject: subject.to_string(),
+            ciphertext,
+        })
+    }
+
+    /// Decrypts this message with the recipient's Private Key.
+    ///
+    /// The body of version 1 messages, encoded with [`PaddingScheme::Oaep`], is detected.
+    ///
+    /// # Errors
+    /// - If `key` is not the Private Key of the recipient.
+    /// - If the ciphertext is corrupted.
+    pub fn decrypt(&self, key: &Key) -> RsaResult<Vec<u8>> {
+        if self
+            .to
+            .as_ref()
+            .is_some_and(|to| *to != key.fingerprint_hex())
+        {
+            return Err(RsaError::WrongRecipient);
+        }
+        let mut plaintext = Vec::new();
+        key.decode(
+            &mut Cursor::new(&self.ciphertext),
+            &mut plaintext,
+            PaddingScheme::Oaep,
+        )?;
+        Ok(plaintext)
+    }
+
+    /// Decrypts this message with the first of `keys` that succeeds,
+    /// only trying the recipient's key if the message has a `To` header.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::message::EncryptedMessage;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let message =
+    ///     EncryptedMessage::encrypt_without_key_hint(&pair.public_key, "Plans", b"attack at dawn")
+    ///         .unwrap();
+    /// assert!(!message.to_string().contains("To:"));
+    ///
+    /// let keys = [&KeyPair::demo_tiny().private_key, &pair.private_key];
+    /// let (index, plaintext) = message.decrypt_with_any(keys).unwrap();
+    /// assert_eq!((index, plaintext.as_slice()), (1, &b"attack at dawn"[..]));
+    /// ```
+    ///
+    /// # Returns
+    /// The index of the key that succeeded and the plaintext.
+    ///
+    /// # Errors
+    /// If none of `keys` could decrypt the message.
+    pub fn decrypt_with_any<'k>(
+        &self,
+        keys: impl IntoIterator<Item = &'k Key>,
+    ) -> RsaResult<(usize, Vec<u8>)> {
+        keys.into_iter()
+            .enumerate()
+            .find_map(|(index, key)| Some((index, self.decrypt(key).ok()?)))
+            .ok_or(RsaError::WrongRecipient)
+    }
+}
+
+impl fmt::Display for EncryptedMessage {
+    /// Formats the given [`EncryptedMessage`] as MIME headers and a base64 body.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MIME-Version: 1.0")?;
+        if let Some(to) = &self.to {
+            writeln!(f, "To: {to}")?;
+        }
+        writeln!(f, "Subject: {}", self.subject)?;
+        writeln!(f, "Content-Type: {}", EncryptedMessage::CONTENT_TYPE)?;
+        writeln!(f, "Content-Transfer-Encoding: base64")?;
+        writeln!(f, "X-Rrsa-Format: {}", EncryptedMessage::VERSION)?;
+        writeln!(f, "X-Rrsa-Producer: {}", version::producer())?;
+        writeln!(f)?;
+        let encoded = STANDARD.encode(&self.ciphertext);
+        for line in encoded.as_bytes().chunks(EncryptedMessage::LINE_LEN) {
+            writeln!(f, "{}", String::from_utf8_lossy(line))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for EncryptedMessage {
+    type Err = RsaError;
+
+    /// Extracts an [`EncryptedMessage`] from the given string slice,
+    /// ignoring unknown headers.
+    ///
+    /// Messages without a `X-Rrsa-Format` header are read as version 1,
+    /// messages without a `To` header as anonymous.
+    fn from_str(s: &str) -> RsaResult<Self> {
+        let s = s.replace("\r\n", "\n");
+        let (headers, body) = s.split_once("\n\n").ok_or_else(|| {
+            RsaError::ImproperlyFormattedStr("because the message has no body".into())
+        })?;
+
+        let (mut to, mut subject, mut content_type) = (None, None, None);
+        for header in headers.lines() {
+            let Some((name, value)) = header.split_once(':') else {
+                return Err(RsaError::ImproperlyFormattedStr(format!(
+                    "because `{header}` is not a valid header"
+                )));
+            };
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "to" => to = Some(value),
+                "subject" => subject = Some(value),
+                "content-type" => content_type = Some(value),
+                "x-rrsa-format" => {
+                    version::check_supported("message", &value, EncryptedMessage::VERSION)?;
+                }
+                _ => {}
+            }
+        }
+        if !content_type.as_deref().is_some_and(|content_type| {
+            [
+                EncryptedMessage::CONTENT_TYPE,
+                EncryptedMessage::LEGACY_CONTENT_TYPE,
+            ]
+            .contains(&content_type)
+        }) {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the message content type is not supported".into(),
+            ));
+        }
+
+        let encoded: String = body.split_whitespace().collect();
+        let ciphertext = STANDARD.decode(encoded).map_err(|_| {
+            RsaError::ImproperlyFormattedStr("because the message body is not valid base64".into())
+        })?;
+        Ok(Self {
+            to,
+            subject: subject.unwrap_or_default(),
+            ciphertext,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::envelope;
+    use crate::key::{
+        tests::{pair_4096, test_pair},
+        KeyPair,
+    };
+    use crate::testvectors::PLAINTEXT;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_encrypted_message() {
+        let pair = pair_4096();
+        let message =
+            EncryptedMessage::encrypt(&pair.public_key, "Lunch", PLAINTEXT.as_bytes()).unwrap();
+        let formatted = message.to_string();
+        assert!(formatted.starts_with(&format!(
+            "MIME-Version: 1.0\nTo: {}\nSubject: Lunch\n",
+            pair.public_key.fingerprint_hex()
+        )));
+        assert_eq!(message.to, Some(pair.public_key.fingerprint_hex()));
+        assert!(formatted.lines().all(|line| line.len() <= 76));
+
+        let parsed = EncryptedMessage::from_str(&formatted.replace('\n', "\r\n")).unwrap();
+        assert_eq!(parsed, message);
+        assert_eq!(
+            parsed.decrypt(&pair.private_key).unwrap(),
+            PLAINTEXT.as_bytes()
+        );
+        assert!(parsed.decrypt(&test_pair().private_key).is_err());
+        assert!(envelope::is_envelope(&message.ciphertext));
+        assert!(formatted.contains("Content-Type: application/x-rrsa-encrypted; format=envelope\n"));
+
+        // version 1 messages, with a body encoded with OAEP, are still read
+        let mut ciphertext = Vec::new();
+        pair.public_key
+            .encode(&mut &b"older"[..], &mut ciphertext, PaddingScheme::Oaep)
+            .unwrap();
+        let legacy = EncryptedMessage {
+            ciphertext,
+            ..message
+        }
+        .to_string()
+        .replace(
+            EncryptedMessage::CONTENT_TYPE,
+            EncryptedMessage::LEGACY_CONTENT_TYPE,
+        )
+        .replace("X-Rrsa-Format: 2", "X-Rrsa-Format: 1");
+        assert_eq!(
+            EncryptedMessage::from_str(&legacy)
+                .unwrap()
+                .decrypt(&pair.private_key)
+                .unwrap(),
+            b"older"
+        );
+    }
+
+    #[test]
+    fn test_encrypted_message_without_key_hint() {
+        let pair = pair_4096();
+        let message =
+            EncryptedMessage::encrypt_without_key_hint(&pair.public_key, "Lunch", b"noon").unwrap();
+        let formatted = message.to_string();
+        assert!(!formatted.contains(&pair.public_key.fingerprint_hex()));
+
+        let parsed = EncryptedMessage::from_str(&formatted).unwrap();
+        assert_eq!(parsed.to, None);
+        assert!(!Key::has_key_hint(&mut Cursor::new(&parsed.ciphertext)).unwrap());
+        assert_eq!(parsed.decrypt(&pair.private_key).unwrap(), b"noon");
+        assert!(parsed.decrypt(&KeyPair::demo().private_key).is_err());
+
+        let keys = [&test_pair().private_key, &KeyPair::demo().private_key];
+        assert!(matches!(
+            parsed.decrypt_with_any(keys),
+            Err(RsaError::WrongRecipient)
+        ));
+        let keys = [&test_pair().private_key, &pair.private_key];
+        assert_eq!(
+            parsed.decrypt_with_any(keys).unwrap(),
+            (1, b"noon".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_encrypted_message_error() {
+        let pair = pair_4096();
+        assert!(EncryptedMessage::encrypt(&pair.public_key, "a\nBcc: x", b"").is_err());
+
+        let valid =
+            "To: SHA256:00\nContent-Type: application/x-rrsa-encrypted; padding=oaep\n\nAQ==\n";
+        assert!(EncryptedMessage::from_str(valid).is_ok());
+        assert!(EncryptedMessage::from_str(&valid.replace("oaep", "none")).is_err());
+        assert!(EncryptedMessage::from_str(&valid.replace("\n\n", "\n")).is_err());
+        assert!(EncryptedMessage::from_str(&valid.replace("AQ==", "A?==")).is_err());
+        assert!(matches!(
+            EncryptedMessage::from_str(&valid.replace("\n\n", "\nX-Rrsa-Format: 3\n\n")),
+            Err(RsaError::UnsupportedVersion { found: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Convenience helpers mixing in-memory buffers and files,
+//! so simple cases don't need to build [`Read`]/[`Write`] implementors.
+
+use crate::atomic::AtomicFile;
+use crate::encoding::{padding::PaddingScheme, Ciphertext, Plaintext};
+use crate::error::RsaResult;
+use crate::key::Key;
+use crate::paths::refuse_overwrite;
+use std::{
+    fs::{create_dir_all, File},
+    io::{BufReader, BufWriter, Cursor, Read, Write},
+    path::Path,
+};
+
+/// Encodes the file at `path` using the Public Key `key` and `padding`, returning the [`Ciphertext`].
+///
+/// # Errors
+/// - If `key` is not a Public Key.
+/// - Propagates [`std::io::Error`].
+pub fn encode_file_to_vec(key: &Key, path: &Path, padding: PaddingScheme) -> RsaResult<Ciphertext> {
+    let mut output = Vec::new();
+    key.encode(&mut open_buffered(path)?, &mut output, padding)?;
+    Ok(Ciphertext::from_bytes(output))
+}
+
+/// Encodes `data` using the Public Key `key` and `padding`, writing the encoded bytes to the file at `path`.
+///
+/// The file only appears once completely written, see [`AtomicFile`].
+///
+/// # Errors
+/// - [`crate::error::RsaError::WouldOverwrite`] if a file already exists at `path`.
+/// - If `key` is not a Public Key.
+/// - Propagates [`std::io::Error`].
+pub fn encode_slice_to_file(
+    key: &Key,
+    data: &Plaintext,
+    path: &Path,
+    padding: PaddingScheme,
+) -> RsaResult<()> {
+    write_new_buffered(path, |output| {
+        key.encode(&mut Cursor::new(data.as_bytes()), output, padding)
+    })
+}
+
+/// Decodes the file at `path` using the Private Key `key` and `padding`, returning the [`Plaintext`].
+///
+/// # Errors
+/// - If `key` is not a Private Key.
+/// - Propagates any decoding error and [`std::io::Error`].
+pub fn decode_file_to_vec(key: &Key, path: &Path, padding: PaddingScheme) -> RsaResult<Plaintext> {
+    let mut output = Vec::new();
+    key.decode(&mut open_buffered(path)?, &mut output, padding)?;
+    Ok(Plaintext::from_bytes(output))
+}
+
+/// Decodes `data` using the Private Key `key` and `padding`, writing the decoded bytes to the file at `path`.
+///
+/// The file only appears once completely written, see [`AtomicFile`],
+/// so a failed decoding never leaves a truncated plaintext behind.
+///
+/// # Errors
+/// - [`crate::error::RsaError::WouldOverwrite`] if a file already exists at `path`.
+/// - If `key` is not a Private Key.
+/// - Propagates any decoding error and [`std::io::Error`].
+pub fn decode_slice_to_file(
+    key: &Key,
+    data: &Ciphertext,
+    path: &Path,
+    padding: PaddingScheme,
+) -> RsaResult<()> {
+    write_new_buffered(path, |output| {
+        key.decode(&mut Cursor::new(data.as_bytes()), output, padding)
+    })
+}
+
+fn open_buffered(path: &Path) -> RsaResult<impl Read> {
+    Ok(BufReader::new(File::open(path)?))
+}
+
+/// Writes the output of `write` to a new file at `path`, only committed if `write` succeeds.
+fn write_new_buffered(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<&mut AtomicFile>) -> RsaResult<()>,
+) -> RsaResult<()> {
+    refuse_overwrite(path)?;
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    let mut file = AtomicFile::create(path)?;
+    let mut output = BufWriter::new(&mut file);
+    write(&mut output)?;
+    output.flush()?;
+    drop(output);
+    file.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RsaError;
+    use crate::fixtures::FixtureBuilder;
+    use crate::key::tests::test_pair;
+
+    #[test]
+    fn test_mixed_round_trip() {
+        let original = Plaintext::from_bytes(b"in memory to file and back".to_vec());
+        let fixture = FixtureBuilder::new()
+            .file("message.txt", original.as_bytes())
+            .build()
+            .unwrap();
+        let plain_path = fixture.join("message.txt");
+        let encoded_path = fixture.join("message.encoded");
+        let decoded_path = fixture.join("message.decoded");
+
+        encode_slice_to_file(
+            &test_pair().public_key,
+            &original,
+            &encoded_path,
+            PaddingScheme::None,
+        )
+        .unwrap();
+        let decoded =
+            decode_file_to_vec(&test_pair().private_key, &encoded_path, PaddingScheme::None)
+                .unwrap();
+        assert_eq!(original, decoded);
+
+        let encoded =
+            encode_file_to_vec(&test_pair().public_key, &plain_path, PaddingScheme::None).unwrap();
+        decode_slice_to_file(
+            &test_pair().private_key,
+            &encoded,
+            &decoded_path,
+            PaddingScheme::None,
+        )
+        .unwrap();
+        assert_eq!(original.as_bytes(), std::fs::read(&decoded_path).unwrap());
+
+        // existing files are kept, and failed decodings leave no file behind
+        assert!(matches!(
+            encode_slice_to_file(
+                &test_pair().public_key,
+                &original,
+                &encoded_path,
+                PaddingScheme::None,
+            ),
+            Err(RsaError::WouldOverwrite(_))
+        ));
+        let corrupt_path = fixture.join("corrupt.decoded");
+        assert!(decode_slice_to_file(
+            &test_pair().private_key,
+            &Ciphertext::from_bytes(vec![0xFF; 7]),
+            &corrupt_path,
+            PaddingScheme::None,
+        )
+        .is_err());
+        assert!(!corrupt_path.exists());
+    }
+}
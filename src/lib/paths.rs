@@ -0,0 +1,167 @@
+//! Module with the resolution of the default paths used when reading and writing,
+//! so every frontend predicts the same locations.
+
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+use std::path::{Path, PathBuf};
+
+/// Operation whose output path is being resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Encode,
+    Decode,
+    Sign,
+}
+
+impl Operation {
+    /// Extension appended to the input path to create the default output path.
+    pub const ENCODED_EXTENSION: &'static str = "encoded";
+    /// Extension replacing the extension of the input path to create the default output path.
+    pub const DECODED_EXTENSION: &'static str = "decoded";
+    /// Extension appended to the input path to create the default signature path.
+    pub const SIGNATURE_EXTENSION: &'static str = "sig";
+}
+
+/// Path standing for stdin when reading and for stdout when writing.
+pub const STDIO_PATH: &str = "-";
+
+/// Returns `true` if `path` is [`STDIO_PATH`].
+#[must_use]
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO_PATH)
+}
+
+/// Refuses to write to `path` if something already exists there,
+/// unless it is [`STDIO_PATH`], which never clobbers anything.
+///
+/// # Errors
+/// [`RsaError::WouldOverwrite`] if `path` exists.
+pub fn refuse_overwrite(path: &Path) -> RsaResult<()> {
+    if !is_stdio(path) && path.exists() {
+        return Err(RsaError::WouldOverwrite(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Resolves the path of a key file.
+///
+/// If `explicit` is present it is returned as is,
+/// otherwise the default key name for `variant` inside [`Key::default_dir()`] is used.
+#[must_use]
+pub fn resolve_key_path(explicit: Option<PathBuf>, variant: KeyVariant) -> PathBuf {
+    explicit.unwrap_or_else(|| {
+        Key::default_dir().join(match variant {
+            KeyVariant::PublicKey => Key::DEFAULT_PUBLIC_KEY_NAME,
+            KeyVariant::PrivateKey => Key::DEFAULT_PRIVATE_KEY_NAME,
+        })
+    })
+}
+
+/// Resolves the path of the output file of an [`Operation`] over `input`.
+///
+/// If `explicit` is present it is returned as is. Otherwise, if `input` is [`STDIO_PATH`]
+/// so is the output, else:
+/// - [`Operation::Encode`] appends [`Operation::ENCODED_EXTENSION`] to `input`,
+///   e.g. `msg.txt` becomes `msg.txt.encoded`.
+/// - [`Operation::Decode`] replaces the extension of `input` with
+///   [`Operation::DECODED_EXTENSION`], e.g. `msg.txt.encoded` becomes `msg.txt.decoded`.
+/// - [`Operation::Sign`] appends [`Operation::SIGNATURE_EXTENSION`] to `input`,
+///   e.g. `msg.txt` becomes `msg.txt.sig`.
+#[must_use]
+pub fn resolve_output_path(
+    input: &Path,
+    explicit: Option<PathBuf>,
+    operation: Operation,
+) -> PathBuf {
+    explicit.unwrap_or_else(|| match operation {
+        _ if is_stdio(input) => PathBuf::from(STDIO_PATH),
+        Operation::Encode => append_extension(input, Operation::ENCODED_EXTENSION),
+        Operation::Decode => input.with_extension(Operation::DECODED_EXTENSION),
+        Operation::Sign => append_extension(input, Operation::SIGNATURE_EXTENSION),
+    })
+}
+
+fn append_extension(input: &Path, new_extension: &str) -> PathBuf {
+    input.with_extension(match input.extension() {
+        Some(extension) => format!("{}.{new_extension}", extension.to_string_lossy()),
+        None => new_extension.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_key_path() {
+        let explicit = PathBuf::from("./keys/my_key");
+        assert_eq!(
+            resolve_key_path(Some(explicit.clone()), KeyVariant::PublicKey),
+            explicit
+        );
+        assert_eq!(
+            resolve_key_path(None, KeyVariant::PublicKey),
+            Key::default_dir().join(Key::DEFAULT_PUBLIC_KEY_NAME)
+        );
+        assert_eq!(
+            resolve_key_path(None, KeyVariant::PrivateKey),
+            Key::default_dir().join(Key::DEFAULT_PRIVATE_KEY_NAME)
+        );
+    }
+
+    #[test]
+    fn test_refuse_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out");
+        refuse_overwrite(&path).unwrap();
+        std::fs::write(&path, b"taken").unwrap();
+        assert!(matches!(
+            refuse_overwrite(&path),
+            Err(RsaError::WouldOverwrite(existing)) if existing == path
+        ));
+        assert!(refuse_overwrite(dir.path()).is_err());
+        refuse_overwrite(Path::new(STDIO_PATH)).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_output_path() {
+        let input = Path::new("./messages/lorem.txt");
+        assert_eq!(
+            resolve_output_path(input, None, Operation::Encode),
+            PathBuf::from("./messages/lorem.txt.encoded")
+        );
+        assert_eq!(
+            resolve_output_path(Path::new("./messages/lorem"), None, Operation::Encode),
+            PathBuf::from("./messages/lorem.encoded")
+        );
+        assert_eq!(
+            resolve_output_path(
+                Path::new("./messages/lorem.txt.encoded"),
+                None,
+                Operation::Decode
+            ),
+            PathBuf::from("./messages/lorem.txt.decoded")
+        );
+        assert_eq!(
+            resolve_output_path(input, None, Operation::Sign),
+            PathBuf::from("./messages/lorem.txt.sig")
+        );
+        assert_eq!(
+            resolve_output_path(input, Some(PathBuf::from("out")), Operation::Decode),
+            PathBuf::from("out")
+        );
+        assert!(is_stdio(&resolve_output_path(
+            Path::new(STDIO_PATH),
+            None,
+            Operation::Encode
+        )));
+        assert_eq!(
+            resolve_output_path(
+                Path::new(STDIO_PATH),
+                Some(PathBuf::from("out")),
+                Operation::Decode
+            ),
+            PathBuf::from("out")
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Module with progress reporting of long running operations through a [`ProgressSink`]
+//! implemented by callers, so the library never prints anything itself.
+
+use std::{io::Read, path::PathBuf, time::Duration};
+
+use crate::config::Config;
+use crate::error::RsaResult;
+
+/// Receives the progress of an operation, e.g. to drive a progress bar.
+pub trait ProgressSink {
+    /// Called after every read, with the amount of bytes processed so far and the total.
+    fn progress(&mut self, processed: u64, total: u64);
+
+    /// Called once the operation is done.
+    fn finish(&mut self) {}
+}
+
+impl<F: FnMut(u64, u64)> ProgressSink for F {
+    fn progress(&mut self, processed: u64, total: u64) {
+        self(processed, total);
+    }
+}
+
+/// Wraps a [`Read`] implementor, reporting every read to a [`ProgressSink`].
+///
+/// Wrapping the input of [`crate::key::Key::encode()`] or [`crate::key::Key::decode()`]
+/// reports their progress.
+///
+/// ```
+/// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair, progress::ProgressReader};
+///
+/// let pair = KeyPair::demo();
+/// let message = vec![42u8; 1000];
+/// let mut reports = Vec::new();
+/// let mut input = ProgressReader::new(message.as_slice(), 1000, |processed: u64, total: u64| {
+///     reports.push((processed, total));
+/// });
+/// pair.public_key
+///     .encode(&mut input, &mut Vec::new(), PaddingScheme::Oaep)
+///     .unwrap();
+/// input.finish();
+/// drop(input);
+/// assert_eq!(reports.last(), Some(&(1000, 1000)));
+/// ```
+#[derive(Debug)]
+pub struct ProgressReader<R, S> {
+    inner: R,
+    sink: S,
+    processed: u64,
+    total: u64,
+}
+
+impl<R: Read, S: ProgressSink> ProgressReader<R, S> {
+    /// Wraps `inner`, whose length is `total` bytes.
+    pub fn new(inner: R, total: u64, sink: S) -> Self {
+        Self {
+            inner,
+            sink,
+            processed: 0,
+            total,
+        }
+    }
+
+    /// Notifies the [`ProgressSink`] that the operation is done.
+    pub fn finish(&mut self) {
+        self.sink.finish();
+    }
+
+    /// Returns the inner reader and the [`ProgressSink`].
+    pub fn into_parts(self) -> (R, S) {
+        (self.inner, self.sink)
+    }
+}
+
+impl<R: Read, S: ProgressSink> Read for ProgressReader<R, S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        self.processed += amount as u64;
+        self.sink.progress(self.processed, self.total);
+        Ok(amount)
+    }
+}
+
+/// Options of the checkpoint log of long running operations,
+/// which can be read from the `checkpoint_log` and `checkpoint_interval` fields of a [`Config`].
+///
+/// Checkpoint lines are appended to the log, so the progress of a long batch job
+/// can be followed from another terminal, or checked after detaching from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointOptions {
+    /// Log file the checkpoints are appended to, no checkpoints are written if absent.
+    pub log_path: Option<PathBuf>,
+    /// Minimum time between two checkpoints, 60 seconds unless configured.
+    pub interval: Duration,
+}
+
+impl Default for CheckpointOptions {
+    fn default() -> Self {
+        Self {
+            log_path: None,
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+}
+
+impl CheckpointOptions {
+    /// Minimum time between two checkpoints when `checkpoint_interval` is not configured.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Reads the options from the config file and environment, see [`Config::load()`].
+    ///
+    /// # Errors
+    /// - If the config is not valid, see [`Config::from_config_str()`].
+    /// - Propagates [`std::io::Error`].
+    pub fn load() -> RsaResult<Self> {
+        Ok(Self::from_config(&Config::load()?))
+    }
+
+    /// Options from the fields of `config`, missing fields keep their default value.
+    ///
+    /// ```
+    /// use rrsa_lib::config::Config;
+    /// use rrsa_lib::progress::CheckpointOptions;
+    /// use std::time::Duration;
+    ///
+    /// let config =
+    ///     Config::from_config_str("checkpoint_log = rrsa.log\ncheckpoint_interval = 5\n").unwrap();
+    /// let options = CheckpointOptions::from_config(&config);
+    /// assert_eq!(options.log_path.unwrap().to_str(), Some("rrsa.log"));
+    /// assert_eq!(options.interval, Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            log_path: config.checkpoint_log.clone(),
+            interval: config.checkpoint_interval.unwrap_or(Self::DEFAULT_INTERVAL),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        reports: Vec<(u64, u64)>,
+        finished: bool,
+    }
+
+    impl ProgressSink for Recorder {
+        fn progress(&mut self, processed: u64, total: u64) {
+            self.reports.push((processed, total));
+        }
+
+        fn finish(&mut self) {
+            self.finished = true;
+        }
+    }
+
+    #[test]
+    fn test_progress_reader() {
+        let mut reader = ProgressReader::new(&b"progress"[..], 8, Recorder::default());
+        let mut buf = [0u8; 3];
+        while reader.read(&mut buf).unwrap() > 0 {}
+        reader.finish();
+
+        let (_, recorder) = reader.into_parts();
+        assert_eq!(recorder.reports, [(3, 8), (6, 8), (8, 8), (8, 8)]);
+        assert!(recorder.finished);
+    }
+
+    #[test]
+    fn test_checkpoint_options_from_config() {
+        assert_eq!(
+            CheckpointOptions::from_config(&Config::default()),
+            CheckpointOptions::default()
+        );
+        let config = Config {
+            checkpoint_log: Some(PathBuf::from("/tmp/rrsa checkpoints.log")),
+            checkpoint_interval: Some(Duration::from_secs(300)),
+            ..Config::default()
+        };
+        assert_eq!(
+            CheckpointOptions::from_config(&config),
+            CheckpointOptions {
+                log_path: Some(PathBuf::from("/tmp/rrsa checkpoints.log")),
+                interval: Duration::from_secs(300),
+            }
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Module centralizing every source of randomness of this crate,
+//! prime candidates, padding bytes, salts and ephemeral exponents alike.
+//!
+//! By default every value is read from the operating system, see [`RandomSource`]
+//! to select a DRBG instead. Any [`SecureRandom`] can also be swapped in for a scope
+//! with [`with_source()`], e.g. a seeded generator to make key generation reproducible.
+//!
+//! Key generation can instead borrow a generator of the caller, see
+//! [`crate::key::KeyPair::generate_with_rng()`], which leaves this thread's source untouched.
+
+use num_bigint::{BigUint, RandBigInt};
+use rand::rngs::{adapter::ReseedingRng, OsRng};
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Core;
+use std::cell::{Cell, RefCell};
+
+/// Random number generator suitable for cryptographic use.
+pub trait SecureRandom: RngCore + CryptoRng {}
+
+impl<R: RngCore + CryptoRng> SecureRandom for R {}
+
+/// Source of randomness used when no other was set with [`with_source()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RandomSource {
+    /// Every value is read from the operating system.
+    #[default]
+    Os,
+    /// `ChaCha20` DRBG seeded from the operating system, and reseeded every
+    /// [`RandomSource::RESEED_THRESHOLD`] bytes, saving a syscall per value
+    /// during long prime searches.
+    ChaCha20,
+}
+
+impl RandomSource {
+    /// Number of bytes generated by [`RandomSource::ChaCha20`] before it is reseeded.
+    pub const RESEED_THRESHOLD: u64 = 1024 * 1024;
+
+    /// Creates a new generator of this kind, e.g. for [`with_source()`].
+    ///
+    /// # Panics
+    /// If the operating system fails to provide a seed.
+    #[must_use]
+    pub fn into_rng(self) -> Box<dyn SecureRandom> {
+        match self {
+            RandomSource::Os => Box::new(OsRng),
+            RandomSource::ChaCha20 => Box::new(chacha20_drbg()),
+        }
+    }
+}
+
+type ChaCha20Drbg = ReseedingRng<ChaCha20Core, OsRng>;
+
+fn chacha20_drbg() -> ChaCha20Drbg {
+    let core = ChaCha20Core::from_rng(OsRng).expect("the OS failed to seed the DRBG");
+    ReseedingRng::new(core, RandomSource::RESEED_THRESHOLD, OsRng)
+}
+
+thread_local! {
+    static SOURCE: RefCell<Option<Box<dyn SecureRandom>>> = const { RefCell::new(None) };
+    static DEFAULT_SOURCE: Cell<RandomSource> = const { Cell::new(RandomSource::Os) };
+    static DRBG: RefCell<Option<ChaCha20Drbg>> = const { RefCell::new(None) };
+}
+
+/// Sets the [`RandomSource`] used by this thread when no source was set with [`with_source()`].
+pub fn set_default_source(source: RandomSource) {
+    DEFAULT_SOURCE.with(|default| default.set(source));
+}
+
+/// Restores the previous source when dropped, even if the scope panicked.
+struct SourceGuard(Option<Box<dyn SecureRandom>>);
+
+impl Drop for SourceGuard {
+    fn drop(&mut self) {
+        SOURCE.with(|source| *source.borrow_mut() = self.0.take());
+    }
+}
+
+/// Runs `operation` with every random value drawn on this thread coming from `source`.
+///
+/// Calls can be nested, the innermost source being used.
+pub fn with_source<T>(source: impl SecureRandom + 'static, operation: impl FnOnce() -> T) -> T {
+    let previous = SOURCE.with(|current| current.borrow_mut().replace(Box::new(source)));
+    let _guard = SourceGuard(previous);
+    operation()
+}
+
+/// The current source of this thread as a generator, the one set with [`with_source()`],
+/// or else the [`RandomSource`] set with [`set_default_source()`].
+///
+/// Used where a generator is expected but none was given, e.g. by [`crate::math::PrimeGenerator::new()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadSource;
+
+impl RngCore for ThreadSource {
+    fn next_u32(&mut self) -> u32 {
+        with_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_rng(|rng| rng.fill_bytes(dest));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        with_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+impl CryptoRng for ThreadSource {}
+
+/// Calls `f` with the current source of this thread.
+fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SOURCE.with(|source| match source.borrow_mut().as_mut() {
+        Some(rng) => f(rng.as_mut()),
+        None => match DEFAULT_SOURCE.with(Cell::get) {
+            RandomSource::Os => f(&mut OsRng),
+            RandomSource::ChaCha20 => {
+                DRBG.with(|drbg| f(drbg.borrow_mut().get_or_insert_with(chacha20_drbg)))
+            }
+        },
+    })
+}
+
+/// Fills `dest` with random bytes.
+pub(crate) fn fill_bytes(dest: &mut [u8]) {
+    with_rng(|rng| rng.fill_bytes(dest));
+}
+
+/// Fills `dest` with random bytes, none of them zero.
+pub(crate) fn fill_nonzero_bytes(dest: &mut [u8]) {
+    with_rng(|rng| {
+        rng.fill_bytes(dest);
+        for byte in dest.iter_mut() {
+            while *byte == 0x00 {
+                *byte = rng.gen();
+            }
+        }
+    });
+}
+
+/// Returns a random [`BigUint`] in `[low, high)`.
+pub(crate) fn gen_biguint_range(low: &BigUint, high: &BigUint) -> BigUint {
+    with_rng(|rng| rng.gen_biguint_range(low, high))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_with_source() {
+        let draw = || {
+            let mut bytes = [0u8; 16];
+            fill_nonzero_bytes(&mut bytes);
+            (bytes, gen_biguint_range(&1u8.into(), &1_000_000u32.into()))
+        };
+
+        let first = with_source(StdRng::seed_from_u64(7), draw);
+        let second = with_source(StdRng::seed_from_u64(7), draw);
+        assert_eq!(first, second);
+        assert!(first.0.iter().all(|byte| *byte != 0x00));
+
+        // nested sources are restored in order
+        let (outer_before, inner, outer_after) = with_source(StdRng::seed_from_u64(1), || {
+            let before = draw();
+            let inner = with_source(StdRng::seed_from_u64(7), draw);
+            (before, inner, draw())
+        });
+        assert_eq!(inner, first);
+        assert_ne!(outer_before, outer_after);
+
+        // back to the default source
+        assert_ne!(draw(), draw());
+    }
+
+    #[test]
+    fn test_default_source() {
+        let draw = || gen_biguint_range(&0u8.into(), &(BigUint::from(1u8) << 128u8));
+        for source in [RandomSource::ChaCha20, RandomSource::Os] {
+            set_default_source(source);
+            assert_ne!(draw(), draw());
+            assert_ne!(with_source(source.into_rng(), draw), draw());
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Module with a compact, single line format for short secrets such as passwords,
+//! so they can be stored encrypted in config files.
+//!
+//! Example of an encrypted secret:
+//! ```text
+//! rrsa-secret:1:gLHnh0Lx0BO0qOGm...
+//! ```
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use std::{fmt, str::FromStr};
+
+use crate::encoding::{padding::PaddingScheme, Ciphertext, Plaintext};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::version;
+
+/// A secret encrypted with [`PaddingScheme::Oaep`], formatted as a single line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedSecret {
+    pub ciphertext: Ciphertext,
+}
+
+impl EncryptedSecret {
+    const PREFIX: &'static str = "rrsa-secret";
+    /// Current version of the format, bumped on any incompatible change.
+    pub const VERSION: u32 = 1;
+    const SPLIT_CHAR: char = ':';
+
+    /// Encrypts `secret` using a Public Key.
+    ///
+    /// ```
+    /// use rrsa_lib::encoding::Plaintext;
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::secret::EncryptedSecret;
+    /// use std::str::FromStr;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let secret = Plaintext::from_bytes(b"hunter2".to_vec());
+    /// let line = EncryptedSecret::encrypt(&pair.public_key, &secret).unwrap().to_string();
+    /// assert!(!line.contains('\n'));
+    ///
+    /// let encrypted = EncryptedSecret::from_str(&line).unwrap();
+    /// assert_eq!(encrypted.decrypt(&pair.private_key).unwrap(), secret);
+    /// ```
+    ///
+    /// # Errors
+    /// If `key` is not a Public Key large enough for OAEP.
+    pub fn encrypt(key: &Key, secret: &Plaintext) -> RsaResult<Self> {
+        Ok(Self {
+            ciphertext: key.encrypt(secret, PaddingScheme::Oaep)?,
+        })
+    }
+
+    /// Decrypts this secret using a Private Key.
+    ///
+    /// # Errors
+    /// - If `key` is not the Private Key of the pair used to encrypt it.
+    /// - If the ciphertext is corrupted.
+    pub fn decrypt(&self, key: &Key) -> RsaResult<Plaintext> {
+        key.decrypt(&self.ciphertext, PaddingScheme::Oaep)
+    }
+}
+
+impl fmt::Display for EncryptedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let split_char = Self::SPLIT_CHAR;
+        write!(
+            f,
+            "{}{split_char}{}{split_char}{}",
+            Self::PREFIX,
+            Self::VERSION,
+            URL_SAFE_NO_PAD.encode(self.ciphertext.as_bytes())
+        )
+    }
+}
+
+impl FromStr for EncryptedSecret {
+    type Err = RsaError;
+
+    fn from_str(s: &str) -> RsaResult<Self> {
+        let mut pieces = s.trim().splitn(3, Self::SPLIT_CHAR);
+        if pieces.next() != Some(Self::PREFIX) {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because the secret does not start with its prefix".into(),
+            ));
+        }
+        version::check_supported("secret", pieces.next().unwrap_or_default(), Self::VERSION)?;
+        let ciphertext = pieces
+            .next()
+            .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+            .ok_or_else(|| {
+                RsaError::ImproperlyFormattedStr("because the secret is not valid base64url".into())
+            })?;
+        Ok(Self {
+            ciphertext: Ciphertext::from_bytes(ciphertext),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{tests::test_pair, KeyPair};
+
+    #[test]
+    fn test_secret_round_trip() {
+        let pair = KeyPair::demo();
+        let secret = Plaintext::from_bytes(b"correct horse battery staple".to_vec());
+        let line = EncryptedSecret::encrypt(&pair.public_key, &secret)
+            .unwrap()
+            .to_string();
+        assert!(line.starts_with("rrsa-secret:1:"));
+        assert_eq!(line.lines().count(), 1);
+
+        let encrypted = EncryptedSecret::from_str(&format!("{line}\n")).unwrap();
+        assert_eq!(encrypted.decrypt(&pair.private_key).unwrap(), secret);
+
+        // too small for OAEP
+        assert!(EncryptedSecret::encrypt(&test_pair().public_key, &secret).is_err());
+    }
+
+    #[test]
+    fn test_secret_from_str_errors() {
+        assert!(EncryptedSecret::from_str("rrsa-message:1:AAAA").is_err());
+        assert!(EncryptedSecret::from_str("rrsa-secret:1:not base64!").is_err());
+        assert!(EncryptedSecret::from_str("rrsa-secret:1").is_err());
+        assert!(matches!(
+            EncryptedSecret::from_str("rrsa-secret:2:AAAA"),
+            Err(RsaError::UnsupportedVersion { found: 2, .. })
+        ));
+    }
+}
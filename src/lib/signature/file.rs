@@ -0,0 +1,207 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{fmt, str::FromStr};
+
+use super::{HashAlgorithm, Signature, SignatureScheme};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::version;
+
+/// Detached signature, with everything needed to verify it besides the Public Key.
+///
+/// Formatted as one field per line, e.g.:
+/// ```text
+/// rrsa-sig 1
+/// scheme pss
+/// hash sha256
+/// key SHA256:a91882fd52c5ccad642fe450a19a90a2d57cf9e597e476b6527bd97678bc6037
+/// signature Ln9mgAk=
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureFile {
+    pub scheme: SignatureScheme,
+    /// [`Key::fingerprint_hex()`] of the Key Pair that produced the signature.
+    pub key_fingerprint: String,
+    pub signature: Signature,
+}
+
+impl SignatureFile {
+    pub(crate) const HEADER: &'static str = "rrsa-sig";
+    /// Current version of the format, bumped on any incompatible change.
+    pub const VERSION: u32 = 1;
+    pub(crate) const SPLIT_CHAR: char = ' ';
+
+    /// Wraps a `signature` produced by `key` with the given [`SignatureScheme`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::signature::{SignatureFile, SignatureScheme};
+    /// use std::str::FromStr;
+    ///
+    /// let pair = KeyPair::demo();
+    /// let scheme = SignatureScheme::Pss;
+    /// let digest = scheme.hash().digest(b"release v1.0");
+    /// let signature = pair.private_key.sign(&digest, scheme).unwrap();
+    ///
+    /// let file = SignatureFile::new(&pair.private_key, scheme, signature);
+    /// let parsed = SignatureFile::from_str(&file.to_string()).unwrap();
+    /// assert!(parsed.is_from_key(&pair.public_key));
+    /// ```
+    #[must_use]
+    pub fn new(key: &Key, scheme: SignatureScheme, signature: Signature) -> Self {
+        Self {
+            scheme,
+            key_fingerprint: key.fingerprint_hex(),
+            signature,
+        }
+    }
+
+    /// Returns `true` if the signature was produced by the Key Pair of `key`.
+    #[must_use]
+    pub fn is_from_key(&self, key: &Key) -> bool {
+        self.key_fingerprint == key.fingerprint_hex()
+    }
+}
+
+impl fmt::Display for SignatureFile {
+    /// Formats the given [`SignatureFile`] as a string,
+    /// which can represent the file content of it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scheme = match self.scheme {
+            SignatureScheme::Pss => "pss",
+            SignatureScheme::Pkcs1v15(_) => "pkcs1v15",
+        };
+        let hash = match self.scheme.hash() {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+        };
+        let split = SignatureFile::SPLIT_CHAR;
+        writeln!(
+            f,
+            "{}{split}{}",
+            SignatureFile::HEADER,
+            SignatureFile::VERSION
+        )?;
+        writeln!(f, "scheme{split}{scheme}")?;
+        writeln!(f, "hash{split}{hash}")?;
+        writeln!(f, "key{split}{}", self.key_fingerprint)?;
+        writeln!(
+            f,
+            "signature{split}{}",
+            STANDARD.encode(self.signature.as_bytes())
+        )
+    }
+}
+
+impl FromStr for SignatureFile {
+    type Err = RsaError;
+
+    /// Extracts a [`SignatureFile`] from the given string slice,
+    /// that represented the file content of it.
+    fn from_str(s: &str) -> RsaResult<Self> {
+        let lines: Vec<_> = s.lines().collect();
+        if lines.len() != 5 {
+            return Err(RsaError::ImproperlyFormattedStr(
+                "because it had the wrong number of lines for a signature".into(),
+            ));
+        }
+        version::check_supported(
+            "signature",
+            field(lines[0], SignatureFile::HEADER)?,
+            SignatureFile::VERSION,
+        )?;
+
+        let hash = match field(lines[2], "hash")? {
+            "sha256" => HashAlgorithm::Sha256,
+            "sha512" => HashAlgorithm::Sha512,
+            _ => {
+                return Err(RsaError::ImproperlyFormattedStr(
+                    "because the hash algorithm is unknown".into(),
+                ))
+            }
+        };
+        let scheme = match (field(lines[1], "scheme")?, hash) {
+            ("pss", HashAlgorithm::Sha256) => SignatureScheme::Pss,
+            ("pkcs1v15", hash) => SignatureScheme::Pkcs1v15(hash),
+            _ => {
+                return Err(RsaError::ImproperlyFormattedStr(
+                    "because the signature scheme is unknown or does not support the hash".into(),
+                ))
+            }
+        };
+        let key_fingerprint = field(lines[3], "key")?.to_string();
+        let signature = STANDARD
+            .decode(field(lines[4], "signature")?)
+            .map_err(|_| {
+                RsaError::ImproperlyFormattedStr("because the signature is not valid base64".into())
+            })?;
+
+        Ok(Self {
+            scheme,
+            key_fingerprint,
+            signature: Signature::from_bytes(signature),
+        })
+    }
+}
+
+/// Returns the value of a `name value` line.
+fn field<'a>(line: &'a str, name: &str) -> RsaResult<&'a str> {
+    match line.trim().split_once(SignatureFile::SPLIT_CHAR) {
+        Some((line_name, value)) if line_name == name => Ok(value.trim()),
+        _ => Err(RsaError::ImproperlyFormattedStr(format!(
+            "because the `{name}` field is missing"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::test_pair;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_signature_file() {
+        let signature_file = SignatureFile::new(
+            &test_pair().private_key,
+            SignatureScheme::Pkcs1v15(HashAlgorithm::Sha512),
+            Signature::from_bytes(vec![0x2e, 0x7f, 0x66, 0x80, 0x09]),
+        );
+        let formatted = signature_file.to_string();
+        assert_eq!(
+            formatted,
+            "rrsa-sig 1
+scheme pkcs1v15
+hash sha512
+key SHA256:a91882fd52c5ccad642fe450a19a90a2d57cf9e597e476b6527bd97678bc6037
+signature Ln9mgAk=
+"
+        );
+        assert_eq!(SignatureFile::from_str(&formatted).unwrap(), signature_file);
+        assert!(signature_file.is_from_key(&test_pair().public_key));
+    }
+
+    #[test]
+    fn test_signature_file_error() {
+        let valid = "rrsa-sig 1\nscheme pss\nhash sha256\nkey SHA256:00\nsignature AQ==\n";
+        assert!(SignatureFile::from_str(valid).is_ok());
+
+        // newer version
+        assert!(matches!(
+            SignatureFile::from_str(&valid.replace("sig 1", "sig 2")),
+            Err(RsaError::UnsupportedVersion { found: 2, .. })
+        ));
+        // pss only supports sha256
+        assert!(SignatureFile::from_str(&valid.replace("sha256", "sha512")).is_err());
+        // unknown scheme
+        assert!(SignatureFile::from_str(&valid.replace("pss", "dsa")).is_err());
+        // invalid base64
+        assert!(SignatureFile::from_str(&valid.replace("AQ==", "A?==")).is_err());
+        // missing field
+        assert!(SignatureFile::from_str(&valid.replace("key SHA256:00\n", "")).is_err());
+        // wrong field order
+        assert!(SignatureFile::from_str(
+            "rrsa-sig 1\nhash sha256\nscheme pss\nkey SHA256:00\nsignature AQ==\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Module containing all code for signing digests with a Private Key
+//! and verifying signatures with a Public Key,
+//! and for the detached [`SignatureFile`] format.
+
+use std::io::{copy, Read};
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::encoding::{i2osp, SizeInBytes};
+use crate::error::{RsaError, RsaResult};
+use crate::key::{Key, KeyVariant};
+
+mod file;
+mod pkcs1v15;
+mod pss;
+
+pub use file::SignatureFile;
+
+/// Hash algorithm used to produce the signed digest.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Length in bytes of a digest produced by this algorithm.
+    #[must_use]
+    pub fn digest_len(self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+
+    /// Returns the digest of `data` using this algorithm.
+    #[must_use]
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    /// Returns the digest of everything read from `input` using this algorithm.
+    ///
+    /// # Errors
+    /// Propagates [`std::io::Error`].
+    pub fn digest_reader<R: Read>(self, input: &mut R) -> RsaResult<Vec<u8>> {
+        Ok(match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                copy(input, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+            HashAlgorithm::Sha512 => {
+                let mut hasher = Sha512::new();
+                copy(input, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+        })
+    }
+}
+
+/// Signature scheme used when signing and verifying.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// RSASSA-PSS with SHA-256, MGF1 (SHA-256) and a 32 bytes random salt.
+    #[default]
+    Pss,
+    /// Deterministic RSASSA-PKCS1-v1_5, interoperable with OpenSSL.
+    Pkcs1v15(HashAlgorithm),
+}
+
+impl SignatureScheme {
+    /// The [`HashAlgorithm`] of the digests signed with this scheme.
+    #[must_use]
+    pub fn hash(self) -> HashAlgorithm {
+        match self {
+            SignatureScheme::Pss => HashAlgorithm::Sha256,
+            SignatureScheme::Pkcs1v15(hash) => hash,
+        }
+    }
+}
+
+/// Raw RSA signature, as big endian bytes with the same length as the modulus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature(Vec<u8>);
+
+impl Signature {
+    #[must_use]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Key {
+    /// Signs a `digest` with this Private Key, using the given [`SignatureScheme`].
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::signature::{HashAlgorithm, SignatureScheme};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let scheme = SignatureScheme::Pkcs1v15(HashAlgorithm::Sha256);
+    /// let digest = scheme.hash().digest(b"release v1.0");
+    /// let signature = pair.private_key.sign(&digest, scheme).unwrap();
+    /// assert!(pair.public_key.verify(&digest, &signature, scheme).unwrap());
+    ///
+    /// let other_digest = scheme.hash().digest(b"release v6.6.6");
+    /// assert!(!pair.public_key.verify(&other_digest, &signature, scheme).unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PrivateKey`].
+    /// - If `digest` is not the length of the scheme's [`HashAlgorithm`] digests.
+    /// - If the key is too small for the signature scheme.
+    pub fn sign(&self, digest: &[u8], scheme: SignatureScheme) -> RsaResult<Signature> {
+        if self.variant != KeyVariant::PrivateKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        if digest.len() != scheme.hash().digest_len() {
+            return Err(RsaError::InvalidDigest);
+        }
+        let em = match scheme {
+            SignatureScheme::Pss => pss::encode(digest, self.encoded_message_bits()?)?,
+            SignatureScheme::Pkcs1v15(hash) => {
+                pkcs1v15::encode(digest, hash, self.modulus.size_in_bytes())?
+            }
+        };
+        let signature = self.apply_exponent(&BigUint::from_bytes_be(&em));
+        Ok(Signature(i2osp(&signature, self.modulus.size_in_bytes())?))
+    }
+
+    /// Verifies a [`Signature`] of a `digest` with this Public Key,
+    /// using the given [`SignatureScheme`].
+    ///
+    /// # Returns
+    /// `true` if the signature is valid for `digest`.
+    ///
+    /// # Errors
+    /// - If `self` is not a [`KeyVariant::PublicKey`].
+    /// - If `digest` is not the length of the scheme's [`HashAlgorithm`] digests.
+    pub fn verify(
+        &self,
+        digest: &[u8],
+        signature: &Signature,
+        scheme: SignatureScheme,
+    ) -> RsaResult<bool> {
+        if self.variant != KeyVariant::PublicKey {
+            return Err(RsaError::WrongKeyVariant);
+        }
+        if digest.len() != scheme.hash().digest_len() {
+            return Err(RsaError::InvalidDigest);
+        }
+        let k = self.modulus.size_in_bytes();
+        if signature.0.len() != k {
+            return Ok(false);
+        }
+        let signature = BigUint::from_bytes_be(&signature.0);
+        if signature >= self.modulus {
+            return Ok(false);
+        }
+        let message = signature.modpow(&self.exponent, &self.modulus);
+
+        Ok(match scheme {
+            SignatureScheme::Pss => {
+                let em_bits = self.encoded_message_bits()?;
+                i2osp(&message, em_bits.div_ceil(8))
+                    .is_ok_and(|em| pss::verify(digest, &em, em_bits))
+            }
+            SignatureScheme::Pkcs1v15(hash) => {
+                let expected = pkcs1v15::encode(digest, hash, k)?;
+                i2osp(&message, k).is_ok_and(|em| em == expected)
+            }
+        })
+    }
+
+    /// Maximum length in bits of an encoded message, one less than the modulus.
+    fn encoded_message_bits(&self) -> RsaResult<usize> {
+        let modulus_bits = usize::try_from(self.modulus.bits())
+            .map_err(|_| RsaError::InvalidKey("the modulus is too large".into()))?;
+        Ok(modulus_bits - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::{pair_4096, test_pair};
+    use crate::testvectors::PLAINTEXT;
+
+    #[test]
+    fn test_sign_verify_pss() {
+        let pair = pair_4096();
+        let scheme = SignatureScheme::Pss;
+        let digest = HashAlgorithm::Sha256.digest(PLAINTEXT.as_bytes());
+        assert_eq!(
+            HashAlgorithm::Sha256
+                .digest_reader(&mut PLAINTEXT.as_bytes())
+                .unwrap(),
+            digest
+        );
+
+        let signature = pair.private_key.sign(&digest, scheme).unwrap();
+        assert_eq!(signature.as_bytes().len(), 512);
+        assert!(pair.public_key.verify(&digest, &signature, scheme).unwrap());
+
+        // signatures are randomized by the salt
+        assert_ne!(signature, pair.private_key.sign(&digest, scheme).unwrap());
+
+        let other_digest = HashAlgorithm::Sha256.digest(b"The quick brown fox");
+        assert!(!pair
+            .public_key
+            .verify(&other_digest, &signature, scheme)
+            .unwrap());
+
+        let mut tampered = signature.as_bytes().to_vec();
+        tampered[100] ^= 0x01;
+        assert!(!pair
+            .public_key
+            .verify(&digest, &Signature::from_bytes(tampered), scheme)
+            .unwrap());
+        assert!(!pair
+            .public_key
+            .verify(&digest, &Signature::from_bytes(vec![0x01; 10]), scheme)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_sign_verify_pkcs1v15() {
+        let pair = pair_4096();
+        for (hash, head, tail) in [
+            (
+                HashAlgorithm::Sha256,
+                [0x5e, 0x72, 0x11, 0x25, 0x99, 0x48, 0x36, 0x0c],
+                [0x82, 0xcc, 0x79, 0x1e, 0x53, 0x04, 0x01, 0xbd],
+            ),
+            (
+                HashAlgorithm::Sha512,
+                [0x5e, 0x08, 0x6d, 0x8d, 0x41, 0x4f, 0x1a, 0x3d],
+                [0xbf, 0xf3, 0x12, 0xa8, 0x38, 0x57, 0x37, 0xfb],
+            ),
+        ] {
+            let scheme = SignatureScheme::Pkcs1v15(hash);
+            let digest = hash.digest(PLAINTEXT.as_bytes());
+
+            // deterministic, matches the signature produced by OpenSSL
+            let signature = pair.private_key.sign(&digest, scheme).unwrap();
+            assert_eq!(&signature.as_bytes()[..8], &head);
+            assert_eq!(&signature.as_bytes()[504..], &tail);
+            assert!(pair.public_key.verify(&digest, &signature, scheme).unwrap());
+
+            let other_digest = hash.digest(b"The quick brown fox");
+            assert!(!pair
+                .public_key
+                .verify(&other_digest, &signature, scheme)
+                .unwrap());
+            assert!(!pair
+                .public_key
+                .verify(&digest, &signature, SignatureScheme::Pss)
+                .unwrap_or(false));
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_errors() {
+        let pair = pair_4096();
+        let scheme = SignatureScheme::Pss;
+        let digest = HashAlgorithm::Sha256.digest(b"rrsa");
+        assert!(pair.public_key.sign(&digest, scheme).is_err());
+        assert!(pair.private_key.sign(b"too short", scheme).is_err());
+        assert!(pair
+            .private_key
+            .sign(&digest, SignatureScheme::Pkcs1v15(HashAlgorithm::Sha512))
+            .is_err());
+
+        let signature = pair.private_key.sign(&digest, scheme).unwrap();
+        assert!(pair
+            .private_key
+            .verify(&digest, &signature, scheme)
+            .is_err());
+
+        assert!(test_pair().private_key.sign(&digest, scheme).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+//! EMSA-PKCS1-v1_5 encoding, as defined by RFC 8017.
+
+use super::HashAlgorithm;
+use crate::error::{RsaError, RsaResult};
+
+/// Minimum amount of `0xFF` padding bytes in an encoded message.
+const MIN_PADDING_LEN: usize = 8;
+
+/// DER encoded `DigestInfo` prefix for SHA-256, followed by the 32 bytes digest.
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+/// DER encoded `DigestInfo` prefix for SHA-512, followed by the 64 bytes digest.
+const SHA512_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+fn digest_info_prefix(hash: HashAlgorithm) -> &'static [u8] {
+    match hash {
+        HashAlgorithm::Sha256 => &SHA256_DIGEST_INFO,
+        HashAlgorithm::Sha512 => &SHA512_DIGEST_INFO,
+    }
+}
+
+/// EMSA-PKCS1-v1_5 encoding of `digest` into an encoded message of `em_len` bytes.
+///
+/// The encoding is deterministic, so verification re-encodes and compares.
+pub(crate) fn encode(digest: &[u8], hash: HashAlgorithm, em_len: usize) -> RsaResult<Vec<u8>> {
+    let prefix = digest_info_prefix(hash);
+    let t_len = prefix.len() + digest.len();
+    if em_len < t_len + MIN_PADDING_LEN + 3 {
+        return Err(RsaError::KeyTooSmallForPadding);
+    }
+
+    // EM = 0x00 || 0x01 || PS || 0x00 || T
+    let mut em = Vec::with_capacity(em_len);
+    em.extend_from_slice(&[0x00, 0x01]);
+    em.resize(em_len - t_len - 1, 0xFF);
+    em.push(0x00);
+    em.extend_from_slice(prefix);
+    em.extend_from_slice(digest);
+    Ok(em)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_pkcs1v15_encode() {
+        let digest = [0xABu8; 32];
+        let em = encode(&digest, HashAlgorithm::Sha256, 64).unwrap();
+        assert_eq!(em.len(), 64);
+        assert_eq!(&em[..2], &[0x00, 0x01]);
+        assert!(em[2..12].iter().all(|byte| *byte == 0xFF));
+        assert_eq!(em[12], 0x00);
+        assert_eq!(&em[13..32], &SHA256_DIGEST_INFO);
+        assert_eq!(&em[32..], &digest);
+
+        assert!(encode(&digest, HashAlgorithm::Sha256, 61).is_err());
+        assert!(encode(&[0xABu8; 64], HashAlgorithm::Sha512, 93).is_err());
+        assert_eq!(
+            encode(&[0xABu8; 64], HashAlgorithm::Sha512, 94)
+                .unwrap()
+                .len(),
+            94
+        );
+    }
+}
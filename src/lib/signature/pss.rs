@@ -0,0 +1,114 @@
+//! EMSA-PSS encoding and verification, as defined by RFC 8017,
+//! with SHA-256, MGF1 (SHA-256) and a salt as long as the digest.
+
+use sha2::{Digest, Sha256};
+
+use crate::encoding::padding::{mgf1, xor_in_place, HASH_LEN};
+use crate::error::{RsaError, RsaResult};
+use crate::rng;
+
+/// Length in bytes of the random salt.
+const SALT_LEN: usize = HASH_LEN;
+/// Last byte of every encoded message.
+const TRAILER: u8 = 0xbc;
+
+/// Returns `H = SHA-256(0x00 * 8 || m_hash || salt)`.
+fn salted_hash(m_hash: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8; 8]);
+    hasher.update(m_hash);
+    hasher.update(salt);
+    hasher.finalize().to_vec()
+}
+
+/// Mask clearing the bits of the first byte beyond `em_bits`.
+fn first_byte_mask(em_bits: usize) -> u8 {
+    0xFF >> (8 * em_bits.div_ceil(8) - em_bits)
+}
+
+/// EMSA-PSS encoding of `m_hash` into an encoded message of at most `em_bits` bits,
+/// using a random salt.
+pub(crate) fn encode(m_hash: &[u8], em_bits: usize) -> RsaResult<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    rng::fill_bytes(&mut salt);
+    encode_with_salt(m_hash, em_bits, &salt)
+}
+
+/// EMSA-PSS encoding of `m_hash` into an encoded message of at most `em_bits` bits,
+/// using the given `salt`.
+pub(crate) fn encode_with_salt(
+    m_hash: &[u8],
+    em_bits: usize,
+    salt: &[u8; SALT_LEN],
+) -> RsaResult<Vec<u8>> {
+    let em_len = em_bits.div_ceil(8);
+    if em_len < HASH_LEN + SALT_LEN + 2 {
+        return Err(RsaError::KeyTooSmallForPadding);
+    }
+
+    let h = salted_hash(m_hash, salt);
+
+    // DB = PS || 0x01 || salt
+    let mut db = vec![0u8; em_len - SALT_LEN - HASH_LEN - 2];
+    db.push(0x01);
+    db.extend_from_slice(salt);
+    let db_mask = mgf1(&h, db.len());
+    xor_in_place(&mut db, &db_mask);
+    db[0] &= first_byte_mask(em_bits);
+
+    // EM = maskedDB || H || 0xbc
+    let mut em = db;
+    em.extend_from_slice(&h);
+    em.push(TRAILER);
+    Ok(em)
+}
+
+/// EMSA-PSS verification of an encoded message `em` of at most `em_bits` bits.
+///
+/// # Returns
+/// `true` if `em` is consistent with `m_hash`.
+pub(crate) fn verify(m_hash: &[u8], em: &[u8], em_bits: usize) -> bool {
+    let em_len = em_bits.div_ceil(8);
+    if em.len() != em_len || em_len < HASH_LEN + SALT_LEN + 2 || em[em_len - 1] != TRAILER {
+        return false;
+    }
+    let (masked_db, h) = em[..em_len - 1].split_at(em_len - HASH_LEN - 1);
+    if masked_db[0] & !first_byte_mask(em_bits) != 0 {
+        return false;
+    }
+
+    let mut db = masked_db.to_vec();
+    xor_in_place(&mut db, &mgf1(h, masked_db.len()));
+    db[0] &= first_byte_mask(em_bits);
+
+    let (padding, salt) = db.split_at(em_len - HASH_LEN - SALT_LEN - 1);
+    let (zeros, separator) = padding.split_at(padding.len() - 1);
+    zeros.iter().all(|byte| *byte == 0x00) && separator[0] == 0x01 && salted_hash(m_hash, salt) == h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_pss_fixed_vector() {
+        let m_hash = Sha256::digest(b"rrsa");
+        let salt = [0x55u8; SALT_LEN];
+        let em = encode_with_salt(&m_hash, 1023, &salt).unwrap();
+        assert_eq!(em.len(), 128);
+        assert_eq!(&em[..8], &[0x7b, 0x05, 0x77, 0x86, 0x8a, 0xbf, 0x01, 0xe1]);
+        assert_eq!(
+            &em[87..95],
+            &[0x24, 0xa0, 0x2f, 0x4a, 0xec, 0xe4, 0x70, 0x59]
+        );
+        assert_eq!(em[127], TRAILER);
+        assert!(verify(&m_hash, &em, 1023));
+
+        let mut tampered = em.clone();
+        tampered[40] ^= 0x01;
+        assert!(!verify(&m_hash, &tampered, 1023));
+        assert!(!verify(&Sha256::digest(b"rsa"), &em, 1023));
+        assert!(encode_with_salt(&m_hash, 8 * 65, &salt).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! Module to emit and verify canonical golden files for the formats produced by this crate.
+//!
+//! Every vector is fully deterministic, so comparing freshly generated vectors
+//! against a directory of previously committed ones catches format regressions mechanically.
+
+use crate::encoding::{armor, padding::PaddingScheme, Plaintext};
+use crate::error::{RsaError, RsaResult};
+use crate::jwt;
+use crate::key::{Key, KeyFormat, KeyPair, KeyVariant};
+use crate::message::EncryptedMessage;
+use crate::rng;
+use crate::secret::EncryptedSecret;
+use crate::signature::{HashAlgorithm, SignatureFile, SignatureScheme};
+use crate::version;
+use num_bigint::BigUint;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::{
+    fs::{create_dir_all, read},
+    io::Cursor,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Plaintext used to produce the ciphertext vectors.
+pub const PLAINTEXT: &str = "The quick brown fox jumps over the lazy dog.\n";
+
+/// Producer stamped in the vectors instead of the current crate version,
+/// so releasing a new version does not change the golden files.
+pub const VECTOR_PRODUCER: &str = "rrsa testvectors";
+
+/// Seed of the generator used for the vectors of formats using randomness,
+/// such as PSS signatures, OAEP ciphertexts and envelopes.
+pub const VECTOR_SEED: u64 = 0x7E57_7EC7_0500_5EED;
+
+/// Claims of the JWT vector.
+pub const JWT_CLAIMS: &str = r#"{"sub":"rrsa","aud":"testvectors"}"#;
+
+/// A single golden file, with its file name and expected contents.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TestVector {
+    pub name: &'static str,
+    pub contents: Vec<u8>,
+}
+
+/// Result of checking a single [`TestVector`] against a directory.
+#[derive(Debug, PartialEq, Eq)]
+pub enum VectorStatus {
+    /// File contents are identical to the generated vector.
+    Match,
+    /// File exists but its contents differ from the generated vector.
+    Mismatch,
+    /// File is not present in the directory.
+    Missing,
+}
+
+/// Generates all the canonical test vectors.
+///
+/// Besides the fixed 32 bits key pair, vectors of every other wire format use
+/// [`KeyPair::demo()`], and their random values come from a generator seeded with [`VECTOR_SEED`].
+///
+/// # Errors
+/// Propagates any error from encoding the ciphertext vectors.
+pub fn generate() -> RsaResult<Vec<TestVector>> {
+    let (public_key, private_key) = vector_pair();
+    let ndex_key = Key::try_new(
+        BigUint::from(0x5B97u32),
+        BigUint::from(0x11C6_8C75u64),
+        KeyVariant::PublicKey,
+    )?;
+
+    let mut ciphertext = Vec::new();
+    public_key.encode(
+        &mut Cursor::new(PLAINTEXT.as_bytes()),
+        &mut ciphertext,
+        PaddingScheme::None,
+    )?;
+    let mut legacy_ciphertext = Vec::new();
+    public_key.encode_blocks(
+        &mut Cursor::new(PLAINTEXT.as_bytes()),
+        &mut legacy_ciphertext,
+        PaddingScheme::None,
+        false,
+    )?;
+
+    let mut vectors = vec![
+        TestVector {
+            name: "public_key.pub",
+            contents: public_key.to_string().into_bytes(),
+        },
+        TestVector {
+            name: "public_key_ndex.pub",
+            contents: ndex_key.to_string().into_bytes(),
+        },
+        TestVector {
+            name: "private_key",
+            contents: with_fixed_producer(private_key.to_string().as_bytes()),
+        },
+        TestVector {
+            name: "plaintext.txt",
+            contents: PLAINTEXT.as_bytes().to_vec(),
+        },
+        TestVector {
+            name: "ciphertext.encoded",
+            contents: ciphertext,
+        },
+        TestVector {
+            name: "ciphertext_legacy.encoded",
+            contents: legacy_ciphertext,
+        },
+    ];
+    vectors.extend(demo_key_vectors()?);
+    vectors.extend(rng::with_source(
+        ChaCha20Rng::seed_from_u64(VECTOR_SEED),
+        seeded_vectors,
+    )?);
+    Ok(vectors)
+}
+
+/// Vectors of [`KeyPair::demo()`] in every [`KeyFormat`].
+fn demo_key_vectors() -> RsaResult<Vec<TestVector>> {
+    let pair = KeyPair::demo();
+    #[cfg_attr(not(feature = "jwk"), allow(unused_mut))]
+    let mut vectors = vec![
+        TestVector {
+            name: "demo_public_key.pub",
+            contents: pair.public_key.to_format(KeyFormat::Rrsa)?,
+        },
+        TestVector {
+            name: "demo_private_key",
+            contents: with_fixed_producer(&pair.private_key.to_format(KeyFormat::Rrsa)?),
+        },
+        TestVector {
+            name: "demo_public_key.pem",
+            contents: pair.public_key.to_format(KeyFormat::Pem)?,
+        },
+        TestVector {
+            name: "demo_private_key.pem",
+            contents: pair.private_key.to_format(KeyFormat::Pem)?,
+        },
+        TestVector {
+            name: "demo_public_key.der",
+            contents: pair.public_key.to_format(KeyFormat::Der)?,
+        },
+        TestVector {
+            name: "demo_private_key.der",
+            contents: pair.private_key.to_format(KeyFormat::Der)?,
+        },
+        TestVector {
+            name: "demo_public_key.openssh",
+            contents: pair.public_key.to_format(KeyFormat::OpenSsh)?,
+        },
+    ];
+    #[cfg(feature = "jwk")]
+    vectors.extend([
+        TestVector {
+            name: "demo_public_key.jwk",
+            contents: pair.public_key.to_format(KeyFormat::Jwk)?,
+        },
+        TestVector {
+            name: "demo_private_key.jwk",
+            contents: pair.private_key.to_format(KeyFormat::Jwk)?,
+        },
+    ]);
+    Ok(vectors)
+}
+
+/// Vectors of the formats using randomness, to be generated with a seeded source.
+fn seeded_vectors() -> RsaResult<Vec<TestVector>> {
+    let pair = KeyPair::demo();
+
+    let signature_vector = |name, scheme: SignatureScheme| -> RsaResult<TestVector> {
+        let digest = scheme.hash().digest(PLAINTEXT.as_bytes());
+        let signature = pair.private_key.sign(&digest, scheme)?;
+        Ok(TestVector {
+            name,
+            contents: SignatureFile::new(&pair.private_key, scheme, signature)
+                .to_string()
+                .into_bytes(),
+        })
+    };
+
+    let mut oaep_ciphertext = Vec::new();
+    pair.public_key.encode(
+        &mut Cursor::new(PLAINTEXT.as_bytes()),
+        &mut oaep_ciphertext,
+        PaddingScheme::Oaep,
+    )?;
+    let mut envelope = Vec::new();
+    pair.public_key
+        .encode_envelope(&mut Cursor::new(PLAINTEXT.as_bytes()), &mut envelope)?;
+    let message = EncryptedMessage::encrypt(&pair.public_key, "Vectors", PLAINTEXT.as_bytes())?;
+    let secret = EncryptedSecret::encrypt(
+        &pair.public_key,
+        &Plaintext::from_bytes(PLAINTEXT.as_bytes().to_vec()),
+    )?;
+
+    Ok(vec![
+        signature_vector("signature_pss.sig", SignatureScheme::Pss)?,
+        signature_vector(
+            "signature_pkcs1v15.sig",
+            SignatureScheme::Pkcs1v15(HashAlgorithm::Sha256),
+        )?,
+        TestVector {
+            name: "ciphertext_oaep.armored",
+            contents: with_fixed_producer(armor::armor(&oaep_ciphertext).as_bytes()),
+        },
+        TestVector {
+            name: "ciphertext_oaep.encoded",
+            contents: oaep_ciphertext,
+        },
+        TestVector {
+            name: "envelope.encoded",
+            contents: envelope,
+        },
+        TestVector {
+            name: "message.eml",
+            contents: with_fixed_producer(message.to_string().as_bytes()),
+        },
+        TestVector {
+            name: "secret.txt",
+            contents: secret.to_string().into_bytes(),
+        },
+        TestVector {
+            name: "token.jwt",
+            contents: jwt::sign_rs256(&pair.private_key, JWT_CLAIMS)?.into_bytes(),
+        },
+    ])
+}
+
+/// Writes all the canonical test vectors to `dir`, creating it if needed.
+///
+/// # Returns
+/// The paths of every file written.
+///
+/// # Errors
+/// Propagates [`std::io::Error`].
+pub fn write_to_dir(dir: &Path) -> RsaResult<Vec<PathBuf>> {
+    create_dir_all(dir)?;
+    generate()?
+        .into_iter()
+        .map(|vector| -> RsaResult<PathBuf> {
+            let path = dir.join(vector.name);
+            std::fs::write(&path, vector.contents)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Checks every canonical test vector against the files present in `dir`.
+///
+/// # Returns
+/// The name and [`VectorStatus`] of every vector.
+///
+/// # Errors
+/// - If a stored ciphertext does not decode back to the stored plaintext.
+/// - If a stored signature or token does not verify.
+/// - Propagates [`std::io::Error`].
+pub fn check_dir(dir: &Path) -> RsaResult<Vec<(&'static str, VectorStatus)>> {
+    let mut report = Vec::new();
+    for vector in generate()? {
+        let path = dir.join(vector.name);
+        let status = if !path.is_file() {
+            VectorStatus::Missing
+        } else if read(&path)? == vector.contents {
+            VectorStatus::Match
+        } else {
+            VectorStatus::Mismatch
+        };
+        report.push((vector.name, status));
+    }
+
+    let (_, private_key) = vector_pair();
+    let ciphertext_path = dir.join("ciphertext.encoded");
+    if ciphertext_path.is_file() {
+        let mut plaintext = Vec::new();
+        private_key.decode(
+            &mut Cursor::new(read(ciphertext_path)?),
+            &mut plaintext,
+            PaddingScheme::None,
+        )?;
+        if plaintext != PLAINTEXT.as_bytes() {
+            return Err(RsaError::EncodingError);
+        }
+    }
+    let legacy_ciphertext_path = dir.join("ciphertext_legacy.encoded");
+    if legacy_ciphertext_path.is_file() {
+        let mut plaintext = Vec::new();
+        private_key.decode_legacy(
+            &mut Cursor::new(read(legacy_ciphertext_path)?),
+            &mut plaintext,
+        )?;
+        if plaintext != PLAINTEXT.as_bytes() {
+            return Err(RsaError::EncodingError);
+        }
+    }
+    check_seeded_vectors(dir)?;
+
+    Ok(report)
+}
+
+/// Makes sure the stored vectors of the formats using randomness
+/// still verify or decrypt with [`KeyPair::demo()`].
+fn check_seeded_vectors(dir: &Path) -> RsaResult<()> {
+    let pair = KeyPair::demo();
+    let read_if_present = |name: &str| -> RsaResult<Option<String>> {
+        let path = dir.join(name);
+        Ok(if path.is_file() {
+            Some(String::from_utf8_lossy(&read(path)?).into_owned())
+        } else {
+            None
+        })
+    };
+    let decode = |ciphertext: Vec<u8>| -> RsaResult<Vec<u8>> {
+        let mut plaintext = Vec::new();
+        pair.private_key.decode(
+            &mut Cursor::new(ciphertext),
+            &mut plaintext,
+            PaddingScheme::Oaep,
+        )?;
+        Ok(plaintext)
+    };
+
+    for name in ["signature_pss.sig", "signature_pkcs1v15.sig"] {
+        if let Some(contents) = read_if_present(name)? {
+            let file = SignatureFile::from_str(&contents)?;
+            let digest = file.scheme.hash().digest(PLAINTEXT.as_bytes());
+            if !file.is_from_key(&pair.public_key)
+                || !pair
+                    .public_key
+                    .verify(&digest, &file.signature, file.scheme)?
+            {
+                return Err(RsaError::InvalidSignature);
+            }
+        }
+    }
+    let mut plaintexts = Vec::new();
+    for name in ["ciphertext_oaep.encoded", "envelope.encoded"] {
+        let path = dir.join(name);
+        if path.is_file() {
+            plaintexts.push(decode(read(path)?)?);
+        }
+    }
+    if let Some(armored) = read_if_present("ciphertext_oaep.armored")? {
+        plaintexts.push(decode(armor::dearmor(&armored)?)?);
+    }
+    if let Some(message) = read_if_present("message.eml")? {
+        plaintexts.push(EncryptedMessage::from_str(&message)?.decrypt(&pair.private_key)?);
+    }
+    if let Some(secret) = read_if_present("secret.txt")? {
+        let secret = EncryptedSecret::from_str(&secret)?.decrypt(&pair.private_key)?;
+        plaintexts.push(secret.as_bytes().to_vec());
+    }
+    if plaintexts
+        .iter()
+        .any(|plaintext| plaintext != PLAINTEXT.as_bytes())
+    {
+        return Err(RsaError::EncodingError);
+    }
+    if let Some(token) = read_if_present("token.jwt")? {
+        if jwt::verify_rs256(&pair.public_key, &token)? != JWT_CLAIMS {
+            return Err(RsaError::InvalidSignature);
+        }
+    }
+    Ok(())
+}
+
+/// Replaces every occurrence of the current producer in `contents` by [`VECTOR_PRODUCER`].
+fn with_fixed_producer(contents: &[u8]) -> Vec<u8> {
+    let producer = version::producer().into_bytes();
+    let mut fixed = Vec::with_capacity(contents.len());
+    let mut rest = contents;
+    while !rest.is_empty() {
+        if rest.starts_with(&producer) {
+            fixed.extend_from_slice(VECTOR_PRODUCER.as_bytes());
+            rest = &rest[producer.len()..];
+        } else {
+            fixed.push(rest[0]);
+            rest = &rest[1..];
+        }
+    }
+    fixed
+}
+
+/// The fixed 32 bits key pair used for all vectors.
+fn vector_pair() -> (Key, Key) {
+    let modulus = BigUint::from(0x9668_F701u64);
+    (
+        Key::try_new(
+            BigUint::from(Key::DEFAULT_EXPONENT),
+            modulus.clone(),
+            KeyVariant::PublicKey,
+        )
+        .expect("vector public key is valid"),
+        Key::try_new(
+            BigUint::from(0x147B_7F71u32),
+            modulus,
+            KeyVariant::PrivateKey,
+        )
+        .expect("vector private key is valid"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::FixtureBuilder;
+
+    const GOLDEN_DIR_PATH: &str = "./testvectors";
+
+    #[test]
+    fn test_golden_files_match() {
+        let report = check_dir(Path::new(GOLDEN_DIR_PATH)).unwrap();
+        for (name, status) in report {
+            assert_eq!(status, VectorStatus::Match, "golden file {name} changed");
+        }
+    }
+
+    #[test]
+    fn test_write_and_check() {
+        let fixture = FixtureBuilder::new().build().unwrap();
+        let dir = fixture.join("testvectors");
+        let written = write_to_dir(&dir).unwrap();
+        assert_eq!(written.len(), generate().unwrap().len());
+
+        std::fs::write(dir.join("public_key.pub"), "rrsa 1234\n").unwrap();
+        std::fs::remove_file(dir.join("private_key")).unwrap();
+        let report = check_dir(&dir).unwrap();
+        assert!(report.contains(&("public_key.pub", VectorStatus::Mismatch)));
+        assert!(report.contains(&("private_key", VectorStatus::Missing)));
+        assert!(report.contains(&("plaintext.txt", VectorStatus::Match)));
+    }
+
+    #[test]
+    fn test_fixed_producer() {
+        let private_key = &generate().unwrap()[2];
+        let contents = String::from_utf8(private_key.contents.clone()).unwrap();
+        assert!(contents.contains(&format!("Producer: {VECTOR_PRODUCER}\n")));
+        assert!(!contents.contains(&version::producer()));
+        assert!(contents.parse::<Key>().is_ok());
+    }
+
+    #[test]
+    fn test_seeded_vectors_are_deterministic() {
+        let vectors = generate().unwrap();
+        assert_eq!(vectors, generate().unwrap());
+
+        let crt_key = vectors
+            .iter()
+            .find(|vector| vector.name == "demo_private_key")
+            .unwrap();
+        assert!(String::from_utf8_lossy(&crt_key.contents).contains("Format: 2\n"));
+    }
+}
@@ -0,0 +1,75 @@
+//! Module with the versions stamped in the formats produced by this crate,
+//! so files written by a newer version are reported as such instead of misparsed.
+
+use crate::encoding::{armor, envelope, header};
+use crate::error::{RsaError, RsaResult};
+use crate::key::Key;
+use crate::message::EncryptedMessage;
+use crate::secret::EncryptedSecret;
+use crate::signature::SignatureFile;
+
+/// Version of this crate, stamped as the producer of key files and containers.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name and current version of every versioned format.
+pub const FORMATS: [(&str, u32); 7] = [
+    ("private key", Key::FORMAT_VERSION),
+    ("signature", SignatureFile::VERSION),
+    ("message", EncryptedMessage::VERSION),
+    ("secret", EncryptedSecret::VERSION),
+    ("armor", armor::ARMOR_VERSION),
+    ("envelope", envelope::ENVELOPE_VERSION),
+    ("ciphertext", header::CIPHERTEXT_VERSION),
+];
+
+/// Value of the producer field written in key files and containers.
+pub(crate) fn producer() -> String {
+    format!("rrsa {CRATE_VERSION}")
+}
+
+/// Parses the `found` version of `format`, making sure it is not newer than `supported`.
+///
+/// # Errors
+/// - If `found` is not a valid version number.
+/// - If `found` is newer than `supported`.
+pub(crate) fn check_supported(format: &'static str, found: &str, supported: u32) -> RsaResult<()> {
+    match found.trim().parse::<u32>() {
+        Ok(version) if (1..=supported).contains(&version) => Ok(()),
+        Ok(version) if version > supported => Err(RsaError::UnsupportedVersion {
+            format,
+            found: version,
+            supported,
+        }),
+        _ => Err(RsaError::ImproperlyFormattedStr(format!(
+            "because the {format} format version is invalid"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_supported() {
+        assert!(check_supported("message", "1", 1).is_ok());
+        assert!(check_supported("message", " 1\n", 2).is_ok());
+        assert!(matches!(
+            check_supported("message", "3", 2),
+            Err(RsaError::UnsupportedVersion {
+                format: "message",
+                found: 3,
+                supported: 2
+            })
+        ));
+        assert!(matches!(
+            check_supported("message", "0", 1),
+            Err(RsaError::ImproperlyFormattedStr(_))
+        ));
+        assert!(matches!(
+            check_supported("message", "one", 1),
+            Err(RsaError::ImproperlyFormattedStr(_))
+        ));
+        assert_eq!(producer(), format!("rrsa {}", env!("CARGO_PKG_VERSION")));
+    }
+}
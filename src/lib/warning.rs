@@ -0,0 +1,252 @@
+//! Warnings about operations that succeeded, but deserve the attention of the user,
+//! collected by the library so every front end presents them the same way.
+
+use num_bigint::BigUint;
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+use crate::encoding::padding::PaddingScheme;
+use crate::error::RsaResult;
+use crate::key::Key;
+
+/// Something worth telling the user about a successful operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The modulus is smaller than [`Key::WEAK_KEY_BITS`].
+    WeakKey { bits: u64 },
+    /// The public exponent is lower than the default 65537.
+    SmallExponent { exponent: BigUint },
+    /// The input was decoded by [`Key::decode_legacy()`], since it had no header,
+    /// so neither the Key nor the integrity of the whole data could be checked.
+    LegacyFormat,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::WeakKey { bits } => write!(
+                f,
+                "the key has {bits} bits, less than the {} bits considered secure",
+                Key::WEAK_KEY_BITS
+            ),
+            Warning::SmallExponent { exponent } => write!(
+                f,
+                "the public exponent {exponent} is lower than {}",
+                Key::DEFAULT_EXPONENT
+            ),
+            Warning::LegacyFormat => write!(
+                f,
+                "the input has no header, it was written by an older version \
+                 and its integrity could not be checked"
+            ),
+        }
+    }
+}
+
+/// Amount of bytes read and written by an operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+}
+
+/// What a successful operation returns besides its output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationOutcome {
+    pub stats: OperationStats,
+    pub warnings: Vec<Warning>,
+}
+
+impl Key {
+    /// Keys with a smaller modulus are reported as [`Warning::WeakKey`].
+    pub const WEAK_KEY_BITS: u64 = 2048;
+
+    /// Returns the warnings about this Key itself.
+    ///
+    /// The public exponent of a Private Key is only known if it carries its CRT components.
+    ///
+    /// ```
+    /// use rrsa_lib::key::KeyPair;
+    /// use rrsa_lib::warning::Warning;
+    ///
+    /// let warnings = KeyPair::demo().public_key.warnings();
+    /// assert_eq!(warnings, [Warning::WeakKey { bits: 1024 }]);
+    /// ```
+    #[must_use]
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let bits = self.modulus.bits();
+        if bits < Key::WEAK_KEY_BITS {
+            warnings.push(Warning::WeakKey { bits });
+        }
+        let public_exponent = if self.is_public() {
+            Some(&self.exponent)
+        } else {
+            self.crt.as_ref().map(|crt| &crt.public_exponent)
+        };
+        if let Some(exponent) = public_exponent {
+            if *exponent < BigUint::from(Key::DEFAULT_EXPONENT) {
+                warnings.push(Warning::SmallExponent {
+                    exponent: exponent.clone(),
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Same as [`Key::encode()`], also returning the [`OperationOutcome`].
+    ///
+    /// # Errors
+    /// Same as [`Key::encode()`].
+    pub fn encode_with_outcome<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<OperationOutcome> {
+        let mut input = Counting::new(input);
+        let mut output = Counting::new(output);
+        self.encode(&mut input, &mut output, padding)?;
+        Ok(OperationOutcome {
+            stats: OperationStats {
+                bytes_read: input.count,
+                bytes_written: output.count,
+            },
+            warnings: self.warnings(),
+        })
+    }
+
+    /// Same as [`Key::decode()`], also returning the [`OperationOutcome`].
+    ///
+    /// ```
+    /// use rrsa_lib::{encoding::padding::PaddingScheme, key::KeyPair};
+    ///
+    /// let pair = KeyPair::demo();
+    /// let mut encoded = Vec::new();
+    /// pair.public_key
+    ///     .encode(&mut &b"attack at dawn"[..], &mut encoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    ///
+    /// let mut decoded = Vec::new();
+    /// let outcome = pair
+    ///     .private_key
+    ///     .decode_with_outcome(&mut encoded.as_slice(), &mut decoded, PaddingScheme::Oaep)
+    ///     .unwrap();
+    /// assert_eq!(outcome.stats.bytes_written, 14);
+    /// assert_eq!(outcome.warnings.len(), 1);
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Key::decode()`].
+    pub fn decode_with_outcome<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        padding: PaddingScheme,
+    ) -> RsaResult<OperationOutcome> {
+        let mut input = Counting::new(input);
+        let mut output = Counting::new(output);
+        self.decode(&mut input, &mut output, padding)?;
+        Ok(OperationOutcome {
+            stats: OperationStats {
+                bytes_read: input.count,
+                bytes_written: output.count,
+            },
+            warnings: self.warnings(),
+        })
+    }
+}
+
+/// Counts the bytes read from or written to `inner`.
+struct Counting<T> {
+    inner: T,
+    count: u64,
+}
+
+impl<T> Counting<T> {
+    fn new(inner: T) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.count += len as u64;
+        Ok(len)
+    }
+}
+
+impl<W: Write> Write for Counting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.count += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::header::CIPHERTEXT_HEADER_LEN;
+    use crate::error::RsaError;
+    use crate::key::tests::test_pair;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_outcome_warnings() {
+        let pair = test_pair();
+        let original = b"warned".to_vec();
+        let mut encoded = Vec::new();
+        let outcome = pair
+            .public_key
+            .encode_with_outcome(
+                &mut Cursor::new(original.clone()),
+                &mut encoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        assert_eq!(outcome.stats.bytes_read, 6);
+        assert_eq!(outcome.stats.bytes_written, encoded.len() as u64);
+        assert_eq!(outcome.warnings, [Warning::WeakKey { bits: 32 }]);
+
+        let mut decoded = Vec::new();
+        let outcome = pair
+            .private_key
+            .decode_with_outcome(
+                &mut Cursor::new(encoded.clone()),
+                &mut decoded,
+                PaddingScheme::None,
+            )
+            .unwrap();
+        assert_eq!(decoded, original);
+        assert_eq!(outcome.stats.bytes_read, encoded.len() as u64);
+        assert_eq!(outcome.warnings, [Warning::WeakKey { bits: 32 }]);
+
+        assert!(matches!(
+            pair.private_key.decode_with_outcome(
+                &mut Cursor::new(&encoded[CIPHERTEXT_HEADER_LEN..]),
+                &mut Vec::new(),
+                PaddingScheme::None,
+            ),
+            Err(RsaError::MissingHeader)
+        ));
+
+        let small_exponent = Key::try_new(
+            BigUint::from(0x5B97u32),
+            BigUint::from(0x11C6_8C75u64),
+            crate::key::KeyVariant::PublicKey,
+        )
+        .unwrap();
+        assert!(small_exponent.warnings().contains(&Warning::SmallExponent {
+            exponent: BigUint::from(0x5B97u32)
+        }));
+        assert!(Warning::LegacyFormat.to_string().contains("no header"));
+    }
+}
@@ -0,0 +1,1356 @@
+use rrsa_lib::cli_test::CliHarness;
+use rrsa_lib::encoding::header::CIPHERTEXT_HEADER_LEN;
+use rrsa_lib::key::Key;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_rrsa-cli");
+const PLAINTEXT: &str = "The quick brown fox jumps over the lazy dog.\n";
+
+/// Size in bits of the Key `name` of `harness`.
+fn key_bits(harness: &CliHarness, name: &str) -> u64 {
+    Key::read_from_path(&harness.path(name))
+        .unwrap()
+        .report()
+        .bits
+}
+
+/// Harness with a 768 bits Key Pair, large enough for every padding scheme.
+fn harness_with_keys() -> CliHarness {
+    let harness = CliHarness::new(BINARY).unwrap();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "key"])
+        .unwrap()
+        .assert_success();
+    harness.write_file("msg.txt", PLAINTEXT).unwrap();
+    harness
+}
+
+#[test]
+fn test_keygen_validate() {
+    let harness = harness_with_keys();
+    assert!(harness.path("key.pub").is_file());
+    assert!(harness.path("key").is_file());
+
+    harness
+        .run(&["validate", "-p", "key.pub", "-k", "key"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Key Pair is valid!");
+    harness
+        .run(&["validate", "-p", "key.pub"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Public Key is valid!");
+    harness
+        .run(&["validate", "-p", "key"])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_logging_verbosity() {
+    let harness = harness_with_keys();
+    let keygen = harness
+        .run(&[
+            "keygen",
+            "-k",
+            "256",
+            "-o",
+            "small",
+            "--results",
+            "--progress",
+        ])
+        .unwrap();
+    keygen.assert_success();
+    assert!(keygen.stderr.contains("Generating P...DONE"));
+    assert!(keygen.stderr.contains("Attempts needed: "));
+    assert!(!keygen.stdout.contains("Attempts needed: "));
+
+    let encrypt = ["encrypt", "-i", "msg.txt", "-k", "key.pub", "--force"];
+    let output = harness.run(&encrypt).unwrap();
+    let bits = key_bits(&harness, "key.pub");
+    assert!(output
+        .assert_success()
+        .stderr
+        .contains(&format!("the key has {bits} bits")));
+    let output = harness.run(&[&encrypt[..], &["--quiet"]].concat()).unwrap();
+    assert!(output.assert_success().stderr.is_empty());
+    harness
+        .run(&[&encrypt[..], &["-q", "-v"]].concat())
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_inspect_validate_output_format() {
+    let harness = harness_with_keys();
+    // the top level `inspect` is a hidden alias of `key inspect`
+    let help = harness.run(&["--help"]).unwrap();
+    help.assert_success();
+    assert!(!help
+        .stdout
+        .lines()
+        .any(|line| line.trim_start().starts_with("inspect ")));
+    harness
+        .run(&["inspect", "-k", "key.pub"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Variant: Public Key")
+        .assert_stdout_line(&format!("Size: {} bits", key_bits(&harness, "key.pub")));
+
+    let output = harness
+        .run(&["inspect", "-k", "key", "--output-format", "json"])
+        .unwrap();
+    output.assert_success();
+    let json: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(json["variant"], "private");
+    assert_eq!(json["bits"], key_bits(&harness, "key"));
+
+    harness
+        .run(&["inspect", "-k", "key.pub", "--output-format", "yaml"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("variant: public")
+        .assert_stdout_line("public_exponent: '65537'");
+
+    let output = harness
+        .run(&[
+            "validate",
+            "-p",
+            "key.pub",
+            "-k",
+            "key",
+            "--output-format",
+            "json",
+        ])
+        .unwrap();
+    output.assert_success();
+    let json: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(json["valid"], true);
+    assert_eq!(
+        json["public_key"]["fingerprint"],
+        json["private_key"]["fingerprint"]
+    );
+    harness
+        .run(&["validate", "-p", "key", "--output-format", "yaml"])
+        .unwrap()
+        .assert_failure()
+        .assert_stdout_line("valid: false");
+}
+
+#[test]
+fn test_key_fingerprint() {
+    let harness = harness_with_keys();
+    let public = harness
+        .run(&["key", "fingerprint", "-k", "key.pub"])
+        .unwrap();
+    public
+        .assert_success()
+        .assert_stdout_line(&format!(
+            "+----[RSA {}]----+",
+            key_bits(&harness, "key.pub")
+        ))
+        .assert_stdout_line("+----[SHA256]-----+");
+    assert!(public.stdout.starts_with("SHA256:"));
+
+    let private = harness.run(&["key", "fingerprint", "-k", "key"]).unwrap();
+    private.assert_success();
+    assert_eq!(private.stdout, public.stdout);
+}
+
+#[test]
+fn test_key_inspect() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["key", "inspect", "-k", "key.pub"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Variant: Public Key")
+        .assert_stdout_line("Public exponent: 65537 (default)");
+
+    let output = harness
+        .run(&["key", "inspect", "-k", "key", "--output-format", "json"])
+        .unwrap();
+    output.assert_success();
+    let json: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(json["variant"], "private");
+    assert!(json["crt"].is_boolean());
+}
+
+#[test]
+fn test_key_metadata() {
+    let harness = CliHarness::new(BINARY).unwrap();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "key", "-C", "me@laptop"])
+        .unwrap()
+        .assert_success();
+    for key_path in ["key", "key.pub"] {
+        let output = harness.run(&["key", "inspect", "-k", key_path]).unwrap();
+        output
+            .assert_success()
+            .assert_stdout_line("Comment: me@laptop");
+        assert!(output.stdout.contains("Created: "));
+        assert!(output.stdout.contains("Generator: rrsa "));
+    }
+    let converted = harness
+        .run(&["key", "convert", "-k", "key.pub", "--to", "openssh"])
+        .unwrap();
+    converted.assert_success();
+    assert!(converted.stdout.trim_end().ends_with(" me@laptop"));
+}
+
+#[test]
+fn test_key_convert() {
+    let harness = harness_with_keys();
+    harness
+        .run(&[
+            "key", "convert", "-k", "key.pub", "--to", "pem", "-o", "key.pem",
+        ])
+        .unwrap()
+        .assert_success();
+    assert!(harness
+        .read_file("key.pem")
+        .unwrap()
+        .starts_with(b"-----BEGIN RSA PUBLIC KEY-----"));
+
+    let converted = harness
+        .run(&["key", "convert", "-k", "key.pem", "--to", "openssh"])
+        .unwrap();
+    converted.assert_success();
+    assert!(converted.stdout.starts_with("ssh-rsa "));
+
+    let original = harness
+        .run(&["key", "fingerprint", "-k", "key.pub"])
+        .unwrap();
+    let pem = harness
+        .run(&["key", "fingerprint", "-k", "key.pem"])
+        .unwrap();
+    assert_eq!(pem.assert_success().stdout, original.stdout);
+
+    harness
+        .run(&["key", "convert", "-k", "key", "--to", "openssh"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run(&[
+            "key", "convert", "-k", "key.pem", "--from", "der", "--to", "rrsa",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_encrypt_decrypt() {
+    let harness = harness_with_keys();
+    for padding in ["none", "oaep", "pkcs1v15"] {
+        harness
+            .run(&[
+                "encrypt",
+                "-i",
+                "msg.txt",
+                "-k",
+                "key.pub",
+                "--raw-rsa",
+                "--padding",
+                padding,
+                "--force",
+            ])
+            .unwrap()
+            .assert_success()
+            .assert_stdout_line("Done encoding file msg.txt.encoded");
+        harness
+            .run(&[
+                "decrypt",
+                "-i",
+                "msg.txt.encoded",
+                "-k",
+                "key",
+                "--padding",
+                padding,
+                "--force",
+            ])
+            .unwrap()
+            .assert_success();
+        assert_eq!(
+            harness.read_file("msg.txt.decoded").unwrap(),
+            PLAINTEXT.as_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_stdio() {
+    let harness = harness_with_keys();
+    let encrypted = harness
+        .run_with_stdin(
+            &["encrypt", "-i", "-", "-k", "key.pub", "--armor"],
+            PLAINTEXT,
+        )
+        .unwrap();
+    encrypted.assert_success();
+    assert!(encrypted.stdout.starts_with("-----BEGIN"));
+    assert!(encrypted.stderr.contains("Done encoding file -"));
+
+    let decrypted = harness
+        .run_with_stdin(&["decrypt", "-i", "-", "-k", "key"], &encrypted.stdout)
+        .unwrap();
+    decrypted.assert_success();
+    assert_eq!(decrypted.stdout, PLAINTEXT);
+
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    let paged = harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "-k",
+            "key",
+            "--stdout",
+            "--progress",
+        ])
+        .unwrap();
+    paged.assert_success();
+    assert_eq!(paged.stdout, PLAINTEXT);
+    assert!(!harness.path("msg.txt.decoded").exists());
+    harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "--stdout",
+            "-o",
+            "out.txt",
+        ])
+        .unwrap()
+        .assert_failure();
+
+    harness
+        .run_with_stdin(&["decrypt", "-i", "-", "--any-key"], "")
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_decrypt_tampered_to_stdout() {
+    let harness = harness_with_keys();
+    harness
+        .write_file("other.txt", PLAINTEXT.replace("fox", "cat"))
+        .unwrap();
+    for name in ["msg.txt", "other.txt"] {
+        harness
+            .run(&[
+                "encrypt",
+                "-i",
+                name,
+                "-k",
+                "key.pub",
+                "--raw-rsa",
+                "--padding",
+                "none",
+            ])
+            .unwrap()
+            .assert_success();
+    }
+
+    // the data chunk of another message, with its checksum recomputed, only fails the tag
+    let mut tampered = harness.read_file("msg.txt.encoded").unwrap();
+    let other = harness.read_file("other.txt.encoded").unwrap();
+    let chunk_len = (tampered.len() - CIPHERTEXT_HEADER_LEN) / 2;
+    let block = CIPHERTEXT_HEADER_LEN..CIPHERTEXT_HEADER_LEN + chunk_len - 4;
+    tampered[block.clone()].copy_from_slice(&other[block.clone()]);
+    let checksum = crc32fast::hash(&tampered[block.clone()]).to_le_bytes();
+    tampered[block.end..block.end + 4].copy_from_slice(&checksum);
+    harness.write_file("tampered.encoded", tampered).unwrap();
+
+    for args in [&["-o", "-"][..], &["--stdout"][..]] {
+        let decrypted = harness
+            .run(
+                &[
+                    &["decrypt", "-i", "tampered.encoded", "-k", "key"][..],
+                    args,
+                ]
+                .concat(),
+            )
+            .unwrap();
+        decrypted.assert_failure();
+        assert_eq!(decrypted.stdout, "");
+    }
+}
+
+#[test]
+fn test_decrypt_to_stdout_limit() {
+    let harness = harness_with_keys();
+    // one byte more than stdout holds back
+    harness
+        .write_file("large.bin", vec![0x5A; 64 * 1024 * 1024 + 1])
+        .unwrap();
+    harness
+        .run(&["encrypt", "-i", "large.bin", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+
+    let decrypted = harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "large.bin.encoded",
+            "-k",
+            "key",
+            "--stdout",
+        ])
+        .unwrap();
+    decrypted.assert_failure();
+    assert_eq!(decrypted.stdout, "");
+    assert!(decrypted.stderr.contains("-o"), "{}", decrypted.stderr);
+}
+
+#[test]
+fn test_encrypt_decrypt_text() {
+    let harness = harness_with_keys();
+    let encrypted = harness
+        .run(&["encrypt-text", "-m", "one line secret", "-k", "key.pub"])
+        .unwrap();
+    encrypted
+        .assert_success()
+        .assert_stdout_line("-----END RRSA MESSAGE-----");
+
+    harness
+        .run_with_stdin(&["decrypt-text", "-k", "key"], &encrypted.stdout)
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("one line secret");
+    harness
+        .run(&["decrypt-text", "-k", "key", "-a", "not armor"])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_overwrite_protection() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    let encrypted = harness.read_file("msg.txt.encoded").unwrap();
+    let refused = harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap();
+    refused.assert_failure();
+    assert!(refused.stderr.contains("already exists"));
+    assert_eq!(harness.read_file("msg.txt.encoded").unwrap(), encrypted);
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub", "--force"])
+        .unwrap()
+        .assert_success();
+
+    harness.write_file("msg.txt.decoded", "keep me").unwrap();
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_failure();
+    assert_eq!(harness.read_file("msg.txt.decoded").unwrap(), b"keep me");
+
+    let key = harness.read_file("key").unwrap();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "key"])
+        .unwrap()
+        .assert_failure();
+    assert_eq!(harness.read_file("key").unwrap(), key);
+    harness
+        .run(&["keygen", "-k", "768", "-o", "key", "--force"])
+        .unwrap()
+        .assert_success();
+    assert_ne!(harness.read_file("key").unwrap(), key);
+    harness.write_file("msg.txt.sig", "keep me").unwrap();
+    harness
+        .run(&["sign", "-f", "msg.txt", "-k", "key"])
+        .unwrap()
+        .assert_failure();
+    assert_eq!(harness.read_file("msg.txt.sig").unwrap(), b"keep me");
+    harness
+        .run(&["sign", "-f", "msg.txt", "-k", "key", "--force"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["verify", "-f", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+}
+
+/// Whether an output of `harness` left its temporary file behind.
+fn has_tmp_files(harness: &CliHarness) -> bool {
+    std::fs::read_dir(harness.path("."))
+        .unwrap()
+        .any(|entry| entry.unwrap().path().extension() == Some("tmp".as_ref()))
+}
+
+#[test]
+fn test_atomic_outputs() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    assert!(!has_tmp_files(&harness));
+
+    // a decryption failing midway neither replaces nor leaves a partial output
+    let encrypted = harness.read_file("msg.txt.encoded").unwrap();
+    harness
+        .write_file("truncated.encoded", &encrypted[..encrypted.len() - 8])
+        .unwrap();
+    harness.write_file("truncated.decoded", "keep me").unwrap();
+    harness
+        .run(&["decrypt", "-i", "truncated.encoded", "-k", "key", "--force"])
+        .unwrap()
+        .assert_failure();
+    assert_eq!(harness.read_file("truncated.decoded").unwrap(), b"keep me");
+    assert!(!has_tmp_files(&harness));
+}
+
+#[test]
+fn test_encrypt_decrypt_envelope() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    assert!(harness
+        .read_file("msg.txt.encoded")
+        .unwrap()
+        .starts_with(b"RRSA-ENV"));
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+
+    // the padding scheme only applies to raw RSA
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            "key.pub",
+            "--padding",
+            "oaep",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_verify_file_envelope() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "encrypt", "-i", "msg.txt", "-k", "key.pub", "--armor", "-o", "msg.asc",
+        ])
+        .unwrap()
+        .assert_success();
+    for (file, key) in [
+        ("msg.txt.encoded", "key"),
+        ("msg.txt.encoded", "key.pub"),
+        ("msg.asc", "key"),
+    ] {
+        harness
+            .run(&["verify-file", "-i", file, "-k", key])
+            .unwrap()
+            .assert_success()
+            .assert_stdout_line(&format!("File {file} is intact!"));
+    }
+
+    let mut tampered = harness.read_file("msg.txt.encoded").unwrap();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 0x01;
+    harness.write_file("tampered.encoded", tampered).unwrap();
+    harness
+        .run(&["verify-file", "-i", "tampered.encoded", "-k", "key"])
+        .unwrap()
+        .assert_failure()
+        .assert_stdout_line("Chunk 0 is damaged");
+}
+
+#[test]
+fn test_encrypt_decrypt_escrow() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "escrow"])
+        .unwrap()
+        .assert_success();
+    let vars = [("RRSA_ESCROW_KEY_PATH", "escrow")];
+    harness
+        .run_with_env(&["encrypt", "-i", "msg.txt", "-k", "key.pub"], &vars)
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Data key escrowed to escrow.pub");
+
+    // both the recipient and the escrow agent can decrypt it
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run_with_env(
+            &[
+                "decrypt",
+                "-i",
+                "msg.txt.encoded",
+                "--as-escrow",
+                "-o",
+                "escrowed.txt",
+            ],
+            &vars,
+        )
+        .unwrap()
+        .assert_success();
+    for name in ["msg.txt.decoded", "escrowed.txt"] {
+        assert_eq!(harness.read_file(name).unwrap(), PLAINTEXT.as_bytes());
+    }
+
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "--as-escrow", "-o", "-"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run_with_env(
+            &[
+                "encrypt",
+                "-i",
+                "msg.txt",
+                "-k",
+                "key.pub",
+                "--raw-rsa",
+                "--force",
+            ],
+            &vars,
+        )
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_reencrypt() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "other"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "reencrypt",
+            "-i",
+            "msg.txt.encoded",
+            "-o",
+            "rekeyed.encoded",
+            "--from",
+            "key",
+            "--to",
+            "other.pub",
+        ])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Done re-encrypting file rekeyed.encoded");
+
+    // the single sealed segment, the plaintext and its tag, is copied as is
+    let sealed = harness.read_file("msg.txt.encoded").unwrap();
+    let rekeyed = harness.read_file("rekeyed.encoded").unwrap();
+    let segment_len = PLAINTEXT.len() + 16;
+    assert_eq!(
+        sealed[sealed.len() - segment_len..],
+        rekeyed[rekeyed.len() - segment_len..]
+    );
+
+    harness
+        .run(&["decrypt", "-i", "rekeyed.encoded", "-k", "other"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("rekeyed.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+    harness
+        .run(&["decrypt", "-i", "rekeyed.encoded", "-k", "key", "--force"])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_encrypt_with_key_bundle() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "other"])
+        .unwrap()
+        .assert_success();
+    for (label, key_path) in [("alice", "key.pub"), ("bob", "other.pub")] {
+        harness
+            .run(&[
+                "bundle",
+                "add",
+                "-b",
+                "team.pubs",
+                "-l",
+                label,
+                "-k",
+                key_path,
+            ])
+            .unwrap()
+            .assert_success();
+    }
+    let output = harness.run(&["bundle", "list", "-b", "team.pubs"]).unwrap();
+    output.assert_success();
+    let labels: Vec<&str> = output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+    assert_eq!(labels, ["alice", "bob"]);
+
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "--key-bundle", "team.pubs"])
+        .unwrap()
+        .assert_success();
+    for key_path in ["key", "other"] {
+        harness
+            .run(&[
+                "decrypt",
+                "-i",
+                "msg.txt.encoded",
+                "-k",
+                key_path,
+                "--force",
+            ])
+            .unwrap()
+            .assert_success();
+        assert_eq!(
+            harness.read_file("msg.txt.decoded").unwrap(),
+            PLAINTEXT.as_bytes()
+        );
+    }
+
+    harness
+        .run(&["bundle", "remove", "-b", "team.pubs", "-l", "bob"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["bundle", "remove", "-b", "team.pubs", "-l", "bob"])
+        .unwrap()
+        .assert_failure();
+    // only one key per bundle label
+    harness
+        .run(&[
+            "bundle",
+            "add",
+            "-b",
+            "team.pubs",
+            "-l",
+            "alice",
+            "-k",
+            "other.pub",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_encrypt_decrypt_archive() {
+    let harness = harness_with_keys();
+    std::fs::create_dir_all(harness.path("photos/2024")).unwrap();
+    harness.write_file("photos/readme.txt", PLAINTEXT).unwrap();
+    harness
+        .write_file("photos/2024/beach.raw", vec![42u8; 100_000])
+        .unwrap();
+
+    harness
+        .run(&["encrypt", "-i", "photos", "-k", "key.pub", "--archive"])
+        .unwrap()
+        .assert_success();
+    assert!(harness.path("photos.encoded").is_file());
+    harness
+        .run(&["decrypt", "-i", "photos.encoded", "-k", "key", "--unpack"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("photos.decoded/readme.txt").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+    assert_eq!(
+        harness.read_file("photos.decoded/2024/beach.raw").unwrap(),
+        vec![42u8; 100_000]
+    );
+
+    // the first segment is extracted before the last one fails, yet nothing is left behind
+    let mut tampered = harness.read_file("photos.encoded").unwrap();
+    *tampered.last_mut().unwrap() ^= 0x01;
+    harness.write_file("tampered.encoded", tampered).unwrap();
+    harness
+        .run(&["decrypt", "-i", "tampered.encoded", "-k", "key", "--unpack"])
+        .unwrap()
+        .assert_failure();
+    assert!(!harness.path("tampered.decoded").exists());
+    assert!(!has_tmp_files(&harness));
+
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub", "--archive"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key", "--unpack"])
+        .unwrap()
+        .assert_failure();
+    assert!(!harness.path("msg.txt.decoded").exists());
+}
+
+#[test]
+fn test_encrypt_decrypt_armored() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub", "--armor"])
+        .unwrap()
+        .assert_success();
+    assert!(harness
+        .read_file("msg.txt.encoded")
+        .unwrap()
+        .starts_with(b"-----BEGIN RRSA MESSAGE-----"));
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+}
+
+#[test]
+fn test_encrypt_decrypt_chacha20() {
+    let harness = harness_with_keys();
+    harness
+        .run(&[
+            "encrypt", "-i", "msg.txt", "-k", "key.pub", "--cipher", "chacha20",
+        ])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            "key.pub",
+            "--raw-rsa",
+            "--cipher",
+            "chacha20",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_init_writes_config() {
+    let harness = CliHarness::new(BINARY).unwrap();
+    harness
+        .run(&["init", "--yes", "-k", "512"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file(".config/rrsa/config.toml").unwrap(),
+        format!(
+            "key_size = 512\nkey_path = \"{}\"\n",
+            harness.path(".config/rrsa").display()
+        )
+        .as_bytes()
+    );
+
+    // an existing config is never replaced
+    harness
+        .write_file(".config/rrsa/config.toml", "armor = true\n")
+        .unwrap();
+    for key_name in ["rrsa_key", "rrsa_key.pub"] {
+        std::fs::remove_file(harness.path(".config/rrsa").join(key_name)).unwrap();
+    }
+    harness
+        .run(&["init", "--yes", "-k", "512"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file(".config/rrsa/config.toml").unwrap(),
+        b"armor = true\n"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_config_defaults() {
+    let harness = harness_with_keys();
+    std::fs::create_dir_all(harness.path(".config/rrsa")).unwrap();
+    std::fs::create_dir(harness.path("out")).unwrap();
+    harness
+        .write_file(
+            ".config/rrsa/config.toml",
+            format!(
+                "key_path = \"{}\"\nout_dir = \"{}\"\narmor = true\n",
+                harness.path("key").display(),
+                harness.path("out").display()
+            ),
+        )
+        .unwrap();
+    harness
+        .run(&["encrypt", "-i", "msg.txt"])
+        .unwrap()
+        .assert_success();
+    assert!(harness
+        .read_file("out/msg.txt.encoded")
+        .unwrap()
+        .starts_with(b"-----BEGIN RRSA MESSAGE-----"));
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-o",
+            "plain.encoded",
+            "--no-armor",
+        ])
+        .unwrap()
+        .assert_success();
+    assert!(!harness
+        .read_file("plain.encoded")
+        .unwrap()
+        .starts_with(b"-----BEGIN RRSA MESSAGE-----"));
+    harness
+        .run(&["decrypt", "-i", "out/msg.txt.encoded"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("out/msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+
+    harness
+        .write_file(".config/rrsa/config.toml", "armor = sometimes\n")
+        .unwrap();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_key_use() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "768", "-o", ".config/rrsa/work"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["key", "use", "work"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("Default Key Pair is now work");
+    harness
+        .run(&["key", "use", "work.pub"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run(&["key", "use", "missing"])
+        .unwrap()
+        .assert_failure();
+
+    harness
+        .run(&["encrypt", "-i", "msg.txt"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "-k",
+            ".config/rrsa/work",
+        ])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+}
+
+#[test]
+fn test_env_overrides() {
+    let harness = harness_with_keys();
+    let home = harness.path("ci_keys");
+    let home = home.to_str().unwrap();
+    harness
+        .run_with_env(&["init", "--yes", "-k", "512"], &[("RRSA_HOME", home)])
+        .unwrap()
+        .assert_success();
+    assert!(harness.path("ci_keys/rrsa_key").is_file());
+    assert!(harness.path("ci_keys/rrsa_key.pub").is_file());
+
+    let key = harness.path("key");
+    let key = key.to_str().unwrap();
+    let vars = [("RRSA_HOME", home), ("RRSA_KEY", key)];
+    harness
+        .run_with_env(&["encrypt", "-i", "msg.txt"], &vars)
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_storage_migration() {
+    let harness = harness_with_keys();
+    let data_home = harness.path(".local/share");
+    let vars = [("XDG_DATA_HOME", data_home.to_str().unwrap())];
+    harness
+        .run_with_env(&["init", "--yes", "-k", "768"], &vars)
+        .unwrap()
+        .assert_success();
+    harness
+        .write_file(".config/rrsa/default.pubs", "# team\n")
+        .unwrap();
+    harness
+        .write_file(".config/rrsa/config.toml", "storage = \"data\"\n")
+        .unwrap();
+
+    harness
+        .run_with_env(&["encrypt", "-i", "msg.txt"], &vars)
+        .unwrap()
+        .assert_success();
+    for moved in ["rrsa_key", "rrsa_key.pub", "default.pubs"] {
+        assert!(harness.path(".local/share/rrsa").join(moved).is_file());
+        assert!(!harness.path(".config/rrsa").join(moved).exists());
+    }
+    assert!(harness.path(".config/rrsa/config.toml").is_file());
+    assert!(harness.path(".config/rrsa/keys_location").is_file());
+    harness
+        .run_with_env(&["decrypt", "-i", "msg.txt.encoded"], &vars)
+        .unwrap()
+        .assert_success();
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+
+    harness
+        .run_with_env(&["migrate-storage", "config"], &vars)
+        .unwrap()
+        .assert_success();
+    assert!(harness.path(".config/rrsa/rrsa_key").is_file());
+    assert!(!harness.path(".config/rrsa/keys_location").exists());
+    assert_eq!(
+        harness.read_file(".config/rrsa/config.toml").unwrap(),
+        b"storage = \"config\"\n"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_encrypt_decrypt_checkpoints() {
+    let harness = harness_with_keys();
+    let log = harness.path("checkpoints.log");
+    std::fs::create_dir_all(harness.path(".config/rrsa")).unwrap();
+    harness
+        .write_file(
+            ".config/rrsa/config.toml",
+            format!("checkpoint_log = \"{}\"\n", log.display()),
+        )
+        .unwrap();
+    harness
+        .run(&["encrypt", "-i", "msg.txt", "-k", "key.pub"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["decrypt", "-i", "msg.txt.encoded", "-k", "key"])
+        .unwrap()
+        .assert_success();
+
+    let log = String::from_utf8(harness.read_file("checkpoints.log").unwrap()).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].ends_with("msg.txt: started, 45 bytes"));
+    assert!(lines[1].contains("msg.txt: [00:00:00] 1/1 chunks"));
+    assert!(lines[1].ends_with("finished"));
+    assert!(lines[2].contains("msg.txt.encoded: started"));
+    assert!(lines[3].ends_with("finished"));
+}
+
+#[test]
+fn test_decrypt_with_wrong_key() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "768", "-o", "other"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            "key.pub",
+            "--raw-rsa",
+            "--padding",
+            "oaep",
+        ])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "-k",
+            "other",
+            "--padding",
+            "oaep",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_decrypt_with_any_key() {
+    let harness = harness_with_keys();
+    // the default keys, then a second pair in the same directory
+    harness
+        .run(&["keygen", "-k", "512"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["keygen", "-k", "768", "-o", ".config/rrsa/work"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            ".config/rrsa/work.pub",
+            "--raw-rsa",
+            "--padding",
+            "oaep",
+        ])
+        .unwrap()
+        .assert_success();
+
+    let work = harness.path(".config/rrsa/work");
+    harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "--any-key",
+            "--padding",
+            "oaep",
+        ])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line(&format!("Decrypting with {}", work.display()));
+    assert_eq!(
+        harness.read_file("msg.txt.decoded").unwrap(),
+        PLAINTEXT.as_bytes()
+    );
+
+    // only `key` can decrypt it, and it is not in the keys directory
+    harness
+        .run(&[
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            "key.pub",
+            "--raw-rsa",
+            "--padding",
+            "oaep",
+            "--force",
+        ])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&[
+            "decrypt",
+            "-i",
+            "msg.txt.encoded",
+            "--any-key",
+            "--padding",
+            "oaep",
+            "--force",
+        ])
+        .unwrap()
+        .assert_failure();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_encrypt_decrypt_no_key_hint() {
+    let harness = harness_with_keys();
+    harness
+        .run(&["keygen", "-k", "512"])
+        .unwrap()
+        .assert_success();
+    harness
+        .run(&["keygen", "-k", "768", "-o", ".config/rrsa/work"])
+        .unwrap()
+        .assert_success();
+    let work = harness.path(".config/rrsa/work");
+
+    for mode in [&[][..], &["--raw-rsa", "--padding", "oaep"][..]] {
+        let mut args = vec![
+            "encrypt",
+            "-i",
+            "msg.txt",
+            "-k",
+            ".config/rrsa/work.pub",
+            "--no-key-hint",
+            "--no-armor",
+            "--force",
+        ];
+        args.extend_from_slice(mode);
+        harness.run(&args).unwrap().assert_success();
+
+        let encoded = harness.read_file("msg.txt.encoded").unwrap();
+        let fingerprint = Key::read_from_path(&harness.path(".config/rrsa/work.pub"))
+            .unwrap()
+            .fingerprint();
+        assert!(!encoded
+            .windows(fingerprint.len())
+            .any(|window| window == fingerprint));
+
+        // no `-k` nor `--any-key`, the anonymous header makes it try every Key
+        let mut args = vec!["decrypt", "-i", "msg.txt.encoded", "--force"];
+        if !mode.is_empty() {
+            args.extend_from_slice(&["--padding", "oaep"]);
+        }
+        harness
+            .run(&args)
+            .unwrap()
+            .assert_success()
+            .assert_stdout_line(&format!("Decrypting with {}", work.display()));
+        assert_eq!(
+            harness.read_file("msg.txt.decoded").unwrap(),
+            PLAINTEXT.as_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_secret_encrypt_decrypt() {
+    let harness = harness_with_keys();
+    let output = harness
+        .run_with_stdin(&["secret", "encrypt", "-k", "key.pub"], "hunter2\n")
+        .unwrap();
+    output.assert_success();
+    let line = output.stdout.trim();
+    assert!(line.starts_with("rrsa-secret:1:"));
+
+    harness
+        .run(&["secret", "decrypt", "-k", "key", "-s", line])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("hunter2");
+    harness
+        .run_with_stdin(&["secret", "decrypt", "-k", "key"], line)
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("hunter2");
+    harness
+        .run(&["secret", "decrypt", "-k", "key", "-s", "rrsa-secret:1:AAAA"])
+        .unwrap()
+        .assert_failure();
+}
+
+#[test]
+fn test_capabilities_json() {
+    let harness = CliHarness::new(BINARY).unwrap();
+    let output = harness.run(&["capabilities", "--json"]).unwrap();
+    output.assert_success();
+    let json: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(json["max_key_size"], 4096);
+    assert!(json["features"]
+        .as_array()
+        .unwrap()
+        .contains(&"cli-test".into()));
+}
+
+#[test]
+fn test_math() {
+    let harness = CliHarness::new(BINARY).unwrap();
+    harness
+        .run(&["math", "modpow", "4", "13", "497"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("445")
+        .assert_stdout_line("0x1bd");
+    harness
+        .run(&["math", "modpow", "5", "0", "1"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("0")
+        .assert_stdout_line("0x0");
+    harness
+        .run(&["math", "modpow", "0x4", "0xd", "0"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run(&["math", "is-prime", "0x10001"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("65537 is probably prime");
+    harness
+        .run(&["math", "is-prime", "561"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("561 is composite");
+    harness
+        .run(&["math", "is-prime", "twelve"])
+        .unwrap()
+        .assert_failure();
+    harness
+        .run(&["math", "is-prime", "65_537"])
+        .unwrap()
+        .assert_success()
+        .assert_stdout_line("65537 is probably prime");
+    let output = harness.run(&["math", "gen-prime", "--bits", "64"]).unwrap();
+    output.assert_success();
+    assert!(output.stdout.lines().nth(1).unwrap().starts_with("0x"));
+}